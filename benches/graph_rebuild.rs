@@ -0,0 +1,90 @@
+//! Allocations and time for rebuilding the graph after a single-value edit
+//!
+//! Run with `cargo bench --bench graph_rebuild`
+
+use serde_json::{Value, json};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use wgpu_canvas_editor::json_editor::JsonGraph;
+
+/// System allocator that counts allocations
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const RUNS: u32 = 20;
+
+/// Document with 1000 graph nodes: the root array and 333 items of 3 nodes
+fn document() -> Value {
+    let items: Vec<Value> = (0..333)
+        .map(|i| {
+            json!({
+                "id": i,
+                "name": format!("item {}", i),
+                "tags": ["a", "b", "c"],
+                "meta": {"rank": i, "active": i % 2 == 0},
+            })
+        })
+        .collect();
+    Value::Array(items)
+}
+
+/// Average allocations and time of `rebuild`, starting from a graph of `before` each run
+fn measure(before: &Value, after: &Value, rebuild: fn(&mut JsonGraph, &Value)) -> (usize, f64) {
+    let mut allocations = 0;
+    let mut seconds = 0.0;
+    for _ in 0..RUNS {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(before);
+
+        let start_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        rebuild(&mut graph, after);
+        seconds += start.elapsed().as_secs_f64();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - start_allocations;
+        assert_eq!(graph.node_count(), 1000);
+    }
+    (allocations / RUNS as usize, seconds / RUNS as f64)
+}
+
+fn main() {
+    let before = document();
+    let mut after = before.clone();
+    after[166]["meta"]["rank"] = json!(-1);
+
+    let (full_allocs, full_time) = measure(&before, &after, JsonGraph::build_from_json);
+    let (incremental_allocs, incremental_time) =
+        measure(&before, &after, JsonGraph::update_from_json);
+
+    println!("single-value edit in a 1000-node document (average of {RUNS} runs)");
+    println!(
+        "  build_from_json:  {:>7} allocations  {:>8.3} ms",
+        full_allocs,
+        full_time * 1000.0
+    );
+    println!(
+        "  update_from_json: {:>7} allocations  {:>8.3} ms",
+        incremental_allocs,
+        incremental_time * 1000.0
+    );
+}
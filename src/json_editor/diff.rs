@@ -0,0 +1,239 @@
+use super::MAX_DEPTH;
+use serde_json::Value;
+
+/// Kind of structural difference, carrying the values involved
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffKind {
+    /// Present only in the new value
+    Added { value: Value },
+    /// Present only in the old value
+    Removed { value: Value },
+    /// Present in both with a different value (or type)
+    Changed { old: Value, new: Value },
+}
+
+/// A single structural difference between two JSON values
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEntry {
+    /// JSON path of the differing value (e.g., ["items", "0", "id"])
+    pub path: Vec<String>,
+    /// Kind of difference
+    pub kind: DiffKind,
+}
+
+impl DiffEntry {
+    /// Symbol for the kind of difference ('+', '-' or '~')
+    pub fn symbol(&self) -> char {
+        match self.kind {
+            DiffKind::Added { .. } => '+',
+            DiffKind::Removed { .. } => '-',
+            DiffKind::Changed { .. } => '~',
+        }
+    }
+
+    /// Format the path for display (e.g., "items.0.id", or "(root)")
+    pub fn path_display(&self) -> String {
+        if self.path.is_empty() {
+            "(root)".to_string()
+        } else {
+            self.path.join(".")
+        }
+    }
+
+    /// One-line description for lists (e.g., "~ a.b: 1 → 2")
+    pub fn summary(&self) -> String {
+        match &self.kind {
+            DiffKind::Added { value } | DiffKind::Removed { value } => {
+                format!("{} {}: {}", self.symbol(), self.path_display(), value)
+            }
+            DiffKind::Changed { old, new } => {
+                format!("~ {}: {} → {}", self.path_display(), old, new)
+            }
+        }
+    }
+}
+
+/// Compute the structural differences from `old` to `new`
+/// Objects are compared by key and arrays by index; the first differing
+/// level is reported (changed containers are not expanded further)
+pub fn diff(old: &Value, new: &Value) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_into(old, new, &mut Vec::new(), &mut entries);
+    entries
+}
+
+/// Recursively compare two values, appending differences
+/// Containers at `MAX_DEPTH` are compared whole instead of descending further
+fn diff_into(old: &Value, new: &Value, path: &mut Vec<String>, entries: &mut Vec<DiffEntry>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) if path.len() < MAX_DEPTH => {
+            for (key, old_child) in old_map {
+                path.push(key.clone());
+                match new_map.get(key) {
+                    Some(new_child) => diff_into(old_child, new_child, path, entries),
+                    None => entries.push(DiffEntry {
+                        path: path.clone(),
+                        kind: DiffKind::Removed {
+                            value: old_child.clone(),
+                        },
+                    }),
+                }
+                path.pop();
+            }
+            for (key, new_child) in new_map {
+                if !old_map.contains_key(key) {
+                    let mut child_path = path.clone();
+                    child_path.push(key.clone());
+                    entries.push(DiffEntry {
+                        path: child_path,
+                        kind: DiffKind::Added {
+                            value: new_child.clone(),
+                        },
+                    });
+                }
+            }
+        }
+        (Value::Array(old_arr), Value::Array(new_arr)) if path.len() < MAX_DEPTH => {
+            for index in 0..old_arr.len().max(new_arr.len()) {
+                path.push(index.to_string());
+                match (old_arr.get(index), new_arr.get(index)) {
+                    (Some(old_child), Some(new_child)) => {
+                        diff_into(old_child, new_child, path, entries)
+                    }
+                    (Some(old_child), None) => entries.push(DiffEntry {
+                        path: path.clone(),
+                        kind: DiffKind::Removed {
+                            value: old_child.clone(),
+                        },
+                    }),
+                    (None, Some(new_child)) => entries.push(DiffEntry {
+                        path: path.clone(),
+                        kind: DiffKind::Added {
+                            value: new_child.clone(),
+                        },
+                    }),
+                    (None, None) => {}
+                }
+                path.pop();
+            }
+        }
+        _ if old != new => entries.push(DiffEntry {
+            path: path.clone(),
+            kind: DiffKind::Changed {
+                old: old.clone(),
+                new: new.clone(),
+            },
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deep_values_are_compared_whole_at_the_depth_limit() {
+        let (mut old, mut new) = (json!(1), json!(2));
+        for _ in 0..MAX_DEPTH + 10 {
+            old = json!([old]);
+            new = json!([new]);
+        }
+        let entries = diff(&old, &new);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path.len(), MAX_DEPTH);
+        assert!(matches!(entries[0].kind, DiffKind::Changed { .. }));
+    }
+
+    #[test]
+    fn test_identical_values() {
+        let value = json!({"a": [1, {"b": null}]});
+        assert!(diff(&value, &value).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_changed() {
+        let old = json!({"a": 1, "b": [1, 2, 3], "c": true});
+        let new = json!({"a": 2, "b": [1, 2], "d": "x"});
+        let summaries: Vec<String> = diff(&old, &new).iter().map(DiffEntry::summary).collect();
+        assert_eq!(
+            summaries,
+            vec!["~ a: 1 → 2", "- b.2: 3", "- c: true", "+ d: \"x\""]
+        );
+    }
+
+    #[test]
+    fn test_type_change_is_not_expanded() {
+        let entries = diff(&json!({"a": {"x": 1}}), &json!({"a": [1]}));
+        assert_eq!(
+            entries,
+            vec![DiffEntry {
+                path: vec!["a".to_string()],
+                kind: DiffKind::Changed {
+                    old: json!({"x": 1}),
+                    new: json!([1]),
+                },
+            }]
+        );
+
+        // Scalar type changes (number -> string, value -> null) are Changed too
+        let entries = diff(&json!([1, true]), &json!(["1", null]));
+        assert_eq!(entries.len(), 2);
+        assert!(
+            entries
+                .iter()
+                .all(|e| matches!(e.kind, DiffKind::Changed { .. }))
+        );
+    }
+
+    #[test]
+    fn test_nested_objects() {
+        let old = json!({"server": {"host": "a", "tls": {"enabled": false}}, "keep": 1});
+        let new =
+            json!({"server": {"host": "a", "tls": {"enabled": true, "cert": "c"}}, "keep": 1});
+        let summaries: Vec<String> = diff(&old, &new).iter().map(DiffEntry::summary).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "~ server.tls.enabled: false → true",
+                "+ server.tls.cert: \"c\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_length_differences() {
+        let grown = diff(&json!({"a": [1]}), &json!({"a": [1, 2, 3]}));
+        let paths: Vec<(char, String)> = grown
+            .iter()
+            .map(|e| (e.symbol(), e.path_display()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![('+', "a.1".to_string()), ('+', "a.2".to_string())]
+        );
+
+        let shrunk = diff(&json!([[1, 2], {"x": [0, 0]}]), &json!([[1], {"x": []}]));
+        let paths: Vec<(char, String)> = shrunk
+            .iter()
+            .map(|e| (e.symbol(), e.path_display()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ('-', "0.1".to_string()),
+                ('-', "1.x.0".to_string()),
+                ('-', "1.x.1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_change() {
+        let entries = diff(&json!(1), &json!(2));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path_display(), "(root)");
+        assert_eq!(entries[0].summary(), "~ (root): 1 → 2");
+    }
+}
@@ -1,6 +1,36 @@
+use super::MAX_DEPTH;
+use super::diff::{self, DiffEntry};
+use super::error::EditorError;
+use super::event::EditEvent;
+use super::graph::{self, NodeType};
+use super::highlight;
+use super::lint::{self, Lint, LintKind, LintSettings};
+use super::number_format::NumberFormat;
+use super::source_map;
+use super::stats::{self, DocumentStats};
+use super::tree_view::{TreeOperation, TreeView};
 use crate::utils;
+use serde::Serialize;
 use serde_json::Value;
+use std::cell::Cell;
 use unicode_normalization::UnicodeNormalization;
+use web_time::SystemTime;
+
+/// Allowed range for the pretty-print indent size
+const MIN_INDENT_SIZE: usize = 1;
+const MAX_INDENT_SIZE: usize = 8;
+
+/// Height of one line in the monospace text editor (in points)
+const LINE_HEIGHT: f32 = 17.0;
+
+/// Keystrokes less than this many seconds apart are merged into one undo step
+const TYPING_UNDO_GROUP_SECS: f64 = 0.5;
+
+/// Delay after the last edit before auto-format pretty-prints the text
+const AUTO_FORMAT_DELAY_SECS: f64 = 0.6;
+
+/// Delay after the last keystroke before typed text is re-parsed
+const VALIDATE_DELAY_SECS: f64 = 0.15;
 
 /// View mode for JSON editor
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -11,7 +41,236 @@ pub enum ViewMode {
     Tree,
 }
 
+/// Append an Object key to a display path: `.key`, or `["key"]` for keys containing
+/// path punctuation, so a key named `a.b` doesn't read as `a` → `b`
+fn push_path_key(formatted: &mut String, key: &str) {
+    if key.is_empty() || key.contains(['.', '[', ']', '"']) {
+        formatted.push('[');
+        formatted.push_str(&Value::String(key.to_string()).to_string());
+        formatted.push(']');
+    } else {
+        if !formatted.is_empty() {
+            formatted.push('.');
+        }
+        formatted.push_str(key);
+    }
+}
+
+/// Format a JSON path as `items[0].id`, using `root` to tell array indices from keys
+/// Segments that don't resolve are formatted as keys
+/// Display only: paths are compared and looked up as `Vec<String>`
+pub fn format_json_path(root: &Value, path: &[String]) -> String {
+    let mut formatted = String::new();
+    let mut current = Some(root);
+    for segment in path {
+        match current {
+            Some(Value::Array(arr)) => {
+                formatted.push_str(&format!("[{}]", segment));
+                current = segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| arr.get(index));
+            }
+            other => {
+                push_path_key(&mut formatted, segment);
+                current = match other {
+                    Some(Value::Object(map)) => map.get(segment),
+                    _ => None,
+                };
+            }
+        }
+    }
+    formatted
+}
+
+/// Format a JSON path as a JSON Pointer (RFC 6901), e.g. `/items/0`
+/// `~` and `/` in keys are escaped as `~0` and `~1`; the root is the empty pointer
+pub fn json_pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// Paths of every leaf under a value, in document order, each starting with `prefix`
+/// Leaves are scalars and empty Objects/Arrays; walked without recursion
+pub fn all_leaf_paths(value: &Value, prefix: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut stack = vec![(prefix.to_string(), value)];
+
+    while let Some((path, value)) = stack.pop() {
+        let children: Vec<(String, &Value)> = match value {
+            Value::Object(map) if !map.is_empty() => map
+                .iter()
+                .map(|(k, v)| {
+                    let mut child = path.clone();
+                    push_path_key(&mut child, k);
+                    (child, v)
+                })
+                .collect(),
+            Value::Array(arr) if !arr.is_empty() => arr
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (format!("{}[{}]", path, i), v))
+                .collect(),
+            _ => {
+                paths.push(path);
+                continue;
+            }
+        };
+        // Reversed so children pop in document order
+        stack.extend(children.into_iter().rev());
+    }
+
+    paths
+}
+
+/// Compact summary of a value's structure and scalar types, e.g.
+/// `{id:number,tags:array<string>}`
+/// Arrays whose items all share one signature collapse to `array<T>`; empty or
+/// mixed arrays become `array<any>`. Keys that aren't plain identifiers are quoted
+/// Containers below `MAX_DEPTH` are summarized as `…`
+pub fn type_signature(value: &Value) -> String {
+    type_signature_at_depth(value, 0)
+}
+
+/// `type_signature` of a value nested `depth` levels deep
+fn type_signature_at_depth(value: &Value, depth: usize) -> String {
+    match value {
+        Value::Array(_) | Value::Object(_) if depth >= MAX_DEPTH => "…".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Array(arr) => {
+            let mut items = arr
+                .iter()
+                .map(|item| type_signature_at_depth(item, depth + 1));
+            let item = match items.next() {
+                Some(first) if items.all(|other| other == first) => first,
+                _ => "any".to_string(),
+            };
+            format!("array<{}>", item)
+        }
+        Value::Object(map) => {
+            let fields: Vec<String> = map
+                .iter()
+                .map(|(key, value)| {
+                    let plain = !key.is_empty()
+                        && !key.starts_with(|c: char| c.is_ascii_digit())
+                        && key
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+                    let key = if plain {
+                        key.clone()
+                    } else {
+                        Value::String(key.clone()).to_string()
+                    };
+                    format!("{}:{}", key, type_signature_at_depth(value, depth + 1))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+    }
+}
+
+/// Parse newline-delimited JSON (one value per line, blank lines ignored)
+/// On failure, returns the 1-indexed line number and the parse error
+pub fn parse_ndjson(text: &str) -> Result<Vec<Value>, (usize, String)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str::<Value>(line).map_err(|e| (i + 1, e.to_string())))
+        .collect()
+}
+
+/// Value an array item is sorted by
+enum SortKey {
+    Number(f64),
+    Text(String),
+}
+
+impl SortKey {
+    fn compare(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(b),
+            (SortKey::Text(a), SortKey::Text(b)) => a.cmp(b),
+            (SortKey::Number(_), SortKey::Text(_)) => std::cmp::Ordering::Less,
+            (SortKey::Text(_), SortKey::Number(_)) => std::cmp::Ordering::Greater,
+        }
+    }
+}
+
+/// Where a new member goes when it is spliced into the text (JSONC mode)
+struct MemberInsertion {
+    /// Byte range replaced by the member (empty unless the container was blank)
+    range: std::ops::Range<usize>,
+    /// Text before the member (separator and line break)
+    before: String,
+    /// Text after the member
+    after: String,
+    /// Indentation of the member's line, or None to keep it on one line
+    indent: Option<String>,
+}
+
+/// Last keystroke of the current typing burst (see `record_typing`)
+#[derive(Clone, Copy)]
+struct TypingBurst {
+    /// Input time of the keystroke, in seconds
+    time: f64,
+    /// Whether it deleted text (a burst doesn't mix insertions and deletions)
+    deleting: bool,
+    /// Caret char index after the keystroke
+    caret: usize,
+}
+
+/// Difference between two texts: `removed` chars at char index `start` were
+/// replaced by `inserted` chars
+struct TextChange {
+    start: usize,
+    removed: usize,
+    inserted: usize,
+    /// First inserted character (None for pure deletions)
+    first_inserted: Option<char>,
+}
+
+/// Text kept in the undo/redo history, with its text version (see `text_version`)
+struct HistoryEntry {
+    text: String,
+    version: u64,
+}
+
+/// Lint results for one document revision and lint settings
+struct LintCache {
+    revision: u64,
+    settings: LintSettings,
+    lints: Vec<Lint>,
+    /// Deepest leaf path and its depth
+    deepest: (Vec<String>, usize),
+}
+
+/// State of the "Diff vs Clipboard" dialog
+#[derive(Debug, Default)]
+struct ClipboardDiff {
+    /// Clipboard text (read directly, or pasted into the dialog)
+    text: String,
+    /// Diff result, or an error message if the text isn't valid JSON
+    result: Option<Result<Vec<DiffEntry>, String>>,
+}
+
 /// JSON Editor state and functionality
+///
+/// Only `ui` needs egui; the editing API works headlessly on its own:
+/// - document: `with_text`, `set_text`, `text`, `is_valid`, `error`, `parsed_value`,
+///   `parsed_value_owned`
+/// - edits (each an undo step, returning `EditorError` on failure):
+///   `update_value_at_path`, `add_value_at_path`, `delete_value_at_path`,
+///   `rename_key_at_path`
+/// - formatting: `apply_pretty_print`, `apply_compact`, `format_selection`, `set_indent_size`
+/// - history: `undo`, `redo`
+/// - events: `drain_events` returns an `EditEvent` for each applied edit
+///
+/// Paths are lists of object keys and array indices. Use `set_quiet` to keep
+/// progress messages off stdout
 pub struct JsonEditor {
     /// The raw JSON text being edited
     text: String,
@@ -19,18 +278,30 @@ pub struct JsonEditor {
     previous_text: String,
     /// Parsed JSON value (None if invalid)
     parsed_value: Option<Value>,
-    /// Last validation error message
-    error_message: Option<String>,
+    /// Last validation error
+    error: Option<EditorError>,
     /// Whether to show pretty-printed JSON
     pretty_print: bool,
     /// Current indentation level for pretty printing
     indent_size: usize,
+    /// Indent pretty-printed output with tabs instead of `indent_size` spaces
+    indent_with_tabs: bool,
+    /// Compact output escapes non-ASCII characters as `\uXXXX`
+    escape_non_ascii: bool,
+    /// Pretty-print emits object keys in the order they appear in the text
+    source_key_order: bool,
     /// Undo history stack
-    undo_stack: Vec<String>,
+    undo_stack: Vec<HistoryEntry>,
     /// Redo history stack
-    redo_stack: Vec<String>,
+    redo_stack: Vec<HistoryEntry>,
     /// Maximum history size
     max_history: usize,
+    /// Nesting depth of open undo groups (0 = no group)
+    undo_group_depth: usize,
+    /// Whether the open undo group has already pushed its undo entry
+    undo_group_pushed: bool,
+    /// Last keystroke of the current typing burst (None = no burst)
+    typing_burst: Option<TypingBurst>,
     /// Show line numbers
     show_line_numbers: bool,
     /// Target line to scroll to (None if no scroll needed)
@@ -39,6 +310,58 @@ pub struct JsonEditor {
     clicked_line: Option<usize>,
     /// Current view mode
     view_mode: ViewMode,
+    /// Enabled lints for the Lints panel
+    lint_settings: LintSettings,
+    /// Color JSON syntax in the text editor
+    syntax_highlight: bool,
+    /// Wrap long lines in the text editor instead of scrolling horizontally
+    /// The gutter still numbers logical lines (placed at each line's first row),
+    /// and scrolling to a line assumes one row per line
+    wrap_lines: bool,
+    /// Cached highlighting for the current text
+    highlight_cache: highlight::HighlightCache,
+    /// Char index of the bracket that breaks nesting, while the document is invalid
+    unbalanced_bracket: Option<usize>,
+    /// Cached document statistics for the current text
+    stats_cache: stats::StatsCache,
+    /// Bumped whenever the text or the parsed document changes (see `revision`)
+    revision: u64,
+    /// Identifies the current text (see `text_version`)
+    text_version: u64,
+    /// Last text version handed out
+    last_text_version: u64,
+    /// Lints of the current revision, recomputed when it or the settings change
+    lint_cache: Option<LintCache>,
+    /// Accept trailing commas (e.g. `{"a":1,}`) when parsing
+    tolerate_trailing_commas: bool,
+    /// NDJSON mode: each non-blank line is a separate record, shown as an array
+    ndjson: bool,
+    /// JSONC mode: accept `//` and `/* */` comments, and apply value/key edits
+    /// directly to the text so comments survive
+    allow_comments: bool,
+    /// Go-to-line dialog input (None if the dialog is closed)
+    goto_line_input: Option<String>,
+    /// Why the last "Format Selection" did nothing (cleared by the next edit)
+    format_selection_hint: Option<String>,
+    /// Diff vs Clipboard dialog (None if the dialog is closed)
+    clipboard_diff: Option<ClipboardDiff>,
+    /// Diff vs Clipboard was clicked and waits for the clipboard text
+    /// (see `diff_clipboard_text`)
+    clipboard_diff_requested: bool,
+    /// Called from `validate` when the document flips between valid and invalid
+    on_validity_change: Option<Box<dyn FnMut(bool)>>,
+    /// Don't log progress messages to the console
+    quiet: bool,
+    /// Pretty-print the text shortly after each edit that leaves it valid
+    auto_format: bool,
+    /// Input time at which the pending auto-format runs (None = nothing pending)
+    auto_format_due: Option<f64>,
+    /// Input time at which typed text is re-validated (None = validation is current)
+    validation_due: Option<f64>,
+    /// Structured edits applied since the last `drain_events`
+    events: Vec<EditEvent>,
+    /// Tree view state: selection, display options, inline edits and expansion
+    tree: TreeView,
 }
 
 impl Default for JsonEditor {
@@ -62,16 +385,46 @@ impl Default for JsonEditor {
             text: default_json.to_string(),
             previous_text: default_json.to_string(),
             parsed_value: serde_json::from_str(default_json).ok(),
-            error_message: None,
+            error: None,
             pretty_print: true,
             indent_size: 2,
+            indent_with_tabs: false,
+            escape_non_ascii: false,
+            source_key_order: false,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            undo_group_depth: 0,
+            undo_group_pushed: false,
+            typing_burst: None,
             max_history: 100,
             show_line_numbers: true,
             target_line: None,
             clicked_line: None,
             view_mode: ViewMode::Text,
+            lint_settings: LintSettings::default(),
+            syntax_highlight: true,
+            wrap_lines: false,
+            highlight_cache: highlight::HighlightCache::new(),
+            stats_cache: stats::StatsCache::new(),
+            revision: 0,
+            text_version: 0,
+            last_text_version: 0,
+            lint_cache: None,
+            unbalanced_bracket: None,
+            tolerate_trailing_commas: false,
+            allow_comments: false,
+            ndjson: false,
+            goto_line_input: None,
+            format_selection_hint: None,
+            clipboard_diff: None,
+            clipboard_diff_requested: false,
+            on_validity_change: None,
+            quiet: false,
+            auto_format: false,
+            auto_format_due: None,
+            validation_due: None,
+            events: Vec::new(),
+            tree: TreeView::new(),
         }
     }
 }
@@ -86,18 +439,8 @@ impl JsonEditor {
     pub fn with_text(text: String) -> Self {
         let mut editor = Self {
             text: text.clone(),
-            previous_text: text.clone(),
-            parsed_value: None,
-            error_message: None,
-            pretty_print: true,
-            indent_size: 2,
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-            max_history: 100,
-            show_line_numbers: true,
-            target_line: None,
-            clicked_line: None,
-            view_mode: ViewMode::Text,
+            previous_text: text,
+            ..Self::default()
         };
         editor.validate();
         editor
@@ -108,6 +451,33 @@ impl JsonEditor {
         &self.text
     }
 
+    /// Counter that changes whenever the text or the parsed document changes
+    /// Anything derived from the document can be cached until it moves on
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Identifies the current text: every edit gives it a new version, and undo/redo
+    /// bring back the version of the text they restore
+    /// Comparing versions tells whether the text changed since, without comparing text
+    pub fn text_version(&self) -> u64 {
+        self.text_version
+    }
+
+    /// Give the text a new version (it is about to change)
+    fn new_text_version(&mut self) {
+        self.last_text_version += 1;
+        self.text_version = self.last_text_version;
+    }
+
+    /// The current text with its version, for the undo/redo history
+    fn history_entry(&self) -> HistoryEntry {
+        HistoryEntry {
+            text: self.text.clone(),
+            version: self.text_version,
+        }
+    }
+
     /// Set new text content
     pub fn set_text(&mut self, text: String) {
         self.push_undo();
@@ -117,20 +487,169 @@ impl JsonEditor {
     }
 
     /// Push current text to undo stack
+    /// Inside an undo group only the first push is kept, so the group undoes as one step
+    /// Called right before the text changes, so it also gives the text a new version
     fn push_undo(&mut self) {
-        self.undo_stack.push(self.text.clone());
+        // Any non-typing change ends the current typing burst
+        self.typing_burst = None;
+        let entry = self.history_entry();
+        self.new_text_version();
+        if self.undo_group_depth > 0 {
+            if self.undo_group_pushed {
+                return;
+            }
+            self.undo_group_pushed = true;
+        }
+        self.undo_stack.push(entry);
+        self.trim_history();
+        self.redo_stack.clear();
+    }
+
+    /// Start grouping changes into a single undo step
+    /// Groups nest; the step is closed by the matching `end_undo_group`
+    pub fn begin_undo_group(&mut self) {
+        if self.undo_group_depth == 0 {
+            self.undo_group_pushed = false;
+        }
+        self.undo_group_depth += 1;
+        self.typing_burst = None;
+    }
+
+    /// Close the undo group opened by `begin_undo_group`
+    pub fn end_undo_group(&mut self) {
+        self.undo_group_depth = self.undo_group_depth.saturating_sub(1);
+    }
+
+    /// Record a text change typed in the editor (at input time `now`, in seconds)
+    /// Keystrokes are merged into the previous undo step while the burst continues:
+    /// less than `TYPING_UNDO_GROUP_SECS` apart, at the caret the last keystroke left,
+    /// all insertions or all deletions, and not inserting whitespace or a structural
+    /// character. Pastes and selection replacements are steps of their own
+    fn record_typing(&mut self, previous: String, now: f64) {
+        let change = Self::text_change(&previous, &self.text);
+        let deleting = change.inserted == 0;
+        // One key, or one IME composition step
+        let keystroke = change.removed <= 1 && change.inserted <= 2;
+        let continues_burst = self.undo_group_depth == 0
+            && keystroke
+            && !change.first_inserted.is_some_and(Self::is_undo_boundary)
+            && self.typing_burst.is_some_and(|burst| {
+                now - burst.time < TYPING_UNDO_GROUP_SECS
+                    && burst.deleting == deleting
+                    && (change.start..=change.start + change.removed).contains(&burst.caret)
+            });
+
+        // Inside an undo group only the group's first change is recorded
+        let group_recorded = self.undo_group_depth > 0 && self.undo_group_pushed;
+        if !continues_burst && !group_recorded {
+            self.undo_group_pushed = self.undo_group_depth > 0;
+            self.undo_stack.push(HistoryEntry {
+                text: previous,
+                version: self.text_version,
+            });
+            self.trim_history();
+        }
+        self.new_text_version();
+        self.redo_stack.clear();
+        self.typing_burst = Some(TypingBurst {
+            time: now,
+            deleting,
+            caret: change.start + change.inserted,
+        });
+    }
+
+    /// Find the changed range between two texts (common prefix and suffix removed)
+    fn text_change(previous: &str, current: &str) -> TextChange {
+        let start = previous
+            .chars()
+            .zip(current.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let previous_len = previous.chars().count();
+        let current_len = current.chars().count();
+        let suffix = previous
+            .chars()
+            .rev()
+            .zip(current.chars().rev())
+            .take(previous_len.min(current_len) - start)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let inserted = current_len - start - suffix;
+        TextChange {
+            start,
+            removed: previous_len - start - suffix,
+            inserted,
+            first_inserted: current.chars().nth(start).filter(|_| inserted > 0),
+        }
+    }
+
+    /// Typing one of these starts a new undo step (word and token boundaries)
+    fn is_undo_boundary(c: char) -> bool {
+        c.is_whitespace() || matches!(c, '{' | '}' | '[' | ']' | ',' | ':' | '"')
+    }
+
+    /// Drop the oldest undo entries beyond `max_history`
+    fn trim_history(&mut self) {
         if self.undo_stack.len() > self.max_history {
-            self.undo_stack.remove(0);
+            let excess = self.undo_stack.len() - self.max_history;
+            self.undo_stack.drain(..excess);
         }
+    }
+
+    /// Set the maximum number of undo steps
+    /// The undo stack is trimmed immediately if it exceeds the new limit
+    pub fn set_max_history(&mut self, max_history: usize) {
+        self.max_history = max_history;
+        self.trim_history();
+    }
+
+    /// Get the maximum number of undo steps
+    pub fn max_history(&self) -> usize {
+        self.max_history
+    }
+
+    /// Clear both undo and redo history
+    /// Useful after loading a new document so undo can't revert to the previous one
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
         self.redo_stack.clear();
+        self.previous_text = self.text.clone();
+    }
+
+    /// Start over with an empty Object (`{}`), forgetting undo/redo history
+    pub fn clear(&mut self) {
+        self.reset_document("{}");
+    }
+
+    /// Start over with an empty Array (`[]`), forgetting undo/redo history
+    pub fn clear_to_array(&mut self) {
+        self.reset_document("[]");
+    }
+
+    /// Replace the document with `text` and drop everything tied to the old one
+    /// (history, pending timers, tree view edits and expansion state)
+    fn reset_document(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.new_text_version();
+        self.typing_burst = None;
+        self.auto_format_due = None;
+        self.validation_due = None;
+        self.format_selection_hint = None;
+        self.target_line = None;
+        self.tree.reset();
+        self.clear_history();
+        self.validate();
+        self.log_to_console("Document cleared");
     }
 
     /// Undo last change
     pub fn undo(&mut self) -> bool {
+        self.typing_burst = None;
         if let Some(previous) = self.undo_stack.pop() {
-            self.redo_stack.push(self.text.clone());
-            self.text = previous.clone();
-            self.previous_text = previous;
+            self.redo_stack.push(self.history_entry());
+            self.text = previous.text.clone();
+            self.previous_text = previous.text;
+            self.text_version = previous.version;
             self.validate();
             self.log_to_console("Undo");
             true
@@ -141,10 +660,12 @@ impl JsonEditor {
 
     /// Redo last undone change
     pub fn redo(&mut self) -> bool {
+        self.typing_burst = None;
         if let Some(next) = self.redo_stack.pop() {
-            self.undo_stack.push(self.text.clone());
-            self.text = next.clone();
-            self.previous_text = next;
+            self.undo_stack.push(self.history_entry());
+            self.text = next.text.clone();
+            self.previous_text = next.text;
+            self.text_version = next.version;
             self.validate();
             self.log_to_console("Redo");
             true
@@ -169,93 +690,247 @@ impl JsonEditor {
         self.log_to_console(&format!("Scroll to line {}", line));
     }
 
+    /// Scroll offset that places a 1-indexed line in the vertical center of the viewport
+    fn scroll_offset_for_line(line: usize, viewport_height: f32) -> f32 {
+        let line_top = line.saturating_sub(1) as f32 * LINE_HEIGHT;
+        (line_top - (viewport_height - LINE_HEIGHT) / 2.0).max(0.0)
+    }
+
     /// Find line number for a JSON path
     /// Returns the line number (1-indexed) where the path can be found
+    ///
+    /// Lines come from a token-level source map, so keys are matched by their actual
+    /// nesting (a key name reused at other depths or as a value never matches).
+    /// If the full path is missing, the line of the deepest matched prefix is returned
     pub fn find_line_for_path(&self, path: &[String]) -> Option<usize> {
         if path.is_empty() {
             return Some(1); // Root is at line 1
         }
 
-        let lines: Vec<&str> = self.text.lines().collect();
-        let mut current_line = 0;
-        let mut path_index = 0;
+        // NDJSON: the first segment is the record index
+        if self.ndjson {
+            let index = match Self::parse_index_segment(&path[0]) {
+                Ok(index) => index,
+                Err(reason) => {
+                    Self::warn_invalid_segment(path, 0, &reason);
+                    return None;
+                }
+            };
+            return self.ndjson_record_lines().get(index).copied();
+        }
+
+        let spans = source_map::value_spans(&self.text);
+        (1..=path.len()).rev().find_map(|len| {
+            source_map::find_span(&spans, &path[..len])
+                .map(|span| Self::line_of_byte(&self.text, Self::span_start(span)))
+        })
+    }
 
-        for (line_num, line) in lines.iter().enumerate() {
-            let trimmed = line.trim();
+    /// Start of a span: its key if it has one, otherwise the value
+    fn span_start(span: &source_map::ValueSpan) -> usize {
+        span.key.as_ref().map_or(span.value.start, |key| key.start)
+    }
 
-            // Check if this line contains the current path segment
-            if path_index < path.len() {
-                let segment = &path[path_index];
+    /// 1-indexed line number containing a byte offset
+    fn line_of_byte(text: &str, byte: usize) -> usize {
+        text[..byte].matches('\n').count() + 1
+    }
 
-                // For object keys
-                if trimmed.contains(&format!("\"{}\"", segment)) {
-                    current_line = line_num + 1; // 1-indexed
-                    path_index += 1;
+    /// Byte offsets where each line starts (the first is always 0)
+    fn line_starts(text: &str) -> Vec<usize> {
+        std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect()
+    }
 
-                    // If we've found all path segments, return this line
-                    if path_index == path.len() {
-                        return Some(current_line);
-                    }
-                }
-            }
+    /// 1-indexed line number containing a byte offset, given `line_starts`
+    fn line_at(line_starts: &[usize], byte: usize) -> usize {
+        line_starts.partition_point(|&start| start <= byte)
+    }
+
+    /// Parse go-to-line input
+    /// Returns the line number if it is within 1..=line_count
+    fn parse_goto_line(input: &str, line_count: usize) -> Option<usize> {
+        input
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|line| (1..=line_count).contains(line))
+    }
+
+    /// Whitespace to insert after a newline typed at char index `newline_char`
+    /// Keeps the previous line's indentation, plus one step after '{' or '['
+    fn auto_indent_for_newline(text: &str, newline_char: usize, indent_unit: &str) -> String {
+        let newline_byte = text
+            .char_indices()
+            .nth(newline_char)
+            .map_or(text.len(), |(i, _)| i);
+        let before = &text[..newline_byte];
+        let previous_line = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+
+        let mut indent: String = previous_line
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        if previous_line.trim_end().ends_with(['{', '[']) {
+            indent.push_str(indent_unit);
         }
+        indent
+    }
 
-        // If we didn't find the exact path, return the last matched line
-        if current_line > 0 {
-            Some(current_line)
+    /// Index in `brackets` of the bracket matching `brackets[index]`
+    fn match_bracket_token(brackets: &[(usize, u8)], index: usize) -> Option<usize> {
+        let (open, close) = match brackets[index].1 {
+            b'{' | b'}' => (b'{', b'}'),
+            _ => (b'[', b']'),
+        };
+        let forward = brackets[index].1 == open;
+        let (same, other) = if forward {
+            (open, close)
         } else {
-            None
+            (close, open)
+        };
+
+        let mut depth = 0usize;
+        let order: Box<dyn Iterator<Item = (usize, &(usize, u8))>> = if forward {
+            Box::new(brackets.iter().enumerate().skip(index))
+        } else {
+            Box::new(brackets[..=index].iter().enumerate().rev())
+        };
+        for (i, (_, b)) in order {
+            if *b == same {
+                depth += 1;
+            } else if *b == other {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
         }
+        None
     }
 
-    /// Get and clear the clicked line (for one-time event handling)
-    pub fn take_clicked_line(&mut self) -> Option<usize> {
-        self.clicked_line.take()
+    /// Char offset of the bracket matching the bracket at char `offset`
+    /// Returns None if there's no structural bracket at `offset` (e.g. it's inside a
+    /// string) or it's unmatched. The text is only tokenized again after it changes
+    pub fn matching_brace(&mut self, offset: usize) -> Option<usize> {
+        let byte = self.text.char_indices().nth(offset)?.0;
+        let brackets = self.highlight_cache.brackets(self.revision, &self.text);
+        let index = brackets.iter().position(|(start, _)| *start == byte)?;
+        let other = Self::match_bracket_token(brackets, index)?;
+        Some(self.text[..brackets[other].0].chars().count())
     }
 
-    /// Find JSON path for a given line number
-    /// This is a reverse lookup: line number -> JSON path
-    pub fn find_path_for_line(&self, target_line: usize) -> Option<Vec<String>> {
-        if target_line == 0 {
-            return None;
-        }
-
-        let lines: Vec<&str> = self.text.lines().collect();
-        if target_line > lines.len() {
+    /// The bracket next to the cursor and its match (see `matching_bracket`)
+    /// Returns right away unless a bracket character touches the cursor; otherwise
+    /// the brackets come from the highlight cache, tokenized once per text change
+    fn bracket_pair_at_cursor(&mut self, cursor_char: usize) -> Option<(usize, usize)> {
+        let first = cursor_char.saturating_sub(1);
+        let touches_bracket = self
+            .text
+            .chars()
+            .skip(first)
+            .take(cursor_char + 1 - first)
+            .any(|c| matches!(c, '{' | '}' | '[' | ']'));
+        if !touches_bracket {
             return None;
         }
+        let brackets = self.highlight_cache.brackets(self.revision, &self.text);
+        Self::matching_bracket(&self.text, brackets, cursor_char)
+    }
 
-        // Simple heuristic: look for JSON keys on or near the target line
-        let mut path = Vec::new();
+    /// Find the bracket adjacent to the cursor and its matching bracket
+    /// `brackets` are the text's structural brackets (see `highlight::bracket_tokens`)
+    /// Checks the character before the cursor first, then the one after
+    /// Returns the char indices of both brackets; brackets inside strings are ignored
+    fn matching_bracket(
+        text: &str,
+        brackets: &[(usize, u8)],
+        cursor_char: usize,
+    ) -> Option<(usize, usize)> {
+        let char_to_byte = |c: usize| text.char_indices().nth(c).map(|(i, _)| i);
+        let candidates = [
+            cursor_char.checked_sub(1).and_then(char_to_byte),
+            char_to_byte(cursor_char),
+        ];
 
-        // Start from beginning and track the path to target_line
-        for line_num in 1..=target_line {
-            if line_num > lines.len() {
-                break;
+        for byte in candidates.into_iter().flatten() {
+            let Some(index) = brackets.iter().position(|(start, _)| *start == byte) else {
+                continue;
+            };
+            if let Some(other) = Self::match_bracket_token(brackets, index) {
+                let to_char = |byte: usize| text[..byte].chars().count();
+                return Some((to_char(byte), to_char(brackets[other].0)));
             }
+        }
 
-            let line = lines[line_num - 1];
-            let trimmed = line.trim();
+        None
+    }
 
-            // Look for object keys: "key":
-            if let Some(key_start) = trimmed.find('"')
-                && let Some(key_end) = trimmed[key_start + 1..].find('"')
-            {
-                let key = &trimmed[key_start + 1..key_start + 1 + key_end];
-                // Check if this is followed by a colon (indicating it's a key)
-                if trimmed[key_start + 1 + key_end + 1..]
-                    .trim_start()
-                    .starts_with(':')
-                {
-                    // This is a key - add to path if we're building towards target
-                    if line_num <= target_line {
-                        path.push(key.to_string());
+    /// Char index of the first bracket that breaks nesting: a closing bracket of the
+    /// wrong kind (or with nothing open), or else the innermost bracket left unclosed
+    /// `brackets` are the text's structural brackets (see `highlight::bracket_tokens`)
+    /// Returns None if the brackets are balanced
+    fn first_unbalanced_bracket(text: &str, brackets: &[(usize, u8)]) -> Option<usize> {
+        let mut open: Vec<(usize, u8)> = Vec::new();
+        let mut unbalanced = None;
+        for &(start, b) in brackets {
+            match b {
+                b'{' | b'[' => open.push((start, b)),
+                _ => {
+                    let expected = if b == b'}' { b'{' } else { b'[' };
+                    if open.pop().map(|(_, o)| o) != Some(expected) {
+                        unbalanced = Some(start);
+                        break;
                     }
                 }
             }
         }
+        let byte = unbalanced.or_else(|| open.last().map(|(start, _)| *start))?;
+        Some(text[..byte].chars().count())
+    }
+
+    /// Get and clear the clicked line (for one-time event handling)
+    pub fn take_clicked_line(&mut self) -> Option<usize> {
+        self.clicked_line.take()
+    }
+
+    /// Take the structured edits applied since the last call (oldest first)
+    /// Events accumulate until drained, so poll this regularly (`App` does every frame)
+    pub fn drain_events(&mut self) -> Vec<EditEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Find JSON path for a given line number
+    /// This is a reverse lookup: line number -> path of the innermost Object/Array
+    /// whose source spans that line (an empty path is the root container)
+    pub fn find_path_for_line(&self, target_line: usize) -> Option<Vec<String>> {
+        if target_line == 0 {
+            return None;
+        }
+
+        // NDJSON: each line is a record of the synthetic root array
+        if self.ndjson {
+            return self
+                .ndjson_record_lines()
+                .iter()
+                .position(|line| *line == target_line)
+                .map(|index| vec![index.to_string()]);
+        }
 
-        if path.is_empty() { None } else { Some(path) }
+        let spans = source_map::value_spans(&self.text);
+        let line_starts = Self::line_starts(&self.text);
+        spans
+            .iter()
+            .enumerate()
+            .filter(|(_, span)| {
+                let value_text = &self.text[span.value.clone()];
+                (value_text.starts_with('{') || value_text.starts_with('['))
+                    && Self::line_at(&line_starts, Self::span_start(span)) <= target_line
+                    && Self::line_at(&line_starts, span.value.end) >= target_line
+            })
+            .max_by_key(|(_, span)| span.depth)
+            .map(|(index, _)| source_map::span_path(&spans, index))
     }
 
     /// Toggle line numbers
@@ -263,717 +938,4089 @@ impl JsonEditor {
         self.show_line_numbers = !self.show_line_numbers;
     }
 
-    /// Validate the JSON syntax
-    pub fn validate(&mut self) -> bool {
-        match serde_json::from_str::<Value>(&self.text) {
-            Ok(value) => {
-                self.parsed_value = Some(value);
-                self.error_message = None;
-                true
-            }
-            Err(e) => {
-                self.parsed_value = None;
-                self.error_message = Some(format!("JSON Error: {}", e));
-                false
-            }
+    /// Set how numbers are displayed in the tree view (the text is never changed)
+    pub fn set_number_format(&mut self, number_format: NumberFormat) {
+        self.tree.set_number_format(number_format);
+    }
+
+    /// Get the tree view number format
+    pub fn number_format(&self) -> NumberFormat {
+        self.tree.number_format()
+    }
+
+    /// Enable or disable auto-format (pretty-print shortly after each valid edit)
+    pub fn set_auto_format(&mut self, enabled: bool) {
+        self.auto_format = enabled;
+        if !enabled {
+            self.auto_format_due = None;
         }
     }
 
-    /// Get the validation error message if any
-    pub fn error_message(&self) -> Option<&str> {
-        self.error_message.as_deref()
+    /// Check if auto-format is enabled
+    pub fn auto_format(&self) -> bool {
+        self.auto_format
     }
 
-    /// Check if the current JSON is valid
-    pub fn is_valid(&self) -> bool {
-        self.parsed_value.is_some()
+    /// Schedule an auto-format after a text edit at input time `now`
+    /// Nothing is scheduled while the text is invalid or when formatting would drop comments
+    fn schedule_auto_format(&mut self, now: f64) {
+        self.auto_format_due =
+            (self.auto_format && self.is_valid() && !self.ndjson && !self.edits_in_place())
+                .then_some(now + AUTO_FORMAT_DELAY_SECS);
     }
 
-    /// Get the parsed JSON value
-    pub fn parsed_value(&self) -> Option<&Value> {
-        self.parsed_value.as_ref()
+    /// Run the pending auto-format if it is due at input time `now`
+    /// `caret` is the cursor's char index; returns its equivalent index in the
+    /// reformatted text, or None if the text wasn't changed
+    fn run_auto_format(&mut self, now: f64, caret: Option<usize>) -> Option<usize> {
+        if self.auto_format_due.is_none_or(|due| now < due) {
+            return None;
+        }
+        self.auto_format_due = None;
+
+        let pretty = self
+            .parsed_value
+            .as_ref()
+            .and_then(|v| self.to_pretty_string(v))?;
+        if pretty == self.text {
+            return None;
+        }
+        let caret = Self::equivalent_offset(&self.text, &pretty, caret.unwrap_or(0));
+        self.push_undo();
+        self.text = pretty.clone();
+        self.previous_text = pretty;
+        self.revision += 1;
+        self.log_to_console("Auto-formatted");
+        Some(caret)
     }
 
-    /// Apply pretty printing to the JSON
-    pub fn apply_pretty_print(&mut self) {
-        if let Some(ref value) = self.parsed_value
-            && let Ok(pretty) = serde_json::to_string_pretty(value)
-        {
-            self.text = pretty.clone();
-            self.previous_text = pretty;
-            self.log_to_console("Applied pretty print");
+    /// Map a char index in `old` to the equivalent index in `new`, where the two texts
+    /// differ only in whitespace: the caret stays after the same non-whitespace character
+    fn equivalent_offset(old: &str, new: &str, char_index: usize) -> usize {
+        let content_chars = old
+            .chars()
+            .take(char_index)
+            .filter(|c| !c.is_whitespace())
+            .count();
+        if content_chars == 0 {
+            return 0;
         }
+        new.chars()
+            .enumerate()
+            .filter(|(_, c)| !c.is_whitespace())
+            .nth(content_chars - 1)
+            .map_or(new.chars().count(), |(index, _)| index + 1)
     }
 
-    /// Compact the JSON (remove unnecessary whitespace)
-    pub fn apply_compact(&mut self) {
-        if let Some(ref value) = self.parsed_value
-            && let Ok(compact) = serde_json::to_string(value)
-        {
-            self.text = compact.clone();
-            self.previous_text = compact;
-            self.log_to_console("Applied compact format");
+    /// Record text typed in the editor at input time `now`
+    /// Validation (and auto-format after it) waits until typing pauses
+    fn text_typed(&mut self, now: f64) {
+        if self.text != self.previous_text {
+            self.revision += 1;
+            let previous = std::mem::take(&mut self.previous_text);
+            self.record_typing(previous, now);
+            self.previous_text = self.text.clone();
         }
+        self.validation_due = Some(now + VALIDATE_DELAY_SECS);
+        self.auto_format_due = None;
+        self.format_selection_hint = None;
     }
 
-    /// Toggle pretty print mode
-    pub fn toggle_pretty_print(&mut self) {
-        self.pretty_print = !self.pretty_print;
-        if self.pretty_print {
-            self.apply_pretty_print();
-        } else {
-            self.apply_compact();
+    /// Run the pending validation of typed text if it is due at input time `now`
+    /// Returns whether it ran
+    fn run_due_validation(&mut self, now: f64) -> bool {
+        if self.validation_due.is_none_or(|due| now < due) {
+            return false;
         }
+        self.validate();
+        self.schedule_auto_format(now);
+        true
     }
 
-    /// Set indent size for pretty printing
-    pub fn set_indent_size(&mut self, size: usize) {
-        self.indent_size = size;
+    /// Validate right away if typed text is still waiting for its validation
+    /// Called before anything reads the parsed document to change the text
+    fn flush_validation(&mut self) {
+        if self.validation_due.is_some() {
+            self.validate();
+        }
     }
 
-    /// Get current indent size
-    pub fn indent_size(&self) -> usize {
-        self.indent_size
+    /// Set a callback fired when validation flips the document between valid and invalid
+    /// It receives the new validity; re-validating without a change doesn't fire it
+    pub fn set_on_validity_change(&mut self, callback: impl FnMut(bool) + 'static) {
+        self.on_validity_change = Some(Box::new(callback));
     }
 
-    /// Toggle view mode between Text and Tree
-    pub fn toggle_view_mode(&mut self) {
-        self.view_mode = match self.view_mode {
-            ViewMode::Text => ViewMode::Tree,
-            ViewMode::Tree => ViewMode::Text,
+    /// Validate the JSON syntax
+    /// Fires `on_validity_change` if the validity changed
+    pub fn validate(&mut self) -> bool {
+        self.validation_due = None;
+        self.revision += 1;
+        let was_valid = self.is_valid();
+        let valid = self.parse_text();
+        // Found once per validation, from the brackets the highlighter already found
+        self.unbalanced_bracket = if valid {
+            None
+        } else {
+            let brackets = self.highlight_cache.brackets(self.revision, &self.text);
+            Self::first_unbalanced_bracket(&self.text, brackets)
         };
-        self.log_to_console(&format!("View mode: {:?}", self.view_mode));
+        if valid != was_valid
+            && let Some(callback) = &mut self.on_validity_change
+        {
+            callback(valid);
+        }
+        valid
     }
 
-    /// Update a value at a specific JSON path
-    /// Returns true if the update succeeded
-    pub fn update_value_at_path(&mut self, path: &[String], new_value_str: &str) -> bool {
-        if let Some(mut value) = self.parsed_value.clone() {
-            // Navigate to the target location
-            if let Some(target) = Self::navigate_to_path_mut(&mut value, path) {
-                // Parse the new value based on its format
-                let new_value = if new_value_str.starts_with('"') && new_value_str.ends_with('"') {
-                    // It's a string (with quotes)
-                    serde_json::Value::String(new_value_str[1..new_value_str.len() - 1].to_string())
-                } else if let Ok(num) = new_value_str.parse::<f64>() {
-                    // It's a number
-                    serde_json::json!(num)
-                } else if new_value_str == "true" {
-                    serde_json::Value::Bool(true)
-                } else if new_value_str == "false" {
-                    serde_json::Value::Bool(false)
-                } else if new_value_str == "null" {
-                    serde_json::Value::Null
-                } else {
-                    // Default to string without quotes
-                    serde_json::Value::String(new_value_str.to_string())
-                };
+    /// Parse the text into `parsed_value`, recording any error
+    /// With `tolerate_trailing_commas` or `allow_comments`, trailing commas and comments
+    /// are stripped before parsing (the displayed text is left untouched)
+    fn parse_text(&mut self) -> bool {
+        if self.ndjson {
+            return match parse_ndjson(&self.text) {
+                Ok(records) => {
+                    self.parsed_value = Some(Value::Array(records));
+                    self.error = None;
+                    true
+                }
+                Err((line, e)) => {
+                    self.parsed_value = None;
+                    self.error = Some(EditorError::RecordError { line, msg: e });
+                    false
+                }
+            };
+        }
 
-                *target = new_value;
+        let mut result = serde_json::from_str::<Value>(&self.text);
+        if result.is_err() && (self.tolerate_trailing_commas || self.allow_comments) {
+            let source = Self::lenient_source(
+                &self.text,
+                self.allow_comments,
+                self.tolerate_trailing_commas,
+            );
+            if let Ok(value) = serde_json::from_str::<Value>(&source) {
+                result = Ok(value);
+            }
+        }
 
-                // Update the text with pretty-printed JSON
-                if let Ok(pretty) = serde_json::to_string_pretty(&value) {
-                    self.push_undo();
-                    self.text = pretty.clone();
-                    self.previous_text = pretty;
-                    self.parsed_value = Some(value);
-                    self.error_message = None;
-                    self.log_to_console(&format!("Updated value at path: {:?}", path));
-                    return true;
-                }
+        match result {
+            Ok(value) => {
+                self.parsed_value = Some(value);
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                self.parsed_value = None;
+                self.error = Some(EditorError::from_parse(&e));
+                false
             }
         }
-        false
     }
 
-    /// Navigate to a mutable reference at a JSON path
-    fn navigate_to_path_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
-        let mut current = value;
+    /// Build the text handed to the parser in lenient modes
+    /// Comments are replaced with a space, and commas that directly precede a
+    /// closing '}' or ']' are removed; string contents are preserved
+    fn lenient_source(text: &str, strip_comments: bool, strip_trailing_commas: bool) -> String {
+        let tokens = highlight::tokenize(text);
+        let mut result = String::with_capacity(text.len());
 
-        for segment in path {
-            current = match current {
-                Value::Object(map) => map.get_mut(segment)?,
-                Value::Array(arr) => {
-                    let index: usize = segment.parse().ok()?;
-                    arr.get_mut(index)?
+        for (i, token) in tokens.iter().enumerate() {
+            let token_text = &text[token.start..token.end];
+            if strip_comments && token.kind == highlight::TokenKind::Comment {
+                result.push(' ');
+                continue;
+            }
+            if strip_trailing_commas && token_text == "," {
+                let next = tokens[i + 1..]
+                    .iter()
+                    .find(|t| {
+                        !matches!(
+                            t.kind,
+                            highlight::TokenKind::Whitespace | highlight::TokenKind::Comment
+                        )
+                    })
+                    .map(|t| &text[t.start..t.end]);
+                if matches!(next, Some("}") | Some("]")) {
+                    continue;
                 }
-                _ => return None,
-            };
+            }
+            result.push_str(token_text);
         }
 
-        Some(current)
+        result
     }
 
-    /// Delete a value at a specific JSON path
-    /// Returns true if the delete succeeded
-    pub fn delete_value_at_path(&mut self, path: &[String]) -> bool {
-        if path.is_empty() {
-            return false;
+    /// Whether structured edits are applied directly to the text (JSONC mode)
+    fn edits_in_place(&self) -> bool {
+        self.allow_comments && !self.ndjson
+    }
+
+    /// Enable or disable NDJSON mode (one JSON value per line)
+    /// Records are presented as a synthetic array; re-validates the current text
+    pub fn set_ndjson(&mut self, ndjson: bool) {
+        self.ndjson = ndjson;
+        self.validate();
+    }
+
+    /// Check if NDJSON mode is enabled
+    pub fn ndjson(&self) -> bool {
+        self.ndjson
+    }
+
+    /// 1-indexed lines of the non-blank NDJSON records
+    fn ndjson_record_lines(&self) -> Vec<usize> {
+        self.text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    /// Enable or disable JSONC mode (comments allowed, edits preserve comments)
+    /// Re-validates the current text
+    pub fn set_allow_comments(&mut self, allow: bool) {
+        self.allow_comments = allow;
+        self.validate();
+    }
+
+    /// Check if JSONC mode is enabled
+    pub fn allow_comments(&self) -> bool {
+        self.allow_comments
+    }
+
+    /// Replace a byte range of the text, keeping everything around it
+    /// Used in JSONC mode so structured edits don't reserialize (and drop comments)
+    /// Fails (leaving the text unchanged) if the result doesn't parse
+    fn splice_text(
+        &mut self,
+        range: std::ops::Range<usize>,
+        replacement: &str,
+    ) -> Result<(), EditorError> {
+        let mut text = self.text.clone();
+        text.replace_range(range, replacement);
+
+        let source =
+            Self::lenient_source(&text, self.allow_comments, self.tolerate_trailing_commas);
+        let value = serde_json::from_str::<Value>(&source).map_err(|_| EditorError::InvalidEdit)?;
+
+        self.push_undo();
+        self.text = text.clone();
+        self.previous_text = text;
+        self.parsed_value = Some(value);
+        self.error = None;
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Replace the value at a JSON path directly in the text (JSONC mode)
+    /// Returns the replaced value
+    fn replace_value_in_text(
+        &mut self,
+        path: &[String],
+        new_value: &Value,
+    ) -> Result<Value, EditorError> {
+        let spans = source_map::value_spans(&self.text);
+        let span = source_map::find_span(&spans, path)
+            .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+        let replacement =
+            serde_json::to_string(new_value).map_err(|_| EditorError::SerializeFailed)?;
+
+        let range = span.value.clone();
+        let old_source = Self::lenient_source(
+            &self.text[range.clone()],
+            self.allow_comments,
+            self.tolerate_trailing_commas,
+        );
+        let old_value =
+            serde_json::from_str(&old_source).map_err(|_| EditorError::InvalidDocument)?;
+        self.splice_text(range, &replacement)?;
+        self.log_to_console(&format!("Updated value in source at path: {:?}", path));
+        Ok(old_value)
+    }
+
+    /// Leading spaces/tabs of the line containing a byte offset
+    fn line_indent(text: &str, offset: usize) -> &str {
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = &text[line_start..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    /// Where to splice a new member into the container at a JSON path, before its
+    /// member at `index` (`index` equal to the member count appends)
+    /// The separator and line layout are copied from the neighbouring member
+    fn member_insertion(
+        &self,
+        container_path: &[String],
+        index: usize,
+    ) -> Result<MemberInsertion, EditorError> {
+        let spans = source_map::value_spans(&self.text);
+        let container = source_map::find_span(&spans, container_path)
+            .ok_or_else(|| EditorError::PathNotFound(container_path.to_vec()))?;
+        let members = source_map::member_spans(&spans, container_path);
+
+        // Whitespace between a member and whatever precedes it
+        let leading_whitespace = |offset: usize| {
+            let before = &self.text[..offset];
+            &before[before.trim_end().len()..]
+        };
+        let indent_of = |whitespace: &str| {
+            whitespace
+                .rfind('\n')
+                .map(|i| whitespace[i + 1..].to_string())
+        };
+
+        if let Some(member) = members.get(index) {
+            let start = Self::span_start(member);
+            let whitespace = leading_whitespace(start);
+            return Ok(MemberInsertion {
+                range: start..start,
+                before: String::new(),
+                after: format!(",{}", whitespace),
+                indent: indent_of(whitespace),
+            });
+        }
+        if let Some(last) = members.last() {
+            let whitespace = leading_whitespace(Self::span_start(last));
+            return Ok(MemberInsertion {
+                range: last.value.end..last.value.end,
+                before: format!(",{}", whitespace),
+                after: String::new(),
+                indent: indent_of(whitespace),
+            });
         }
 
-        if let Some(mut value) = self.parsed_value.clone() {
-            let parent_path = &path[..path.len() - 1];
-            let key = &path[path.len() - 1];
-
-            if let Some(parent) = Self::navigate_to_path_mut(&mut value, parent_path) {
-                match parent {
-                    Value::Object(map) => {
-                        if map.remove(key).is_some() {
-                            // Update the text with pretty-printed JSON
-                            if let Ok(pretty) = serde_json::to_string_pretty(&value) {
-                                self.push_undo();
-                                self.text = pretty.clone();
-                                self.previous_text = pretty;
-                                self.parsed_value = Some(value);
-                                self.error_message = None;
-                                self.log_to_console(&format!("Deleted property: {}", key));
-                                return true;
-                            }
-                        }
-                    }
-                    Value::Array(arr) => {
-                        if let Ok(index) = key.parse::<usize>()
-                            && index < arr.len()
-                        {
-                            arr.remove(index);
-                            // Update the text with pretty-printed JSON
-                            if let Ok(pretty) = serde_json::to_string_pretty(&value) {
-                                self.push_undo();
-                                self.text = pretty.clone();
-                                self.previous_text = pretty;
-                                self.parsed_value = Some(value);
-                                self.error_message = None;
-                                self.log_to_console(&format!(
-                                    "Deleted array item at index: {}",
-                                    index
-                                ));
-                                return true;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        // Empty container: open it up over lines unless it holds comments
+        let inside = container.value.start + 1..container.value.end - 1;
+        if self.text[inside.clone()].trim().is_empty() {
+            let parent_indent = Self::line_indent(&self.text, container.value.start);
+            let indent = format!("{}{}", parent_indent, self.indent_unit());
+            Ok(MemberInsertion {
+                range: inside,
+                before: format!("\n{}", indent),
+                after: format!("\n{}", parent_indent),
+                indent: Some(indent),
+            })
+        } else {
+            Ok(MemberInsertion {
+                range: inside.start..inside.start,
+                before: " ".to_string(),
+                after: " ".to_string(),
+                indent: None,
+            })
         }
-        false
     }
 
-    /// Add a value at a specific JSON path
-    /// For Objects: key is the property name, value_str is the value
-    /// For Arrays: key is empty, value_str is appended to the array
-    /// Returns true if the add succeeded
-    pub fn add_value_at_path(&mut self, path: &[String], key: &str, value_str: &str) -> bool {
-        if let Some(mut value) = self.parsed_value.clone()
-            && let Some(target) = Self::navigate_to_path_mut(&mut value, path)
-        {
-            // Parse the new value based on its format
-            let new_value = if value_str.starts_with('"') && value_str.ends_with('"') {
-                // It's a string (with quotes)
-                serde_json::Value::String(value_str[1..value_str.len() - 1].to_string())
-            } else if let Ok(num) = value_str.parse::<f64>() {
-                // It's a number
-                serde_json::json!(num)
-            } else if value_str == "true" {
-                serde_json::Value::Bool(true)
-            } else if value_str == "false" {
-                serde_json::Value::Bool(false)
-            } else if value_str == "null" {
-                serde_json::Value::Null
-            } else {
-                // Default to string without quotes
-                serde_json::Value::String(value_str.to_string())
-            };
+    /// Splice a new member into the container at a JSON path (JSONC mode)
+    /// `key` is the property name for Objects and None for Arrays
+    fn insert_member_in_text(
+        &mut self,
+        container_path: &[String],
+        index: usize,
+        key: Option<&str>,
+        value: &Value,
+    ) -> Result<(), EditorError> {
+        let insertion = self.member_insertion(container_path, index)?;
+        let value_text = match &insertion.indent {
+            Some(indent) => self
+                .to_pretty_string(value)
+                .ok_or(EditorError::SerializeFailed)?
+                .replace('\n', &format!("\n{}", indent)),
+            None => serde_json::to_string(value).map_err(|_| EditorError::SerializeFailed)?,
+        };
+        let member = match key {
+            Some(key) => format!(
+                "{}: {}",
+                serde_json::to_string(key).map_err(|_| EditorError::SerializeFailed)?,
+                value_text
+            ),
+            None => value_text,
+        };
+        self.splice_text(
+            insertion.range,
+            &format!("{}{}{}", insertion.before, member, insertion.after),
+        )
+    }
 
-            match target {
-                Value::Object(map) => {
-                    if key.is_empty() {
-                        self.log_to_console("Property name cannot be empty");
-                        return false;
-                    }
-                    // Add new property to object
-                    map.insert(key.to_string(), new_value);
-
-                    // Update the text with pretty-printed JSON
-                    if let Ok(pretty) = serde_json::to_string_pretty(&value) {
-                        self.push_undo();
-                        self.text = pretty.clone();
-                        self.previous_text = pretty;
-                        self.parsed_value = Some(value);
-                        self.error_message = None;
-                        self.log_to_console(&format!("Added property: {} = {}", key, value_str));
-                        return true;
-                    }
-                }
-                Value::Array(arr) => {
-                    // Append new item to array
-                    arr.push(new_value);
-
-                    // Update the text with pretty-printed JSON
-                    if let Ok(pretty) = serde_json::to_string_pretty(&value) {
-                        self.push_undo();
-                        self.text = pretty.clone();
-                        self.previous_text = pretty;
-                        self.parsed_value = Some(value);
-                        self.error_message = None;
-                        self.log_to_console(&format!("Added array item: {}", value_str));
-                        return true;
-                    }
-                }
-                _ => {
-                    self.log_to_console("Cannot add to non-Object/Array value");
-                    return false;
-                }
+    /// Copy the source of the member at a JSON path right after it (JSONC mode),
+    /// named `copy_key` if it is an Object property
+    fn duplicate_member_in_text(
+        &mut self,
+        path: &[String],
+        copy_key: &str,
+    ) -> Result<(), EditorError> {
+        let Some((segment, parent_path)) = path.split_last() else {
+            return Err(EditorError::PathNotFound(path.to_vec()));
+        };
+        let spans = source_map::value_spans(&self.text);
+        let members = source_map::member_spans(&spans, parent_path);
+        let index = members
+            .iter()
+            .rposition(|member| member.segment == *segment)
+            .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+        let source = &self.text[members[index].value.clone()];
+        let member = match members[index].key {
+            Some(_) => format!(
+                "{}: {}",
+                serde_json::to_string(copy_key).map_err(|_| EditorError::SerializeFailed)?,
+                source
+            ),
+            None => source.to_string(),
+        };
+
+        let insertion = self.member_insertion(parent_path, index + 1)?;
+        self.splice_text(
+            insertion.range,
+            &format!("{}{}{}", insertion.before, member, insertion.after),
+        )
+    }
+
+    /// How to delete the member at a JSON path from the text: the byte range to
+    /// replace and its replacement. One adjacent comma goes with the member (and its
+    /// line, if nothing else is on it); comments around it are kept
+    fn member_removal(
+        &self,
+        path: &[String],
+    ) -> Result<(std::ops::Range<usize>, String), EditorError> {
+        let spans = source_map::value_spans(&self.text);
+        let span = source_map::find_span(&spans, path)
+            .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+        let (start, end) = (Self::span_start(span), span.value.end);
+        let text = self.text.as_str();
+
+        let tokens = source_map::significant_tokens(text);
+        let is_comma = |token: &std::ops::Range<usize>| &text[token.clone()] == ",";
+        let next = tokens.iter().find(|token| token.start >= end);
+        let previous = tokens.iter().rev().find(|token| token.end <= start);
+        let (range, kept) = match (next, previous) {
+            (Some(comma), _) if is_comma(comma) => {
+                // The spaces after the comma go too
+                let after = &text[comma.end..];
+                let spaces = after.len() - after.trim_start_matches([' ', '\t']).len();
+                (start..comma.end + spaces, &text[end..comma.start])
+            }
+            // Last member: drop the comma before it, keeping what is between them
+            (_, Some(comma)) if is_comma(comma) => {
+                return Ok((
+                    comma.start..end,
+                    text[comma.end..start].trim_end().to_string(),
+                ));
             }
+            _ => (start..end, ""),
+        };
+
+        let line_start = text[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[range.end..]
+            .find('\n')
+            .map_or(text.len(), |i| range.end + i + 1);
+        if kept.trim().is_empty()
+            && text[line_start..range.start].trim().is_empty()
+            && text[range.end..line_end].trim().is_empty()
+        {
+            Ok((line_start..line_end, String::new()))
+        } else {
+            Ok((range, kept.to_string()))
         }
-        false
     }
 
-    /// Rename a property key in an Object
-    /// Path points to the Object containing the key to rename
-    /// Returns true if the rename succeeded
-    pub fn rename_key_at_path(&mut self, path: &[String], old_key: &str, new_key: &str) -> bool {
-        if let Some(mut value) = self.parsed_value.clone()
-            && let Some(target) = Self::navigate_to_path_mut(&mut value, path)
+    /// Reorder the items of the Array at a JSON path in the text (JSONC mode)
+    /// `order[i]` is the original index of the item that ends up at position `i`;
+    /// the separators between items (and comments in them) stay in place
+    fn reorder_items_in_text(
+        &mut self,
+        path: &[String],
+        order: &[usize],
+    ) -> Result<(), EditorError> {
+        let spans = source_map::value_spans(&self.text);
+        let container = source_map::find_span(&spans, path)
+            .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+        let items = source_map::member_spans(&spans, path);
+        if items.len() != order.len() {
+            return Err(EditorError::InvalidEdit);
+        }
+
+        let range = container.value.clone();
+        let first = items.first().map_or(range.end, |item| item.value.start);
+        let mut text = self.text[range.start..first].to_string();
+        for (position, &source) in order.iter().enumerate() {
+            text.push_str(&self.text[items[source].value.clone()]);
+            let gap_end = items
+                .get(position + 1)
+                .map_or(range.end, |next| next.value.start);
+            text.push_str(&self.text[items[position].value.end..gap_end]);
+        }
+        self.splice_text(range, &text)
+    }
+
+    /// Enable or disable lenient parsing of trailing commas
+    /// Re-validates the current text
+    pub fn set_tolerate_trailing_commas(&mut self, tolerate: bool) {
+        self.tolerate_trailing_commas = tolerate;
+        self.validate();
+    }
+
+    /// Check if trailing commas are tolerated
+    pub fn tolerate_trailing_commas(&self) -> bool {
+        self.tolerate_trailing_commas
+    }
+
+    /// Get the validation error message if any
+    pub fn error_message(&self) -> Option<String> {
+        self.error.as_ref().map(EditorError::to_string)
+    }
+
+    /// Get the validation error if any
+    pub fn error(&self) -> Option<&EditorError> {
+        self.error.as_ref()
+    }
+
+    /// Check if the current JSON is valid
+    /// Whether the Go to Line or Diff vs Clipboard dialog is open
+    pub fn dialog_open(&self) -> bool {
+        self.goto_line_input.is_some() || self.clipboard_diff.is_some()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.parsed_value.is_some()
+    }
+
+    /// Get the parsed JSON value
+    pub fn parsed_value(&self) -> Option<&Value> {
+        self.parsed_value.as_ref()
+    }
+
+    /// Owned copy of the parsed JSON value
+    pub fn parsed_value_owned(&self) -> Option<Value> {
+        self.parsed_value.clone()
+    }
+
+    /// Newline-separated paths of every leaf under the value at `path`
+    /// Paths are relative to that value if `relative`, otherwise from the document root
+    pub fn leaf_paths_at(&self, path: &[String], relative: bool) -> Option<String> {
+        let root = self.parsed_value.as_ref()?;
+        let value = Self::navigate_to_path(root, path)?;
+        let prefix = if relative {
+            String::new()
+        } else {
+            format_json_path(root, path)
+        };
+        Some(all_leaf_paths(value, &prefix).join("\n"))
+    }
+
+    /// Type signature of the value at `path` (see `type_signature`)
+    pub fn type_signature_at(&self, path: &[String]) -> Option<String> {
+        self.value_at_path(path).map(type_signature)
+    }
+
+    /// Lines, characters and top-level keys of the text, plus counts of value types,
+    /// nesting depth and total values (None if the document is invalid)
+    /// Cached: only recomputed when the revision changes
+    pub fn stats(&mut self) -> DocumentStats {
+        self.stats_cache
+            .stats(self.revision, &self.text, self.parsed_value.as_ref())
+    }
+
+    /// Apply pretty printing to the JSON
+    pub fn apply_pretty_print(&mut self) {
+        self.flush_validation();
+        if let Some(ref value) = self.parsed_value
+            && let Some(pretty) = self.serialize_document(value)
         {
-            match target {
-                Value::Object(map) => {
-                    // Check if old key exists
-                    if !map.contains_key(old_key) {
-                        self.log_to_console(&format!("Property '{}' not found", old_key));
-                        return false;
-                    }
+            self.text = pretty.clone();
+            self.previous_text = pretty;
+            self.revision += 1;
+            self.new_text_version();
+            self.log_to_console("Applied pretty print");
+        }
+    }
 
-                    // Check if new key already exists
-                    if map.contains_key(new_key) && old_key != new_key {
-                        self.log_to_console(&format!("Property '{}' already exists", new_key));
-                        return false;
-                    }
+    /// Compact the JSON (remove unnecessary whitespace)
+    /// In NDJSON mode records are already compact, one per line
+    pub fn apply_compact(&mut self) {
+        self.flush_validation();
+        let compact = match &self.parsed_value {
+            Some(value) if self.ndjson => self.serialize_document(value),
+            Some(value) => serde_json::to_string(value).ok(),
+            None => None,
+        };
+        let compact = if self.escape_non_ascii {
+            compact.map(|compact| Self::escape_non_ascii(&compact))
+        } else {
+            compact
+        };
+        if let Some(compact) = compact {
+            self.text = compact.clone();
+            self.previous_text = compact;
+            self.revision += 1;
+            self.new_text_version();
+            self.log_to_console("Applied compact format");
+        }
+    }
 
-                    // Remove old key and insert with new key
-                    if let Some(old_value) = map.remove(old_key) {
-                        map.insert(new_key.to_string(), old_value);
-
-                        // Update the text with pretty-printed JSON
-                        if let Ok(pretty) = serde_json::to_string_pretty(&value) {
-                            self.push_undo();
-                            self.text = pretty.clone();
-                            self.previous_text = pretty;
-                            self.parsed_value = Some(value);
-                            self.error_message = None;
-                            self.log_to_console(&format!(
-                                "Renamed property: {} -> {}",
-                                old_key, new_key
-                            ));
-                            return true;
-                        }
-                    }
-                }
-                _ => {
-                    self.log_to_console("Cannot rename key in non-Object value");
-                    return false;
+    /// Pretty-print just the text in a char range, which must be valid JSON on its own
+    /// Whitespace around the selected value is kept and the formatted lines are
+    /// indented to match the line the selection starts on. Returns the char range
+    /// of the formatted value (one undo step)
+    pub fn format_selection(
+        &mut self,
+        range: std::ops::Range<usize>,
+    ) -> Result<std::ops::Range<usize>, EditorError> {
+        let byte = |char_index: usize| {
+            self.text
+                .char_indices()
+                .nth(char_index)
+                .map_or(self.text.len(), |(i, _)| i)
+        };
+        let (start, end) = (byte(range.start), byte(range.end));
+        let selected = &self.text[start..end];
+        let leading = selected.len() - selected.trim_start().len();
+        let (value_start, value_end) = (start + leading, start + selected.trim_end().len());
+
+        let source = &self.text[value_start..value_end];
+        let value: Value = serde_json::from_str(source).map_err(|e| EditorError::from_parse(&e))?;
+        let line_start = self.text[..value_start].rfind('\n').map_or(0, |i| i + 1);
+        let base_indent: String = self.text[line_start..]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let formatted = self
+            .to_pretty_string_from(&value, source)
+            .ok_or(EditorError::SerializeFailed)?
+            .replace('\n', &format!("\n{}", base_indent));
+
+        self.push_undo();
+        self.text.replace_range(value_start..value_end, &formatted);
+        self.previous_text = self.text.clone();
+        self.validate();
+        self.log_to_console("Formatted selection");
+
+        let formatted_start = self.text[..value_start].chars().count();
+        Ok(formatted_start..formatted_start + formatted.chars().count())
+    }
+
+    /// Escape every non-ASCII character in serialized JSON as `\uXXXX`
+    /// Characters outside the BMP become a UTF-16 surrogate pair (two escapes).
+    /// Non-ASCII can only occur inside strings, so the result is still valid JSON
+    fn escape_non_ascii(json: &str) -> String {
+        let mut escaped = String::with_capacity(json.len());
+        for c in json.chars() {
+            if c.is_ascii() {
+                escaped.push(c);
+            } else {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
                 }
             }
         }
-        false
+        escaped
     }
 
-    /// Render JSON tree view recursively
-    #[allow(clippy::only_used_in_recursion)]
-    fn render_tree_view(&self, ui: &mut egui::Ui, value: &Value, key: Option<&str>, path: String) {
-        match value {
-            Value::Object(map) => {
-                let header_text = if let Some(k) = key {
-                    format!("{}: {{ {} items }}", k, map.len())
-                } else {
-                    format!("{{ {} items }}", map.len())
-                };
+    /// Set whether Compact escapes non-ASCII characters as `\uXXXX`
+    pub fn set_escape_non_ascii(&mut self, enabled: bool) {
+        self.escape_non_ascii = enabled;
+    }
 
-                egui::CollapsingHeader::new(header_text)
-                    .id_salt(path.clone())
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        for (k, v) in map {
-                            let new_path = if path.is_empty() {
-                                k.clone()
-                            } else {
-                                format!("{}.{}", path, k)
-                            };
-                            self.render_tree_view(ui, v, Some(k), new_path);
-                        }
-                    });
-            }
-            Value::Array(arr) => {
-                let header_text = if let Some(k) = key {
-                    format!("{}: [ {} items ]", k, arr.len())
-                } else {
-                    format!("[ {} items ]", arr.len())
-                };
+    /// Check if Compact escapes non-ASCII characters
+    pub fn escape_non_ascii_enabled(&self) -> bool {
+        self.escape_non_ascii
+    }
 
-                egui::CollapsingHeader::new(header_text)
-                    .id_salt(path.clone())
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        for (idx, v) in arr.iter().enumerate() {
-                            let new_path = format!("{}[{}]", path, idx);
-                            self.render_tree_view(ui, v, Some(&format!("[{}]", idx)), new_path);
-                        }
-                    });
-            }
-            Value::String(s) => {
-                let text = if let Some(k) = key {
-                    format!("{}: \"{}\"", k, s)
-                } else {
-                    format!("\"{}\"", s)
-                };
-                ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(100, 200, 100)));
-            }
-            Value::Number(n) => {
-                let text = if let Some(k) = key {
-                    format!("{}: {}", k, n)
-                } else {
-                    format!("{}", n)
-                };
-                ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(200, 150, 100)));
-            }
-            Value::Bool(b) => {
-                let text = if let Some(k) = key {
-                    format!("{}: {}", k, b)
-                } else {
-                    format!("{}", b)
-                };
-                ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(200, 100, 150)));
-            }
-            Value::Null => {
-                let text = if let Some(k) = key {
-                    format!("{}: null", k)
+    /// Convert leading tabs to spaces (one tab = `indent_size` spaces)
+    /// Only leading whitespace is touched, so line structure is preserved
+    /// Returns true if the text changed
+    pub fn convert_tabs_to_spaces(&mut self) -> bool {
+        let retabbed = Self::retab(&self.text, self.indent_size, false);
+        if retabbed == self.text {
+            return false;
+        }
+        self.push_undo();
+        self.text = retabbed.clone();
+        self.previous_text = retabbed;
+        self.validate();
+        self.log_to_console("Converted indentation to spaces");
+        true
+    }
+
+    /// Convert leading spaces to tabs (every `indent_size` spaces become one tab)
+    /// Only leading whitespace is touched, so line structure is preserved
+    /// Returns true if the text changed
+    pub fn convert_spaces_to_tabs(&mut self) -> bool {
+        let retabbed = Self::retab(&self.text, self.indent_size, true);
+        if retabbed == self.text {
+            return false;
+        }
+        self.push_undo();
+        self.text = retabbed.clone();
+        self.previous_text = retabbed;
+        self.validate();
+        self.log_to_console("Converted indentation to tabs");
+        true
+    }
+
+    /// Rewrite the leading whitespace of each line using spaces or tabs
+    /// Leftover columns that don't fill a whole tab stay as spaces
+    fn retab(text: &str, indent_size: usize, use_tabs: bool) -> String {
+        let indent_size = indent_size.max(1);
+
+        text.split('\n')
+            .map(|line| {
+                let content = line.trim_start_matches([' ', '\t']);
+                let leading = &line[..line.len() - content.len()];
+                let width: usize = leading
+                    .chars()
+                    .map(|c| if c == '\t' { indent_size } else { 1 })
+                    .sum();
+
+                let indent = if use_tabs {
+                    format!(
+                        "{}{}",
+                        "\t".repeat(width / indent_size),
+                        " ".repeat(width % indent_size)
+                    )
                 } else {
-                    "null".to_string()
+                    " ".repeat(width)
                 };
-                ui.label(egui::RichText::new(text).color(egui::Color32::from_gray(150)));
-            }
+                format!("{}{}", indent, content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Toggle pretty print mode
+    pub fn toggle_pretty_print(&mut self) {
+        self.pretty_print = !self.pretty_print;
+        if self.pretty_print {
+            self.apply_pretty_print();
+        } else {
+            self.apply_compact();
         }
     }
 
-    /// Log message to browser console (WASM) or stdout (desktop)
-    fn log_to_console(&self, message: &str) {
-        utils::log("JSON Editor", message);
+    /// Set indent size for pretty printing (clamped to 1-8)
+    pub fn set_indent_size(&mut self, size: usize) {
+        self.indent_size = size.clamp(MIN_INDENT_SIZE, MAX_INDENT_SIZE);
     }
 
-    /// Render the editor UI using egui
-    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
-        let mut changed = false;
-        let text_edit_id = ui.id().with("json_text_edit");
+    /// Get current indent size
+    pub fn indent_size(&self) -> usize {
+        self.indent_size
+    }
 
-        // Handle keyboard shortcuts
-        let mut undo_requested = false;
-        let mut redo_requested = false;
-        let mut select_all_requested = false;
+    /// Use tabs instead of spaces for pretty-print indentation
+    pub fn set_indent_with_tabs(&mut self, use_tabs: bool) {
+        self.indent_with_tabs = use_tabs;
+    }
 
-        // Use ctx.input() to check global keyboard events instead of ui.input()
-        // This ensures shortcuts work even when focus is in nested UI elements
-        ui.ctx().input(|i| {
-            // Undo: Ctrl+Z (Windows/Linux) or Cmd+Z (macOS)
-            if i.modifiers.command && i.key_pressed(egui::Key::Z) && !i.modifiers.shift {
-                undo_requested = true;
-            }
+    /// Check if pretty printing indents with tabs
+    pub fn indent_with_tabs(&self) -> bool {
+        self.indent_with_tabs
+    }
+
+    /// Pretty-print object keys in the order they appear in the text
+    /// Fallback for builds without serde_json's `preserve_order` feature
+    pub fn set_source_key_order(&mut self, enabled: bool) {
+        self.source_key_order = enabled;
+    }
+
+    /// Check if pretty printing follows the text's key order
+    pub fn source_key_order(&self) -> bool {
+        self.source_key_order
+    }
+
+    /// One level of indentation (a tab, or `indent_size` spaces)
+    fn indent_unit(&self) -> String {
+        if self.indent_with_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_size)
+        }
+    }
+
+    /// Serialize the whole document after a structured edit
+    /// NDJSON documents are written back one compact record per line
+    fn serialize_document(&self, value: &Value) -> Option<String> {
+        if self.ndjson {
+            let records = value.as_array()?;
+            let lines: Option<Vec<String>> = records
+                .iter()
+                .map(|record| serde_json::to_string(record).ok())
+                .collect();
+            Some(lines?.join("\n"))
+        } else {
+            self.to_pretty_string(value)
+        }
+    }
+
+    /// Pretty-print a value using the configured indentation
+    fn to_pretty_string(&self, value: &Value) -> Option<String> {
+        self.to_pretty_string_from(value, &self.text)
+    }
+
+    /// Pretty-print a value parsed from `source` (the key order `source_key_order` follows)
+    fn to_pretty_string_from(&self, value: &Value, source: &str) -> Option<String> {
+        let indent = self.indent_unit();
+        if self.source_key_order {
+            return source_map::to_pretty_string_in_source_order(value, source, &indent);
+        }
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut buffer = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        value.serialize(&mut serializer).ok()?;
+        String::from_utf8(buffer).ok()
+    }
+
+    /// Enable or disable syntax highlighting in the text editor
+    pub fn set_syntax_highlight(&mut self, enabled: bool) {
+        self.syntax_highlight = enabled;
+    }
+
+    /// Check if syntax highlighting is enabled
+    pub fn syntax_highlight(&self) -> bool {
+        self.syntax_highlight
+    }
+
+    /// Wrap long lines in the text editor (off = scroll horizontally)
+    pub fn set_wrap_lines(&mut self, enabled: bool) {
+        self.wrap_lines = enabled;
+    }
+
+    /// Check if long lines wrap in the text editor
+    pub fn wrap_lines(&self) -> bool {
+        self.wrap_lines
+    }
+
+    /// Show array indices starting at 1 instead of 0 in the tree view (display only)
+    pub fn set_one_based_indices(&mut self, one_based: bool) {
+        self.tree.set_one_based_indices(one_based);
+    }
+
+    /// Check if the tree view shows array indices starting at 1
+    pub fn one_based_indices(&self) -> bool {
+        self.tree.one_based_indices()
+    }
+
+    /// Set the selected JSON path (e.g. from the graph selection)
+    /// The tree view highlights it and scrolls to it when it changes
+    pub fn set_selected_path(&mut self, path: Option<Vec<String>>) {
+        self.tree.set_selected_path(path);
+    }
+
+    /// Selected JSON path, if any
+    pub fn selected_path(&self) -> Option<&[String]> {
+        self.tree.selected_path()
+    }
+
+    /// Expand every container in the tree view
+    pub fn expand_all_tree(&mut self) {
+        if let Some(value) = &self.parsed_value {
+            self.tree.expand_all(value);
+        }
+    }
+
+    /// Collapse every container in the tree view
+    pub fn collapse_all_tree(&mut self) {
+        if let Some(value) = &self.parsed_value {
+            self.tree.collapse_all(value);
+        }
+    }
+
+    /// Toggle view mode between Text and Tree
+    pub fn toggle_view_mode(&mut self) {
+        self.set_view_mode(match self.view_mode {
+            ViewMode::Text => ViewMode::Tree,
+            ViewMode::Tree => ViewMode::Text,
+        });
+    }
+
+    /// Get the current view mode
+    pub fn view_mode(&self) -> ViewMode {
+        self.view_mode
+    }
+
+    /// Switch to a view mode
+    /// Text still waiting for validation is parsed first, so the tree shows it
+    pub fn set_view_mode(&mut self, mode: ViewMode) {
+        self.flush_validation();
+        self.view_mode = mode;
+        self.log_to_console(&format!("View mode: {:?}", self.view_mode));
+    }
+
+    /// Update a value at a specific JSON path
+    pub fn update_value_at_path(
+        &mut self,
+        path: &[String],
+        new_value_str: &str,
+    ) -> Result<(), EditorError> {
+        self.update_subtree_at_path(path, Self::parse_value_str(new_value_str))
+    }
+
+    /// Replace the value at a specific JSON path with a parsed JSON value
+    /// Unlike `update_value_at_path`, nested Objects/Arrays are inserted intact
+    pub fn update_subtree_at_path(
+        &mut self,
+        path: &[String],
+        new_value: Value,
+    ) -> Result<(), EditorError> {
+        let old_value = if self.edits_in_place() {
+            self.replace_value_in_text(path, &new_value)?
+        } else {
+            let mut value = self.document_for_edit()?;
+            let old_value =
+                std::mem::replace(Self::resolve_path_mut(&mut value, path)?, new_value.clone());
+            self.commit_document(value, &format!("Updated value at path: {:?}", path))?;
+            old_value
+        };
+        self.events.push(EditEvent::Update {
+            path: path.to_vec(),
+            old_value,
+            new_value,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Copy of the parsed document for a structured edit
+    fn document_for_edit(&mut self) -> Result<Value, EditorError> {
+        self.flush_validation();
+        self.parsed_value
+            .clone()
+            .ok_or(EditorError::InvalidDocument)
+    }
+
+    /// Replace the document with an edited value and write it back to the text
+    fn commit_document(&mut self, value: Value, message: &str) -> Result<(), EditorError> {
+        let text = self
+            .serialize_document(&value)
+            .ok_or(EditorError::SerializeFailed)?;
+        self.push_undo();
+        self.text = text.clone();
+        self.previous_text = text;
+        self.parsed_value = Some(value);
+        self.error = None;
+        self.revision += 1;
+        self.log_to_console(message);
+        Ok(())
+    }
+
+    /// Parse a value string coming from the graph into a JSON value
+    /// Quoted strings are decoded as JSON string literals so escapes (\n, \", \\)
+    /// round-trip correctly. Numbers follow JSON syntax and keep their literal
+    /// (`-42` stays an integer, `1.5e-3` isn't rounded through f64); forms JSON
+    /// doesn't allow, like `007`, `+1`, `NaN` or `inf`, are kept as strings
+    fn parse_value_str(value_str: &str) -> Value {
+        if value_str.len() >= 2 && value_str.starts_with('"') && value_str.ends_with('"') {
+            // It's a string (with quotes)
+            match serde_json::from_str::<String>(value_str) {
+                Ok(decoded) => Value::String(decoded),
+                // Not a valid JSON literal - keep the raw text between the quotes
+                Err(_) => Value::String(value_str[1..value_str.len() - 1].to_string()),
+            }
+        } else if value_str.trim() == value_str
+            && let Ok(number) = serde_json::from_str::<serde_json::Number>(value_str)
+        {
+            // It's a number
+            Value::Number(number)
+        } else if value_str == "true" {
+            Value::Bool(true)
+        } else if value_str == "false" {
+            Value::Bool(false)
+        } else if value_str == "null" {
+            Value::Null
+        } else {
+            // Default to string without quotes
+            Value::String(value_str.to_string())
+        }
+    }
+
+    /// Get the pretty-printed JSON of the subtree at a JSON path
+    /// An empty path returns the whole document
+    pub fn get_subtree_json(&self, path: &[String]) -> Option<String> {
+        self.to_pretty_string(self.value_at_path(path)?)
+    }
+
+    /// Structural diff of the current document against a base value
+    /// Entries describe how the document differs from `base`
+    /// Returns None if the document is invalid
+    pub fn diff_against(&self, base: &Value) -> Option<Vec<DiffEntry>> {
+        Some(diff::diff(base, self.parsed_value.as_ref()?))
+    }
+
+    /// Parse clipboard text and diff the document against it
+    fn diff_against_text(&self, text: &str) -> Result<Vec<DiffEntry>, String> {
+        let base = serde_json::from_str::<Value>(text)
+            .map_err(|e| format!("Clipboard is not valid JSON: {}", e))?;
+        self.diff_against(&base)
+            .ok_or_else(|| "Current document is not valid JSON".to_string())
+    }
+
+    /// Get the parsed value at a JSON path (an empty path is the whole document)
+    /// Returns None if the document is invalid or the path doesn't exist
+    pub fn value_at_path(&self, path: &[String]) -> Option<&Value> {
+        Self::navigate_to_path(self.parsed_value.as_ref()?, path)
+    }
+
+    /// Parse an array index path segment
+    /// Only plain decimal digits are accepted (so "", "-1", "+1" and " 1" are rejected)
+    fn parse_index_segment(segment: &str) -> Result<usize, String> {
+        if segment.is_empty() {
+            return Err("empty path segment used as an array index".to_string());
+        }
+        if !segment.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("'{}' is not a valid array index", segment));
+        }
+        segment
+            .parse()
+            .map_err(|_| format!("array index '{}' is too large", segment))
+    }
+
+    /// Check that a path segment is well-formed for the container it indexes
+    /// Returns the reason if it is malformed (a missing key or index is not malformed)
+    fn path_segment_error(container: &Value, segment: &str) -> Option<String> {
+        match container {
+            // "" is a legal JSON key, so it is only rejected if the object doesn't have it
+            Value::Object(map) if segment.is_empty() && !map.contains_key(segment) => {
+                Some("empty path segment used as an object key".to_string())
+            }
+            Value::Array(_) => Self::parse_index_segment(segment).err(),
+            _ => None,
+        }
+    }
+
+    /// Log a warning for a malformed path segment
+    fn warn_invalid_segment(path: &[String], depth: usize, reason: &str) {
+        utils::log(
+            "JSON Editor",
+            &format!(
+                "Warning: invalid path {:?} at segment {}: {}",
+                path, depth, reason
+            ),
+        );
+    }
+
+    /// Navigate to a reference at a JSON path
+    /// Malformed segments are logged and resolve to None
+    fn navigate_to_path<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+        let mut current = value;
+
+        for (depth, segment) in path.iter().enumerate() {
+            if let Some(reason) = Self::path_segment_error(current, segment) {
+                Self::warn_invalid_segment(path, depth, &reason);
+                return None;
+            }
+            current = match current {
+                Value::Object(map) => map.get(segment)?,
+                Value::Array(arr) => arr.get(Self::parse_index_segment(segment).ok()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+
+    /// Navigate to a mutable reference at a JSON path
+    /// Malformed segments are logged and reported as `InvalidPath`
+    fn resolve_path_mut<'a>(
+        value: &'a mut Value,
+        path: &[String],
+    ) -> Result<&'a mut Value, EditorError> {
+        let mut current = value;
+
+        for (depth, segment) in path.iter().enumerate() {
+            if let Some(reason) = Self::path_segment_error(current, segment) {
+                Self::warn_invalid_segment(path, depth, &reason);
+                return Err(EditorError::InvalidPath {
+                    path: path.to_vec(),
+                    reason,
+                });
+            }
+            current = match current {
+                Value::Object(map) => map.get_mut(segment),
+                Value::Array(arr) => Self::parse_index_segment(segment)
+                    .ok()
+                    .and_then(|index| arr.get_mut(index)),
+                _ => None,
+            }
+            .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+        }
+
+        Ok(current)
+    }
+
+    /// Parse the array index segment at `depth` of a path
+    /// Malformed segments are logged and reported as `InvalidPath`
+    fn index_segment(path: &[String], depth: usize) -> Result<usize, EditorError> {
+        Self::parse_index_segment(&path[depth]).map_err(|reason| {
+            Self::warn_invalid_segment(path, depth, &reason);
+            EditorError::InvalidPath {
+                path: path.to_vec(),
+                reason,
+            }
+        })
+    }
+
+    /// Delete a value at a specific JSON path
+    pub fn delete_value_at_path(&mut self, path: &[String]) -> Result<(), EditorError> {
+        let Some((key, parent_path)) = path.split_last() else {
+            return Err(EditorError::InvalidPath {
+                path: Vec::new(),
+                reason: "the document root cannot be deleted".to_string(),
+            });
+        };
+
+        let mut value = self.document_for_edit()?;
+        let (old_value, message) = match Self::resolve_path_mut(&mut value, parent_path)? {
+            Value::Object(map) => {
+                let old_value = map
+                    .shift_remove(key)
+                    .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+                (old_value, format!("Deleted property: {}", key))
+            }
+            Value::Array(arr) => {
+                let index = Self::index_segment(path, parent_path.len())?;
+                if index >= arr.len() {
+                    return Err(EditorError::PathNotFound(path.to_vec()));
+                }
+                let old_value = arr.remove(index);
+                (old_value, format!("Deleted array item at index: {}", index))
+            }
+            _ => return Err(EditorError::PathNotFound(path.to_vec())),
+        };
+        if self.edits_in_place() {
+            let (range, kept) = self.member_removal(path)?;
+            self.splice_text(range, &kept)?;
+            self.log_to_console(&message);
+        } else {
+            self.commit_document(value, &message)?;
+        }
+        self.events.push(EditEvent::Delete {
+            path: path.to_vec(),
+            old_value,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Duplicate the property or array item at a JSON path
+    /// Arrays get the copy right after the source index; Objects get `key_copy`
+    /// (`key_copy_2`, `key_copy_3`, ... if taken) right after the source property
+    pub fn duplicate_at_path(&mut self, path: &[String]) -> Result<(), EditorError> {
+        let Some((key, parent_path)) = path.split_last() else {
+            return Err(EditorError::InvalidPath {
+                path: Vec::new(),
+                reason: "the document root cannot be duplicated".to_string(),
+            });
+        };
+
+        let mut value = self.document_for_edit()?;
+        let (copy_key, message) = match Self::resolve_path_mut(&mut value, parent_path)? {
+            Value::Object(map) => {
+                let (Some(index), Some(copy)) =
+                    (map.keys().position(|k| k == key), map.get(key).cloned())
+                else {
+                    return Err(EditorError::PathNotFound(path.to_vec()));
+                };
+                let new_key = Self::duplicate_key_name(map, key);
+                map.shift_insert(index + 1, new_key.clone(), copy);
+                let message = format!("Duplicated property: {} -> {}", key, new_key);
+                (new_key, message)
+            }
+            Value::Array(arr) => {
+                let index = Self::index_segment(path, parent_path.len())?;
+                let copy = arr
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| EditorError::PathNotFound(path.to_vec()))?;
+                arr.insert(index + 1, copy);
+                let message = format!("Duplicated array item at index: {}", index);
+                ((index + 1).to_string(), message)
+            }
+            _ => return Err(EditorError::PathNotFound(path.to_vec())),
+        };
+        if self.edits_in_place() {
+            self.duplicate_member_in_text(path, &copy_key)?;
+            self.log_to_console(&message);
+        } else {
+            self.commit_document(value, &message)?;
+        }
+
+        let mut copy_path = parent_path.to_vec();
+        copy_path.push(copy_key);
+        self.events.push(EditEvent::Duplicate {
+            path: path.to_vec(),
+            copy_path,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Sort the array at a JSON path (one undo step)
+    /// Numbers sort numerically and strings lexicographically; mixed or nested items
+    /// sort by their compact JSON text. Arrays containing objects are only sorted by
+    /// `key` (items without it go last). Equal items keep their order
+    pub fn sort_array_at_path(
+        &mut self,
+        path: &[String],
+        descending: bool,
+        key: Option<&str>,
+    ) -> Result<(), EditorError> {
+        let mut value = self.document_for_edit()?;
+        let Value::Array(arr) = Self::resolve_path_mut(&mut value, path)? else {
+            return Err(EditorError::TypeMismatch(
+                "Only arrays can be sorted".to_string(),
+            ));
+        };
+        if key.is_none() && arr.iter().any(Value::is_object) {
+            return Err(EditorError::TypeMismatch(
+                "Arrays containing objects need a key to sort by".to_string(),
+            ));
+        }
+        let old_value = Value::Array(arr.clone());
+
+        let sort_values: Vec<Option<&Value>> = arr
+            .iter()
+            .map(|item| match key {
+                Some(key) => item.get(key),
+                None => Some(item),
+            })
+            .collect();
+        let numeric = sort_values.iter().flatten().all(|v| v.is_number());
+        let textual = sort_values.iter().flatten().all(|v| v.is_string());
+        let sort_keys: Vec<Option<SortKey>> = sort_values
+            .into_iter()
+            .map(|v| {
+                v.map(|v| match v {
+                    Value::Number(n) if numeric => SortKey::Number(n.as_f64().unwrap_or(0.0)),
+                    Value::String(s) if textual => SortKey::Text(s.clone()),
+                    other => SortKey::Text(other.to_string()),
+                })
+            })
+            .collect();
+
+        let mut items: Vec<(Option<SortKey>, (usize, Value))> = sort_keys
+            .into_iter()
+            .zip(std::mem::take(arr).into_iter().enumerate())
+            .collect();
+        items.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) if descending => b.compare(a),
+            (Some(a), Some(b)) => a.compare(b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        let order: Vec<usize> = items.iter().map(|(_, (index, _))| *index).collect();
+        *arr = items.into_iter().map(|(_, (_, item))| item).collect();
+
+        let message = format!(
+            "Sorted array {}{}",
+            if descending {
+                "descending"
+            } else {
+                "ascending"
+            },
+            key.map(|k| format!(" by {}", k)).unwrap_or_default()
+        );
+        if self.edits_in_place() {
+            self.reorder_items_in_text(path, &order)?;
+            self.log_to_console(&message);
+        } else {
+            self.commit_document(value, &message)?;
+        }
+        self.events.push(EditEvent::SortArray {
+            path: path.to_vec(),
+            descending,
+            key: key.map(str::to_string),
+            old_value,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// First free `key_copy` / `key_copy_N` name in an Object
+    fn duplicate_key_name(map: &serde_json::Map<String, Value>, key: &str) -> String {
+        let base = format!("{}_copy", key);
+        if !map.contains_key(&base) {
+            return base;
+        }
+        (2..)
+            .map(|n| format!("{}_{}", base, n))
+            .find(|candidate| !map.contains_key(candidate))
+            .unwrap_or(base)
+    }
+
+    /// Add a value at a specific JSON path
+    /// For Objects: key is the property name, value_str is the value
+    /// For Arrays: key is empty, value_str is appended to the array
+    pub fn add_value_at_path(
+        &mut self,
+        path: &[String],
+        key: &str,
+        value_str: &str,
+    ) -> Result<(), EditorError> {
+        self.add_json_value_at_path(path, key, Self::parse_value_str(value_str))
+    }
+
+    /// Add a parsed JSON value at a specific JSON path
+    /// Unlike `add_value_at_path`, nested Objects/Arrays are inserted intact
+    /// For Objects: key is the property name
+    /// For Arrays: key is empty, the value is appended to the array
+    pub fn add_json_value_at_path(
+        &mut self,
+        path: &[String],
+        key: &str,
+        new_value: Value,
+    ) -> Result<(), EditorError> {
+        let mut value = self.document_for_edit()?;
+        let description = new_value.to_string();
+        let mut new_path = path.to_vec();
+
+        let (replaced, index, member_key, message) = match Self::resolve_path_mut(&mut value, path)?
+        {
+            Value::Object(map) => {
+                if key.is_empty() {
+                    return Err(EditorError::EmptyKey);
+                }
+                // Add new property to object
+                new_path.push(key.to_string());
+                let index = map.len();
+                let replaced = map.insert(key.to_string(), new_value.clone());
+                (
+                    replaced,
+                    index,
+                    Some(key),
+                    format!("Added property: {} = {}", key, description),
+                )
+            }
+            Value::Array(arr) => {
+                // Append new item to array
+                let index = arr.len();
+                new_path.push(index.to_string());
+                arr.push(new_value.clone());
+                (
+                    None,
+                    index,
+                    None,
+                    format!("Added array item: {}", description),
+                )
+            }
+            _ => {
+                return Err(EditorError::TypeMismatch(
+                    "Cannot add to non-Object/Array value".to_string(),
+                ));
+            }
+        };
+        if self.edits_in_place() {
+            if replaced.is_some() {
+                self.replace_value_in_text(&new_path, &new_value)?;
+            } else {
+                self.insert_member_in_text(path, index, member_key, &new_value)?;
+            }
+            self.log_to_console(&message);
+        } else {
+            self.commit_document(value, &message)?;
+        }
+        self.events.push(EditEvent::Add {
+            path: new_path,
+            value: new_value,
+            replaced,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Insert an item into the Array at a JSON path, before the item at `index`
+    /// (`index` equal to the length appends). Later items shift up by one
+    pub fn insert_at_path(
+        &mut self,
+        parent_path: &[String],
+        index: usize,
+        value_str: &str,
+    ) -> Result<(), EditorError> {
+        let new_value = Self::parse_value_str(value_str);
+        let mut value = self.document_for_edit()?;
+        let mut new_path = parent_path.to_vec();
+        new_path.push(index.to_string());
+
+        match Self::resolve_path_mut(&mut value, parent_path)? {
+            Value::Array(arr) => {
+                if index > arr.len() {
+                    return Err(EditorError::InvalidPath {
+                        path: new_path,
+                        reason: format!(
+                            "index {} is past the end of the array ({} items)",
+                            index,
+                            arr.len()
+                        ),
+                    });
+                }
+                arr.insert(index, new_value.clone());
+            }
+            _ => {
+                return Err(EditorError::TypeMismatch(
+                    "Can only insert at an index in an Array".to_string(),
+                ));
+            }
+        }
+        let message = format!("Inserted array item at index {}: {}", index, new_value);
+        if self.edits_in_place() {
+            self.insert_member_in_text(parent_path, index, None, &new_value)?;
+            self.log_to_console(&message);
+        } else {
+            self.commit_document(value, &message)?;
+        }
+        self.events.push(EditEvent::Add {
+            path: new_path,
+            value: new_value,
+            replaced: None,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Rename a property key in an Object
+    /// Path points to the Object containing the key to rename
+    pub fn rename_key_at_path(
+        &mut self,
+        path: &[String],
+        old_key: &str,
+        new_key: &str,
+    ) -> Result<(), EditorError> {
+        let mut value = self.document_for_edit()?;
+        let Value::Object(map) = Self::resolve_path_mut(&mut value, path)? else {
+            return Err(EditorError::TypeMismatch(
+                "Cannot rename key in non-Object value".to_string(),
+            ));
+        };
+
+        let mut key_path = path.to_vec();
+        key_path.push(old_key.to_string());
+        if !map.contains_key(old_key) {
+            return Err(EditorError::PathNotFound(key_path));
+        }
+        if map.contains_key(new_key) && old_key != new_key {
+            return Err(EditorError::KeyExists(new_key.to_string()));
+        }
+
+        // In JSONC mode, replace just the key token so comments survive
+        if self.edits_in_place() {
+            let spans = source_map::value_spans(&self.text);
+            let key_range = source_map::find_span(&spans, &key_path)
+                .and_then(|span| span.key.clone())
+                .ok_or(EditorError::PathNotFound(key_path))?;
+            let replacement =
+                serde_json::to_string(new_key).map_err(|_| EditorError::SerializeFailed)?;
+            self.splice_text(key_range, &replacement)?;
+        } else {
+            // Replace the key in place so the property keeps its position
+            let index = map.keys().position(|k| k == old_key).unwrap_or(0);
+            if let Some(old_value) = map.shift_remove(old_key) {
+                map.shift_insert(index, new_key.to_string(), old_value);
+            }
+            self.commit_document(
+                value,
+                &format!("Renamed property: {} -> {}", old_key, new_key),
+            )?;
+        }
+        self.events.push(EditEvent::Rename {
+            path: path.to_vec(),
+            old_key: old_key.to_string(),
+            new_key: new_key.to_string(),
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Convert the primitive value at a JSON path to another primitive type
+    /// Fails with `TypeMismatch` if the value cannot be coerced (e.g. "abc" to Number)
+    pub fn change_type_at_path(
+        &mut self,
+        path: &[String],
+        target: &NodeType,
+    ) -> Result<(), EditorError> {
+        let mut value = self.document_for_edit()?;
+        let current = Self::resolve_path_mut(&mut value, path)?;
+        let converted = Self::coerce_value(current, target).ok_or_else(|| {
+            EditorError::TypeMismatch(format!("Cannot convert {} to {:?}", current, target))
+        })?;
+
+        let old_value = current.clone();
+
+        // In JSONC mode, replace just the value token so comments survive
+        if self.edits_in_place() {
+            self.replace_value_in_text(path, &converted)?;
+        } else {
+            *current = converted.clone();
+            self.commit_document(
+                value,
+                &format!("Changed type to {:?} at path: {:?}", target, path),
+            )?;
+        }
+        self.events.push(EditEvent::ChangeType {
+            path: path.to_vec(),
+            old_value,
+            new_value: converted,
+            timestamp: SystemTime::now(),
+        });
+        Ok(())
+    }
+
+    /// Coerce a primitive value to the target type
+    /// Returns None if the coercion is not possible
+    fn coerce_value(value: &Value, target: &NodeType) -> Option<Value> {
+        match (target, value) {
+            (NodeType::Object | NodeType::Array, _) => None,
+            (_, Value::Object(_) | Value::Array(_)) => None,
+            (NodeType::Null, _) => Some(Value::Null),
+            (NodeType::String, Value::String(_)) => Some(value.clone()),
+            (NodeType::String, other) => Some(Value::String(other.to_string())),
+            (NodeType::Number, Value::Number(_)) => Some(value.clone()),
+            (NodeType::Number, Value::String(s)) => {
+                serde_json::from_str::<serde_json::Number>(s.trim())
+                    .ok()
+                    .map(Value::Number)
+            }
+            (NodeType::Number, Value::Bool(b)) => Some(Value::from(u8::from(*b))),
+            (NodeType::Number, _) => None,
+            (NodeType::Boolean, Value::Bool(_)) => Some(value.clone()),
+            (NodeType::Boolean, Value::String(s)) => match s.trim().to_lowercase().as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            (NodeType::Boolean, Value::Number(n)) => n.as_f64().map(|f| Value::Bool(f != 0.0)),
+            (NodeType::Boolean, _) => Some(Value::Bool(false)),
+        }
+    }
+
+    /// Apply a queued tree view edit with the same path-based operations the graph uses
+    /// Returns true if the document changed
+    fn apply_tree_operation(&mut self, operation: TreeOperation) -> bool {
+        let result = match operation {
+            TreeOperation::Update { path, value } => self.update_value_at_path(&path, &value),
+            TreeOperation::Delete { path } => self.delete_value_at_path(&path),
+            TreeOperation::Rename { path, new_key } => match path.split_last() {
+                Some((old_key, parent)) => self.rename_key_at_path(parent, old_key, &new_key),
+                None => Err(EditorError::PathNotFound(path)),
+            },
+            TreeOperation::Add { path, key, value } => self.add_value_at_path(&path, &key, &value),
+        };
+        if let Err(error) = &result {
+            self.log_to_console(&format!("Tree edit failed: {}", error));
+        }
+        result.is_ok()
+    }
+
+    /// Stop (or resume) logging progress messages, e.g. when stdout is the output
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Log message to browser console (WASM) or stdout (desktop)
+    fn log_to_console(&self, message: &str) {
+        if !self.quiet {
+            utils::log("JSON Editor", message);
+        }
+    }
+
+    /// Render the editor UI using egui
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let text_edit_id = ui.id().with("json_text_edit");
+
+        // Handle keyboard shortcuts
+        let mut undo_requested = false;
+        let mut redo_requested = false;
+        let mut select_all_requested = false;
+        let mut goto_line_requested = false;
+
+        // Use ctx.input() to check global keyboard events instead of ui.input()
+        // This ensures shortcuts work even when focus is in nested UI elements
+        ui.ctx().input(|i| {
+            // Undo: Ctrl+Z (Windows/Linux) or Cmd+Z (macOS)
+            if i.modifiers.command && i.key_pressed(egui::Key::Z) && !i.modifiers.shift {
+                undo_requested = true;
+            }
+
+            // Redo: Ctrl+Y (Windows/Linux) or Cmd+Shift+Z (macOS) or Ctrl+Shift+Z
+            if (i.modifiers.command && i.key_pressed(egui::Key::Y))
+                || (i.modifiers.command && i.modifiers.shift && i.key_pressed(egui::Key::Z))
+            {
+                redo_requested = true;
+            }
+
+            // Select All: Ctrl+A (Windows/Linux) or Cmd+A (macOS)
+            // Note: egui's TextEdit handles this automatically, but we detect it here for logging
+            if i.modifiers.command && i.key_pressed(egui::Key::A) {
+                select_all_requested = true;
+            }
+
+            // Go to line: Ctrl+G (Windows/Linux) or Cmd+G (macOS)
+            if i.modifiers.command && i.key_pressed(egui::Key::G) {
+                goto_line_requested = true;
+            }
+        });
+
+        // Process undo/redo requests
+        if undo_requested && self.can_undo() {
+            self.undo();
+            changed = true;
+            self.log_to_console("Undo via keyboard shortcut");
+        }
+        if redo_requested && self.can_redo() {
+            self.redo();
+            changed = true;
+            self.log_to_console("Redo via keyboard shortcut");
+        }
+        if select_all_requested {
+            self.log_to_console("Select all via keyboard shortcut");
+        }
+        if goto_line_requested && self.view_mode == ViewMode::Text {
+            self.goto_line_input = Some(String::new());
+            self.log_to_console("Go to line via keyboard shortcut");
+        }
+
+        self.render_goto_line_dialog(ui);
+        self.render_clipboard_diff_dialog(ui);
+
+        // Toolbar
+        ui.horizontal_wrapped(|ui| {
+            // View mode toggle
+            let view_text = match self.view_mode {
+                ViewMode::Text => "📝 Text",
+                ViewMode::Tree => "🌲 Tree",
+            };
+            if ui.button(view_text).clicked() {
+                self.toggle_view_mode();
+            }
+
+            if self.view_mode == ViewMode::Tree {
+                if ui.button("⊞ Expand All").clicked() {
+                    self.expand_all_tree();
+                }
+                if ui.button("⊟ Collapse All").clicked() {
+                    self.collapse_all_tree();
+                }
+                self.tree.render_number_format_menu(ui);
+            }
+
+            if ui
+                .button("⇄ Diff vs Clipboard")
+                .on_hover_text("Compare the document with JSON from the clipboard")
+                .clicked()
+            {
+                // The platform layer reads the clipboard after this frame
+                self.clipboard_diff_requested = true;
+            }
+
+            ui.separator();
+
+            // Format buttons (only in text mode)
+            if self.view_mode == ViewMode::Text {
+                if ui.button("Pretty").clicked() && self.is_valid() {
+                    self.push_undo();
+                    self.apply_pretty_print();
+                    changed = true;
+                }
+
+                if ui.button("Compact").clicked() && self.is_valid() {
+                    self.push_undo();
+                    self.apply_compact();
+                    changed = true;
+                }
+
+                // Reformat just the selected value, keeping it selected afterwards
+                let selection = egui::TextEdit::load_state(ui.ctx(), text_edit_id)
+                    .and_then(|state| state.cursor.char_range())
+                    .filter(|range| !range.is_empty());
+                if ui
+                    .add_enabled(selection.is_some(), egui::Button::new("Format Selection"))
+                    .on_hover_text("Pretty-print only the selected JSON value")
+                    .on_disabled_hover_text("Select a JSON value in the text first")
+                    .clicked()
+                    && let Some(selection) = selection
+                {
+                    match self.format_selection(selection.as_sorted_char_range()) {
+                        Ok(formatted) => {
+                            if let Some(mut state) =
+                                egui::TextEdit::load_state(ui.ctx(), text_edit_id)
+                            {
+                                state
+                                    .cursor
+                                    .set_char_range(Some(egui::text::CCursorRange::two(
+                                        egui::text::CCursor::new(formatted.start),
+                                        egui::text::CCursor::new(formatted.end),
+                                    )));
+                                state.store(ui.ctx(), text_edit_id);
+                            }
+                            ui.memory_mut(|mem| mem.request_focus(text_edit_id));
+                            self.format_selection_hint = None;
+                            changed = true;
+                        }
+                        Err(error) => {
+                            self.format_selection_hint = Some(format!(
+                                "Selection is not valid JSON on its own ({})",
+                                error
+                            ));
+                        }
+                    }
+                }
+                if let Some(hint) = &self.format_selection_hint {
+                    ui.label(
+                        egui::RichText::new(format!("💡 {}", hint))
+                            .small()
+                            .italics(),
+                    );
+                }
+                ui.checkbox(&mut self.escape_non_ascii, "Escape non-ASCII")
+                    .on_hover_text("Compact writes non-ASCII characters as \\uXXXX escapes");
+
+                if ui
+                    .button("→ Spaces")
+                    .on_hover_text("Convert leading tabs to spaces")
+                    .clicked()
+                {
+                    changed |= self.convert_tabs_to_spaces();
+                }
+
+                if ui
+                    .button("→ Tabs")
+                    .on_hover_text("Convert leading spaces to tabs")
+                    .clicked()
+                {
+                    changed |= self.convert_spaces_to_tabs();
+                }
+
+                // Indentation used by Pretty and structured edits
+                ui.label("Indent:");
+                ui.add_enabled(
+                    !self.indent_with_tabs,
+                    egui::DragValue::new(&mut self.indent_size)
+                        .range(MIN_INDENT_SIZE..=MAX_INDENT_SIZE),
+                );
+                ui.checkbox(&mut self.indent_with_tabs, "Tab")
+                    .on_hover_text("Indent with tabs instead of spaces");
+                ui.checkbox(&mut self.source_key_order, "Source order")
+                    .on_hover_text("Keep object keys in the order they appear in the text");
+                if ui
+                    .checkbox(&mut self.auto_format, "Auto-format")
+                    .on_hover_text(
+                        "Pretty-print shortly after each edit that leaves the JSON valid",
+                    )
+                    .changed()
+                    && !self.auto_format
+                {
+                    self.auto_format_due = None;
+                }
+            }
+
+            ui.separator();
+
+            // Edit buttons
+            if ui
+                .add_enabled(self.can_undo(), egui::Button::new("Undo"))
+                .clicked()
+            {
+                self.undo();
+                changed = true;
+            }
+
+            if ui
+                .add_enabled(self.can_redo(), egui::Button::new("Redo"))
+                .clicked()
+            {
+                self.redo();
+                changed = true;
+            }
+
+            ui.separator();
+
+            // Line numbers toggle (only in text mode)
+            if self.view_mode == ViewMode::Text {
+                if ui
+                    .checkbox(&mut self.show_line_numbers, "Line Numbers")
+                    .clicked()
+                {
+                    self.log_to_console(&format!(
+                        "Line numbers: {}",
+                        if self.show_line_numbers { "on" } else { "off" }
+                    ));
+                }
+
+                if ui
+                    .checkbox(&mut self.syntax_highlight, "Highlight")
+                    .clicked()
+                {
+                    self.log_to_console(&format!(
+                        "Syntax highlight: {}",
+                        if self.syntax_highlight { "on" } else { "off" }
+                    ));
+                }
+
+                if ui
+                    .checkbox(&mut self.wrap_lines, "Wrap")
+                    .on_hover_text("Wrap long lines instead of scrolling horizontally")
+                    .clicked()
+                {
+                    self.log_to_console(&format!(
+                        "Line wrap: {}",
+                        if self.wrap_lines { "on" } else { "off" }
+                    ));
+                }
+
+                if ui
+                    .checkbox(&mut self.tolerate_trailing_commas, "Trailing Commas")
+                    .on_hover_text("Accept trailing commas like {\"a\": 1,}")
+                    .clicked()
+                {
+                    self.validate();
+                    changed = true;
+                    self.log_to_console(&format!(
+                        "Tolerate trailing commas: {}",
+                        if self.tolerate_trailing_commas {
+                            "on"
+                        } else {
+                            "off"
+                        }
+                    ));
+                }
+
+                if ui
+                    .checkbox(&mut self.ndjson, "NDJSON")
+                    .on_hover_text("Treat each line as a separate record")
+                    .clicked()
+                {
+                    self.validate();
+                    changed = true;
+                    self.log_to_console(&format!(
+                        "NDJSON mode: {}",
+                        if self.ndjson { "on" } else { "off" }
+                    ));
+                }
+
+                if ui
+                    .checkbox(&mut self.allow_comments, "Comments (JSONC)")
+                    .on_hover_text("Accept // and /* */ comments and keep them when editing values")
+                    .clicked()
+                {
+                    self.validate();
+                    changed = true;
+                    self.log_to_console(&format!(
+                        "JSONC comments: {}",
+                        if self.allow_comments { "on" } else { "off" }
+                    ));
+                }
+
+                ui.separator();
+            }
+
+            // Validation status
+            if self.is_valid() {
+                ui.colored_label(egui::Color32::GREEN, "✓ Valid JSON");
+            } else {
+                ui.colored_label(egui::Color32::RED, "✗ Invalid JSON");
+            }
+        });
+
+        ui.separator();
+
+        // Error message
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error.to_string());
+        }
+
+        // Lints panel (collapsed by default)
+        self.render_lints_panel(ui);
+
+        // Render based on view mode
+        match self.view_mode {
+            ViewMode::Tree => {
+                // Tree view with folding
+                if let Some(value) = &self.parsed_value {
+                    egui::ScrollArea::vertical()
+                        .max_height(ui.available_height())
+                        .show(ui, |ui| {
+                            self.tree.show(ui, value);
+                        });
+                    if let Some(operation) = self.tree.take_operation()
+                        && self.apply_tree_operation(operation)
+                    {
+                        changed = true;
+                    }
+                } else {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        "Invalid JSON - cannot display tree view",
+                    );
+                }
+            }
+            ViewMode::Text => {
+                // Original text editor view
+                self.render_text_editor(ui, &mut changed, text_edit_id);
+            }
+        }
+
+        changed
+    }
+
+    /// Render the Go-to-line dialog (if open)
+    fn render_goto_line_dialog(&mut self, ui: &mut egui::Ui) {
+        let Some(input) = &mut self.goto_line_input else {
+            return;
+        };
+
+        let line_count = self.text.lines().count();
+        let mut close_dialog = false;
+        let mut target: Option<usize> = None;
+
+        egui::Window::new("Go to Line")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("Line number (1-{}):", line_count));
+                let response = ui.add(
+                    egui::TextEdit::singleline(input)
+                        .desired_width(120.0)
+                        .font(egui::TextStyle::Monospace),
+                );
+
+                // Auto-focus on first show
+                if !response.has_focus() {
+                    response.request_focus();
+                }
+
+                let parsed = Self::parse_goto_line(input, line_count);
+
+                // Handle Enter/ESC
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    target = parsed;
+                } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close_dialog = true;
+                }
+
+                // Show hint for out-of-range input
+                if !input.trim().is_empty() && parsed.is_none() {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "💡 Enter a line between 1 and {}",
+                            line_count
+                        ))
+                        .small()
+                        .italics(),
+                    );
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(parsed.is_some(), egui::Button::new("Go"))
+                        .clicked()
+                    {
+                        target = parsed;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close_dialog = true;
+                    }
+                });
+            });
+
+        if let Some(line) = target {
+            self.scroll_to_line(line);
+            close_dialog = true;
+        }
+
+        if close_dialog {
+            self.goto_line_input = None;
+        }
+    }
+
+    /// Whether Diff vs Clipboard is waiting for the clipboard text
+    pub fn wants_clipboard_text(&self) -> bool {
+        self.clipboard_diff_requested
+    }
+
+    /// Diff the document against the clipboard text and show the result
+    /// Without clipboard text (it can't be read directly on the web) the dialog
+    /// opens empty, to paste the JSON into with Ctrl+V
+    pub fn diff_clipboard_text(&mut self, text: Option<String>) {
+        if !std::mem::take(&mut self.clipboard_diff_requested) {
+            return;
+        }
+        self.clipboard_diff = Some(ClipboardDiff::default());
+        if let Some(text) = text {
+            self.set_clipboard_diff_text(text);
+        }
+    }
+
+    /// Diff the document against `text` in the open Diff vs Clipboard dialog
+    fn set_clipboard_diff_text(&mut self, text: String) {
+        let result = self.diff_against_text(&text);
+        if let Ok(entries) = &result {
+            self.log_to_console(&format!(
+                "Diff vs clipboard: {} difference(s)",
+                entries.len()
+            ));
+        }
+        if let Some(state) = &mut self.clipboard_diff {
+            state.text = text;
+            state.result = Some(result);
+        }
+    }
+
+    /// Render the Diff vs Clipboard dialog (if open)
+    /// Shows the diff of the clipboard text; where the clipboard can't be read
+    /// directly, the JSON arrives via a paste event into the dialog's text box
+    fn render_clipboard_diff_dialog(&mut self, ui: &mut egui::Ui) {
+        let Some(state) = &mut self.clipboard_diff else {
+            return;
+        };
+
+        let mut close_dialog = false;
+        let mut pasted: Option<String> = None;
+        let mut jump_to: Option<Vec<String>> = None;
+
+        egui::Window::new("Diff vs Clipboard")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui.ctx(), |ui| {
+                ui.label("Clipboard JSON (Ctrl+V to paste):");
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut state.text)
+                        .desired_width(400.0)
+                        .desired_rows(4)
+                        .font(egui::TextStyle::Monospace),
+                );
+
+                // Auto-focus so the paste lands here instead of the document
+                if state.result.is_none() && !response.has_focus() {
+                    response.request_focus();
+                }
+                if response.changed() {
+                    pasted = Some(state.text.clone());
+                }
+
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close_dialog = true;
+                }
+
+                ui.separator();
+
+                match &state.result {
+                    None => {
+                        ui.label(egui::RichText::new("Waiting for clipboard JSON").italics());
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(egui::Color32::RED, error);
+                    }
+                    Some(Ok(entries)) if entries.is_empty() => {
+                        ui.colored_label(egui::Color32::GREEN, "✓ No differences");
+                    }
+                    Some(Ok(entries)) => {
+                        ui.label(format!("{} difference(s):", entries.len()));
+                        egui::ScrollArea::vertical()
+                            .id_salt("clipboard_diff_scroll")
+                            .max_height(250.0)
+                            .show(ui, |ui| {
+                                for entry in entries {
+                                    let color = graph::diff_color(&entry.kind);
+                                    if ui
+                                        .link(egui::RichText::new(entry.summary()).color(color))
+                                        .clicked()
+                                    {
+                                        jump_to = Some(entry.path.clone());
+                                    }
+                                }
+                            });
+                    }
+                }
+
+                ui.separator();
+
+                if ui.button("Close").clicked() {
+                    close_dialog = true;
+                }
+            });
+
+        if let Some(text) = pasted {
+            self.set_clipboard_diff_text(text);
+        }
+
+        if let Some(path) = jump_to
+            && let Some(line) = self.find_line_for_path(&path)
+        {
+            self.scroll_to_line(line);
+        }
+
+        if close_dialog {
+            self.clipboard_diff = None;
+        }
+    }
+
+    /// Render the collapsible Lints panel with per-lint toggles and jump-to-line
+    fn render_lints_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(value) = &self.parsed_value else {
+            return;
+        };
+
+        let cache = match self.lint_cache.take() {
+            Some(cache)
+                if cache.revision == self.revision && cache.settings == self.lint_settings =>
+            {
+                cache
+            }
+            _ => LintCache {
+                revision: self.revision,
+                settings: self.lint_settings.clone(),
+                lints: lint::run_lints_with(value, &self.lint_settings),
+                deepest: lint::deepest_path(value),
+            },
+        };
+        let lints = &cache.lints;
+        let (deepest, max_depth) = &cache.deepest;
+        let mut jump_to: Option<Vec<String>> = None;
+        let mut goto_deepest = false;
+
+        egui::CollapsingHeader::new(format!("Lints ({})", lints.len()))
+            .id_salt("json_editor_lints")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for kind in LintKind::ALL {
+                        ui.checkbox(self.lint_settings.flag_mut(kind), kind.name());
+                    }
+                });
+
+                ui.separator();
+
+                if lints.is_empty() {
+                    ui.label(egui::RichText::new("No lints").italics());
+                }
+
+                egui::ScrollArea::vertical()
+                    .id_salt("json_editor_lints_scroll")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for lint in lints {
+                            let text = format!("{}: {}", lint.path_display(), lint.message);
+                            if ui
+                                .link(egui::RichText::new(text).color(egui::Color32::YELLOW))
+                                .clicked()
+                            {
+                                jump_to = Some(lint.path.clone());
+                            }
+                        }
+                    });
+
+                ui.separator();
+
+                // Deepest leaf (helps find the source of excessive nesting)
+                ui.horizontal_wrapped(|ui| {
+                    let path_display = if deepest.is_empty() {
+                        "(root)".to_string()
+                    } else {
+                        format_json_path(value, deepest)
+                    };
+                    ui.label(format!("Deepest: {} (depth {})", path_display, max_depth));
+                    if ui.button("Go to deepest").clicked() {
+                        jump_to = Some(deepest.clone());
+                        goto_deepest = true;
+                    }
+                });
+            });
+        self.lint_cache = Some(cache);
+
+        if let Some(path) = jump_to
+            && let Some(line) = self.find_line_for_path(&path)
+        {
+            self.scroll_to_line(line);
+            if goto_deepest {
+                // Also select it in the graph (handled like a line click)
+                self.clicked_line = Some(line);
+                self.log_to_console(&format!("Go to deepest: line {}", line));
+            }
+        }
+    }
+
+    /// Render the text editor mode
+    fn render_text_editor(
+        &mut self,
+        ui: &mut egui::Ui,
+        changed: &mut bool,
+        text_edit_id: egui::Id,
+    ) {
+        // Editor area with line numbers - use all available height
+        let available_height = ui.available_height();
+
+        // Calculate scroll offset if we need to scroll to a target line
+        let scroll_offset = self
+            .target_line
+            .map(|target| Self::scroll_offset_for_line(target, available_height));
+
+        // Clear target_line after calculating offset
+        if self.target_line.is_some() {
+            self.target_line = None;
+        }
+
+        // Single ScrollArea containing both line numbers and editor
+        // Without wrapping, long lines are reached by scrolling horizontally
+        let mut scroll_area = if self.wrap_lines {
+            egui::ScrollArea::vertical()
+        } else {
+            egui::ScrollArea::both()
+        }
+        .id_salt("json_editor_scroll")
+        .max_height(available_height);
+
+        // Apply scroll offset if needed
+        if let Some(offset) = scroll_offset {
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+
+        scroll_area.show(ui, |ui| {
+            ui.horizontal_top(|ui| {
+                // Line numbers column
+                // With wrapping a line can span several rows, so the numbers are placed
+                // from the laid-out text once it is shown (left edge of the gutter)
+                let line_number_width = 50.0;
+                let mut wrapped_gutter = None;
+                if self.show_line_numbers {
+                    if self.wrap_lines {
+                        let (_, rect) = ui.allocate_space(egui::vec2(line_number_width, 0.0));
+                        wrapped_gutter = Some(rect.min.x);
+                    } else {
+                        let line_count = self.text.lines().count();
+
+                        ui.allocate_ui_with_layout(
+                            egui::vec2(line_number_width, available_height),
+                            egui::Layout::top_down(egui::Align::Min),
+                            |ui| {
+                                ui.style_mut().spacing.item_spacing.y = 0.0;
+                                // Use fixed line height matching monospace font
+                                let line_height = LINE_HEIGHT;
+
+                                for i in 1..=line_count {
+                                    ui.allocate_ui_with_layout(
+                                        egui::vec2(line_number_width, line_height),
+                                        egui::Layout::top_down(egui::Align::Max),
+                                        |ui| {
+                                            let line_label = Self::line_number_label(ui, i);
+                                            self.handle_line_number_click(&line_label, i);
+                                        },
+                                    );
+                                }
+                            },
+                        );
+                    }
+
+                    ui.separator();
+                }
+
+                // Layouter for syntax highlighting and wrapping
+                // Reuses the cached job so the text is only re-tokenized when it changes
+                // The job is keyed on the revision plus the edits typed this frame
+                let highlight_cache = &mut self.highlight_cache;
+                let (syntax_highlight, wrap_lines) = (self.syntax_highlight, self.wrap_lines);
+                let (revision, edits) = (self.revision, Cell::new(0));
+                let mut layouter = |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
+                    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                    let color = ui.visuals().text_color();
+                    let mut job = if syntax_highlight {
+                        let version = highlight::TextVersion {
+                            revision,
+                            edits: edits.get(),
+                        };
+                        highlight_cache.layout_job(version, buf.as_str(), font_id, color)
+                    } else {
+                        egui::text::LayoutJob::simple(buf.as_str().to_owned(), font_id, color, 0.0)
+                    };
+                    job.wrap.max_width = if wrap_lines {
+                        wrap_width
+                    } else {
+                        f32::INFINITY
+                    };
+                    ui.fonts_mut(|f| f.layout_job(job))
+                };
+
+                // Text editor - now using full available space
+                let mut buffer = highlight::TrackedText {
+                    text: &mut self.text,
+                    edits: &edits,
+                };
+                let text_edit = egui::TextEdit::multiline(&mut buffer)
+                    .id(text_edit_id)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY)
+                    .code_editor()
+                    .char_limit(usize::MAX) // No character limit for JSON spec compliance
+                    .lock_focus(true) // Maintain focus for IME input (Korean, etc.)
+                    .layouter(&mut layouter);
+
+                let mut output = text_edit.show(ui);
+                if let Some(gutter_left) = wrapped_gutter {
+                    self.render_wrapped_line_numbers(ui, gutter_left, line_number_width, &output);
+                }
+                let response = output.response.clone();
+                let cursor = output
+                    .cursor_range
+                    .filter(|range| range.is_empty())
+                    .map(|range| range.primary.index);
+
+                // Re-parse once typing pauses
+                if let Some(due) = self.validation_due {
+                    let now = ui.input(|i| i.time);
+                    let was_valid = self.is_valid();
+                    if self.run_due_validation(now) {
+                        *changed = true;
+
+                        // If validation failed, maintain focus on the text editor
+                        if !self.is_valid() && was_valid {
+                            ui.memory_mut(|mem| mem.request_focus(text_edit_id));
+                            self.log_to_console("JSON validation failed - focus maintained");
+                        }
+                    } else {
+                        ui.ctx()
+                            .request_repaint_after(std::time::Duration::from_secs_f64(
+                                (due - now).max(0.0),
+                            ));
+                    }
+                }
+
+                // Pretty-print once the edits settle, keeping the caret after the same character
+                if let Some(due) = self.auto_format_due {
+                    let now = ui.input(|i| i.time);
+                    let caret = output.cursor_range.map(|range| range.primary.index);
+                    if let Some(new_caret) = self.run_auto_format(now, caret) {
+                        output
+                            .state
+                            .cursor
+                            .set_char_range(Some(egui::text::CCursorRange::one(
+                                egui::text::CCursor::new(new_caret),
+                            )));
+                        output.state.clone().store(ui.ctx(), text_edit_id);
+                        *changed = true;
+                    } else if self.auto_format_due.is_some() {
+                        ui.ctx()
+                            .request_repaint_after(std::time::Duration::from_secs_f64(
+                                (due - now).max(0.0),
+                            ));
+                    }
+                }
+
+                if response.changed() {
+                    // Auto-indent after a typed newline (skipped while the document is
+                    // invalid so we don't fight the user mid-typo)
+                    if self.is_valid()
+                        && let Some(cursor) = cursor
+                        && cursor > 0
+                        && self.text.chars().count() == self.previous_text.chars().count() + 1
+                        && self.text.chars().nth(cursor - 1) == Some('\n')
+                    {
+                        let indent = Self::auto_indent_for_newline(
+                            &self.text,
+                            cursor - 1,
+                            &self.indent_unit(),
+                        );
+                        if !indent.is_empty() {
+                            let byte = self
+                                .text
+                                .char_indices()
+                                .nth(cursor)
+                                .map_or(self.text.len(), |(i, _)| i);
+                            self.text.insert_str(byte, &indent);
+
+                            let new_cursor =
+                                egui::text::CCursor::new(cursor + indent.chars().count());
+                            output
+                                .state
+                                .cursor
+                                .set_char_range(Some(egui::text::CCursorRange::one(new_cursor)));
+                            output.state.clone().store(ui.ctx(), text_edit_id);
+                        }
+                    }
+
+                    // Apply Unicode NFC normalization for Korean input
+                    self.text = self.text.nfc().collect();
+
+                    // Record the change (a typing burst is one undo step) and
+                    // re-validate once typing pauses
+                    self.text_typed(ui.input(|i| i.time));
+                    self.log_to_console("Text changed");
+                } else if response.has_focus()
+                    && let Some(cursor) = cursor
+                    && let Some((a, b)) = self.bracket_pair_at_cursor(cursor)
+                {
+                    // Highlight the bracket next to the cursor and its match
+                    for index in [a, b] {
+                        ui.painter().rect_stroke(
+                            Self::glyph_rect(&output, index),
+                            2.0,
+                            egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 200, 0)),
+                            egui::StrokeKind::Outside,
+                        );
+                    }
+                }
+
+                // Underline the bracket that breaks nesting (the galley is stale on the
+                // frame the text changed, so wait for the next one)
+                if !response.changed()
+                    && let Some(index) = self.unbalanced_bracket
+                {
+                    let rect = Self::glyph_rect(&output, index);
+                    ui.painter().line_segment(
+                        [rect.left_bottom(), rect.right_bottom()],
+                        egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 80, 80)),
+                    );
+                }
+            });
+        });
+    }
+
+    /// Clickable line number in the gutter (right-aligned, dimmed)
+    fn line_number_label(ui: &mut egui::Ui, line: usize) -> egui::Response {
+        ui.selectable_label(
+            false,
+            egui::RichText::new(format!("{:>4}", line)).color(egui::Color32::from_gray(128)),
+        )
+    }
+
+    /// Record a clicked line number (for editor-to-graph sync)
+    fn handle_line_number_click(&mut self, response: &egui::Response, line: usize) {
+        if response.clicked() {
+            self.clicked_line = Some(line);
+            self.log_to_console(&format!("Line {} clicked", line));
+        }
+    }
+
+    /// Number the logical lines of wrapped text next to their first row
+    fn render_wrapped_line_numbers(
+        &mut self,
+        ui: &mut egui::Ui,
+        gutter_left: f32,
+        width: f32,
+        output: &egui::text_edit::TextEditOutput,
+    ) {
+        for (line, top) in Self::logical_line_tops(&output.galley) {
+            let rect = egui::Rect::from_min_size(
+                egui::pos2(gutter_left, output.galley_pos.y + top),
+                egui::vec2(width, LINE_HEIGHT),
+            );
+            if !ui.is_rect_visible(rect) {
+                continue;
+            }
+            let response = ui
+                .scope_builder(
+                    egui::UiBuilder::new()
+                        .max_rect(rect)
+                        .layout(egui::Layout::top_down(egui::Align::Max)),
+                    |ui| Self::line_number_label(ui, line),
+                )
+                .inner;
+            self.handle_line_number_click(&response, line);
+        }
+    }
+
+    /// 1-indexed logical line numbers with the top of their first row in a galley
+    /// A row that doesn't end with a newline is continued (wrapped) by the next row
+    fn logical_line_tops(galley: &egui::Galley) -> Vec<(usize, f32)> {
+        let mut tops = Vec::new();
+        let mut starts_line = true;
+        for row in &galley.rows {
+            if starts_line {
+                tops.push((tops.len() + 1, row.pos.y));
+            }
+            starts_line = row.ends_with_newline;
+        }
+        tops
+    }
+
+    /// Screen rect of the character at `index` in the text editor
+    fn glyph_rect(output: &egui::text_edit::TextEditOutput, index: usize) -> egui::Rect {
+        let start = output
+            .galley
+            .pos_from_cursor(egui::text::CCursor::new(index));
+        let end = output
+            .galley
+            .pos_from_cursor(egui::text::CCursor::new(index + 1));
+        egui::Rect::from_min_max(start.min, egui::pos2(end.max.x, start.max.y))
+            .translate(output.galley_pos.to_vec2())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_editor() {
+        let editor = JsonEditor::new();
+        assert!(editor.is_valid());
+        assert!(!editor.text().is_empty());
+    }
+
+    #[test]
+    fn test_valid_json() {
+        let editor = JsonEditor::with_text(r#"{"key": "value"}"#.to_string());
+        assert!(editor.is_valid());
+        assert!(editor.error_message().is_none());
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let editor = JsonEditor::with_text(r#"{"key": invalid}"#.to_string());
+        assert!(!editor.is_valid());
+        assert!(editor.error_message().is_some());
+    }
+
+    #[test]
+    fn test_parse_failure_is_parse_error() {
+        let editor = JsonEditor::with_text("{\n  \"key\": invalid\n}".to_string());
+        match editor.error() {
+            Some(EditorError::ParseError { line, col, msg }) => {
+                assert_eq!(*line, 2);
+                assert!(*col > 0);
+                assert!(!msg.contains("at line"));
+            }
+            other => panic!("Expected ParseError, got {:?}", other),
+        }
+        assert!(editor.error_message().unwrap().starts_with("JSON Error:"));
+    }
+
+    #[test]
+    fn test_delete_missing_path_is_path_not_found() {
+        let mut editor = JsonEditor::with_text(r#"{"a": {"b": 1}, "list": [1]}"#.to_string());
+        let missing = vec!["a".to_string(), "c".to_string()];
+        assert_eq!(
+            editor.delete_value_at_path(&missing),
+            Err(EditorError::PathNotFound(missing.clone()))
+        );
+        let out_of_range = vec!["list".to_string(), "5".to_string()];
+        assert_eq!(
+            editor.delete_value_at_path(&out_of_range),
+            Err(EditorError::PathNotFound(out_of_range.clone()))
+        );
+        assert!(matches!(
+            editor.delete_value_at_path(&[]),
+            Err(EditorError::InvalidPath { .. })
+        ));
+        // Failed edits leave the document alone
+        assert_eq!(editor.text(), r#"{"a": {"b": 1}, "list": [1]}"#);
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let mut editor = JsonEditor::with_text(r#"{"a":1,"b":2}"#.to_string());
+        assert!(editor.is_valid());
+
+        editor.apply_pretty_print();
+        assert!(editor.text().contains('\n'));
+        assert!(editor.text().contains("\n  \"a\""));
+
+        editor.set_indent_size(4);
+        editor.apply_pretty_print();
+        assert_eq!(editor.text(), "{\n    \"a\": 1,\n    \"b\": 2\n}");
+
+        editor.set_indent_with_tabs(true);
+        editor.apply_pretty_print();
+        assert_eq!(editor.text(), "{\n\t\"a\": 1,\n\t\"b\": 2\n}");
+
+        // Out-of-range sizes are clamped
+        editor.set_indent_size(20);
+        assert_eq!(editor.indent_size(), 8);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut editor = JsonEditor::with_text(
+            r#"{
+  "a": 1,
+  "b": 2
+}"#
+            .to_string(),
+        );
+        assert!(editor.is_valid());
+
+        editor.apply_compact();
+        assert!(!editor.text().contains('\n'));
+    }
+
+    #[test]
+    fn test_auto_indent_for_newline() {
+        let text = "{\n  \"a\": [\n\n  ]\n}";
+        // Newline typed after '[' on an indented line
+        let newline = text.find("[\n").unwrap() + 1;
+        assert_eq!(
+            JsonEditor::auto_indent_for_newline(text, newline, "  "),
+            "    "
+        );
+
+        // Newline after a plain value keeps the current indentation
+        let text = "{\n  \"a\": 1,\n}";
+        let newline = text.find(",\n").unwrap() + 1;
+        assert_eq!(
+            JsonEditor::auto_indent_for_newline(text, newline, "  "),
+            "  "
+        );
+    }
+
+    #[test]
+    fn test_matching_bracket() {
+        let text = r#"{"a": [1, "]"], "b": {}}"#;
+        let brackets = highlight::bracket_tokens(text, &highlight::tokenize(text));
+        let matching = |cursor| JsonEditor::matching_bracket(text, &brackets, cursor);
+        // Cursor right after the opening '{'
+        assert_eq!(matching(1), Some((0, 23)));
+        // Cursor before '[' (the bracket inside the string is ignored)
+        assert_eq!(matching(6), Some((6, 13)));
+        // Cursor after the last '}'
+        assert_eq!(matching(24), Some((23, 0)));
+        // Cursor not next to a bracket
+        assert_eq!(matching(3), None);
+
+        // Same results through the cursor lookup, which skips non-bracket positions
+        let mut editor = JsonEditor::with_text(text.to_string());
+        assert_eq!(editor.bracket_pair_at_cursor(1), Some((0, 23)));
+        assert_eq!(editor.bracket_pair_at_cursor(11), None);
+        assert_eq!(editor.bracket_pair_at_cursor(3), None);
+    }
+
+    #[test]
+    fn test_matching_brace_skips_strings() {
+        let mut editor = JsonEditor::with_text(r#"{"a": "{[\"}", "b": [{}]}"#.to_string());
+        assert_eq!(editor.matching_brace(0), Some(24));
+        assert_eq!(editor.matching_brace(24), Some(0));
+        assert_eq!(editor.matching_brace(20), Some(23));
+        assert_eq!(editor.matching_brace(22), Some(21));
+        // Brackets and escaped quotes inside the string don't count
+        assert_eq!(editor.matching_brace(7), None);
+        assert_eq!(editor.matching_brace(11), None);
+        // Not a bracket
+        assert_eq!(editor.matching_brace(1), None);
+    }
+
+    #[test]
+    fn test_unbalanced_bracket() {
+        let unbalanced = |text: &str| {
+            JsonEditor::first_unbalanced_bracket(
+                text,
+                &highlight::bracket_tokens(text, &highlight::tokenize(text)),
+            )
+        };
+        assert_eq!(unbalanced(r#"{"a": "}", "b": [1]}"#), None);
+        // Wrong kind of closing bracket
+        assert_eq!(unbalanced(r#"{"a": [1, 2}"#), Some(11));
+        // Closing bracket with nothing open
+        assert_eq!(unbalanced(r#"[1]]"#), Some(3));
+        // Innermost bracket left unclosed
+        assert_eq!(unbalanced(r#"{"a": {"b": 1"#), Some(6));
+
+        let mut editor = JsonEditor::with_text(r#"{"a": [1, 2}"#.to_string());
+        assert_eq!(editor.unbalanced_bracket, Some(11));
+        editor.set_text(r#"{"a": [1, 2]}"#.to_string());
+        assert_eq!(editor.unbalanced_bracket, None);
+        // Invalid for another reason
+        editor.set_text(r#"{"a": [1, 2,]}"#.to_string());
+        assert!(!editor.is_valid());
+        assert_eq!(editor.unbalanced_bracket, None);
+    }
+
+    #[test]
+    fn test_update_string_with_escapes() {
+        let mut editor = JsonEditor::with_text(r#"{"note": "old"}"#.to_string());
+        let original = "line 1\nline \"2\" with \\ backslash";
+        let encoded = serde_json::to_string(original).unwrap();
+
+        assert!(
+            editor
+                .update_value_at_path(&["note".to_string()], &encoded)
+                .is_ok()
+        );
+        assert!(
+            editor
+                .text()
+                .contains(r#"line 1\nline \"2\" with \\ backslash"#)
+        );
+        assert_eq!(
+            editor.parsed_value().unwrap()["note"],
+            Value::String(original.to_string())
+        );
+    }
+
+    #[test]
+    fn test_change_type() {
+        let mut editor =
+            JsonEditor::with_text(r#"{"n": "42", "b": "true", "s": 7, "x": "abc"}"#.to_string());
+
+        assert!(
+            editor
+                .change_type_at_path(&["n".to_string()], &NodeType::Number)
+                .is_ok()
+        );
+        assert_eq!(editor.parsed_value().unwrap()["n"], serde_json::json!(42));
+
+        assert!(
+            editor
+                .change_type_at_path(&["b".to_string()], &NodeType::Boolean)
+                .is_ok()
+        );
+        assert_eq!(editor.parsed_value().unwrap()["b"], Value::Bool(true));
+
+        assert!(
+            editor
+                .change_type_at_path(&["s".to_string()], &NodeType::String)
+                .is_ok()
+        );
+        assert_eq!(editor.parsed_value().unwrap()["s"], serde_json::json!("7"));
+
+        assert!(
+            editor
+                .change_type_at_path(&["x".to_string()], &NodeType::Number)
+                .is_err()
+        );
+        assert_eq!(
+            editor.parsed_value().unwrap()["x"],
+            serde_json::json!("abc")
+        );
+
+        assert!(
+            editor
+                .change_type_at_path(&["x".to_string()], &NodeType::Null)
+                .is_ok()
+        );
+        assert_eq!(editor.parsed_value().unwrap()["x"], Value::Null);
+    }
+
+    #[test]
+    fn test_scroll_offset_centers_line() {
+        // Lines near the top are clamped to the start
+        assert_eq!(JsonEditor::scroll_offset_for_line(1, 340.0), 0.0);
+        assert_eq!(JsonEditor::scroll_offset_for_line(5, 340.0), 0.0);
+
+        // Line 101 starts at 1700.0; centering subtracts half the remaining viewport
+        let offset = JsonEditor::scroll_offset_for_line(101, 340.0);
+        assert_eq!(offset, 1700.0 - (340.0 - LINE_HEIGHT) / 2.0);
+    }
+
+    #[test]
+    fn test_max_history() {
+        let mut editor = JsonEditor::with_text("1".to_string());
+        assert_eq!(editor.max_history(), 100);
+
+        for i in 2..=6 {
+            editor.set_text(i.to_string());
+        }
+        assert!(editor.can_undo());
+
+        // Shrinking the limit trims the oldest entries immediately
+        editor.set_max_history(2);
+        assert_eq!(editor.max_history(), 2);
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert!(!editor.undo());
+        assert_eq!(editor.text(), "4");
+        assert!(editor.can_redo());
+
+        // New edits respect the limit
+        for i in 7..=10 {
+            editor.set_text(i.to_string());
+        }
+        assert!(!editor.can_redo());
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert!(!editor.can_undo());
+        assert_eq!(editor.text(), "8");
+    }
+
+    #[test]
+    fn test_clear_history() {
+        let mut editor = JsonEditor::with_text("1".to_string());
+        editor.set_text("2".to_string());
+        editor.set_text("3".to_string());
+        editor.undo();
+        assert!(editor.can_undo());
+        assert!(editor.can_redo());
+
+        editor.clear_history();
+        assert!(!editor.can_undo());
+        assert!(!editor.can_redo());
+        assert_eq!(editor.text(), "2");
+    }
+
+    #[test]
+    fn test_clear_resets_document() {
+        let mut editor = JsonEditor::with_text("oops {".to_string());
+        editor.set_text(r#"{"a": [1]}"#.to_string());
+        editor.tree.set_expanded(&["a".to_string()], false);
+        editor.set_selected_path(Some(vec!["a".to_string(), "0".to_string()]));
+
+        editor.clear();
+        assert_eq!(editor.text(), "{}");
+        assert_eq!(editor.parsed_value(), Some(&serde_json::json!({})));
+        assert!(editor.error.is_none());
+        assert!(!editor.can_undo());
+        assert!(!editor.can_redo());
+        assert!(editor.selected_path().is_none());
+        assert!(
+            editor
+                .tree
+                .is_expanded(&["a".to_string()], &serde_json::json!([1]))
+        );
+
+        editor.set_text("[1]".to_string());
+        editor.clear_to_array();
+        assert_eq!(editor.parsed_value(), Some(&serde_json::json!([])));
+        assert!(!editor.can_undo());
+    }
+
+    #[test]
+    fn test_wrapped_lines_number_logical_lines() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            let job = egui::text::LayoutJob::simple(
+                "\"aaaa bbbb cccc dddd\"\n1\n".to_string(),
+                egui::FontId::monospace(12.0),
+                egui::Color32::WHITE,
+                40.0,
+            );
+            let galley = ctx.fonts_mut(|f| f.layout_job(job));
+            assert!(galley.rows.len() > 3);
+
+            // The wrapped first line is numbered once; the empty last line counts too
+            let tops = JsonEditor::logical_line_tops(&galley);
+            assert_eq!(
+                tops.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+                [1, 2, 3]
+            );
+            assert_eq!(tops[0].1, 0.0);
+            let second_line_row = galley.rows.len() - 2;
+            assert_eq!(tops[1].1, galley.rows[second_line_row].pos.y);
+        });
+    }
+
+    #[test]
+    fn test_retab_round_trip() {
+        let spaces = "{\n  \"a\": [\n    1\n  ]\n}";
+        let tabs = "{\n\t\"a\": [\n\t\t1\n\t]\n}";
+        let mut editor = JsonEditor::with_text(spaces.to_string());
+
+        editor.convert_spaces_to_tabs();
+        assert_eq!(editor.text(), tabs);
+        assert!(editor.is_valid());
+        assert!(editor.can_undo());
+
+        editor.convert_tabs_to_spaces();
+        assert_eq!(editor.text(), spaces);
+    }
+
+    #[test]
+    fn test_diff_against_clipboard_value() {
+        let editor = JsonEditor::with_text(
+            r#"{"name": "app", "version": 2, "tags": ["a", "b"], "new": true}"#.to_string(),
+        );
+        let clipboard: Value =
+            serde_json::from_str(r#"{"name": "app", "version": 1, "tags": ["a"], "old": null}"#)
+                .unwrap();
+
+        let entries = editor.diff_against(&clipboard).unwrap();
+        let summary: Vec<(char, String)> = entries
+            .iter()
+            .map(|e| (e.symbol(), e.path_display()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('~', "version".to_string()),
+                ('+', "tags.1".to_string()),
+                ('-', "old".to_string()),
+                ('+', "new".to_string()),
+            ]
+        );
+
+        assert!(editor.diff_against_text("not json").is_err());
+        assert!(editor.diff_against_text(&editor.text).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_clipboard_text() {
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+
+        // Nothing asked for the clipboard
+        editor.diff_clipboard_text(Some("{}".to_string()));
+        assert!(editor.clipboard_diff.is_none());
+
+        // Read directly: the diff is shown right away
+        editor.clipboard_diff_requested = true;
+        assert!(editor.wants_clipboard_text());
+        editor.diff_clipboard_text(Some(r#"{"a": 2}"#.to_string()));
+        assert!(!editor.wants_clipboard_text());
+        let state = editor.clipboard_diff.as_ref().unwrap();
+        assert_eq!(state.text, r#"{"a": 2}"#);
+        assert!(matches!(&state.result, Some(Ok(entries)) if entries.len() == 1));
+
+        // Unreadable clipboard: the dialog waits for a paste
+        editor.clipboard_diff = None;
+        editor.clipboard_diff_requested = true;
+        editor.diff_clipboard_text(None);
+        assert!(editor.clipboard_diff.as_ref().unwrap().result.is_none());
+    }
+
+    #[test]
+    fn test_value_at_path() {
+        let editor = JsonEditor::with_text(r#"{"a": {"b": [1, {"c": true}]}}"#.to_string());
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+
+        assert_eq!(
+            editor.value_at_path(&path(&["a", "b", "1", "c"])),
+            Some(&Value::Bool(true))
+        );
+        assert_eq!(editor.value_at_path(&[]), editor.parsed_value());
+        assert!(editor.value_at_path(&path(&["a", "b", "5"])).is_none());
+        assert!(editor.value_at_path(&path(&["a", "x"])).is_none());
+    }
+
+    #[test]
+    fn test_tree_operations_use_path_edits() {
+        let mut editor =
+            JsonEditor::with_text(r#"{"name": "a", "tags": ["x"], "old": 1}"#.to_string());
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+
+        assert!(editor.apply_tree_operation(TreeOperation::Update {
+            path: path(&["name"]),
+            value: "\"b\"".to_string(),
+        }));
+        assert!(editor.apply_tree_operation(TreeOperation::Delete {
+            path: path(&["old"]),
+        }));
+        assert!(editor.apply_tree_operation(TreeOperation::Rename {
+            path: path(&["name"]),
+            new_key: "title".to_string(),
+        }));
+        // Renaming onto an existing key fails and leaves the document alone
+        assert!(!editor.apply_tree_operation(TreeOperation::Rename {
+            path: path(&["title"]),
+            new_key: "tags".to_string(),
+        }));
+        assert!(editor.apply_tree_operation(TreeOperation::Add {
+            path: path(&["tags"]),
+            key: String::new(),
+            value: "y".to_string(),
+        }));
+        assert!(editor.apply_tree_operation(TreeOperation::Add {
+            path: Vec::new(),
+            key: "flag".to_string(),
+            value: "true".to_string(),
+        }));
+
+        assert_eq!(
+            editor.parsed_value().unwrap(),
+            &serde_json::json!({"title": "b", "tags": ["x", "y"], "flag": true})
+        );
+    }
+
+    #[test]
+    fn test_tree_expansion_survives_edits() {
+        let mut editor = JsonEditor::with_text(r#"{"a": {"b": [1, 2]}}"#.to_string());
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+
+        // A collapsed container stays collapsed after the document changes
+        editor.tree.set_expanded(&path(&["a"]), false);
+        assert!(
+            editor
+                .update_value_at_path(&path(&["a", "b", "0"]), "5")
+                .is_ok()
+        );
+        let value = editor.parsed_value().cloned().unwrap();
+        assert!(!editor.tree.is_expanded(&path(&["a"]), &value["a"]));
+
+        editor.expand_all_tree();
+        assert!(editor.tree.is_expanded(&path(&["a"]), &value["a"]));
+    }
+
+    #[test]
+    fn test_type_signature() {
+        let editor = JsonEditor::new();
+        assert_eq!(
+            editor.type_signature_at(&["items".to_string()]),
+            Some("array<{id:number,value:string}>".to_string())
+        );
+        assert_eq!(
+            editor.type_signature_at(&["languages".to_string()]),
+            Some("{korean:string,chinese:string,japanese:string,english:string}".to_string())
+        );
+
+        let value = serde_json::json!({
+            "mixed": [1, "a"],
+            "empty": [],
+            "nested": [[true], [false]],
+            "odd key": null,
+            "varying": [{"a": 1}, {"a": 1, "b": 2}]
+        });
+        assert_eq!(
+            type_signature(&value),
+            r#"{mixed:array<any>,empty:array<any>,nested:array<array<boolean>>,"odd key":null,varying:array<any>}"#
+        );
+        assert_eq!(type_signature(&serde_json::json!({})), "{}");
+
+        // Containers past the depth limit are elided
+        let mut deep = serde_json::json!(1);
+        for _ in 0..MAX_DEPTH + 10 {
+            deep = Value::Array(vec![deep]);
+        }
+        assert_eq!(
+            type_signature(&deep),
+            format!("{}…{}", "array<".repeat(MAX_DEPTH), ">".repeat(MAX_DEPTH))
+        );
+    }
+
+    #[test]
+    fn test_dotted_keys_do_not_collide_with_nested_paths() {
+        let mut editor = JsonEditor::with_text(
+            r#"{"weird.key": {"x": 1}, "weird": {"key": {"y": 2}}}"#.to_string(),
+        );
+        editor.validate();
+        let dotted = vec!["weird.key".to_string()];
+        let nested = vec!["weird".to_string(), "key".to_string()];
+        let dotted_value = editor.value_at_path(&dotted).unwrap().clone();
+        let nested_value = editor.value_at_path(&nested).unwrap().clone();
+        assert_ne!(dotted_value, nested_value);
+
+        // Tree expansion is tracked per path
+        assert!(editor.tree.is_expanded(&dotted, &dotted_value));
+        editor.tree.set_expanded(&dotted, false);
+        assert!(!editor.tree.is_expanded(&dotted, &dotted_value));
+        assert!(editor.tree.is_expanded(&nested, &nested_value));
+
+        // Each finds its own line
+        assert_eq!(editor.find_line_for_path(&dotted), Some(1));
+        let text = "{\n  \"weird.key\": 1,\n  \"weird\": {\n    \"key\": 2\n  }\n}";
+        let lines = JsonEditor::with_text(text.to_string());
+        assert_eq!(lines.find_line_for_path(&dotted), Some(2));
+        assert_eq!(lines.find_line_for_path(&nested), Some(4));
+
+        // Display paths quote keys with path punctuation
+        let root = editor.parsed_value().unwrap();
+        assert_eq!(format_json_path(root, &dotted), r#"["weird.key"]"#);
+        assert_eq!(format_json_path(root, &nested), "weird.key");
+        assert_eq!(
+            editor.leaf_paths_at(&[], false).unwrap(),
+            "[\"weird.key\"].x\nweird.key.y"
+        );
+    }
+
+    #[test]
+    fn test_leaf_paths_at() {
+        let editor = JsonEditor::new();
+        let items = vec!["items".to_string()];
+
+        let absolute = editor.leaf_paths_at(&items, false).unwrap();
+        assert_eq!(
+            absolute.lines().collect::<Vec<_>>(),
+            vec![
+                "items[0].id",
+                "items[0].value",
+                "items[1].id",
+                "items[1].value"
+            ]
+        );
+
+        let relative = editor.leaf_paths_at(&items, true).unwrap();
+        assert_eq!(relative.lines().next(), Some("[0].id"));
+
+        let nested = JsonEditor::with_text(r#"{"a": [{"b": {}}, []], "c": 1}"#.to_string());
+        assert_eq!(nested.leaf_paths_at(&[], false).unwrap(), "a[0].b\na[1]\nc");
+        assert!(nested.leaf_paths_at(&["x".to_string()], false).is_none());
+    }
+
+    /// Simulate typing `typed` one character at a time, `interval` seconds apart
+    fn type_text(editor: &mut JsonEditor, typed: &str, start: f64, interval: f64) -> f64 {
+        let mut now = start;
+        for c in typed.chars() {
+            let previous = editor.text.clone();
+            editor.text.push(c);
+            editor.record_typing(previous, now);
+            editor.previous_text = editor.text.clone();
+            now += interval;
+        }
+        now
+    }
+
+    #[test]
+    fn test_set_view_mode() {
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+        assert_eq!(editor.view_mode(), ViewMode::Text);
+
+        // Typing that hasn't been validated yet shows up in the tree
+        editor.text = r#"{"a": 2}"#.to_string();
+        editor.text_typed(10.0);
+        editor.set_view_mode(ViewMode::Tree);
+        assert_eq!(editor.view_mode(), ViewMode::Tree);
+        assert_eq!(editor.parsed_value().unwrap()["a"], serde_json::json!(2));
+
+        editor.set_view_mode(ViewMode::Tree);
+        assert_eq!(editor.view_mode(), ViewMode::Tree);
+        editor.toggle_view_mode();
+        assert_eq!(editor.view_mode(), ViewMode::Text);
+    }
+
+    #[test]
+    fn test_number_format_is_display_only() {
+        let text = r#"{"amount": 1234567.891}"#;
+        let mut editor = JsonEditor::with_text(text.to_string());
+        editor.set_number_format(NumberFormat {
+            precision: Some(2),
+            thousands_separator: true,
+        });
+        editor.toggle_view_mode();
+        assert_eq!(editor.view_mode, ViewMode::Tree);
+
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                editor.ui(ui);
+            });
+        });
+
+        assert_eq!(editor.text(), text);
+        assert_eq!(
+            editor.parsed_value().unwrap()["amount"],
+            serde_json::json!(1234567.891)
+        );
+    }
+
+    #[test]
+    fn test_validation_waits_for_typing_pause() {
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+
+        // Typing "23" after the 1: nothing is parsed mid-burst
+        editor.text.insert(7, '2');
+        editor.text_typed(10.0);
+        editor.text.insert(8, '3');
+        editor.text_typed(10.1);
+        assert!(!editor.run_due_validation(10.2));
+        assert_eq!(editor.parsed_value().unwrap()["a"], serde_json::json!(1));
+
+        assert!(editor.run_due_validation(10.1 + VALIDATE_DELAY_SECS));
+        assert_eq!(editor.parsed_value().unwrap()["a"], serde_json::json!(123));
+        assert!(!editor.run_due_validation(20.0));
+        assert_eq!(editor.undo_stack.len(), 1);
+    }
+
+    #[test]
+    fn test_structured_edit_flushes_pending_validation() {
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+        editor.text = r#"{"a": 1, "b": 2}"#.to_string();
+        editor.text_typed(10.0);
+
+        // The edit sees the typed key instead of reverting to the stale document
+        editor
+            .update_value_at_path(&["a".to_string()], "true")
+            .unwrap();
+        let value = editor.parsed_value().unwrap();
+        assert_eq!(value["a"], serde_json::json!(true));
+        assert_eq!(value["b"], serde_json::json!(2));
+        assert!(!editor.run_due_validation(20.0));
+    }
+
+    #[test]
+    fn test_edit_events_describe_applied_edits() {
+        let mut editor =
+            JsonEditor::with_text(r#"{"name": "old", "tags": ["b", "a"]}"#.to_string());
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+
+        editor
+            .update_value_at_path(&path(&["name"]), "\"new\"")
+            .unwrap();
+        editor
+            .add_value_at_path(&path(&["tags"]), "", "\"c\"")
+            .unwrap();
+        editor.rename_key_at_path(&[], "name", "title").unwrap();
+        editor.delete_value_at_path(&path(&["tags", "0"])).unwrap();
+        // Failed edits don't emit events
+        assert!(editor.delete_value_at_path(&path(&["missing"])).is_err());
+
+        let events = editor.drain_events();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            &events[0],
+            EditEvent::Update { path, old_value, new_value, .. }
+                if path == &["name"] && old_value == "old" && new_value == "new"
+        ));
+        assert!(matches!(
+            &events[1],
+            EditEvent::Add { path, value, replaced: None, .. }
+                if path == &["tags", "2"] && value == "c"
+        ));
+        assert!(matches!(
+            &events[2],
+            EditEvent::Rename { path, old_key, new_key, .. }
+                if path.is_empty() && old_key == "name" && new_key == "title"
+        ));
+        assert!(matches!(
+            &events[3],
+            EditEvent::Delete { path, old_value, .. } if path == &["tags", "0"] && old_value == "b"
+        ));
+        assert!(
+            events
+                .windows(2)
+                .all(|w| w[0].timestamp() <= w[1].timestamp())
+        );
+        assert!(editor.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_jsonc_update_event_carries_old_value() {
+        let text = "{\n  // Retries\n  \"retries\": [1, /* two */ 2]\n}";
+        let mut editor = JsonEditor::with_text(text.to_string());
+        editor.set_allow_comments(true);
+
+        editor
+            .update_value_at_path(&["retries".to_string()], "3")
+            .unwrap();
+        assert!(matches!(
+            editor.drain_events().as_slice(),
+            [EditEvent::Update { old_value, .. }] if old_value == &serde_json::json!([1, 2])
+        ));
+    }
+
+    #[test]
+    fn test_auto_format_after_delay() {
+        let mut editor = JsonEditor::with_text(r#"{"a":[1,2]}"#.to_string());
+        editor.set_auto_format(true);
+        editor.schedule_auto_format(10.0);
+
+        // Not yet due
+        assert_eq!(editor.run_auto_format(10.1, Some(6)), None);
+        assert_eq!(editor.text(), r#"{"a":[1,2]}"#);
+
+        // Caret after "1" stays after "1"
+        let caret = editor
+            .run_auto_format(10.0 + AUTO_FORMAT_DELAY_SECS, Some(7))
+            .unwrap();
+        assert_eq!(editor.text(), "{\n  \"a\": [\n    1,\n    2\n  ]\n}");
+        let before: String = editor.text().chars().take(caret).collect();
+        assert!(before.ends_with('1'));
+
+        // Formatting is a single undo step
+        assert!(editor.undo());
+        assert_eq!(editor.text(), r#"{"a":[1,2]}"#);
+
+        // Invalid text is never scheduled
+        editor.set_text("{".to_string());
+        editor.schedule_auto_format(20.0);
+        assert_eq!(editor.run_auto_format(30.0, None), None);
+    }
+
+    #[test]
+    fn test_on_validity_change_fires_on_transitions() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+        editor.validate();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&events);
+        editor.set_on_validity_change(move |valid| recorded.borrow_mut().push(valid));
+
+        // Edits that stay valid don't fire
+        editor.set_text(r#"{"a": 12}"#.to_string());
+        editor.set_text(r#"{"a": 123}"#.to_string());
+        assert!(events.borrow().is_empty());
+
+        editor.set_text(r#"{"a": 123"#.to_string());
+        editor.set_text(r#"{"a": 12"#.to_string());
+        editor.set_text(r#"{"a": 123}"#.to_string());
+        assert_eq!(*events.borrow(), vec![false, true]);
+    }
+
+    #[test]
+    fn test_typing_burst_is_one_undo_step() {
+        let mut editor = JsonEditor::with_text(String::new());
+        let now = type_text(&mut editor, "hello", 0.0, 0.1);
+        let now = type_text(&mut editor, " world", now, 0.1);
+        assert_eq!(editor.text(), "hello world");
+
+        // Whitespace starts a new step: one undo removes the last word
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "hello");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "");
+
+        // A pause also ends the burst
+        assert!(editor.redo());
+        let now = type_text(&mut editor, "ab", now, 0.1);
+        type_text(&mut editor, "cd", now + TYPING_UNDO_GROUP_SECS, 0.1);
+        assert_eq!(editor.text(), "helloabcd");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "helloab");
+    }
+
+    #[test]
+    fn test_graph_edit_is_separate_undo_step() {
+        let mut editor = JsonEditor::with_text(r#"{"a":1"#.to_string());
+        let now = type_text(&mut editor, "}", 0.0, 0.1);
+        editor.validate();
+
+        editor.begin_undo_group();
+        assert!(editor.add_value_at_path(&[], "b", "2").is_ok());
+        assert!(editor.rename_key_at_path(&[], "b", "c").is_ok());
+        editor.end_undo_group();
+
+        // Typing right after the edit starts a new step
+        let before_typing = editor.text().to_string();
+        type_text(&mut editor, " ", now, 0.1);
+
+        assert!(editor.undo());
+        assert_eq!(editor.text(), before_typing);
+        // Both grouped edits undo at once, back to the typed text
+        assert!(editor.undo());
+        assert_eq!(editor.text(), r#"{"a":1}"#);
+        assert!(editor.undo());
+        assert_eq!(editor.text(), r#"{"a":1"#);
+    }
+
+    #[test]
+    fn test_typing_splits_undo_at_tokens_jumps_and_deletions() {
+        let mut editor = JsonEditor::with_text(String::new());
+
+        // Structural characters start a new step, like whitespace
+        let now = type_text(&mut editor, r#"{"name":"#, 0.0, 0.1);
+        assert!(editor.undo());
+        assert_eq!(editor.text(), r#"{"name""#);
+        assert!(editor.undo());
+        assert_eq!(editor.text(), r#"{"name"#);
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "{");
+
+        // Backspacing is a step of its own, separate from the typing before it
+        let now = type_text(&mut editor, "abc", now, 0.1);
+        for now in [now, now + 0.1] {
+            let previous = editor.text.clone();
+            editor.text.pop();
+            editor.record_typing(previous, now);
+        }
+        assert_eq!(editor.text(), "{a");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "{abc");
+
+        // Typing somewhere else right away is a new step too
+        let previous = editor.text.clone();
+        editor.text.insert(1, 'x');
+        editor.record_typing(previous, now + 0.3);
+        assert_eq!(editor.text(), "{xabc");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "{abc");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "{");
+    }
+
+    #[test]
+    fn test_format_selection_reindents_only_the_selection() {
+        let text = "{\n  \"a\": {\"b\":1,\"c\":[1,2]},\n  \"d\": [3,4]\n}";
+        let mut editor = JsonEditor::with_text(text.to_string());
+        let char_of = |needle: &str| text[..text.find(needle).unwrap()].chars().count();
+
+        // Surrounding whitespace in the selection is kept
+        let start = char_of(r#" {"b""#);
+        let end = char_of(",\n  \"d\"");
+        let formatted = editor.format_selection(start..end).unwrap();
+        assert_eq!(
+            editor.text(),
+            "{\n  \"a\": {\n    \"b\": 1,\n    \"c\": [\n      1,\n      2\n    ]\n  },\n  \"d\": [3,4]\n}"
+        );
+        let selected: String = editor
+            .text()
+            .chars()
+            .skip(formatted.start)
+            .take(formatted.len())
+            .collect();
+        assert!(selected.starts_with('{') && selected.ends_with('}'));
+        assert!(editor.is_valid());
+
+        assert!(editor.undo());
+        assert_eq!(editor.text(), text);
+    }
+
+    #[test]
+    fn test_format_selection_rejects_partial_json() {
+        let text = r#"{"a": {"b": 1}}"#;
+        let mut editor = JsonEditor::with_text(text.to_string());
+
+        assert!(matches!(
+            editor.format_selection(1..8),
+            Err(EditorError::ParseError { .. })
+        ));
+        assert_eq!(editor.text(), text);
+        assert!(!editor.can_undo());
+    }
+
+    #[test]
+    fn test_pretty_print_source_key_order() {
+        // The value's own key order differs from the text it is printed against, so only
+        // the source-order writer can put `b` first
+        let value = serde_json::json!({"a": {"c": 3, "d": 2}, "b": 1});
+        let source = r#"{"b":0,"a":{"d":0,"c":0}}"#;
+        let mut editor = JsonEditor::new();
+
+        assert_eq!(
+            editor.to_pretty_string_from(&value, source).unwrap(),
+            "{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}"
+        );
+
+        editor.set_source_key_order(true);
+        assert_eq!(
+            editor.to_pretty_string_from(&value, source).unwrap(),
+            "{\n  \"b\": 1,\n  \"a\": {\n    \"d\": 2,\n    \"c\": 3\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_at_path() {
+        let mut editor = JsonEditor::with_text(
+            r#"{"items": [{"id": 1, "tags": ["a"]}, 2], "name": "x", "name_copy": "y"}"#
+                .to_string(),
+        );
+        editor.validate();
+
+        assert!(
+            editor
+                .duplicate_at_path(&["items".to_string(), "0".to_string()])
+                .is_ok()
+        );
+        let items = &editor.parsed_value().unwrap()["items"];
+        assert_eq!(items.as_array().unwrap().len(), 3);
+        assert_eq!(items[1], serde_json::json!({"id": 1, "tags": ["a"]}));
+        assert_eq!(items[2], serde_json::json!(2));
+
+        assert!(editor.duplicate_at_path(&["name".to_string()]).is_ok());
+        let keys: Vec<_> = editor
+            .parsed_value()
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(keys, ["items", "name", "name_copy_2", "name_copy"]);
+        assert_eq!(editor.parsed_value().unwrap()["name_copy_2"], "x");
+
+        assert!(editor.duplicate_at_path(&["missing".to_string()]).is_err());
+        assert!(
+            editor
+                .duplicate_at_path(&["items".to_string(), "9".to_string()])
+                .is_err()
+        );
+        assert!(editor.duplicate_at_path(&[]).is_err());
+    }
+
+    #[test]
+    fn test_insert_at_path() {
+        let mut editor = JsonEditor::with_text(r#"{"items": ["a", "c"], "name": "x"}"#.to_string());
+        editor.validate();
+        let items = vec!["items".to_string()];
+        let value = |editor: &JsonEditor| editor.parsed_value().unwrap()["items"].clone();
+
+        assert!(editor.insert_at_path(&items, 1, "\"b\"").is_ok());
+        assert_eq!(value(&editor), serde_json::json!(["a", "b", "c"]));
+        assert!(editor.insert_at_path(&items, 0, "null").is_ok());
+        assert!(editor.insert_at_path(&items, 4, "true").is_ok());
+        assert_eq!(
+            value(&editor),
+            serde_json::json!([null, "a", "b", "c", true])
+        );
+        assert!(editor.undo());
+        assert_eq!(value(&editor), serde_json::json!([null, "a", "b", "c"]));
+
+        // Past the end, or into a non-Array, leaves the document alone
+        let text = editor.text().to_string();
+        assert!(matches!(
+            editor.insert_at_path(&items, 5, "1"),
+            Err(EditorError::InvalidPath { .. })
+        ));
+        assert!(matches!(
+            editor.insert_at_path(&[], 0, "1"),
+            Err(EditorError::TypeMismatch(_))
+        ));
+        assert_eq!(editor.text(), text);
+    }
+
+    #[test]
+    fn test_sort_array_at_path() {
+        let mut editor = JsonEditor::with_text(
+            r#"{"n": [10, 2, 33, 2.5], "s": ["b", "a", "C"], "m": [1, "1", [0], null], "o": [{"id": 2}, {"x": 0}, {"id": 1}]}"#
+                .to_string(),
+        );
+        let path = |key: &str| vec![key.to_string()];
+        let value = |editor: &JsonEditor, key: &str| editor.parsed_value().unwrap()[key].clone();
+
+        assert!(editor.sort_array_at_path(&path("n"), false, None).is_ok());
+        assert_eq!(value(&editor, "n"), serde_json::json!([2, 2.5, 10, 33]));
+        assert!(editor.sort_array_at_path(&path("n"), true, None).is_ok());
+        assert_eq!(value(&editor, "n"), serde_json::json!([33, 10, 2.5, 2]));
+
+        assert!(editor.sort_array_at_path(&path("s"), false, None).is_ok());
+        assert_eq!(value(&editor, "s"), serde_json::json!(["C", "a", "b"]));
+
+        // Mixed items sort by their JSON text: "\"1\"" < "1" < "[0]" < "null"
+        assert!(editor.sort_array_at_path(&path("m"), false, None).is_ok());
+        assert_eq!(value(&editor, "m"), serde_json::json!(["1", 1, [0], null]));
+
+        // Objects need a key; items without it go last
+        assert!(matches!(
+            editor.sort_array_at_path(&path("o"), false, None),
+            Err(EditorError::TypeMismatch(_))
+        ));
+        assert!(
+            editor
+                .sort_array_at_path(&path("o"), false, Some("id"))
+                .is_ok()
+        );
+        assert_eq!(
+            value(&editor, "o"),
+            serde_json::json!([{"id": 1}, {"id": 2}, {"x": 0}])
+        );
+
+        // Each sort is one undo step
+        editor.undo();
+        assert_eq!(
+            value(&editor, "o"),
+            serde_json::json!([{"id": 2}, {"x": 0}, {"id": 1}])
+        );
+        assert!(matches!(
+            editor.sort_array_at_path(&[], false, None),
+            Err(EditorError::TypeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_large_integers_survive_formatting() {
+        let mut editor = JsonEditor::with_text(
+            r#"{"id": 12345678901234567890, "amount": 0.1000000000000000055511151231257827}"#
+                .to_string(),
+        );
+        editor.validate();
+        editor.apply_pretty_print();
+        assert!(editor.text().contains("12345678901234567890"));
+        editor.validate();
+        editor.apply_compact();
+
+        assert_eq!(
+            editor.text(),
+            r#"{"id":12345678901234567890,"amount":0.1000000000000000055511151231257827}"#
+        );
+    }
+
+    #[test]
+    fn test_compact_escapes_non_ascii() {
+        let mut editor = JsonEditor::with_text(r#"{"k": "한글", "e": "a😀"}"#.to_string());
+        editor.set_escape_non_ascii(true);
+        editor.apply_compact();
+
+        assert_eq!(editor.text(), r#"{"k":"\ud55c\uae00","e":"a\ud83d\ude00"}"#);
+        assert!(editor.text().is_ascii());
+        // Escapes decode back to the original strings
+        editor.validate();
+        assert_eq!(
+            editor.parsed_value().unwrap()["e"],
+            Value::String("a😀".to_string())
+        );
+
+        editor.set_escape_non_ascii(false);
+        editor.apply_compact();
+        assert_eq!(editor.text(), r#"{"k":"한글","e":"a😀"}"#);
+    }
+
+    #[test]
+    fn test_invalid_path_segments_are_rejected() {
+        let mut editor = JsonEditor::with_text(r#"{"a": [1, 2], "": {"ok": true}}"#.to_string());
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+        let array = serde_json::json!([1, 2]);
+        let object = serde_json::json!({"a": 1});
+
+        assert_eq!(
+            JsonEditor::path_segment_error(&array, ""),
+            Some("empty path segment used as an array index".to_string())
+        );
+        assert_eq!(
+            JsonEditor::path_segment_error(&array, "+1"),
+            Some("'+1' is not a valid array index".to_string())
+        );
+        assert_eq!(
+            JsonEditor::path_segment_error(&object, ""),
+            Some("empty path segment used as an object key".to_string())
+        );
+        assert_eq!(JsonEditor::path_segment_error(&array, "7"), None);
+
+        assert!(editor.value_at_path(&path(&["a", ""])).is_none());
+        assert!(matches!(
+            editor.delete_value_at_path(&path(&["a", ""])),
+            Err(EditorError::InvalidPath { .. })
+        ));
+        assert!(
+            editor
+                .update_value_at_path(&path(&["a", " 1"]), "3")
+                .is_err()
+        );
+
+        // "" is a legal key when the object actually has it
+        assert_eq!(
+            editor.value_at_path(&path(&["", "ok"])),
+            Some(&Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn test_get_subtree_json() {
+        let editor = JsonEditor::with_text(r#"{"a": {"b": [1, 2]}}"#.to_string());
+
+        let subtree = editor
+            .get_subtree_json(&["a".to_string(), "b".to_string()])
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&subtree).unwrap(),
+            serde_json::json!([1, 2])
+        );
+
+        let root = editor.get_subtree_json(&[]).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&root).unwrap(),
+            serde_json::json!({"a": {"b": [1, 2]}})
+        );
+
+        assert!(editor.get_subtree_json(&["missing".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_find_line_for_path_duplicate_keys() {
+        let editor = JsonEditor::with_text(
+            r#"{
+  "admin": {
+    "name": "root"
+  },
+  "user": {
+    "id": 1,
+    "name": "alice"
+  },
+  "items": [
+    {"name": "first"},
+    {
+      "name": "second"
+    }
+  ]
+}"#
+            .to_string(),
+        );
+
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+
+        assert_eq!(
+            editor.find_line_for_path(&path(&["admin", "name"])),
+            Some(3)
+        );
+        assert_eq!(editor.find_line_for_path(&path(&["user", "name"])), Some(7));
+        assert_eq!(editor.find_line_for_path(&path(&["items", "0"])), Some(10));
+        assert_eq!(
+            editor.find_line_for_path(&path(&["items", "1", "name"])),
+            Some(12)
+        );
+        // Missing leaf falls back to the deepest matched segment
+        assert_eq!(
+            editor.find_line_for_path(&path(&["user", "email"])),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_path_line_mapping_deep_unbalanced_text() {
+        let depth = 50_000;
+        let editor = JsonEditor::with_text(format!("{}\n1", "[\n".repeat(depth)));
+        assert!(!editor.is_valid());
+
+        let path = editor.find_path_for_line(depth).unwrap();
+        assert_eq!(path.len(), depth - 1);
+        assert_eq!(editor.find_line_for_path(&path), Some(depth));
+    }
+
+    #[test]
+    fn test_path_line_mapping_with_repeated_names() {
+        let editor = JsonEditor::with_text(
+            r#"{
+  "name": "name",
+  "child": {
+    "name": {
+      "name": 1
+    }
+  },
+  "list": [
+    {"name": "x"}
+  ]
+}"#
+            .to_string(),
+        );
 
-            // Redo: Ctrl+Y (Windows/Linux) or Cmd+Shift+Z (macOS) or Ctrl+Shift+Z
-            if (i.modifiers.command && i.key_pressed(egui::Key::Y))
-                || (i.modifiers.command && i.modifiers.shift && i.key_pressed(egui::Key::Z))
-            {
-                redo_requested = true;
-            }
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
 
-            // Select All: Ctrl+A (Windows/Linux) or Cmd+A (macOS)
-            // Note: egui's TextEdit handles this automatically, but we detect it here for logging
-            if i.modifiers.command && i.key_pressed(egui::Key::A) {
-                select_all_requested = true;
-            }
-        });
+        assert_eq!(editor.find_line_for_path(&path(&["name"])), Some(2));
+        assert_eq!(
+            editor.find_line_for_path(&path(&["child", "name"])),
+            Some(4)
+        );
+        assert_eq!(
+            editor.find_line_for_path(&path(&["child", "name", "name"])),
+            Some(5)
+        );
+        assert_eq!(
+            editor.find_line_for_path(&path(&["list", "0", "name"])),
+            Some(9)
+        );
 
-        // Process undo/redo requests
-        if undo_requested && self.can_undo() {
-            self.undo();
-            changed = true;
-            self.log_to_console("Undo via keyboard shortcut");
-        }
-        if redo_requested && self.can_redo() {
-            self.redo();
-            changed = true;
-            self.log_to_console("Redo via keyboard shortcut");
-        }
-        if select_all_requested {
-            self.log_to_console("Select all via keyboard shortcut");
-        }
+        assert_eq!(editor.find_path_for_line(2), Some(path(&[])));
+        assert_eq!(editor.find_path_for_line(5), Some(path(&["child", "name"])));
+        assert_eq!(editor.find_path_for_line(7), Some(path(&["child"])));
+        assert_eq!(editor.find_path_for_line(9), Some(path(&["list", "0"])));
+        assert_eq!(editor.find_path_for_line(10), Some(path(&["list"])));
+        assert_eq!(editor.find_path_for_line(99), None);
+    }
 
-        // Toolbar
-        ui.horizontal(|ui| {
-            // View mode toggle
-            let view_text = match self.view_mode {
-                ViewMode::Text => "📝 Text",
-                ViewMode::Tree => "🌲 Tree",
-            };
-            if ui.button(view_text).clicked() {
-                self.toggle_view_mode();
-            }
+    #[test]
+    fn test_revision_changes_with_the_document() {
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+        let mut last = editor.revision();
+        let mut assert_bumped = |editor: &JsonEditor| {
+            assert!(editor.revision() != last);
+            last = editor.revision();
+        };
 
-            ui.separator();
+        assert!(editor.update_value_at_path(&["a".to_string()], "2").is_ok());
+        assert_bumped(&editor);
+        editor.apply_compact();
+        assert_bumped(&editor);
+        assert!(editor.undo());
+        assert_bumped(&editor);
+        editor.set_text("[]".to_string());
+        assert_bumped(&editor);
 
-            // Format buttons (only in text mode)
-            if self.view_mode == ViewMode::Text {
-                if ui.button("Pretty").clicked() && self.is_valid() {
-                    self.push_undo();
-                    self.apply_pretty_print();
-                    changed = true;
-                }
+        // Reading the document doesn't change it
+        let _ = editor.stats();
+        assert_eq!(editor.revision(), last);
+    }
 
-                if ui.button("Compact").clicked() && self.is_valid() {
-                    self.push_undo();
-                    self.apply_compact();
-                    changed = true;
-                }
-            }
+    #[test]
+    fn test_text_version_follows_the_text() {
+        let mut editor = JsonEditor::with_text(r#"{"a": 1}"#.to_string());
+        let loaded = editor.text_version();
 
-            ui.separator();
+        assert!(editor.update_value_at_path(&["a".to_string()], "2").is_ok());
+        let edited = editor.text_version();
+        assert_ne!(edited, loaded);
 
-            // Edit buttons
-            if ui
-                .add_enabled(self.can_undo(), egui::Button::new("Undo"))
-                .clicked()
-            {
-                self.undo();
-                changed = true;
-            }
+        // Re-validating (e.g. after a parser setting changed) keeps the text as it is
+        editor.set_tolerate_trailing_commas(true);
+        assert_eq!(editor.text_version(), edited);
 
-            if ui
-                .add_enabled(self.can_redo(), egui::Button::new("Redo"))
-                .clicked()
-            {
-                self.redo();
-                changed = true;
-            }
+        // Undo and redo bring back the version of the text they restore
+        assert!(editor.undo());
+        assert_eq!(editor.text_version(), loaded);
+        assert!(editor.redo());
+        assert_eq!(editor.text_version(), edited);
 
-            ui.separator();
+        editor.apply_compact();
+        assert_ne!(editor.text_version(), edited);
+    }
 
-            // Line numbers toggle (only in text mode)
-            if self.view_mode == ViewMode::Text {
-                if ui
-                    .checkbox(&mut self.show_line_numbers, "Line Numbers")
-                    .clicked()
-                {
-                    self.log_to_console(&format!(
-                        "Line numbers: {}",
-                        if self.show_line_numbers { "on" } else { "off" }
-                    ));
-                }
+    #[test]
+    fn test_line_at_matches_line_of_byte() {
+        let text = "{\n  \"a\": 1,\n\n  \"b\": [2]\n}\n";
+        let line_starts = JsonEditor::line_starts(text);
+        for byte in 0..=text.len() {
+            assert_eq!(
+                JsonEditor::line_at(&line_starts, byte),
+                JsonEditor::line_of_byte(text, byte)
+            );
+        }
+    }
 
-                ui.separator();
-            }
+    #[test]
+    fn test_add_json_value_nested() {
+        let mut editor = JsonEditor::with_text(r#"{"list": []}"#.to_string());
+        let nested = serde_json::json!({"a": [1, {"b": null}]});
 
-            // Validation status
-            if self.is_valid() {
-                ui.colored_label(egui::Color32::GREEN, "✓ Valid JSON");
-            } else {
-                ui.colored_label(egui::Color32::RED, "✗ Invalid JSON");
-            }
-        });
+        assert!(
+            editor
+                .add_json_value_at_path(&[], "obj", nested.clone())
+                .is_ok()
+        );
+        assert!(
+            editor
+                .add_json_value_at_path(&["list".to_string()], "", nested.clone())
+                .is_ok()
+        );
+        assert_eq!(editor.parsed_value().unwrap()["obj"], nested);
+        assert_eq!(editor.parsed_value().unwrap()["list"][0], nested);
 
-        ui.separator();
+        // Objects require a key
+        assert!(editor.add_json_value_at_path(&[], "", nested).is_err());
+    }
 
-        // Error message
-        if let Some(error) = &self.error_message {
-            ui.colored_label(egui::Color32::RED, error);
-        }
+    #[test]
+    fn test_update_subtree_at_path() {
+        let mut editor =
+            JsonEditor::with_text(r#"{"a": {"b": 1}, "list": [[1], "x"]}"#.to_string());
+        let replacement = serde_json::json!([{"c": true}, null]);
 
-        // Render based on view mode
-        match self.view_mode {
-            ViewMode::Tree => {
-                // Tree view with folding
-                if let Some(value) = &self.parsed_value {
-                    egui::ScrollArea::vertical()
-                        .max_height(ui.available_height())
-                        .show(ui, |ui| {
-                            self.render_tree_view(ui, value, None, String::new());
-                        });
-                } else {
-                    ui.colored_label(
-                        egui::Color32::RED,
-                        "Invalid JSON - cannot display tree view",
-                    );
-                }
-            }
-            ViewMode::Text => {
-                // Original text editor view
-                self.render_text_editor(ui, &mut changed, text_edit_id);
-            }
-        }
+        assert!(
+            editor
+                .update_subtree_at_path(&["a".to_string()], replacement.clone())
+                .is_ok()
+        );
+        assert!(
+            editor
+                .update_subtree_at_path(&["list".to_string(), "0".to_string()], "s".into())
+                .is_ok()
+        );
+        assert_eq!(
+            editor.parsed_value().unwrap(),
+            &serde_json::json!({"a": replacement, "list": ["s", "x"]})
+        );
+        // Undo brings back the replaced subtree
+        assert!(editor.undo());
+        assert_eq!(
+            editor.parsed_value().unwrap()["list"][0],
+            serde_json::json!([1])
+        );
 
-        changed
+        assert!(
+            editor
+                .update_subtree_at_path(&["missing".to_string(), "x".to_string()], 1.into())
+                .is_err()
+        );
     }
 
-    /// Render the text editor mode
-    fn render_text_editor(
-        &mut self,
-        ui: &mut egui::Ui,
-        changed: &mut bool,
-        text_edit_id: egui::Id,
-    ) {
-        // Editor area with line numbers - use all available height
-        let available_height = ui.available_height();
+    #[test]
+    fn test_parse_goto_line() {
+        assert_eq!(JsonEditor::parse_goto_line("3", 10), Some(3));
+        assert_eq!(JsonEditor::parse_goto_line(" 10 ", 10), Some(10));
+        assert_eq!(JsonEditor::parse_goto_line("0", 10), None);
+        assert_eq!(JsonEditor::parse_goto_line("11", 10), None);
+        assert_eq!(JsonEditor::parse_goto_line("abc", 10), None);
+    }
 
-        // Calculate scroll offset if we need to scroll to a target line
-        let scroll_offset = if let Some(target) = self.target_line {
-            let line_height = 17.0;
-            Some((target as f32 - 1.0) * line_height)
-        } else {
-            None
-        };
+    #[test]
+    fn test_jsonc_edits_preserve_comments() {
+        let text = "{\n  // Display name\n  \"name\": \"old\", /* trailing */\n  \"count\": 1\n}";
+        let mut editor = JsonEditor::with_text(text.to_string());
+        assert!(!editor.is_valid());
 
-        // Clear target_line after calculating offset
-        if self.target_line.is_some() {
-            self.target_line = None;
-        }
+        editor.set_allow_comments(true);
+        assert!(editor.is_valid());
 
-        // Single ScrollArea containing both line numbers and editor
-        let mut scroll_area = egui::ScrollArea::vertical()
-            .id_salt("json_editor_scroll")
-            .max_height(available_height);
+        assert!(
+            editor
+                .update_value_at_path(&["name".to_string()], "\"new\"")
+                .is_ok()
+        );
+        assert_eq!(
+            editor.text(),
+            "{\n  // Display name\n  \"name\": \"new\", /* trailing */\n  \"count\": 1\n}"
+        );
+        assert_eq!(editor.parsed_value().unwrap()["name"], "new");
 
-        // Apply scroll offset if needed
-        if let Some(offset) = scroll_offset {
-            scroll_area = scroll_area.vertical_scroll_offset(offset);
-        }
+        assert!(editor.rename_key_at_path(&[], "count", "total").is_ok());
+        assert!(
+            editor
+                .change_type_at_path(&["total".to_string()], &NodeType::String)
+                .is_ok()
+        );
+        assert!(editor.text().contains("// Display name"));
+        assert!(editor.text().contains("/* trailing */"));
+        assert!(editor.text().contains("\"total\": \"1\""));
 
-        scroll_area.show(ui, |ui| {
-            ui.horizontal_top(|ui| {
-                // Line numbers column
-                if self.show_line_numbers {
-                    let line_count = self.text.lines().count();
-                    let line_number_width = 50.0;
-
-                    ui.allocate_ui_with_layout(
-                        egui::vec2(line_number_width, available_height),
-                        egui::Layout::top_down(egui::Align::Min),
-                        |ui| {
-                            ui.style_mut().spacing.item_spacing.y = 0.0;
-                            // Use fixed line height matching monospace font
-                            let line_height = 17.0;
-
-                            for i in 1..=line_count {
-                                ui.allocate_ui_with_layout(
-                                    egui::vec2(line_number_width, line_height),
-                                    egui::Layout::top_down(egui::Align::Max),
-                                    |ui| {
-                                        // Make line number clickable
-                                        let line_label = ui.selectable_label(
-                                            false,
-                                            egui::RichText::new(format!("{:>4}", i))
-                                                .color(egui::Color32::from_gray(128)),
-                                        );
-
-                                        // Detect click
-                                        if line_label.clicked() {
-                                            self.clicked_line = Some(i);
-                                            self.log_to_console(&format!("Line {} clicked", i));
-                                        }
-                                    },
-                                );
-                            }
-                        },
-                    );
+        // Undo restores the previous source text
+        assert!(editor.undo());
+        assert!(editor.text().contains("\"total\": 1"));
+    }
 
-                    ui.separator();
-                }
+    fn jsonc_editor(text: &str) -> JsonEditor {
+        let mut editor = JsonEditor::with_text(text.to_string());
+        editor.set_allow_comments(true);
+        assert!(editor.is_valid());
+        editor
+    }
 
-                // Text editor - now using full available space
-                let text_edit = egui::TextEdit::multiline(&mut self.text)
-                    .id(text_edit_id)
-                    .font(egui::TextStyle::Monospace)
-                    .desired_width(f32::INFINITY)
-                    .code_editor()
-                    .char_limit(usize::MAX) // No character limit for JSON spec compliance
-                    .lock_focus(true); // Maintain focus for IME input (Korean, etc.)
+    #[test]
+    fn test_jsonc_delete_preserves_comments() {
+        let mut editor = jsonc_editor(
+            "{\n  // Display name\n  \"name\": \"a\",\n  \"count\": 1, // items\n  \"on\": true\n}",
+        );
+        assert!(editor.delete_value_at_path(&["count".to_string()]).is_ok());
+        assert_eq!(
+            editor.text(),
+            "{\n  // Display name\n  \"name\": \"a\",\n  // items\n  \"on\": true\n}"
+        );
 
-                let response = ui.add(text_edit);
+        // Deleting the last member drops the comma before it
+        assert!(editor.delete_value_at_path(&["on".to_string()]).is_ok());
+        assert_eq!(
+            editor.text(),
+            "{\n  // Display name\n  \"name\": \"a\"\n  // items\n}"
+        );
 
-                if response.changed() {
-                    // Apply Unicode NFC normalization for Korean input
-                    self.text = self.text.nfc().collect();
+        let mut editor = jsonc_editor("[1, /* two */ 2, 3]");
+        assert!(editor.delete_value_at_path(&["0".to_string()]).is_ok());
+        assert_eq!(editor.text(), "[/* two */ 2, 3]");
+    }
 
-                    // Push previous text to undo stack for per-character undo
-                    if self.text != self.previous_text {
-                        self.undo_stack.push(self.previous_text.clone());
-                        if self.undo_stack.len() > self.max_history {
-                            self.undo_stack.remove(0);
-                        }
-                        self.redo_stack.clear();
-                        self.previous_text = self.text.clone();
-                    }
+    #[test]
+    fn test_jsonc_add_preserves_comments() {
+        let mut editor = jsonc_editor("{\n  // Display name\n  \"name\": \"a\"\n}");
+        assert!(editor.add_value_at_path(&[], "count", "1").is_ok());
+        assert_eq!(
+            editor.text(),
+            "{\n  // Display name\n  \"name\": \"a\",\n  \"count\": 1\n}"
+        );
 
-                    let was_valid = self.is_valid();
-                    self.validate();
-                    self.log_to_console("Text changed");
-                    *changed = true;
+        // An existing key only has its value replaced
+        assert!(editor.add_value_at_path(&[], "name", "\"b\"").is_ok());
+        assert_eq!(
+            editor.text(),
+            "{\n  // Display name\n  \"name\": \"b\",\n  \"count\": 1\n}"
+        );
 
-                    // If validation failed, maintain focus on the text editor
-                    if !self.is_valid() && was_valid {
-                        ui.memory_mut(|mem| mem.request_focus(text_edit_id));
-                        self.log_to_console("JSON validation failed - focus maintained");
-                    }
-                }
-            });
-        });
+        let mut editor = jsonc_editor("{\n  \"items\": [] // none yet\n}");
+        let nested = serde_json::json!({"id": 1});
+        assert!(
+            editor
+                .add_json_value_at_path(&["items".to_string()], "", nested.clone())
+                .is_ok()
+        );
+        assert_eq!(
+            editor.text(),
+            "{\n  \"items\": [\n    {\n      \"id\": 1\n    }\n  ] // none yet\n}"
+        );
+        assert_eq!(editor.parsed_value().unwrap()["items"][0], nested);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_jsonc_insert_preserves_comments() {
+        let mut editor = jsonc_editor("[\n  // first\n  1,\n  2 // last\n]");
+        assert!(editor.insert_at_path(&[], 1, "5").is_ok());
+        assert_eq!(editor.text(), "[\n  // first\n  1,\n  5,\n  2 // last\n]");
+
+        assert!(editor.insert_at_path(&[], 3, "9").is_ok());
+        assert_eq!(
+            editor.text(),
+            "[\n  // first\n  1,\n  5,\n  2,\n  9 // last\n]"
+        );
+    }
 
     #[test]
-    fn test_new_editor() {
-        let editor = JsonEditor::new();
-        assert!(editor.is_valid());
-        assert!(!editor.text().is_empty());
+    fn test_jsonc_duplicate_preserves_comments() {
+        let mut editor = jsonc_editor("{\n  \"a\": [1, /* one */ 2], // list\n  \"b\": 2\n}");
+        assert!(editor.duplicate_at_path(&["a".to_string()]).is_ok());
+        assert_eq!(
+            editor.text(),
+            "{\n  \"a\": [1, /* one */ 2], // list\n  \"a_copy\": [1, /* one */ 2],\n  \"b\": 2\n}"
+        );
+
+        let mut editor = jsonc_editor("[\"x\" /* only */]");
+        assert!(editor.duplicate_at_path(&["0".to_string()]).is_ok());
+        assert_eq!(editor.text(), "[\"x\",\"x\" /* only */]");
     }
 
     #[test]
-    fn test_valid_json() {
-        let editor = JsonEditor::with_text(r#"{"key": "value"}"#.to_string());
-        assert!(editor.is_valid());
-        assert!(editor.error_message().is_none());
+    fn test_jsonc_sort_preserves_comments() {
+        let mut editor = jsonc_editor("[\n  3, // high\n  1,\n  /* mid */ 2\n]");
+        assert!(editor.sort_array_at_path(&[], false, None).is_ok());
+        assert_eq!(editor.text(), "[\n  1, // high\n  2,\n  /* mid */ 3\n]");
+        assert_eq!(
+            editor.parsed_value().unwrap(),
+            &serde_json::json!([1, 2, 3])
+        );
+
+        // Undo restores the original order and comments
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "[\n  3, // high\n  1,\n  /* mid */ 2\n]");
     }
 
     #[test]
-    fn test_invalid_json() {
-        let editor = JsonEditor::with_text(r#"{"key": invalid}"#.to_string());
-        assert!(!editor.is_valid());
-        assert!(editor.error_message().is_some());
+    fn test_edits_keep_key_order() {
+        let mut editor = JsonEditor::with_text(r#"{"b": 1, "a": 2, "c": 3}"#.to_string());
+
+        assert!(editor.rename_key_at_path(&[], "a", "z").is_ok());
+        assert!(editor.delete_value_at_path(&["b".to_string()]).is_ok());
+        let keys: Vec<&String> = editor
+            .parsed_value()
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(keys, vec!["z", "c"]);
     }
 
     #[test]
-    fn test_pretty_print() {
-        let mut editor = JsonEditor::with_text(r#"{"a":1,"b":2}"#.to_string());
-        assert!(editor.is_valid());
+    fn test_parse_ndjson() {
+        let records = parse_ndjson("{\"a\": 1}\n[2]\n\n\"three\"\n").unwrap();
+        assert_eq!(
+            records,
+            vec![
+                serde_json::json!({"a": 1}),
+                serde_json::json!([2]),
+                serde_json::json!("three")
+            ]
+        );
 
-        editor.apply_pretty_print();
-        assert!(editor.text().contains('\n'));
-        assert!(editor.text().contains("  "));
+        let (line, _) = parse_ndjson("{\"a\": 1}\n{\"b\": }\n{\"c\": 3}").unwrap_err();
+        assert_eq!(line, 2);
     }
 
     #[test]
-    fn test_compact() {
-        let mut editor = JsonEditor::with_text(
-            r#"{
-  "a": 1,
-  "b": 2
-}"#
-            .to_string(),
+    fn test_ndjson_mode() {
+        let mut editor =
+            JsonEditor::with_text("{\"level\": \"info\"}\n{\"level\": \"warn\"}".to_string());
+        assert!(!editor.is_valid());
+
+        editor.set_ndjson(true);
+        assert!(editor.is_valid());
+        assert_eq!(editor.parsed_value().unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(editor.find_line_for_path(&["1".to_string()]), Some(2));
+        assert_eq!(editor.find_path_for_line(2), Some(vec!["1".to_string()]));
+
+        // Edits are written back as one record per line
+        assert!(
+            editor
+                .update_value_at_path(&["1".to_string(), "level".to_string()], "\"error\"")
+                .is_ok()
         );
+        assert_eq!(editor.text(), "{\"level\":\"info\"}\n{\"level\":\"error\"}");
+    }
+
+    #[test]
+    fn test_trailing_commas() {
+        let mut editor = JsonEditor::with_text(r#"{"a":1,"b":[1,2,],"c":"x,}",}"#.to_string());
+        assert!(!editor.is_valid());
+
+        editor.set_tolerate_trailing_commas(true);
         assert!(editor.is_valid());
+        // Display text is kept as typed
+        assert_eq!(editor.text(), r#"{"a":1,"b":[1,2,],"c":"x,}",}"#);
+        assert_eq!(editor.parsed_value().unwrap()["c"], "x,}");
 
-        editor.apply_compact();
-        assert!(!editor.text().contains('\n'));
+        editor.apply_pretty_print();
+        assert!(!editor.text().contains(",\n}"));
+        assert!(!editor.text().contains(",\n  ]"));
+        assert!(
+            serde_json::from_str::<Value>(editor.text()).is_ok(),
+            "pretty output must be standards-compliant"
+        );
     }
 
     #[test]
@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// Error from parsing the document or applying a structured edit
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorError {
+    /// The text is not valid JSON (1-based line and column)
+    ParseError {
+        line: usize,
+        col: usize,
+        msg: String,
+    },
+    /// An NDJSON record is not valid JSON (1-based line of the record)
+    RecordError { line: usize, msg: String },
+    /// The document is invalid, so there is nothing to edit
+    InvalidDocument,
+    /// No value exists at the path
+    PathNotFound(Vec<String>),
+    /// A path segment is malformed for the container it indexes (e.g. "x" in an array)
+    InvalidPath { path: Vec<String>, reason: String },
+    /// The object already has a property with this key
+    KeyExists(String),
+    /// Property names cannot be empty
+    EmptyKey,
+    /// The value has the wrong type for the operation
+    TypeMismatch(String),
+    /// The edit would leave the text invalid (in-place JSONC edits)
+    InvalidEdit,
+    /// The edited document could not be serialized
+    SerializeFailed,
+}
+
+impl EditorError {
+    /// Parse error with the location split out of serde_json's message
+    pub fn from_parse(error: &serde_json::Error) -> Self {
+        let (line, col) = (error.line(), error.column());
+        let text = error.to_string();
+        let location = format!(" at line {} column {}", line, col);
+        let msg = text.strip_suffix(&location).unwrap_or(&text).to_string();
+        Self::ParseError { line, col, msg }
+    }
+}
+
+impl fmt::Display for EditorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParseError { line, col, msg } => {
+                write!(f, "JSON Error: {} at line {} column {}", msg, line, col)
+            }
+            Self::RecordError { line, msg } => write!(f, "NDJSON Error (line {}): {}", line, msg),
+            Self::InvalidDocument => write!(f, "Document is not valid JSON"),
+            Self::PathNotFound(path) => write!(f, "No value at path {:?}", path),
+            Self::InvalidPath { path, reason } => write!(f, "Invalid path {:?}: {}", path, reason),
+            Self::KeyExists(key) => write!(f, "Property '{}' already exists", key),
+            Self::EmptyKey => write!(f, "Property name cannot be empty"),
+            Self::TypeMismatch(msg) => write!(f, "{}", msg),
+            Self::InvalidEdit => write!(f, "Edit would produce invalid JSON"),
+            Self::SerializeFailed => write!(f, "Failed to serialize JSON"),
+        }
+    }
+}
+
+impl std::error::Error for EditorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_parse_splits_location() {
+        let error = serde_json::from_str::<serde_json::Value>("{\n  \"a\": }").unwrap_err();
+        match EditorError::from_parse(&error) {
+            EditorError::ParseError { line, col, msg } => {
+                assert_eq!((line, col), (2, 8));
+                assert_eq!(msg, "expected value");
+            }
+            other => panic!("Expected ParseError, got {:?}", other),
+        }
+        assert_eq!(
+            EditorError::from_parse(&error).to_string(),
+            format!("JSON Error: {}", error)
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use serde_json::Value;
+use web_time::SystemTime;
+
+/// Structured edit applied to the document (see `JsonEditor::drain_events`)
+/// Mirrors `ModifyOperation` with the resolved path and the time it was applied;
+/// each event carries what is needed to reverse it
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditEvent {
+    /// The value at `path` was replaced
+    Update {
+        path: Vec<String>,
+        old_value: Value,
+        new_value: Value,
+        timestamp: SystemTime,
+    },
+    /// The property or array item at `path` was removed
+    Delete {
+        path: Vec<String>,
+        old_value: Value,
+        timestamp: SystemTime,
+    },
+    /// A property or array item was added at `path`
+    /// `replaced` is the previous value if an existing property was overwritten
+    Add {
+        path: Vec<String>,
+        value: Value,
+        replaced: Option<Value>,
+        timestamp: SystemTime,
+    },
+    /// A property of the Object at `path` was renamed
+    Rename {
+        path: Vec<String>,
+        old_key: String,
+        new_key: String,
+        timestamp: SystemTime,
+    },
+    /// The primitive value at `path` was converted to another type
+    ChangeType {
+        path: Vec<String>,
+        old_value: Value,
+        new_value: Value,
+        timestamp: SystemTime,
+    },
+    /// The property or array item at `path` was copied to `copy_path`
+    Duplicate {
+        path: Vec<String>,
+        copy_path: Vec<String>,
+        timestamp: SystemTime,
+    },
+    /// The array at `path` was sorted (`old_value` is the array before sorting)
+    SortArray {
+        path: Vec<String>,
+        descending: bool,
+        key: Option<String>,
+        old_value: Value,
+        timestamp: SystemTime,
+    },
+}
+
+impl EditEvent {
+    /// JSON path the edit was applied at
+    pub fn path(&self) -> &[String] {
+        match self {
+            Self::Update { path, .. }
+            | Self::Delete { path, .. }
+            | Self::Add { path, .. }
+            | Self::Rename { path, .. }
+            | Self::ChangeType { path, .. }
+            | Self::Duplicate { path, .. }
+            | Self::SortArray { path, .. } => path,
+        }
+    }
+
+    /// Wall-clock time the edit was applied
+    pub fn timestamp(&self) -> SystemTime {
+        match self {
+            Self::Update { timestamp, .. }
+            | Self::Delete { timestamp, .. }
+            | Self::Add { timestamp, .. }
+            | Self::Rename { timestamp, .. }
+            | Self::ChangeType { timestamp, .. }
+            | Self::Duplicate { timestamp, .. }
+            | Self::SortArray { timestamp, .. } => *timestamp,
+        }
+    }
+}
@@ -1,9 +1,15 @@
 use crate::utils;
 use egui::{Color32, Pos2, Rect, Stroke, StrokeKind, Vec2};
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use unicode_normalization::UnicodeNormalization;
 
-use super::minimap::Minimap;
+use super::diff::{DiffEntry, DiffKind};
+use super::editor::json_pointer;
+use super::minimap::{Minimap, MinimapSettings};
+
+mod build;
+mod hit_test;
 
 /// A node in the JSON graph visualization
 #[derive(Debug, Clone)]
@@ -22,6 +28,103 @@ pub struct GraphNode {
     pub json_path: Vec<String>,
     /// Node content (for table-based rendering of Objects and Arrays)
     pub content: NodeContent,
+    /// Height of each visible table row (unzoomed); taller rows hold wrapped values
+    pub row_heights: Vec<f32>,
+    /// Whether `row_heights` have been fitted to the wrapped values (see
+    /// `measure_wrapped_rows`); nodes are built with single-line rows
+    rows_measured: bool,
+    /// Lowercased key, label and row text the filter searches (see `node_matches`)
+    search_text: Vec<String>,
+    /// Hash of what the node's content was built from (see `content_signature`)
+    signature: u64,
+}
+
+impl GraphNode {
+    /// Lowercased text for the filter to search: the node's key, its label and the
+    /// keys and values of its rows (values shown as references to child nodes don't count)
+    fn search_text(json_path: &[String], label: &str, content: &NodeContent) -> Vec<String> {
+        let mut text: Vec<String> = json_path.last().into_iter().cloned().collect();
+        text.push(label.to_string());
+        match content {
+            NodeContent::Object(pairs) => {
+                for pair in pairs {
+                    text.push(pair.key.clone());
+                    if !pair.is_reference {
+                        text.push(pair.value_display.clone());
+                    }
+                }
+            }
+            NodeContent::Array(items) => text.extend(
+                items
+                    .iter()
+                    .filter(|item| !item.is_reference)
+                    .map(|item| item.value_display.clone()),
+            ),
+            NodeContent::Primitive(value) => text.push(value.clone()),
+        }
+        text.iter()
+            .map(|fragment| fragment.to_lowercase())
+            .collect()
+    }
+
+    /// Offset of a visible row's top below the header (unzoomed)
+    fn row_top(&self, index: usize) -> f32 {
+        self.row_heights[..index.min(self.row_heights.len())]
+            .iter()
+            .sum()
+    }
+
+    /// Index of the visible row at an offset below the header (unzoomed)
+    /// Returns the number of visible rows if the offset is below the last row
+    fn row_at(&self, offset: f32) -> usize {
+        let mut top = 0.0;
+        for (index, height) in self.row_heights.iter().enumerate() {
+            top += height;
+            if offset < top {
+                return index;
+            }
+        }
+        self.row_heights.len()
+    }
+
+    /// Number of table rows (Object entries or Array items), shown or not
+    fn row_count(&self) -> usize {
+        match &self.content {
+            NodeContent::Object(pairs) => pairs.len(),
+            NodeContent::Array(items) => items.len(),
+            NodeContent::Primitive(_) => 0,
+        }
+    }
+
+    /// Complete text of each visible row's value, and whether it is a reference
+    fn visible_values(&self) -> Vec<(&str, bool)> {
+        let rows = self.row_heights.len();
+        match &self.content {
+            NodeContent::Object(pairs) => pairs
+                .iter()
+                .take(rows)
+                .map(|pair| {
+                    let text = pair.full_value.as_deref().unwrap_or(&pair.value_display);
+                    (text, pair.is_reference)
+                })
+                .collect(),
+            NodeContent::Array(items) => items
+                .iter()
+                .take(rows)
+                .map(|item| {
+                    let text = item.full_value.as_deref().unwrap_or(&item.value_display);
+                    (text, item.is_reference)
+                })
+                .collect(),
+            NodeContent::Primitive(_) => Vec::new(),
+        }
+    }
+
+    /// Largest scroll offset: the one that shows the last row in the bottom slot
+    /// Zero for tables whose rows all fit
+    fn max_scroll_offset(&self) -> usize {
+        self.row_count().saturating_sub(self.row_heights.len())
+    }
 }
 
 /// Content of a node (for table-based display)
@@ -42,6 +145,8 @@ pub struct KeyValuePair {
     pub key: String,
     /// Value representation
     pub value_display: String,
+    /// Complete value when `value_display` is a truncated preview (shown on hover)
+    pub full_value: Option<String>,
     /// Type of the value
     pub value_type: NodeType,
     /// Whether this value is a reference to a child node (object/array)
@@ -55,13 +160,15 @@ pub struct ArrayItem {
     pub index: usize,
     /// Value representation
     pub value_display: String,
+    /// Complete value when `value_display` is a truncated preview (shown on hover)
+    pub full_value: Option<String>,
     /// Type of the value
     pub value_type: NodeType,
     /// Whether this value is a reference to a child node (object/array)
     pub is_reference: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum NodeType {
     Object,
     Array,
@@ -72,6 +179,16 @@ pub enum NodeType {
 }
 
 impl NodeType {
+    /// Every value type, in the order the type filter lists them
+    pub const ALL: [NodeType; 6] = [
+        NodeType::Object,
+        NodeType::Array,
+        NodeType::String,
+        NodeType::Number,
+        NodeType::Boolean,
+        NodeType::Null,
+    ];
+
     fn color(&self) -> Color32 {
         match self {
             NodeType::Object => Color32::from_rgb(100, 150, 200),
@@ -82,6 +199,21 @@ impl NodeType {
             NodeType::Null => Color32::from_rgb(150, 150, 150),
         }
     }
+
+    /// Node fill color for the current theme
+    /// Darkened in light mode so white labels stay readable against a light canvas
+    fn fill_color(&self, dark_mode: bool) -> Color32 {
+        let base = self.color();
+        if dark_mode {
+            base
+        } else {
+            Color32::from_rgb(
+                (base.r() as f32 * 0.75) as u8,
+                (base.g() as f32 * 0.75) as u8,
+                (base.b() as f32 * 0.75) as u8,
+            )
+        }
+    }
 }
 
 /// An edge connecting two nodes
@@ -118,6 +250,8 @@ pub struct AddingState {
     pub value: String,
     /// Selected value type
     pub value_type: NodeType,
+    /// Index to insert the new item at (Arrays only); None appends
+    pub index: Option<usize>,
 }
 
 /// State for renaming a property key
@@ -131,6 +265,30 @@ pub struct RenamingKey {
     pub new_key: String,
 }
 
+/// State for pasting JSON as a new child
+#[derive(Debug, Clone)]
+pub struct PastingState {
+    /// Node ID where we're pasting
+    pub node_id: usize,
+    /// Whether this is an Object (true) or Array (false)
+    pub is_object: bool,
+    /// Key for new property (Object only)
+    pub key: String,
+    /// Pasted JSON text
+    pub text: String,
+}
+
+/// State for replacing an Object/Array value with edited JSON
+#[derive(Debug, Clone)]
+pub struct SubtreeEdit {
+    /// JSON path of the value being replaced
+    pub json_path: Vec<String>,
+    /// JSON text being edited (starts as the value's current JSON)
+    pub text: String,
+    /// Parse error from the last save attempt, cleared when the text changes
+    pub error: Option<String>,
+}
+
 /// Context menu state
 #[derive(Debug, Clone)]
 pub struct ContextMenuState {
@@ -157,6 +315,29 @@ pub enum ClickAction {
     DeleteRow(String),
     /// Rename a key (Object properties only)
     RenameKey(String),
+    /// Collapse or expand the node's subtree (header toggle)
+    ToggleCollapse,
+}
+
+/// Something to copy to the clipboard (processed by App)
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopyRequest {
+    /// The subtree at a JSON path, as formatted JSON
+    Subtree(Vec<String>),
+    /// Every descendant leaf path of the node at a JSON path, one per line
+    LeafPaths { path: Vec<String>, relative: bool },
+    /// Structure summary of the node at a JSON path, e.g. `array<{id:number}>`
+    TypeSignature(Vec<String>),
+}
+
+/// Direction the graph grows in from the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutDirection {
+    /// Children below their parent, siblings side by side
+    #[default]
+    TopDown,
+    /// Children to the right of their parent, siblings stacked vertically
+    LeftRight,
 }
 
 /// Type of modification operation
@@ -168,8 +349,23 @@ pub enum ModifyOperation {
     Delete,
     /// Add a new property (for Objects) or item (for Arrays)
     Add { key: String, value: String },
+    /// Insert a new item into an Array before the item at `index`
+    InsertAt { index: usize, value: String },
     /// Rename a property key (Object properties only)
     Rename { old_key: String, new_key: String },
+    /// Convert a primitive value to another primitive type
+    ChangeType { target: NodeType },
+    /// Add a parsed JSON value (may be nested) as a new property or item
+    AddJson { key: String, value: Value },
+    /// Replace a value with a parsed JSON value (may be nested)
+    UpdateJson { value: Value },
+    /// Insert a copy of a property or array item next to the original
+    Duplicate,
+    /// Sort an array's items (arrays containing objects need a `key` to sort by)
+    SortArray {
+        descending: bool,
+        key: Option<String>,
+    },
 }
 
 /// Result of a completed modification operation
@@ -181,10 +377,153 @@ pub struct EditResult {
     pub operation: ModifyOperation,
 }
 
+/// Default maximum number of characters shown for string previews
+const DEFAULT_PREVIEW_LEN: usize = 30;
+/// Range of the string preview length slider
+const MIN_PREVIEW_LEN: usize = 10;
+const MAX_PREVIEW_LEN: usize = 200;
+/// Default number of table rows shown in an Object/Array node
+const DEFAULT_MAX_VISIBLE_ROWS: usize = 10;
+/// Range of the visible rows slider
+const MIN_VISIBLE_ROWS: usize = 1;
+const MAX_VISIBLE_ROWS: usize = 100;
+
+/// Range of the canvas zoom
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 5.0;
+/// Default zoom below which Object/Array nodes are drawn without their tables
+const DEFAULT_DETAIL_ZOOM: f32 = 0.4;
+/// Range of the detail threshold slider
+const MAX_DETAIL_ZOOM: f32 = 1.0;
+/// Keyboard panning distance per frame while a key is held (times 4 with Shift)
+const KEYBOARD_PAN_STEP: f32 = 10.0;
+/// Zoom factor of one +/- key press
+const KEYBOARD_ZOOM_STEP: f32 = 1.1;
+
+/// Opacity of nodes that neither match the filter nor contain a match
+const FILTER_DIM_OPACITY: f32 = 0.2;
+/// Outline of nodes that match the filter themselves
+const FILTER_MATCH_COLOR: Color32 = Color32::from_rgb(0, 200, 255);
+
+/// Height of one table row in an Object/Array node
+const ROW_HEIGHT: f32 = 22.0;
+/// Font size of table cell values (unzoomed)
+const VALUE_FONT_SIZE: f32 = 11.0;
+/// Width of Object/Array nodes
+const TABLE_NODE_WIDTH: f32 = 250.0;
+/// Width of the value column of an Object (or Array) node (unzoomed)
+/// Column layout matches `render_node_content`: key column is 40% of the node
+/// (index column 40px), with the delete button and padding on the right
+fn value_column_width(is_object: bool) -> f32 {
+    let label_column = if is_object {
+        TABLE_NODE_WIDTH * 0.4
+    } else {
+        40.0
+    };
+    TABLE_NODE_WIDTH - label_column - 16.0 - 20.0
+}
+
+/// First of `base`, `base_2`, `base_3`, ... not used as a key of an Object node
+fn free_key(pairs: &[KeyValuePair], base: &str) -> String {
+    let taken = |key: &str| pairs.iter().any(|pair| pair.key == key);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| base.to_string())
+}
+
+/// Footer of a table with more rows than fit: the rows below the shown ones, or
+/// the shown range once the table has been scrolled
+fn rows_footer(total: usize, shown: usize, scroll_offset: usize) -> String {
+    if scroll_offset == 0 {
+        format!("... {} more", total - shown)
+    } else {
+        format!(
+            "{}–{} of {}",
+            scroll_offset + 1,
+            scroll_offset + shown,
+            total
+        )
+    }
+}
+
+/// Tab order of a dialog window's fields and buttons, collected while it renders
+struct DialogFocus {
+    /// Widget focused when the dialog started rendering
+    focused_before: Option<egui::Id>,
+    /// Fields and buttons in Tab order
+    order: Vec<egui::Id>,
+    /// Widget focused when the dialog opens
+    initial: egui::Id,
+}
+
+impl DialogFocus {
+    /// Start rendering a dialog: focus the target carried over from the last frame
+    /// (see `trap`) and return the focused widget
+    fn begin(ui: &egui::Ui, target: Option<egui::Id>) -> Option<egui::Id> {
+        if let Some(target) = target {
+            ui.memory_mut(|mem| mem.request_focus(target));
+        }
+        ui.memory(|mem| mem.focused())
+    }
+
+    /// Keep keyboard focus inside the dialog: Tab / Shift+Tab cycle through its fields
+    /// and buttons only, wrapping at either end, and the initial widget is focused when
+    /// focus is elsewhere (e.g. the dialog just opened). Widgets behind the dialog
+    /// (`layer_id`) can't take focus or clicks while it is shown
+    /// Returns the widget given focus, to focus again when the next frame starts: egui's
+    /// own Tab handling can still move focus to the window frame in between
+    fn trap(&self, ctx: &egui::Context, layer_id: egui::LayerId) -> Option<egui::Id> {
+        ctx.memory_mut(|mem| mem.set_modal_layer(layer_id));
+
+        let current = self
+            .focused_before
+            .and_then(|id| self.order.iter().position(|&o| o == id));
+        let (tab, shift) = ctx.input(|i| (i.key_pressed(egui::Key::Tab), i.modifiers.shift));
+        let target = match current {
+            Some(index) if tab => {
+                let step = if shift { self.order.len() - 1 } else { 1 };
+                self.order[(index + step) % self.order.len()]
+            }
+            _ if ctx
+                .memory(|mem| mem.focused())
+                .is_some_and(|id| self.order.contains(&id)) =>
+            {
+                return None;
+            }
+            _ => self.initial,
+        };
+        ctx.memory_mut(|mem| mem.request_focus(target));
+        Some(target)
+    }
+}
+
+/// Display label for an array index, e.g. `[0]` (or `[1]` when one-based)
+/// Display only: JSON paths always use 0-based indices
+pub fn index_label(index: usize, one_based: bool) -> String {
+    format!("[{}]", if one_based { index + 1 } else { index })
+}
+
+/// Highlight color for a kind of difference (green added, red removed, yellow changed)
+pub fn diff_color(kind: &DiffKind) -> Color32 {
+    match kind {
+        DiffKind::Added { .. } => Color32::from_rgb(100, 200, 100),
+        DiffKind::Removed { .. } => Color32::from_rgb(230, 100, 100),
+        DiffKind::Changed { .. } => Color32::YELLOW,
+    }
+}
+
 /// JSON Graph visualization
 pub struct JsonGraph {
     nodes: Vec<GraphNode>,
     edges: Vec<GraphEdge>,
+    /// Child node IDs of each node that has children (indexed when the graph is built)
+    children: HashMap<usize, Vec<usize>>,
+    /// Number of descendant nodes of each node that has children
+    descendant_counts: HashMap<usize, usize>,
     next_id: usize,
     /// Zoom level
     zoom: f32,
@@ -200,12 +539,74 @@ pub struct JsonGraph {
     adding_state: Option<AddingState>,
     /// Currently renaming a key (if any)
     renaming_key: Option<RenamingKey>,
+    /// Currently pasting JSON as a new child (if any)
+    pasting_state: Option<PastingState>,
+    /// Node (and whether it is an Object) waiting for the clipboard text to paste
+    /// as a new child (see `paste_clipboard_text`)
+    clipboard_paste: Option<(usize, bool)>,
+    /// Currently editing an Object/Array value as JSON (if any)
+    subtree_edit: Option<SubtreeEdit>,
     /// Context menu state (if showing)
     context_menu: Option<ContextMenuState>,
     /// Pending edit result to be processed by App
     pending_edit: Option<EditResult>,
+    /// JSON path of a subtree to copy to the clipboard (processed by App)
+    pending_copy: Option<CopyRequest>,
+    /// JSON path of a value to edit (App looks up its current value and calls
+    /// `open_cell_editor` or `open_subtree_editor`)
+    pending_value_edit: Option<Vec<String>>,
     /// Minimap for navigation
     minimap: Minimap,
+    /// Maximum number of characters shown for string previews
+    preview_len: usize,
+    /// Maximum number of table rows shown in an Object/Array node
+    /// Shared by sizing, rendering and hit-testing so they stay in sync
+    max_visible_rows: usize,
+    /// Whether the graph should be rebuilt from the document (processed by App)
+    pending_rebuild: bool,
+    /// Whether a display setting changed and the graph should be updated to the
+    /// document, reusing unaffected nodes (processed by App)
+    pending_refresh: bool,
+    /// Highlight colors for rows/nodes that differ from a compare baseline, by JSON path
+    diff_highlights: HashMap<Vec<String>, Color32>,
+    /// Pan to the selected node on the next frame (after a breadcrumb click)
+    center_on_selection: bool,
+    /// Canvas size from the last frame (used by `center_on_node`)
+    canvas_size: Vec2,
+    /// `select_by_path` selects nothing instead of falling back to the closest match
+    exact_path_only: bool,
+    /// Case-insensitive substring filter; nodes without a match (and no matching
+    /// descendant) are dimmed. Empty = no filter
+    filter: String,
+    /// `filter` lowercased, updated whenever the filter changes
+    filter_lowercase: String,
+    /// Pan to the first node matching the filter as it is typed
+    center_on_filter_match: bool,
+    /// Value types shown at full opacity; rows of other types are dimmed inside
+    /// their node, and Object/Array nodes of other types are dimmed as a whole
+    visible_types: HashSet<NodeType>,
+    /// Wrap long primitive values across multiple lines in table cells
+    wrap_values: bool,
+    /// Below this zoom, Object/Array nodes show only their label (which includes the
+    /// row count): tables are unreadable that small and costly to paint
+    detail_zoom: f32,
+    /// Show a node's JSON Pointer when hovering its header
+    show_pointer_tooltips: bool,
+    /// Direction the graph grows in (applied when the graph is built)
+    layout_direction: LayoutDirection,
+    /// Show array indices as 1-based in tables, edge labels and breadcrumbs
+    one_based_indices: bool,
+    /// Paths of Object/Array nodes whose descendant nodes are hidden (view only)
+    collapsed_paths: HashSet<Vec<String>>,
+    /// First shown table row of scrolled Object/Array nodes, by node ID
+    /// Nodes that aren't scrolled have no entry
+    scroll_offsets: HashMap<usize, usize>,
+    /// Wheel movement over a table not yet turned into a whole row of scrolling
+    row_scroll_remainder: f32,
+    /// Node selected by a closest-match fallback (shown as "approximate match")
+    approximate_match: Option<usize>,
+    /// Widget to focus when the open dialog next renders (see `DialogFocus::trap`)
+    dialog_focus: Option<egui::Id>,
 }
 
 impl Default for JsonGraph {
@@ -213,6 +614,8 @@ impl Default for JsonGraph {
         Self {
             nodes: Vec::new(),
             edges: Vec::new(),
+            children: HashMap::new(),
+            descendant_counts: HashMap::new(),
             next_id: 0,
             zoom: 1.0,
             offset: Vec2::ZERO,
@@ -221,9 +624,36 @@ impl Default for JsonGraph {
             editing_cell: None,
             adding_state: None,
             renaming_key: None,
+            pasting_state: None,
+            clipboard_paste: None,
+            subtree_edit: None,
             context_menu: None,
             pending_edit: None,
+            pending_copy: None,
+            pending_value_edit: None,
             minimap: Minimap::new(),
+            preview_len: DEFAULT_PREVIEW_LEN,
+            max_visible_rows: DEFAULT_MAX_VISIBLE_ROWS,
+            pending_rebuild: false,
+            pending_refresh: false,
+            diff_highlights: HashMap::new(),
+            center_on_selection: false,
+            canvas_size: Vec2::ZERO,
+            exact_path_only: false,
+            filter: String::new(),
+            filter_lowercase: String::new(),
+            center_on_filter_match: false,
+            visible_types: NodeType::ALL.into_iter().collect(),
+            approximate_match: None,
+            dialog_focus: None,
+            wrap_values: false,
+            detail_zoom: DEFAULT_DETAIL_ZOOM,
+            show_pointer_tooltips: false,
+            layout_direction: LayoutDirection::default(),
+            one_based_indices: false,
+            collapsed_paths: HashSet::new(),
+            scroll_offsets: HashMap::new(),
+            row_scroll_remainder: 0.0,
         }
     }
 }
@@ -233,364 +663,753 @@ impl JsonGraph {
         Self::default()
     }
 
-    /// Build graph from JSON value
-    pub fn build_from_json(&mut self, value: &Value) {
-        self.nodes.clear();
-        self.edges.clear();
-        self.next_id = 0;
-        self.selected_node = None;
-        self.editing_cell = None; // Cancel any ongoing edits
-        self.adding_state = None; // Cancel any ongoing adds
-        self.renaming_key = None; // Cancel any ongoing renames
-        self.context_menu = None; // Clear any context menu
-        self.pending_edit = None; // Clear any pending edits
+    /// Set the maximum number of characters shown for string previews
+    /// Applies to nodes built after the change
+    pub fn set_preview_len(&mut self, preview_len: usize) {
+        self.preview_len = preview_len.clamp(MIN_PREVIEW_LEN, MAX_PREVIEW_LEN);
+    }
 
-        if value.is_null() {
-            return;
-        }
+    /// Get the maximum number of characters shown for string previews
+    pub fn preview_len(&self) -> usize {
+        self.preview_len
+    }
 
-        self.build_node(value, None, None, 0, 0.0, Vec::new());
-        self.log_to_console(&format!("Built graph with {} nodes", self.nodes.len()));
-    }
-
-    /// Recursively build nodes from JSON value
-    /// Returns the width used by this subtree
-    fn build_node(
-        &mut self,
-        value: &Value,
-        parent_id: Option<usize>,
-        edge_label: Option<String>,
-        depth: usize,
-        x_offset: f32,
-        json_path: Vec<String>,
-    ) -> f32 {
-        let node_id = self.next_id;
-        self.next_id += 1;
-
-        // Build node content and determine type
-        let (label, node_type, content) = match value {
-            Value::Object(map) => {
-                let label = format!("Object ({})", map.len());
-                let mut pairs = Vec::new();
-
-                for (key, val) in map {
-                    let (value_display, value_type, is_reference) = match val {
-                        Value::Object(m) => (format!("{{ {} }}", m.len()), NodeType::Object, true),
-                        Value::Array(a) => (format!("[ {} ]", a.len()), NodeType::Array, true),
-                        Value::String(s) => {
-                            let display = if s.len() > 30 {
-                                format!("\"{}...\"", &s[..30])
-                            } else {
-                                format!("\"{}\"", s)
-                            };
-                            (display, NodeType::String, false)
-                        }
-                        Value::Number(n) => (n.to_string(), NodeType::Number, false),
-                        Value::Bool(b) => (b.to_string(), NodeType::Boolean, false),
-                        Value::Null => ("null".to_string(), NodeType::Null, false),
-                    };
+    /// Set the zoom below which Object/Array nodes are drawn without their tables
+    pub fn set_detail_zoom(&mut self, detail_zoom: f32) {
+        self.detail_zoom = detail_zoom.clamp(MIN_ZOOM, MAX_DETAIL_ZOOM);
+    }
 
-                    pairs.push(KeyValuePair {
-                        key: key.clone(),
-                        value_display,
-                        value_type,
-                        is_reference,
-                    });
-                }
+    /// Get the zoom below which Object/Array nodes are drawn without their tables
+    pub fn detail_zoom(&self) -> f32 {
+        self.detail_zoom
+    }
 
-                (label, NodeType::Object, NodeContent::Object(pairs))
-            }
-            Value::Array(arr) => {
-                let label = format!("Array [{}]", arr.len());
-                let mut items = Vec::new();
+    /// Check if table rows are drawn (and can be clicked) at the current zoom
+    fn shows_tables(&self) -> bool {
+        self.zoom >= self.detail_zoom
+    }
 
-                for (index, val) in arr.iter().enumerate() {
-                    let (value_display, value_type, is_reference) = match val {
-                        Value::Object(m) => (format!("{{ {} }}", m.len()), NodeType::Object, true),
-                        Value::Array(a) => (format!("[ {} ]", a.len()), NodeType::Array, true),
-                        Value::String(s) => {
-                            let display = if s.len() > 30 {
-                                format!("\"{}...\"", &s[..30])
-                            } else {
-                                format!("\"{}\"", s)
-                            };
-                            (display, NodeType::String, false)
-                        }
-                        Value::Number(n) => (n.to_string(), NodeType::Number, false),
-                        Value::Bool(b) => (b.to_string(), NodeType::Boolean, false),
-                        Value::Null => ("null".to_string(), NodeType::Null, false),
-                    };
+    /// Set the maximum number of table rows shown in an Object/Array node
+    /// Node sizes are computed at build time, so the graph should be rebuilt afterwards
+    pub fn set_max_visible_rows(&mut self, max_visible_rows: usize) {
+        self.max_visible_rows = max_visible_rows.clamp(MIN_VISIBLE_ROWS, MAX_VISIBLE_ROWS);
+    }
 
-                    items.push(ArrayItem {
-                        index,
-                        value_display,
-                        value_type,
-                        is_reference,
-                    });
-                }
+    /// Get the maximum number of table rows shown in an Object/Array node
+    pub fn max_visible_rows(&self) -> usize {
+        self.max_visible_rows
+    }
 
-                (label, NodeType::Array, NodeContent::Array(items))
-            }
-            Value::String(s) => {
-                let display = if s.len() > 20 {
-                    format!("\"{}...\"", &s[..20])
-                } else {
-                    format!("\"{}\"", s)
-                };
-                (
-                    display.clone(),
-                    NodeType::String,
-                    NodeContent::Primitive(display),
-                )
-            }
-            Value::Number(n) => {
-                let display = n.to_string();
-                (
-                    display.clone(),
-                    NodeType::Number,
-                    NodeContent::Primitive(display),
-                )
-            }
-            Value::Bool(b) => {
-                let display = b.to_string();
-                (
-                    display.clone(),
-                    NodeType::Boolean,
-                    NodeContent::Primitive(display),
-                )
-            }
-            Value::Null => (
-                "null".to_string(),
-                NodeType::Null,
-                NodeContent::Primitive("null".to_string()),
-            ),
-        };
+    /// Set whether `select_by_path` requires an exact path match
+    /// When off, a missing path selects the node sharing the longest path prefix
+    pub fn set_exact_path_only(&mut self, exact_path_only: bool) {
+        self.exact_path_only = exact_path_only;
+    }
 
-        // Calculate position based on depth and offset
-        let x = 100.0 + x_offset; // Increased left margin
-        let y = 50.0 + depth as f32 * 200.0; // Increased vertical spacing significantly
-
-        // Calculate node size based on content
-        let size = self.calculate_node_size(&content);
-
-        let node = GraphNode {
-            id: node_id,
-            label,
-            node_type,
-            position: Pos2::new(x, y),
-            size,
-            json_path: json_path.clone(),
-            content,
-        };
+    /// Check if `select_by_path` requires an exact path match
+    pub fn exact_path_only(&self) -> bool {
+        self.exact_path_only
+    }
+
+    /// Set the node filter (empty shows every node at full opacity)
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        self.filter_lowercase = self.filter.to_lowercase();
+    }
 
-        self.nodes.push(node);
+    /// Current node filter
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
 
-        // Create edge from parent
-        if let Some(parent) = parent_id {
-            self.edges.push(GraphEdge {
-                from: parent,
-                to: node_id,
-                label: edge_label,
-            });
+    /// Check if a node's key name, label or primitive values contain the filter text
+    /// (case-insensitive). Every node matches an empty filter
+    fn node_matches(&self, node: &GraphNode) -> bool {
+        self.filter_lowercase.is_empty()
+            || node
+                .search_text
+                .iter()
+                .any(|text| text.contains(&self.filter_lowercase))
+    }
+
+    /// Set whether typing a filter pans to the first matching node
+    pub fn set_center_on_filter_match(&mut self, enabled: bool) {
+        self.center_on_filter_match = enabled;
+    }
+
+    /// Check if typing a filter pans to the first matching node
+    pub fn center_on_filter_match(&self) -> bool {
+        self.center_on_filter_match
+    }
+
+    /// Set whether values of a type are shown at full opacity
+    pub fn set_type_visible(&mut self, node_type: NodeType, visible: bool) {
+        if visible {
+            self.visible_types.insert(node_type);
+        } else {
+            self.visible_types.remove(&node_type);
         }
+    }
 
-        // Process children and calculate total width
-        // Only create child nodes for Object and Array values (not primitives)
-        let mut child_offset = x_offset;
-        let mut total_width = 0.0;
-
-        match value {
-            Value::Object(map) => {
-                for (key, child_value) in map {
-                    // Only create child nodes for Object and Array types
-                    if child_value.is_object() || child_value.is_array() {
-                        let mut child_path = json_path.clone();
-                        child_path.push(key.clone());
-                        let child_width = self.build_node(
-                            child_value,
-                            Some(node_id),
-                            Some(key.clone()),
-                            depth + 1,
-                            child_offset,
-                            child_path,
-                        );
-                        child_offset += child_width;
-                        total_width += child_width;
-                    }
-                    // Primitive values are already displayed in the table
-                }
-            }
-            Value::Array(arr) => {
-                for (idx, child_value) in arr.iter().enumerate() {
-                    // Only create child nodes for Object and Array types
-                    if child_value.is_object() || child_value.is_array() {
-                        let mut child_path = json_path.clone();
-                        child_path.push(idx.to_string());
-                        let child_width = self.build_node(
-                            child_value,
-                            Some(node_id),
-                            Some(format!("[{}]", idx)),
-                            depth + 1,
-                            child_offset,
-                            child_path,
-                        );
-                        child_offset += child_width;
-                        total_width += child_width;
-                    }
-                    // Primitive values are already displayed in the table
-                }
-            }
-            _ => {}
+    /// Check if values of a type are shown at full opacity
+    pub fn is_type_visible(&self, node_type: &NodeType) -> bool {
+        self.visible_types.contains(node_type)
+    }
+
+    /// IDs of the nodes dimmed by the type filter (their own type is deselected)
+    fn type_filtered_ids(&self) -> HashSet<usize> {
+        self.nodes
+            .iter()
+            .filter(|n| !self.visible_types.contains(&n.node_type))
+            .map(|n| n.id)
+            .collect()
+    }
+
+    /// IDs of the nodes that match the filter themselves (outlined)
+    /// Empty when there's no filter
+    fn filter_match_ids(&self) -> HashSet<usize> {
+        if self.filter.is_empty() {
+            return HashSet::new();
         }
+        self.nodes
+            .iter()
+            .filter(|n| self.node_matches(n))
+            .map(|n| n.id)
+            .collect()
+    }
 
-        // Return the width used by this subtree
-        // If no children, return a base width; otherwise return children's total width
-        if total_width > 0.0 {
-            total_width
-        } else {
-            300.0 // Base width for leaf nodes (increased for better spacing)
+    /// First visible node (in build order, root first) that matches the filter
+    fn first_filter_match(&self) -> Option<usize> {
+        if self.filter.is_empty() {
+            return None;
         }
+        self.nodes
+            .iter()
+            .find(|n| !self.is_hidden(n) && self.node_matches(n))
+            .map(|n| n.id)
     }
 
-    /// Calculate node size based on content
-    fn calculate_node_size(&self, content: &NodeContent) -> Vec2 {
-        match content {
-            NodeContent::Object(pairs) => {
-                // Width: enough for key + value columns
-                let width = 250.0;
-                // Height: header + rows (20px per row) + padding
-                let row_height = 22.0;
-                let header_height = 25.0;
-                let padding = 10.0;
-                let max_visible_rows = 10; // Limit height for very large objects
-                let visible_rows = pairs.len().min(max_visible_rows);
-                let height = header_height + (visible_rows as f32 * row_height) + padding;
-                Vec2::new(width, height.max(60.0))
-            }
-            NodeContent::Array(items) => {
-                // Similar to Object but with index column
-                let width = 250.0;
-                let row_height = 22.0;
-                let header_height = 25.0;
-                let padding = 10.0;
-                let max_visible_rows = 10;
-                let visible_rows = items.len().min(max_visible_rows);
-                let height = header_height + (visible_rows as f32 * row_height) + padding;
-                Vec2::new(width, height.max(60.0))
-            }
-            NodeContent::Primitive(_) => {
-                // Small fixed size for primitive values
-                Vec2::new(120.0, 40.0)
+    /// IDs of the nodes to dim: those that don't match the filter and have no
+    /// matching descendant (so ancestors keep the matches in context)
+    /// Empty when there's no filter
+    fn filtered_out_ids(&self) -> HashSet<usize> {
+        if self.filter.is_empty() {
+            return HashSet::new();
+        }
+        let mut kept: HashSet<&[String]> = HashSet::new();
+        for node in self.nodes.iter().filter(|n| self.node_matches(n)) {
+            for len in 0..=node.json_path.len() {
+                kept.insert(&node.json_path[..len]);
             }
         }
+        self.nodes
+            .iter()
+            .filter(|n| !kept.contains(n.json_path.as_slice()))
+            .map(|n| n.id)
+            .collect()
     }
 
-    /// Get the selected node's JSON path
-    pub fn get_selected_path(&self) -> Option<Vec<String>> {
-        self.selected_node
-            .and_then(|id| self.nodes.iter().find(|n| n.id == id))
-            .map(|node| node.json_path.clone())
+    /// Check if the current selection came from a closest-match fallback
+    pub fn is_approximate_selection(&self) -> bool {
+        self.approximate_match.is_some() && self.approximate_match == self.selected_node
     }
 
-    /// Take and return the pending edit result (if any)
-    /// This clears the pending edit after returning it
-    pub fn take_pending_edit(&mut self) -> Option<EditResult> {
-        self.pending_edit.take()
+    /// Set whether long values wrap across multiple lines in table cells
+    /// Row heights are computed at build time, so the graph should be rebuilt afterwards
+    pub fn set_wrap_values(&mut self, wrap_values: bool) {
+        self.wrap_values = wrap_values;
     }
 
-    /// Clear selection
-    pub fn clear_selection(&mut self) {
-        self.selected_node = None;
+    /// Check if long values wrap in table cells
+    pub fn wrap_values(&self) -> bool {
+        self.wrap_values
     }
 
-    /// Select a node by its JSON path
-    /// Returns true if a matching node was found and selected
-    pub fn select_by_path(&mut self, path: &[String]) -> bool {
-        // Find node with matching path
-        for node in &self.nodes {
-            if node.json_path == path {
-                self.selected_node = Some(node.id);
-                self.log_to_console(&format!(
-                    "Selected node by path: {} (path: {:?})",
-                    node.label, node.json_path
-                ));
-                return true;
-            }
-        }
+    /// Show a node's JSON Pointer (e.g. `/items/0`) when hovering its header
+    pub fn set_show_pointer_tooltips(&mut self, show: bool) {
+        self.show_pointer_tooltips = show;
+    }
 
-        // No exact match found - try to find the closest match
-        let mut best_match: Option<&GraphNode> = None;
-        let mut best_match_len = 0;
+    /// Check if hovering a node header shows its JSON Pointer
+    pub fn show_pointer_tooltips(&self) -> bool {
+        self.show_pointer_tooltips
+    }
 
-        for node in &self.nodes {
-            // Count how many path segments match
-            let match_len = node
-                .json_path
-                .iter()
-                .zip(path.iter())
-                .take_while(|(a, b)| a == b)
-                .count();
+    /// Show array indices starting at 1 instead of 0 (display only)
+    /// Edge labels are set at build time, so the graph should be rebuilt afterwards
+    pub fn set_one_based_indices(&mut self, one_based: bool) {
+        self.one_based_indices = one_based;
+    }
 
-            if match_len > 0 && match_len > best_match_len {
-                best_match = Some(node);
-                best_match_len = match_len;
-            }
-        }
+    /// Check if array indices are shown starting at 1
+    pub fn one_based_indices(&self) -> bool {
+        self.one_based_indices
+    }
 
-        if let Some(node) = best_match {
-            self.selected_node = Some(node.id);
-            self.log_to_console(&format!(
-                "Selected closest match: {} (path: {:?}, matched {} segments)",
-                node.label, node.json_path, best_match_len
-            ));
-            true
+    /// Set the direction the graph grows in
+    /// Positions are set at build time, so the graph should be rebuilt afterwards
+    pub fn set_layout_direction(&mut self, direction: LayoutDirection) {
+        self.layout_direction = direction;
+    }
+
+    /// Get the direction the graph grows in
+    pub fn layout_direction(&self) -> LayoutDirection {
+        self.layout_direction
+    }
+
+    /// Collapse or expand the subtree under the node at a JSON path (view only)
+    pub fn set_collapsed(&mut self, path: &[String], collapsed: bool) {
+        if collapsed {
+            self.collapsed_paths.insert(path.to_vec());
         } else {
-            false
+            self.collapsed_paths.remove(path);
         }
     }
 
-    /// Render node content (table for Object/Array, text for primitives)
-    fn render_node_content(
-        &self,
-        painter: &egui::Painter,
-        node: &GraphNode,
-        rect: Rect,
-        zoom: f32,
-    ) {
-        let font_size = (11.0 * zoom).max(8.0);
-        let header_font_size = (12.0 * zoom).max(9.0);
-
-        match &node.content {
-            NodeContent::Object(pairs) => {
-                // Draw header with label
-                let header_height = 25.0 * zoom;
-                let header_rect =
-                    Rect::from_min_size(rect.min, Vec2::new(rect.width(), header_height));
+    /// Check if the subtree under the node at a JSON path is collapsed
+    pub fn is_collapsed(&self, path: &[String]) -> bool {
+        self.collapsed_paths.contains(path)
+    }
 
-                painter.text(
-                    header_rect.center(),
-                    egui::Align2::CENTER_CENTER,
-                    &node.label,
-                    egui::FontId::proportional(header_font_size),
-                    Color32::WHITE,
-                );
+    /// Check if a node is hidden because one of its ancestors is collapsed
+    fn is_hidden(&self, node: &GraphNode) -> bool {
+        !self.collapsed_paths.is_empty()
+            && (0..node.json_path.len())
+                .any(|len| self.collapsed_paths.contains(&node.json_path[..len]))
+    }
 
-                // Draw header separator
-                painter.line_segment(
-                    [
-                        Pos2::new(rect.min.x, rect.min.y + header_height),
-                        Pos2::new(rect.max.x, rect.min.y + header_height),
-                    ],
-                    Stroke::new(1.0 * zoom, Color32::from_gray(200)),
-                );
+    /// Number of descendant nodes under a node (hidden when it is collapsed)
+    fn descendant_count(&self, node: &GraphNode) -> usize {
+        self.descendant_counts.get(&node.id).copied().unwrap_or(0)
+    }
 
-                // Draw table rows
-                let row_height = 22.0 * zoom;
-                let key_column_width = rect.width() * 0.4;
-                let max_visible_rows = 10;
+    /// Check if a node has child nodes (and so a collapse toggle)
+    fn has_child_nodes(&self, node: &GraphNode) -> bool {
+        self.children.contains_key(&node.id)
+    }
 
-                for (i, pair) in pairs.iter().enumerate().take(max_visible_rows) {
-                    let y = rect.min.y + header_height + (i as f32 * row_height);
+    /// Index the edges by parent node and count each node's descendants
+    /// Nodes are in pre-order, so walking them backwards counts children first
+    fn index_children(&mut self) {
+        self.children.clear();
+        for edge in &self.edges {
+            self.children.entry(edge.from).or_default().push(edge.to);
+        }
+
+        self.descendant_counts.clear();
+        for node in self.nodes.iter().rev() {
+            if let Some(children) = self.children.get(&node.id) {
+                let count = children
+                    .iter()
+                    .map(|child| 1 + self.descendant_counts.get(child).copied().unwrap_or(0))
+                    .sum();
+                self.descendant_counts.insert(node.id, count);
+            }
+        }
+    }
+
+    /// Expand collapsed ancestors so the node at a path is visible
+    fn reveal_path(&mut self, path: &[String]) {
+        for len in 0..path.len() {
+            self.collapsed_paths.remove(&path[..len]);
+        }
+    }
+
+    /// Rect of the collapse toggle in a node's header (screen space)
+    fn collapse_toggle_rect(&self, rect: Rect) -> Rect {
+        let size = 16.0 * self.zoom;
+        Rect::from_min_size(
+            rect.min + Vec2::new(4.0 * self.zoom, (25.0 * self.zoom - size) / 2.0),
+            Vec2::splat(size),
+        )
+    }
+
+    /// Draw the ▸/▾ header toggle and, when collapsed, a "+N hidden" badge below the node
+    fn render_collapse_toggle(&self, painter: &egui::Painter, node: &GraphNode, rect: Rect) {
+        let collapsed = self.is_collapsed(&node.json_path);
+        painter.text(
+            self.collapse_toggle_rect(rect).center(),
+            egui::Align2::CENTER_CENTER,
+            if collapsed { "▸" } else { "▾" },
+            egui::FontId::proportional((12.0 * self.zoom).max(9.0)),
+            Color32::WHITE,
+        );
+
+        if collapsed {
+            painter.text(
+                Pos2::new(rect.center().x, rect.max.y + 4.0 * self.zoom),
+                egui::Align2::CENTER_TOP,
+                format!("+{} hidden", self.descendant_count(node)),
+                egui::FontId::proportional((11.0 * self.zoom).max(8.0)),
+                Color32::GRAY,
+            );
+        }
+    }
+
+    /// Keys of an Array node's Object items, in order of first appearance
+    /// Empty if no item is an Object
+    fn sort_key_candidates(&self, node_id: usize) -> Vec<String> {
+        let Some(array) = self.nodes.iter().find(|n| n.id == node_id) else {
+            return Vec::new();
+        };
+        let mut keys: Vec<String> = Vec::new();
+        for node in &self.nodes {
+            if node.json_path.len() == array.json_path.len() + 1
+                && node.json_path.starts_with(&array.json_path)
+                && let NodeContent::Object(pairs) = &node.content
+            {
+                for pair in pairs {
+                    if !keys.contains(&pair.key) {
+                        keys.push(pair.key.clone());
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    /// Number of nodes in the graph
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Request a full rebuild of the graph from the document
+    pub fn request_rebuild(&mut self) {
+        self.pending_rebuild = true;
+    }
+
+    /// Take the pending rebuild request (if any)
+    pub fn take_pending_rebuild(&mut self) -> bool {
+        std::mem::take(&mut self.pending_rebuild)
+    }
+
+    /// Request updating the graph after a display setting changed
+    /// Node signatures include the display settings, so `update_from_json` rebuilds
+    /// just the affected nodes and keeps the selection
+    pub fn request_refresh(&mut self) {
+        self.pending_refresh = true;
+    }
+
+    /// Take the pending refresh request (if any)
+    pub fn take_pending_refresh(&mut self) -> bool {
+        std::mem::take(&mut self.pending_refresh)
+    }
+
+    /// Highlight rows and nodes that appear in a diff (empty slice clears highlights)
+    /// Removed values aren't in the graph, so their parent node is highlighted instead
+    pub fn set_diff_highlights(&mut self, entries: &[DiffEntry]) {
+        self.diff_highlights.clear();
+        for entry in entries {
+            let path = match entry.kind {
+                DiffKind::Removed { .. } => match entry.path.split_last() {
+                    Some((_, parent)) => parent.to_vec(),
+                    None => Vec::new(),
+                },
+                _ => entry.path.clone(),
+            };
+            self.diff_highlights
+                .entry(path)
+                .or_insert_with(|| diff_color(&entry.kind));
+        }
+    }
+
+    /// Highlight color for a JSON path, if it differs from the compare baseline
+    fn diff_highlight(&self, path: &[String]) -> Option<Color32> {
+        self.diff_highlights.get(path).copied()
+    }
+
+    /// Highlight color for a table row (node path + key or index)
+    fn row_highlight(&self, node: &GraphNode, key: &str) -> Option<Color32> {
+        if self.diff_highlights.is_empty() {
+            return None;
+        }
+        let mut path = node.json_path.clone();
+        path.push(key.to_string());
+        self.diff_highlight(&path)
+    }
+
+    /// Current minimap preferences (for persistence)
+    pub fn minimap_settings(&self) -> MinimapSettings {
+        self.minimap.settings()
+    }
+
+    /// Apply persisted minimap preferences
+    pub fn apply_minimap_settings(&mut self, settings: &MinimapSettings) {
+        self.minimap.apply_settings(settings);
+    }
+
+    /// Remove all nodes and cancel any in-progress interaction
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.children.clear();
+        self.descendant_counts.clear();
+        self.next_id = 0;
+        self.selected_node = None;
+        self.scroll_offsets.clear();
+        self.cancel_interactions();
+    }
+
+    /// Cancel in-progress edits and menus, and drop requests not yet processed by App
+    fn cancel_interactions(&mut self) {
+        self.editing_cell = None; // Cancel any ongoing edits
+        self.adding_state = None; // Cancel any ongoing adds
+        self.renaming_key = None; // Cancel any ongoing renames
+        self.pasting_state = None; // Cancel any ongoing pastes
+        self.clipboard_paste = None; // Drop any paste waiting for the clipboard
+        self.subtree_edit = None; // Cancel any ongoing JSON edits
+        self.context_menu = None; // Clear any context menu
+        self.pending_edit = None; // Clear any pending edits
+        self.pending_copy = None; // Clear any pending copy
+        self.pending_value_edit = None; // Clear any pending value edit request
+    }
+
+    /// Get the selected node's JSON path
+    pub fn get_selected_path(&self) -> Option<Vec<String>> {
+        self.selected_node
+            .and_then(|id| self.nodes.iter().find(|n| n.id == id))
+            .map(|node| node.json_path.clone())
+    }
+
+    /// Check if there's a node at an exact JSON path (no closest-match fallback)
+    pub fn has_node_at_path(&self, path: &[String]) -> bool {
+        self.find_node_by_path(path).is_some()
+    }
+
+    /// Find the node with an exact JSON path
+    fn find_node_by_path(&self, path: &[String]) -> Option<&GraphNode> {
+        self.nodes.iter().find(|n| n.json_path == path)
+    }
+
+    /// Breadcrumb labels for a JSON path (e.g., ["root", "items", "[2]", "value"])
+    /// Segments under an Array node are shown as indices
+    fn breadcrumb_labels(&self, path: &[String]) -> Vec<String> {
+        let mut labels = vec!["root".to_string()];
+        for (i, segment) in path.iter().enumerate() {
+            let parent_is_array = self
+                .find_node_by_path(&path[..i])
+                .is_some_and(|n| matches!(n.content, NodeContent::Array(_)));
+            let index = segment.parse::<usize>().ok().filter(|_| parent_is_array);
+            labels.push(match index {
+                Some(index) => index_label(index, self.one_based_indices),
+                None => segment.clone(),
+            });
+        }
+        labels
+    }
+
+    /// Take and return the pending edit result (if any)
+    /// This clears the pending edit after returning it
+    pub fn take_pending_edit(&mut self) -> Option<EditResult> {
+        self.pending_edit.take()
+    }
+
+    /// Take and return the pending clipboard copy request (if any)
+    /// This clears the pending copy after returning it
+    pub fn take_pending_copy(&mut self) -> Option<CopyRequest> {
+        self.pending_copy.take()
+    }
+
+    /// Whether a Paste JSON action is waiting for the clipboard text
+    pub fn wants_clipboard_text(&self) -> bool {
+        self.clipboard_paste.is_some()
+    }
+
+    /// Paste the clipboard text as a new child of the node Paste JSON was used on
+    /// Object children get a free `pasted` key (`pasted_2`, ... if taken) that can be
+    /// renamed afterwards. Without clipboard text (it can't be read directly on the
+    /// web) the Paste JSON dialog opens instead, to paste into with Ctrl+V
+    pub fn paste_clipboard_text(&mut self, text: Option<String>) {
+        let Some((node_id, is_object)) = self.clipboard_paste.take() else {
+            return;
+        };
+        let Some(text) = text else {
+            self.pasting_state = Some(PastingState {
+                node_id,
+                is_object,
+                key: String::new(),
+                text: String::new(),
+            });
+            return;
+        };
+        let key = match self.nodes.iter().find(|n| n.id == node_id) {
+            Some(GraphNode {
+                content: NodeContent::Object(pairs),
+                ..
+            }) => free_key(pairs, "pasted"),
+            _ => String::new(),
+        };
+        self.queue_paste(node_id, key, &text);
+    }
+
+    /// Queue adding the JSON `text` as a child of a node (`key` is empty for Arrays)
+    /// Returns false (and logs why) if the text isn't valid JSON
+    fn queue_paste(&mut self, node_id: usize, key: String, text: &str) -> bool {
+        match serde_json::from_str::<Value>(text) {
+            Ok(value) => {
+                let Some(node) = self.nodes.iter().find(|n| n.id == node_id) else {
+                    return false;
+                };
+                self.pending_edit = Some(EditResult {
+                    json_path: node.json_path.clone(),
+                    operation: ModifyOperation::AddJson { key, value },
+                });
+                self.log_to_console("Pasted JSON");
+                true
+            }
+            Err(e) => {
+                self.log_to_console(&format!("Cannot paste invalid JSON: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Take and return the JSON path of a value to edit (if any)
+    /// Cells only show previews (strings may be truncated), so App answers with the
+    /// current value: `open_cell_editor` for primitives, `open_subtree_editor` for
+    /// Objects/Arrays
+    pub fn take_pending_value_edit(&mut self) -> Option<Vec<String>> {
+        self.pending_value_edit.take()
+    }
+
+    /// Open the Edit Value dialog for the primitive at a JSON path
+    /// Strings are edited without quotes. Returns false if no node shows the value
+    pub fn open_cell_editor(&mut self, json_path: &[String], value: &Value) -> bool {
+        let Some((key, parent)) = json_path.split_last() else {
+            return false;
+        };
+        let Some(node_id) = self.find_node_by_path(parent).map(|n| n.id) else {
+            return false;
+        };
+        let (text, value_type) = match value {
+            Value::String(s) => (s.clone(), NodeType::String),
+            Value::Number(n) => (n.to_string(), NodeType::Number),
+            Value::Bool(b) => (b.to_string(), NodeType::Boolean),
+            Value::Null => ("null".to_string(), NodeType::Null),
+            Value::Object(_) | Value::Array(_) => return false,
+        };
+        self.log_to_console(&format!("Editing cell: {} = {:?}", key, text));
+        self.editing_cell = Some(EditingCell {
+            node_id,
+            key: key.clone(),
+            text,
+            value_type,
+        });
+        true
+    }
+
+    /// Open the Edit JSON dialog for the value at a JSON path, pre-filled with `text`
+    pub fn open_subtree_editor(&mut self, json_path: Vec<String>, text: String) {
+        self.log_to_console(&format!("Editing JSON of: {:?}", json_path));
+        self.subtree_edit = Some(SubtreeEdit {
+            json_path,
+            text,
+            error: None,
+        });
+    }
+
+    /// Queue the Edit JSON dialog's text as a replacement value
+    /// Returns true if it was saved; invalid JSON keeps the dialog open and shows the error
+    fn save_subtree_edit(&mut self) -> bool {
+        let Some(editing) = &mut self.subtree_edit else {
+            return false;
+        };
+        match serde_json::from_str::<Value>(&editing.text) {
+            Ok(value) => {
+                self.pending_edit = Some(EditResult {
+                    json_path: editing.json_path.clone(),
+                    operation: ModifyOperation::UpdateJson { value },
+                });
+                self.log_to_console("Saved JSON edit");
+                true
+            }
+            Err(e) => {
+                editing.error = Some(format!("JSON Error: {}", e));
+                self.log_to_console(&format!("Cannot save invalid JSON: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Clear selection
+    pub fn clear_selection(&mut self) {
+        self.selected_node = None;
+    }
+
+    /// Select a node by its JSON path
+    /// Falls back to the closest match unless `exact_path_only` is set
+    /// Returns true if a matching node was found and selected
+    pub fn select_by_path(&mut self, path: &[String]) -> bool {
+        self.approximate_match = None;
+
+        // Find node with matching path
+        if let Some(node) = self.find_node_by_path(path) {
+            let (id, label) = (node.id, node.label.clone());
+            self.reveal_path(path);
+            self.selected_node = Some(id);
+            self.log_to_console(&format!(
+                "Selected node by path: {} (path: {:?})",
+                label, path
+            ));
+            return true;
+        }
+
+        if self.exact_path_only {
+            self.log_to_console(&format!("No node at path: {:?}", path));
+            return false;
+        }
+
+        // No exact match found - try to find the closest match
+        let mut best_match: Option<&GraphNode> = None;
+        let mut best_match_len = 0;
+
+        for node in &self.nodes {
+            // Count how many path segments match
+            let match_len = node
+                .json_path
+                .iter()
+                .zip(path.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            if match_len > 0 && match_len > best_match_len {
+                best_match = Some(node);
+                best_match_len = match_len;
+            }
+        }
+
+        if let Some(node) = best_match {
+            let (id, label, node_path) = (node.id, node.label.clone(), node.json_path.clone());
+            self.reveal_path(&node_path);
+            self.selected_node = Some(id);
+            self.approximate_match = Some(id);
+            self.log_to_console(&format!(
+                "Selected closest match: {} (path: {:?}, matched {} segments)",
+                label, node_path, best_match_len
+            ));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Draw a table cell value starting at the top-left of its row
+    /// With `wrap` the text is laid out over the width of the value column
+    fn draw_cell_value(
+        painter: &egui::Painter,
+        text: &str,
+        wrap: bool,
+        top_left: Pos2,
+        is_object: bool,
+        zoom: f32,
+        color: Color32,
+    ) {
+        let font_id = egui::FontId::monospace((VALUE_FONT_SIZE * zoom).max(8.0));
+        if !wrap {
+            painter.text(
+                Pos2::new(top_left.x, top_left.y + ROW_HEIGHT / 2.0 * zoom),
+                egui::Align2::LEFT_CENTER,
+                text,
+                font_id,
+                color,
+            );
+            return;
+        }
+        let line_height = painter.fonts_mut(|fonts| fonts.row_height(&font_id));
+        let galley = painter.layout(
+            text.to_string(),
+            font_id,
+            color,
+            value_column_width(is_object) * zoom,
+        );
+        // The first line sits where a single-line value would
+        let top = top_left.y + (ROW_HEIGHT * zoom - line_height) / 2.0;
+        painter.galley(Pos2::new(top_left.x, top), galley, color);
+    }
+
+    /// Render node content (table for Object/Array, text for primitives)
+    /// Rows whose value type isn't in `visible_types` are drawn dimmed
+    fn render_node_content(
+        &self,
+        painter: &egui::Painter,
+        node: &GraphNode,
+        rect: Rect,
+        zoom: f32,
+        visible_types: &HashSet<NodeType>,
+    ) {
+        let font_size = (11.0 * zoom).max(8.0);
+        let header_font_size = (12.0 * zoom).max(9.0);
+        // Zoomed out: the label alone, skipping every row's text and separators
+        if zoom < self.detail_zoom && !matches!(node.content, NodeContent::Primitive(_)) {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                &node.label,
+                egui::FontId::proportional(header_font_size),
+                Color32::WHITE,
+            );
+            return;
+        }
+
+        let mut dimmed_row_painter = painter.clone();
+        dimmed_row_painter.multiply_opacity(FILTER_DIM_OPACITY);
+
+        match &node.content {
+            NodeContent::Object(pairs) => {
+                // Draw header with label
+                let header_height = 25.0 * zoom;
+                let header_rect =
+                    Rect::from_min_size(rect.min, Vec2::new(rect.width(), header_height));
+
+                painter.text(
+                    header_rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    &node.label,
+                    egui::FontId::proportional(header_font_size),
+                    Color32::WHITE,
+                );
+
+                // Draw header separator
+                painter.line_segment(
+                    [
+                        Pos2::new(rect.min.x, rect.min.y + header_height),
+                        Pos2::new(rect.max.x, rect.min.y + header_height),
+                    ],
+                    Stroke::new(1.0 * zoom, Color32::from_gray(200)),
+                );
+
+                // Draw table rows
+                let line_height = ROW_HEIGHT * zoom;
+                let key_column_width = rect.width() * 0.4;
+                let max_visible_rows = node.row_heights.len();
+                let scroll_offset = self.scroll_offset(node);
+                let wrap_rows = self.wraps_rows(node);
+
+                for (i, pair) in pairs
+                    .iter()
+                    .skip(scroll_offset)
+                    .take(max_visible_rows)
+                    .enumerate()
+                {
+                    let painter = if visible_types.contains(&pair.value_type) {
+                        painter
+                    } else {
+                        &dimmed_row_painter
+                    };
+                    let y = rect.min.y + header_height + node.row_top(i) * zoom;
+                    let row_height = node.row_heights[i] * zoom;
+
+                    // Tint rows that differ from the compare baseline
+                    if let Some(color) = self.row_highlight(node, &pair.key) {
+                        painter.rect_filled(
+                            Rect::from_min_size(
+                                Pos2::new(rect.min.x, y),
+                                Vec2::new(rect.width(), row_height),
+                            ),
+                            0.0,
+                            color.gamma_multiply(0.45),
+                        );
+                    }
 
                     // Draw horizontal separator
                     if i > 0 {
@@ -615,7 +1434,7 @@ impl JsonGraph {
                     // Draw key (left column)
                     let key_rect = Rect::from_min_size(
                         Pos2::new(rect.min.x + 5.0, y),
-                        Vec2::new(key_column_width - 10.0, row_height),
+                        Vec2::new(key_column_width - 10.0, line_height),
                     );
                     painter.text(
                         Pos2::new(key_rect.min.x, key_rect.center().y),
@@ -630,30 +1449,32 @@ impl JsonGraph {
                     let delete_button_x = rect.max.x - delete_button_size - 5.0;
 
                     // Draw value (right column) with type-specific color
-                    let value_rect = Rect::from_min_size(
-                        Pos2::new(rect.min.x + key_column_width + 5.0, y),
-                        Vec2::new(
-                            rect.width() - key_column_width - delete_button_size - 20.0,
-                            row_height,
-                        ),
-                    );
+                    let value_x = rect.min.x + key_column_width + 5.0;
                     let value_color = if pair.is_reference {
                         Color32::from_rgb(150, 200, 255) // Light blue for references
                     } else {
                         pair.value_type.color()
                     };
-                    painter.text(
-                        Pos2::new(value_rect.min.x, value_rect.center().y),
-                        egui::Align2::LEFT_CENTER,
-                        &pair.value_display,
-                        egui::FontId::monospace(font_size),
+                    // Wrapped rows show the whole value, not the truncated preview
+                    let wrap = wrap_rows && !pair.is_reference;
+                    let text = match &pair.full_value {
+                        Some(full_value) if wrap => full_value,
+                        _ => &pair.value_display,
+                    };
+                    Self::draw_cell_value(
+                        painter,
+                        text,
+                        wrap,
+                        Pos2::new(value_x, y),
+                        true,
+                        zoom,
                         value_color,
                     );
 
-                    // Draw delete button (X icon)
+                    // Draw delete button (X icon), aligned with the first line
                     let delete_center = Pos2::new(
                         delete_button_x + delete_button_size / 2.0,
-                        y + row_height / 2.0,
+                        y + line_height / 2.0,
                     );
 
                     // Draw button background (light gray circle)
@@ -681,13 +1502,13 @@ impl JsonGraph {
                     );
                 }
 
-                // Show "..." if there are more rows
+                // Show "..." (or the shown range once scrolled) if there are more rows
                 if pairs.len() > max_visible_rows {
-                    let y = rect.min.y + header_height + (max_visible_rows as f32 * row_height);
+                    let y = rect.min.y + header_height + node.row_top(max_visible_rows) * zoom;
                     painter.text(
                         Pos2::new(rect.center().x, y),
                         egui::Align2::CENTER_CENTER,
-                        format!("... {} more", pairs.len() - max_visible_rows),
+                        rows_footer(pairs.len(), max_visible_rows, scroll_offset),
                         egui::FontId::proportional(font_size),
                         Color32::from_gray(200),
                     );
@@ -717,12 +1538,37 @@ impl JsonGraph {
                 );
 
                 // Draw table rows
-                let row_height = 22.0 * zoom;
+                let line_height = ROW_HEIGHT * zoom;
                 let index_column_width = 40.0 * zoom;
-                let max_visible_rows = 10;
-
-                for (i, item) in items.iter().enumerate().take(max_visible_rows) {
-                    let y = rect.min.y + header_height + (i as f32 * row_height);
+                let max_visible_rows = node.row_heights.len();
+                let scroll_offset = self.scroll_offset(node);
+                let wrap_rows = self.wraps_rows(node);
+
+                for (i, item) in items
+                    .iter()
+                    .skip(scroll_offset)
+                    .take(max_visible_rows)
+                    .enumerate()
+                {
+                    let painter = if visible_types.contains(&item.value_type) {
+                        painter
+                    } else {
+                        &dimmed_row_painter
+                    };
+                    let y = rect.min.y + header_height + node.row_top(i) * zoom;
+                    let row_height = node.row_heights[i] * zoom;
+
+                    // Tint rows that differ from the compare baseline
+                    if let Some(color) = self.row_highlight(node, &item.index.to_string()) {
+                        painter.rect_filled(
+                            Rect::from_min_size(
+                                Pos2::new(rect.min.x, y),
+                                Vec2::new(rect.width(), row_height),
+                            ),
+                            0.0,
+                            color.gamma_multiply(0.45),
+                        );
+                    }
 
                     // Draw horizontal separator
                     if i > 0 {
@@ -747,12 +1593,12 @@ impl JsonGraph {
                     // Draw index (left column)
                     let index_rect = Rect::from_min_size(
                         Pos2::new(rect.min.x + 5.0, y),
-                        Vec2::new(index_column_width - 10.0, row_height),
+                        Vec2::new(index_column_width - 10.0, line_height),
                     );
                     painter.text(
                         Pos2::new(index_rect.center().x, index_rect.center().y),
                         egui::Align2::CENTER_CENTER,
-                        format!("[{}]", item.index),
+                        index_label(item.index, self.one_based_indices),
                         egui::FontId::monospace(font_size),
                         Color32::from_gray(200),
                     );
@@ -762,30 +1608,32 @@ impl JsonGraph {
                     let delete_button_x = rect.max.x - delete_button_size - 5.0;
 
                     // Draw value (right column) with type-specific color
-                    let value_rect = Rect::from_min_size(
-                        Pos2::new(rect.min.x + index_column_width + 5.0, y),
-                        Vec2::new(
-                            rect.width() - index_column_width - delete_button_size - 20.0,
-                            row_height,
-                        ),
-                    );
+                    let value_x = rect.min.x + index_column_width + 5.0;
                     let value_color = if item.is_reference {
                         Color32::from_rgb(150, 200, 255) // Light blue for references
                     } else {
                         item.value_type.color()
                     };
-                    painter.text(
-                        Pos2::new(value_rect.min.x, value_rect.center().y),
-                        egui::Align2::LEFT_CENTER,
-                        &item.value_display,
-                        egui::FontId::monospace(font_size),
+                    // Wrapped rows show the whole value, not the truncated preview
+                    let wrap = wrap_rows && !item.is_reference;
+                    let text = match &item.full_value {
+                        Some(full_value) if wrap => full_value,
+                        _ => &item.value_display,
+                    };
+                    Self::draw_cell_value(
+                        painter,
+                        text,
+                        wrap,
+                        Pos2::new(value_x, y),
+                        false,
+                        zoom,
                         value_color,
                     );
 
-                    // Draw delete button (X icon)
+                    // Draw delete button (X icon), aligned with the first line
                     let delete_center = Pos2::new(
                         delete_button_x + delete_button_size / 2.0,
-                        y + row_height / 2.0,
+                        y + line_height / 2.0,
                     );
 
                     // Draw button background (light gray circle)
@@ -813,13 +1661,13 @@ impl JsonGraph {
                     );
                 }
 
-                // Show "..." if there are more rows
+                // Show "..." (or the shown range once scrolled) if there are more rows
                 if items.len() > max_visible_rows {
-                    let y = rect.min.y + header_height + (max_visible_rows as f32 * row_height);
+                    let y = rect.min.y + header_height + node.row_top(max_visible_rows) * zoom;
                     painter.text(
                         Pos2::new(rect.center().x, y),
                         egui::Align2::CENTER_CENTER,
-                        format!("... {} more", items.len() - max_visible_rows),
+                        rows_footer(items.len(), max_visible_rows, scroll_offset),
                         egui::FontId::proportional(font_size),
                         Color32::from_gray(200),
                     );
@@ -861,6 +1709,15 @@ impl JsonGraph {
                 self.log_to_console("Selection cleared");
             }
 
+            if ui
+                .button("🔄 Rebuild Graph")
+                .on_hover_text("Reparse the document and rebuild the graph from scratch")
+                .clicked()
+            {
+                self.request_rebuild();
+                self.log_to_console("Rebuild requested");
+            }
+
             ui.separator();
 
             // Minimap toggle
@@ -872,18 +1729,190 @@ impl JsonGraph {
                     if minimap_visible { "on" } else { "off" }
                 ));
             }
+            if minimap_visible {
+                let mut opacity = self.minimap.background_opacity();
+                if ui
+                    .add(egui::Slider::new(&mut opacity, 0.0..=1.0).text("Opacity"))
+                    .on_hover_text("Minimap background opacity")
+                    .changed()
+                {
+                    self.minimap.set_background_opacity(opacity);
+                }
+            }
 
             ui.separator();
-            ui.label(format!("Zoom: {:.2}x", self.zoom));
-        });
-
-        ui.separator();
-
-        // Canvas
-        let (response, painter) = ui.allocate_painter(
-            Vec2::new(ui.available_width(), ui.available_height().max(400.0)),
-            egui::Sense::click_and_drag(),
-        );
+            ui.label("Filter:");
+            let filter_changed = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.filter)
+                        .hint_text("key or value")
+                        .desired_width(120.0),
+                )
+                .on_hover_text("Dim nodes that don't contain this text")
+                .changed();
+            let filter_cleared = !self.filter.is_empty() && ui.small_button("✖").clicked();
+            if filter_cleared {
+                self.filter.clear();
+            }
+            if filter_changed || filter_cleared {
+                self.filter_lowercase = self.filter.to_lowercase();
+            }
+            ui.checkbox(&mut self.center_on_filter_match, "Center")
+                .on_hover_text("Pan to the first matching node while typing");
+            ui.menu_button("Types", |ui| {
+                for node_type in NodeType::ALL {
+                    let mut visible = self.is_type_visible(&node_type);
+                    if ui
+                        .checkbox(&mut visible, format!("{:?}", node_type))
+                        .changed()
+                    {
+                        self.set_type_visible(node_type, visible);
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Dim rows and nodes whose value type is unchecked");
+            if filter_changed
+                && self.center_on_filter_match
+                && let Some(id) = self.first_filter_match()
+            {
+                self.center_on_node(id);
+            }
+
+            ui.separator();
+            ui.checkbox(&mut self.exact_path_only, "Exact paths")
+                .on_hover_text(
+                    "Selecting a missing path selects nothing instead of the closest match",
+                );
+
+            ui.separator();
+            ui.label(format!("Zoom: {:.2}x", self.zoom));
+
+            ui.separator();
+
+            // String preview length (takes effect on the next rebuild)
+            let mut preview_len = self.preview_len;
+            if ui
+                .add(
+                    egui::Slider::new(&mut preview_len, MIN_PREVIEW_LEN..=MAX_PREVIEW_LEN)
+                        .text("Preview"),
+                )
+                .on_hover_text("Maximum characters shown for string values")
+                .changed()
+            {
+                self.set_preview_len(preview_len);
+                self.request_refresh();
+            }
+
+            // Visible table rows per node (node sizes change, so rebuild)
+            let mut max_visible_rows = self.max_visible_rows;
+            if ui
+                .add(
+                    egui::Slider::new(&mut max_visible_rows, MIN_VISIBLE_ROWS..=MAX_VISIBLE_ROWS)
+                        .text("Rows"),
+                )
+                .on_hover_text("Maximum rows shown per Object/Array node")
+                .changed()
+            {
+                self.set_max_visible_rows(max_visible_rows);
+                self.request_refresh();
+            }
+
+            // Zoom below which nodes are drawn as just their label
+            let mut detail_zoom = self.detail_zoom;
+            if ui
+                .add(egui::Slider::new(&mut detail_zoom, MIN_ZOOM..=MAX_DETAIL_ZOOM).text("Detail"))
+                .on_hover_text("Below this zoom, Object/Array nodes show only their label")
+                .changed()
+            {
+                self.set_detail_zoom(detail_zoom);
+            }
+
+            // Wrapped rows are taller (node sizes change, so rebuild)
+            if ui
+                .checkbox(&mut self.wrap_values, "Wrap values")
+                .on_hover_text("Wrap long values across multiple lines in table cells")
+                .changed()
+            {
+                self.request_refresh();
+            }
+
+            ui.checkbox(&mut self.show_pointer_tooltips, "Pointer tooltips")
+                .on_hover_text("Show a node's JSON Pointer when hovering its header");
+
+            // Node positions are set at build time, so switching rebuilds
+            let mut left_right = self.layout_direction == LayoutDirection::LeftRight;
+            if ui
+                .checkbox(&mut left_right, "Horizontal")
+                .on_hover_text("Lay children out to the right of their parent")
+                .changed()
+            {
+                self.layout_direction = if left_right {
+                    LayoutDirection::LeftRight
+                } else {
+                    LayoutDirection::TopDown
+                };
+                self.request_refresh();
+            }
+        });
+
+        // Breadcrumb for the selected node; clicking a segment selects that ancestor
+        ui.horizontal_wrapped(|ui| {
+            let Some(path) = self.get_selected_path() else {
+                ui.label(egui::RichText::new("root").weak());
+                return;
+            };
+
+            if self.is_approximate_selection() {
+                ui.colored_label(Color32::from_rgb(230, 180, 60), "≈ approximate match")
+                    .on_hover_text("The exact path wasn't found; the closest ancestor is selected");
+            }
+
+            let mut clicked_len: Option<usize> = None;
+            for (i, label) in self.breadcrumb_labels(&path).into_iter().enumerate() {
+                if i > 0 {
+                    ui.label("›");
+                }
+                if ui.link(label).clicked() {
+                    clicked_len = Some(i);
+                }
+            }
+
+            if let Some(len) = clicked_len
+                && self.select_by_path(&path[..len])
+            {
+                self.center_on_selection = true;
+                selection_changed = true;
+            }
+        });
+
+        ui.separator();
+
+        // Rows of nodes built since the last frame are sized before anything is hit-tested
+        self.measure_wrapped_rows(ui.ctx());
+
+        // Canvas
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(ui.available_width(), ui.available_height().max(400.0)),
+            egui::Sense::click_and_drag(),
+        );
+        // Dialogs hand keyboard focus back to the canvas when they close
+        let canvas_id = response.id;
+        self.canvas_size = response.rect.size();
+
+        // Recenter on a node picked from the breadcrumb, or on F / . with the canvas
+        // focused (or hovered while no other widget has focus)
+        let center_key = (response.has_focus()
+            || (response.hovered() && !ui.ctx().wants_keyboard_input()))
+            && ui.input(|i| {
+                i.modifiers.is_none()
+                    && (i.key_pressed(egui::Key::F) || i.key_pressed(egui::Key::Period))
+            });
+        if (std::mem::take(&mut self.center_on_selection) || center_key)
+            && let Some(id) = self.selected_node
+        {
+            self.center_on_node(id);
+        }
 
         // Handle panning
         if response.dragged() {
@@ -894,27 +1923,33 @@ impl JsonGraph {
             self.dragging = false;
         }
 
-        // Handle zoom with scroll
-        if response.hovered() {
+        // Scrolling over the rows of a table with more rows than fit scrolls them;
+        // anywhere else it zooms
+        let scrolled_node = response
+            .hover_pos()
+            .and_then(|pos| self.scrollable_node_at(pos, response.rect));
+        if response.hovered()
+            && let Some(node_id) = scrolled_node
+        {
+            let scroll_delta = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll_delta != 0.0 {
+                // Scrolling up (positive delta) moves towards the first row
+                self.row_scroll_remainder -= scroll_delta / (ROW_HEIGHT * self.zoom);
+                let rows = self.row_scroll_remainder.trunc();
+                self.row_scroll_remainder -= rows;
+                self.scroll_rows(node_id, rows as isize);
+                ui.ctx().request_repaint();
+            }
+        } else if response.hovered() {
+            self.row_scroll_remainder = 0.0;
             let scroll_delta = ui.input(|i| i.smooth_scroll_delta.y);
             if scroll_delta != 0.0 {
-                let old_zoom = self.zoom;
-                self.zoom *= 1.0 + scroll_delta * 0.001;
-                self.zoom = self.zoom.clamp(0.1, 5.0);
-
-                // Adjust offset to zoom towards mouse position
-                if let Some(hover_pos) = response.hover_pos() {
-                    let hover_pos_in_canvas = hover_pos - response.rect.min;
-
-                    // Keep the point under the mouse fixed during zoom
-                    // Before zoom: world_pos = (screen_pos - offset) / old_zoom
-                    // After zoom:  world_pos = (screen_pos - new_offset) / new_zoom
-                    // We want world_pos to remain the same, so:
-                    // (screen_pos - offset) / old_zoom = (screen_pos - new_offset) / new_zoom
-                    let world_pos_before = (hover_pos_in_canvas - self.offset) / old_zoom;
-                    let world_pos_after = (hover_pos_in_canvas - self.offset) / self.zoom;
-                    let offset_delta = (world_pos_after - world_pos_before) * self.zoom;
-                    self.offset += offset_delta;
+                let zoom = self.zoom * (1.0 + scroll_delta * 0.001);
+
+                // Zoom towards the mouse position
+                match response.hover_pos() {
+                    Some(hover_pos) => self.zoom_around(zoom, hover_pos - response.rect.min),
+                    None => self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM),
                 }
 
                 self.log_to_console(&format!("Zoom: {:.2}x", self.zoom));
@@ -922,22 +1957,53 @@ impl JsonGraph {
             }
         }
 
+        // Keyboard navigation once the canvas has focus (clicking or dragging it gives
+        // focus). Arrow keys stay with the canvas instead of moving focus to other widgets
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+        if response.has_focus() && !self.dialog_open() {
+            ui.memory_mut(|mem| {
+                mem.set_focus_lock_filter(
+                    canvas_id,
+                    egui::EventFilter {
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        ..Default::default()
+                    },
+                )
+            });
+            if self.keyboard_navigation(ui, response.rect.size()) {
+                ui.ctx().request_repaint(); // Keep panning while a key is held
+            }
+        }
+
         let canvas_rect = response.rect;
+        let dark_mode = ui.visuals().dark_mode;
+
+        // Nodes dimmed by the text or type filter (drawn in place so the layout
+        // doesn't jump)
+        let mut filtered_out = self.filtered_out_ids();
+        filtered_out.extend(self.type_filtered_ids());
+        let filter_matches = self.filter_match_ids();
+        let mut dimmed_painter = painter.clone();
+        dimmed_painter.multiply_opacity(FILTER_DIM_OPACITY);
 
-        // Draw edges
+        // Draw edges (skipping those into collapsed subtrees)
         for edge in &self.edges {
             if let (Some(from_node), Some(to_node)) = (
                 self.nodes.iter().find(|n| n.id == edge.from),
                 self.nodes.iter().find(|n| n.id == edge.to),
-            ) {
-                let from_pos = self.transform_pos(
-                    from_node.position + Vec2::new(from_node.size.x / 2.0, from_node.size.y),
-                    canvas_rect,
-                );
-                let to_pos = self.transform_pos(
-                    to_node.position + Vec2::new(to_node.size.x / 2.0, 0.0),
-                    canvas_rect,
-                );
+            ) && !self.is_hidden(to_node)
+            {
+                let painter = if filtered_out.contains(&to_node.id) {
+                    &dimmed_painter
+                } else {
+                    &painter
+                };
+                let (from_anchor, to_anchor) = self.edge_anchors(from_node, to_node);
+                let from_pos = self.transform_pos(from_anchor, canvas_rect);
+                let to_pos = self.transform_pos(to_anchor, canvas_rect);
 
                 painter.line_segment(
                     [from_pos, to_pos],
@@ -960,7 +2026,11 @@ impl JsonGraph {
         }
 
         // Draw nodes and handle clicks
+        let mut toggle_collapse: Option<Vec<String>> = None;
         for node in &self.nodes {
+            if self.is_hidden(node) {
+                continue;
+            }
             let pos = self.transform_pos(node.position, canvas_rect);
             let size = node.size * self.zoom;
 
@@ -986,21 +2056,11 @@ impl JsonGraph {
                 // Check what action was clicked
                 if let Some(action) = self.get_click_action(node, rect, click_pos) {
                     match action {
-                        ClickAction::EditCell(key, value_type) => {
-                            // Enter edit mode for this cell
-                            if let Some(current_value) = self.get_cell_value(node, &key) {
-                                self.editing_cell = Some(EditingCell {
-                                    node_id: node.id,
-                                    key: key.clone(),
-                                    text: current_value,
-                                    value_type,
-                                });
-                                self.log_to_console(&format!(
-                                    "Editing cell: {} = {:?}",
-                                    key,
-                                    self.editing_cell.as_ref().unwrap().text
-                                ));
-                            }
+                        ClickAction::EditCell(key, _) => {
+                            // The editor opens once App provides the current value
+                            let mut json_path = node.json_path.clone();
+                            json_path.push(key);
+                            self.pending_value_edit = Some(json_path);
                         }
                         ClickAction::DeleteRow(key) => {
                             // Handle delete operation
@@ -1024,6 +2084,9 @@ impl JsonGraph {
                             });
                             self.log_to_console(&format!("Rename key dialog opened: {}", old_key));
                         }
+                        ClickAction::ToggleCollapse => {
+                            toggle_collapse = Some(node.json_path.clone());
+                        }
                     }
                 } else {
                     // Just select the node
@@ -1038,18 +2101,23 @@ impl JsonGraph {
 
             // Check if this node is selected
             let is_selected = self.selected_node == Some(node.id);
+            let painter = if filtered_out.contains(&node.id) {
+                &dimmed_painter
+            } else {
+                &painter
+            };
 
             // Node background (highlight if selected)
             let bg_color = if is_selected {
                 // Brighter version for selected node
-                let base = node.node_type.color();
+                let base = node.node_type.fill_color(dark_mode);
                 Color32::from_rgb(
                     base.r().saturating_add(50),
                     base.g().saturating_add(50),
                     base.b().saturating_add(50),
                 )
             } else {
-                node.node_type.color()
+                node.node_type.fill_color(dark_mode)
             };
 
             painter.rect_filled(rect, 5.0, bg_color);
@@ -1060,6 +2128,10 @@ impl JsonGraph {
                     if is_selected { 3.0 } else { 2.0 },
                     if is_selected {
                         Color32::YELLOW
+                    } else if let Some(color) = self.diff_highlight(&node.json_path) {
+                        color
+                    } else if filter_matches.contains(&node.id) {
+                        FILTER_MATCH_COLOR
                     } else {
                         Color32::BLACK
                     },
@@ -1068,7 +2140,51 @@ impl JsonGraph {
             );
 
             // Render node content based on type
-            self.render_node_content(&painter, node, rect, self.zoom);
+            self.render_node_content(painter, node, rect, self.zoom, &self.visible_types);
+            if self.has_child_nodes(node) {
+                self.render_collapse_toggle(painter, node, rect);
+            }
+        }
+
+        // Full value of the hovered row (readable even when zoomed out), or the
+        // node's JSON Pointer over its header
+        if self.context_menu.is_none()
+            && self.editing_cell.is_none()
+            && let Some(pointer) = response.hover_pos()
+            && let Some(text) = self
+                .nodes
+                .iter()
+                .rev() // Later nodes are drawn on top
+                .filter(|n| !self.is_hidden(n))
+                .find_map(|node| {
+                    let pos = self.transform_pos(node.position, canvas_rect);
+                    let rect = Rect::from_min_size(pos, node.size * self.zoom);
+                    rect.contains(pointer).then(|| {
+                        self.row_at_pos(node, rect, pointer)
+                            .and_then(|row| self.row_tooltip(node, row))
+                            .or_else(|| {
+                                (pointer.y < rect.min.y + 25.0 * self.zoom)
+                                    .then(|| self.pointer_tooltip(node))
+                                    .flatten()
+                            })
+                    })
+                })
+                .flatten()
+        {
+            response.clone().on_hover_ui_at_pointer(|ui| {
+                ui.set_max_width(400.0);
+                ui.label(text);
+            });
+        }
+
+        if let Some(path) = toggle_collapse {
+            let collapsed = !self.is_collapsed(&path);
+            self.set_collapsed(&path, collapsed);
+            self.log_to_console(&format!(
+                "{} subtree: {:?}",
+                if collapsed { "Collapsed" } else { "Expanded" },
+                path
+            ));
         }
 
         // Instructions
@@ -1082,17 +2198,22 @@ impl JsonGraph {
             );
         }
 
+        // Dialog windows keep keyboard focus to themselves while shown
+        let focus_target = self.dialog_focus.take();
+
         // Show editing window if a cell is being edited
         let mut close_window = false;
         let mut save_edit = false;
         let mut edit_data: Option<(usize, String, String, NodeType)> = None;
 
         if let Some(editing) = &mut self.editing_cell {
-            egui::Window::new("Edit Value")
+            let shown = egui::Window::new("Edit Value")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ui.ctx(), |ui| {
+                    let focused_before = DialogFocus::begin(ui, focus_target);
+
                     ui.horizontal(|ui| {
                         ui.label("Key:");
                         ui.label(&editing.key);
@@ -1106,9 +2227,16 @@ impl JsonGraph {
                     ui.separator();
 
                     ui.label("Value:");
-                    let text_edit = egui::TextEdit::singleline(&mut editing.text)
-                        .desired_width(300.0)
-                        .font(egui::TextStyle::Monospace);
+                    // Strings may contain newlines, so edit them in a multiline box where
+                    // Enter inserts a newline and Ctrl+Enter (Cmd+Enter) commits
+                    let is_multiline = editing.value_type == NodeType::String;
+                    let text_edit = if is_multiline {
+                        egui::TextEdit::multiline(&mut editing.text).desired_rows(4)
+                    } else {
+                        egui::TextEdit::singleline(&mut editing.text)
+                    }
+                    .desired_width(300.0)
+                    .font(egui::TextStyle::Monospace);
 
                     let response = ui.add(text_edit);
 
@@ -1117,33 +2245,50 @@ impl JsonGraph {
                         editing.text = editing.text.nfc().collect();
                     }
 
-                    // Auto-focus on first show
-                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    // Handle Enter (Ctrl+Enter in multiline mode) / ESC
+                    let commit_requested = if is_multiline {
+                        ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter))
+                    } else {
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                    };
+                    if commit_requested {
                         save_edit = true;
                     } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
                         close_window = true;
                     }
 
-                    // Request focus if not focused
-                    if !response.has_focus() {
-                        response.request_focus();
-                    }
-
                     ui.separator();
 
-                    ui.horizontal(|ui| {
-                        if ui.button("Save").clicked() {
-                            save_edit = true;
-                        }
-                        if ui.button("Cancel").clicked() {
-                            close_window = true;
-                        }
-                    });
+                    let button_ids = ui
+                        .horizontal(|ui| {
+                            let save = ui.button("Save");
+                            let cancel = ui.button("Cancel");
+                            save_edit |= save.clicked();
+                            close_window |= cancel.clicked();
+                            [save.id, cancel.id]
+                        })
+                        .inner;
+                    let focus = DialogFocus {
+                        focused_before,
+                        order: vec![response.id, button_ids[0], button_ids[1]],
+                        initial: response.id,
+                    };
 
                     // Show validation hint
                     match editing.value_type {
+                        NodeType::String => {
+                            ui.label(
+                                egui::RichText::new("💡 Ctrl+Enter to save")
+                                    .small()
+                                    .italics(),
+                            );
+                        }
                         NodeType::Number => {
-                            ui.label(egui::RichText::new("💡 Enter a number").small().italics());
+                            ui.label(
+                                egui::RichText::new("💡 Enter a JSON number, e.g. -42 or 1.5e-3")
+                                    .small()
+                                    .italics(),
+                            );
                         }
                         NodeType::Boolean => {
                             ui.label(
@@ -1154,8 +2299,15 @@ impl JsonGraph {
                         }
                         _ => {}
                     }
+                    focus
                 });
 
+            if let Some(shown) = shown
+                && let Some(focus) = shown.inner
+            {
+                self.dialog_focus = focus.trap(ui.ctx(), shown.response.layer_id);
+            }
+
             // Extract data for later use (to avoid borrow checker issues)
             if save_edit {
                 edit_data = Some((
@@ -1199,23 +2351,31 @@ impl JsonGraph {
         if close_window {
             self.editing_cell = None;
         }
+        let mut restore_canvas_focus = close_window;
 
         // Show adding dialog if adding a new property/item
         let mut close_add_dialog = false;
         let mut save_add = false;
-        let mut add_data: Option<(usize, bool, String, String, NodeType)> = None;
+        let mut add_data: Option<(usize, bool, String, String, NodeType, Option<usize>)> = None;
 
         if let Some(adding) = &mut self.adding_state {
-            egui::Window::new(if adding.is_object {
-                "Add Property"
-            } else {
-                "Add Item"
+            let shown = egui::Window::new(match (adding.is_object, adding.index) {
+                (true, _) => "Add Property".to_string(),
+                (false, None) => "Add Item".to_string(),
+                (false, Some(index)) => format!(
+                    "Insert Item at {}",
+                    index_label(index, self.one_based_indices)
+                ),
             })
             .collapsible(false)
             .resizable(false)
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ui.ctx(), |ui| {
-                if adding.is_object {
+                let focused_before = DialogFocus::begin(ui, focus_target);
+                // Focus order: property name (Objects only), type, value, buttons
+                let mut focus_order = Vec::new();
+
+                let key_response = if adding.is_object {
                     // Object: need key and value
                     ui.label("Property Name:");
                     let key_response = ui.add(
@@ -1228,152 +2388,71 @@ impl JsonGraph {
                     if key_response.changed() {
                         adding.key = adding.key.nfc().collect();
                     }
+                    focus_order.push(key_response.id);
 
                     ui.separator();
-
-                    ui.label("Value Type:");
-                    ui.horizontal(|ui| {
-                        if ui
-                            .selectable_label(
-                                matches!(adding.value_type, NodeType::String),
-                                "String",
-                            )
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::String;
-                        }
-                        if ui
-                            .selectable_label(
-                                matches!(adding.value_type, NodeType::Number),
-                                "Number",
-                            )
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::Number;
-                        }
-                        if ui
-                            .selectable_label(
-                                matches!(adding.value_type, NodeType::Boolean),
-                                "Boolean",
-                            )
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::Boolean;
-                        }
-                        if ui
-                            .selectable_label(matches!(adding.value_type, NodeType::Null), "Null")
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::Null;
-                        }
-                    });
-
-                    ui.separator();
-
-                    ui.label("Value:");
-                    let value_response = ui.add(
-                        egui::TextEdit::singleline(&mut adding.value)
-                            .desired_width(300.0)
-                            .font(egui::TextStyle::Monospace),
-                    );
-
-                    // Apply Unicode NFC normalization for Korean input
-                    if value_response.changed() {
-                        adding.value = adding.value.nfc().collect();
-                    }
-
-                    // Auto-focus on first show
-                    if !key_response.has_focus() && !value_response.has_focus() {
-                        key_response.request_focus();
-                    }
-
-                    // Handle Enter/ESC
-                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                        save_add = true;
-                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
-                        close_add_dialog = true;
-                    }
+                    Some(key_response)
                 } else {
                     // Array: only need value (index is automatic)
-                    ui.label("Value Type:");
-                    ui.horizontal(|ui| {
-                        if ui
-                            .selectable_label(
-                                matches!(adding.value_type, NodeType::String),
-                                "String",
-                            )
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::String;
-                        }
-                        if ui
-                            .selectable_label(
-                                matches!(adding.value_type, NodeType::Number),
-                                "Number",
-                            )
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::Number;
-                        }
-                        if ui
-                            .selectable_label(
-                                matches!(adding.value_type, NodeType::Boolean),
-                                "Boolean",
-                            )
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::Boolean;
-                        }
-                        if ui
-                            .selectable_label(matches!(adding.value_type, NodeType::Null), "Null")
-                            .clicked()
-                        {
-                            adding.value_type = NodeType::Null;
-                        }
-                    });
-
-                    ui.separator();
+                    None
+                };
 
-                    ui.label("Value:");
-                    let value_response = ui.add(
-                        egui::TextEdit::singleline(&mut adding.value)
-                            .desired_width(300.0)
-                            .font(egui::TextStyle::Monospace),
-                    );
+                ui.label("Value Type:");
+                focus_order.extend(Self::value_type_selector(ui, &mut adding.value_type));
 
-                    // Apply Unicode NFC normalization for Korean input
-                    if value_response.changed() {
-                        adding.value = adding.value.nfc().collect();
-                    }
+                ui.separator();
 
-                    // Auto-focus
-                    if !value_response.has_focus() {
-                        value_response.request_focus();
-                    }
+                ui.label("Value:");
+                let value_response = ui.add(
+                    egui::TextEdit::singleline(&mut adding.value)
+                        .desired_width(300.0)
+                        .font(egui::TextStyle::Monospace),
+                );
 
-                    // Handle Enter/ESC
-                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                        save_add = true;
-                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
-                        close_add_dialog = true;
-                    }
+                // Apply Unicode NFC normalization for Korean input
+                if value_response.changed() {
+                    adding.value = adding.value.nfc().collect();
+                }
+                focus_order.push(value_response.id);
+
+                // Handle Enter (in a text field; focused buttons handle their own) / ESC
+                let text_committed = value_response.lost_focus()
+                    || key_response.as_ref().is_some_and(|r| r.lost_focus());
+                if text_committed && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    save_add = true;
+                } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close_add_dialog = true;
                 }
 
                 ui.separator();
 
-                ui.horizontal(|ui| {
-                    if ui.button("Add").clicked() {
-                        save_add = true;
-                    }
-                    if ui.button("Cancel").clicked() {
-                        close_add_dialog = true;
-                    }
-                });
+                let button_ids = ui
+                    .horizontal(|ui| {
+                        let add = ui.button("Add");
+                        let cancel = ui.button("Cancel");
+                        save_add |= add.clicked();
+                        close_add_dialog |= cancel.clicked();
+                        [add.id, cancel.id]
+                    })
+                    .inner;
+                focus_order.extend(button_ids);
+
+                // Start in the property name for Objects, the value for Arrays
+                let initial = key_response.map_or(value_response.id, |r| r.id);
+                let focus = DialogFocus {
+                    focused_before,
+                    order: focus_order,
+                    initial,
+                };
 
                 // Show validation hint
                 match adding.value_type {
                     NodeType::Number => {
-                        ui.label(egui::RichText::new("💡 Enter a number").small().italics());
+                        ui.label(
+                            egui::RichText::new("💡 Enter a JSON number, e.g. -42 or 1.5e-3")
+                                .small()
+                                .italics(),
+                        );
                     }
                     NodeType::Boolean => {
                         ui.label(
@@ -1387,8 +2466,15 @@ impl JsonGraph {
                     }
                     _ => {}
                 }
+                focus
             });
 
+            if let Some(shown) = shown
+                && let Some(focus) = shown.inner
+            {
+                self.dialog_focus = focus.trap(ui.ctx(), shown.response.layer_id);
+            }
+
             // Extract data for later use
             if save_add {
                 add_data = Some((
@@ -1397,12 +2483,13 @@ impl JsonGraph {
                     adding.key.clone(),
                     adding.value.clone(),
                     adding.value_type.clone(),
+                    adding.index,
                 ));
             }
         }
 
         // Process add outside of the borrow
-        if let Some((node_id, is_object, key, value, value_type)) = add_data {
+        if let Some((node_id, is_object, key, value, value_type, index)) = add_data {
             // Validate key for Object
             if is_object && key.is_empty() {
                 self.log_to_console("Property name cannot be empty");
@@ -1411,10 +2498,13 @@ impl JsonGraph {
                 if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
                     let json_path = node.json_path.clone();
 
-                    // Create the add operation
-                    self.pending_edit = Some(EditResult {
-                        json_path,
-                        operation: ModifyOperation::Add {
+                    // Create the add (or insert) operation
+                    let operation = match index {
+                        Some(index) if !is_object => ModifyOperation::InsertAt {
+                            index,
+                            value: validated_value,
+                        },
+                        _ => ModifyOperation::Add {
                             key: if is_object {
                                 key.clone()
                             } else {
@@ -1422,6 +2512,10 @@ impl JsonGraph {
                             },
                             value: validated_value,
                         },
+                    };
+                    self.pending_edit = Some(EditResult {
+                        json_path,
+                        operation,
                     });
 
                     self.log_to_console(&format!(
@@ -1439,6 +2533,7 @@ impl JsonGraph {
 
         if close_add_dialog {
             self.adding_state = None;
+            restore_canvas_focus = true;
         }
 
         // Show renaming dialog if renaming a key
@@ -1447,11 +2542,13 @@ impl JsonGraph {
         let mut rename_data: Option<(usize, String, String)> = None;
 
         if let Some(renaming) = &mut self.renaming_key {
-            egui::Window::new("Rename Property")
+            let shown = egui::Window::new("Rename Property")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ui.ctx(), |ui| {
+                    let focused_before = DialogFocus::begin(ui, focus_target);
+
                     ui.horizontal(|ui| {
                         ui.label("Old Name:");
                         ui.label(&renaming.old_key);
@@ -1471,11 +2568,6 @@ impl JsonGraph {
                         renaming.new_key = renaming.new_key.nfc().collect();
                     }
 
-                    // Auto-focus on first show
-                    if !key_response.has_focus() {
-                        key_response.request_focus();
-                    }
-
                     // Handle Enter/ESC
                     if key_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                         save_rename = true;
@@ -1485,16 +2577,28 @@ impl JsonGraph {
 
                     ui.separator();
 
-                    ui.horizontal(|ui| {
-                        if ui.button("Rename").clicked() {
-                            save_rename = true;
-                        }
-                        if ui.button("Cancel").clicked() {
-                            close_rename_dialog = true;
-                        }
-                    });
+                    let button_ids = ui
+                        .horizontal(|ui| {
+                            let rename = ui.button("Rename");
+                            let cancel = ui.button("Cancel");
+                            save_rename |= rename.clicked();
+                            close_rename_dialog |= cancel.clicked();
+                            [rename.id, cancel.id]
+                        })
+                        .inner;
+                    DialogFocus {
+                        focused_before,
+                        order: vec![key_response.id, button_ids[0], button_ids[1]],
+                        initial: key_response.id,
+                    }
                 });
 
+            if let Some(shown) = shown
+                && let Some(focus) = shown.inner
+            {
+                self.dialog_focus = focus.trap(ui.ctx(), shown.response.layer_id);
+            }
+
             // Extract data for later use
             if save_rename {
                 rename_data = Some((
@@ -1536,63 +2640,321 @@ impl JsonGraph {
 
         if close_rename_dialog {
             self.renaming_key = None;
+            restore_canvas_focus = true;
         }
 
-        // Show context menu if active
-        let mut close_context_menu = false;
-
-        if let Some(menu_state) = &self.context_menu {
-            // Clone data for use after borrow ends
-            let node_id = menu_state.node_id;
-            let row_key = menu_state.row_key.clone();
-            let is_object = menu_state.is_object;
-            let is_primitive = menu_state.is_primitive;
-            let value_type = menu_state.value_type.clone();
-            let menu_position = menu_state.position;
+        // Show paste dialog if pasting JSON as a new child
+        // Only used where the clipboard can't be read directly (see
+        // `paste_clipboard_text`): the JSON arrives via a paste event into the text box
+        let mut close_paste_dialog = false;
+        let mut save_paste = false;
+        let mut paste_data: Option<(usize, bool, String, String)> = None;
 
-            egui::Area::new(egui::Id::new("context_menu"))
-                .fixed_pos(menu_position) // Use the saved position
-                .order(egui::Order::Foreground)
+        if let Some(pasting) = &mut self.pasting_state {
+            let shown = egui::Window::new("Paste JSON")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ui.ctx(), |ui| {
-                    egui::Frame::popup(ui.style()).show(ui, |ui| {
-                        ui.set_min_width(150.0);
+                    let focused_before = DialogFocus::begin(ui, focus_target);
+
+                    let key_response = if pasting.is_object {
+                        ui.label("Property Name:");
+                        let key_response = ui.add(
+                            egui::TextEdit::singleline(&mut pasting.key)
+                                .desired_width(300.0)
+                                .font(egui::TextStyle::Monospace),
+                        );
 
-                        if let Some(key) = &row_key {
-                            // Row-level context menu
-                            if is_primitive && ui.button("✏ Edit Value").clicked() {
-                                // Trigger edit action
-                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id)
-                                    && let Some(current_value) = self.get_cell_value(node, key)
-                                {
-                                    self.editing_cell = Some(EditingCell {
-                                        node_id,
-                                        key: key.clone(),
-                                        text: current_value,
-                                        value_type: value_type.clone().unwrap(),
-                                    });
-                                }
-                                close_context_menu = true;
-                            }
+                        // Apply Unicode NFC normalization for Korean input
+                        if key_response.changed() {
+                            pasting.key = pasting.key.nfc().collect();
+                        }
 
-                            if is_object && ui.button("✎ Rename Key").clicked() {
-                                // Trigger rename action
-                                self.renaming_key = Some(RenamingKey {
-                                    node_id,
-                                    old_key: key.clone(),
-                                    new_key: key.clone(),
-                                });
-                                close_context_menu = true;
-                            }
+                        ui.separator();
+                        Some(key_response)
+                    } else {
+                        None
+                    };
 
-                            if ui.button("🗑 Delete").clicked() {
-                                // Trigger delete action
-                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
-                                    let mut json_path = node.json_path.clone();
-                                    json_path.push(key.clone());
+                    ui.label("JSON (Ctrl+V to paste):");
+                    let text_response = ui.add(
+                        egui::TextEdit::multiline(&mut pasting.text)
+                            .desired_width(300.0)
+                            .desired_rows(8)
+                            .font(egui::TextStyle::Monospace),
+                    );
 
-                                    self.pending_edit = Some(EditResult {
-                                        json_path,
-                                        operation: ModifyOperation::Delete,
+                    // Handle Ctrl+Enter/ESC
+                    if ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter)) {
+                        save_paste = true;
+                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        close_paste_dialog = true;
+                    }
+
+                    ui.separator();
+
+                    let button_ids = ui
+                        .horizontal(|ui| {
+                            let insert = ui.button("Insert");
+                            let cancel = ui.button("Cancel");
+                            save_paste |= insert.clicked();
+                            close_paste_dialog |= cancel.clicked();
+                            [insert.id, cancel.id]
+                        })
+                        .inner;
+
+                    // Start in the key field for Objects, the JSON box for Arrays
+                    let key_id = key_response.map(|r| r.id);
+                    DialogFocus {
+                        focused_before,
+                        order: key_id
+                            .into_iter()
+                            .chain([text_response.id])
+                            .chain(button_ids)
+                            .collect(),
+                        initial: key_id.unwrap_or(text_response.id),
+                    }
+                });
+
+            if let Some(shown) = shown
+                && let Some(focus) = shown.inner
+            {
+                self.dialog_focus = focus.trap(ui.ctx(), shown.response.layer_id);
+            }
+
+            // Extract data for later use
+            if save_paste {
+                paste_data = Some((
+                    pasting.node_id,
+                    pasting.is_object,
+                    pasting.key.clone(),
+                    pasting.text.clone(),
+                ));
+            }
+        }
+
+        // Process paste outside of the borrow
+        if let Some((node_id, is_object, key, text)) = paste_data {
+            if is_object && key.is_empty() {
+                self.log_to_console("Property name cannot be empty");
+            } else if self.queue_paste(node_id, if is_object { key } else { String::new() }, &text)
+            {
+                close_paste_dialog = true;
+                selection_changed = true;
+            }
+        }
+
+        if close_paste_dialog {
+            self.pasting_state = None;
+            restore_canvas_focus = true;
+        }
+
+        // Show Edit JSON dialog if replacing an Object/Array value
+        let mut close_subtree_dialog = false;
+        let mut save_subtree = false;
+
+        if let Some(editing) = &mut self.subtree_edit {
+            let shown = egui::Window::new("Edit JSON")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ui.ctx(), |ui| {
+                    let focused_before = DialogFocus::begin(ui, focus_target);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Path:");
+                        ui.label(json_pointer(&editing.json_path));
+                    });
+
+                    ui.separator();
+
+                    ui.label("JSON:");
+                    let text_response = ui.add(
+                        egui::TextEdit::multiline(&mut editing.text)
+                            .desired_width(400.0)
+                            .desired_rows(12)
+                            .font(egui::TextStyle::Monospace),
+                    );
+                    if text_response.changed() {
+                        editing.error = None;
+                    }
+                    if let Some(error) = &editing.error {
+                        ui.colored_label(egui::Color32::RED, error);
+                    }
+
+                    // Handle Ctrl+Enter/ESC
+                    if ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter)) {
+                        save_subtree = true;
+                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        close_subtree_dialog = true;
+                    }
+
+                    ui.separator();
+
+                    let button_ids = ui
+                        .horizontal(|ui| {
+                            let save = ui.button("Save");
+                            let cancel = ui.button("Cancel");
+                            save_subtree |= save.clicked();
+                            close_subtree_dialog |= cancel.clicked();
+                            [save.id, cancel.id]
+                        })
+                        .inner;
+
+                    ui.label(
+                        egui::RichText::new("💡 Ctrl+Enter to save")
+                            .small()
+                            .italics(),
+                    );
+                    DialogFocus {
+                        focused_before,
+                        order: vec![text_response.id, button_ids[0], button_ids[1]],
+                        initial: text_response.id,
+                    }
+                });
+
+            if let Some(shown) = shown
+                && let Some(focus) = shown.inner
+            {
+                self.dialog_focus = focus.trap(ui.ctx(), shown.response.layer_id);
+            }
+        }
+
+        // Process save outside of the borrow
+        if save_subtree {
+            close_subtree_dialog = self.save_subtree_edit();
+            selection_changed |= close_subtree_dialog;
+        }
+
+        if close_subtree_dialog {
+            self.subtree_edit = None;
+            restore_canvas_focus = true;
+        }
+
+        if restore_canvas_focus {
+            ui.memory_mut(|mem| mem.request_focus(canvas_id));
+        }
+
+        // Show context menu if active
+        let mut close_context_menu = false;
+
+        if let Some(menu_state) = &self.context_menu {
+            // Clone data for use after borrow ends
+            let node_id = menu_state.node_id;
+            let row_key = menu_state.row_key.clone();
+            let is_object = menu_state.is_object;
+            let is_primitive = menu_state.is_primitive;
+            let value_type = menu_state.value_type.clone();
+            let menu_position = menu_state.position;
+
+            egui::Area::new(egui::Id::new("context_menu"))
+                .fixed_pos(menu_position) // Use the saved position
+                .order(egui::Order::Foreground)
+                .show(ui.ctx(), |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_min_width(150.0);
+
+                        if let Some(key) = &row_key {
+                            // Row-level context menu
+                            let edit_label = if is_primitive {
+                                "✏ Edit Value"
+                            } else {
+                                "✏ Edit JSON"
+                            };
+                            if ui.button(edit_label).clicked() {
+                                // The editor opens once App provides the current value
+                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
+                                    let mut json_path = node.json_path.clone();
+                                    json_path.push(key.clone());
+                                    self.pending_value_edit = Some(json_path);
+                                }
+                                close_context_menu = true;
+                            }
+
+                            if is_primitive && let Some(current_type) = &value_type {
+                                ui.menu_button("🔁 Change Type", |ui| {
+                                    for target in [
+                                        NodeType::String,
+                                        NodeType::Number,
+                                        NodeType::Boolean,
+                                        NodeType::Null,
+                                    ] {
+                                        if ui
+                                            .add_enabled(
+                                                &target != current_type,
+                                                egui::Button::new(format!("{:?}", target)),
+                                            )
+                                            .clicked()
+                                            && let Some(node) =
+                                                self.nodes.iter().find(|n| n.id == node_id)
+                                        {
+                                            let mut json_path = node.json_path.clone();
+                                            json_path.push(key.clone());
+
+                                            self.pending_edit = Some(EditResult {
+                                                json_path,
+                                                operation: ModifyOperation::ChangeType { target },
+                                            });
+                                            selection_changed = true;
+                                            close_context_menu = true;
+                                        }
+                                    }
+                                });
+                            }
+
+                            if is_object && ui.button("✎ Rename Key").clicked() {
+                                // Trigger rename action
+                                self.renaming_key = Some(RenamingKey {
+                                    node_id,
+                                    old_key: key.clone(),
+                                    new_key: key.clone(),
+                                });
+                                close_context_menu = true;
+                            }
+
+                            if ui.button("⧉ Duplicate").clicked() {
+                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
+                                    let mut json_path = node.json_path.clone();
+                                    json_path.push(key.clone());
+
+                                    self.pending_edit = Some(EditResult {
+                                        json_path,
+                                        operation: ModifyOperation::Duplicate,
+                                    });
+                                    selection_changed = true;
+                                }
+                                close_context_menu = true;
+                            }
+
+                            // Array rows: add an item next to this one
+                            if !is_object && let Ok(index) = key.parse::<usize>() {
+                                for (label, insert_index) in
+                                    [("⬆ Insert Above", index), ("⬇ Insert Below", index + 1)]
+                                {
+                                    if ui.button(label).clicked() {
+                                        self.adding_state = Some(AddingState {
+                                            node_id,
+                                            is_object,
+                                            key: String::new(),
+                                            value: String::new(),
+                                            value_type: NodeType::String,
+                                            index: Some(insert_index),
+                                        });
+                                        close_context_menu = true;
+                                    }
+                                }
+                            }
+
+                            if ui.button("🗑 Delete").clicked() {
+                                // Trigger delete action
+                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
+                                    let mut json_path = node.json_path.clone();
+                                    json_path.push(key.clone());
+
+                                    self.pending_edit = Some(EditResult {
+                                        json_path,
+                                        operation: ModifyOperation::Delete,
                                     });
                                     selection_changed = true;
                                 }
@@ -1612,9 +2974,108 @@ impl JsonGraph {
                                     key: String::new(),
                                     value: String::new(),
                                     value_type: NodeType::String,
+                                    index: None,
                                 });
                                 close_context_menu = true;
                             }
+
+                            if !is_object {
+                                let sort_keys = self.sort_key_candidates(node_id);
+                                let mut sort_choice = None;
+                                ui.menu_button("↕ Sort", |ui| {
+                                    for (label, descending) in
+                                        [("Ascending", false), ("Descending", true)]
+                                    {
+                                        if sort_keys.is_empty() {
+                                            if ui.button(label).clicked() {
+                                                sort_choice = Some((descending, None));
+                                            }
+                                        } else {
+                                            // Items are objects: sort by one of their keys
+                                            ui.menu_button(format!("{} by", label), |ui| {
+                                                for key in &sort_keys {
+                                                    if ui.button(key).clicked() {
+                                                        sort_choice =
+                                                            Some((descending, Some(key.clone())));
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    }
+                                });
+                                if let Some((descending, key)) = sort_choice {
+                                    if let Some(node) = self.nodes.iter().find(|n| n.id == node_id)
+                                    {
+                                        self.pending_edit = Some(EditResult {
+                                            json_path: node.json_path.clone(),
+                                            operation: ModifyOperation::SortArray {
+                                                descending,
+                                                key,
+                                            },
+                                        });
+                                        selection_changed = true;
+                                    }
+                                    close_context_menu = true;
+                                }
+                            }
+
+                            if ui
+                                .button("📥 Paste JSON")
+                                .on_hover_text("Add the JSON on the clipboard as a new child")
+                                .clicked()
+                            {
+                                // The platform layer reads the clipboard after this frame
+                                self.clipboard_paste = Some((node_id, is_object));
+                                close_context_menu = true;
+                            }
+
+                            if ui
+                                .button("📋 Copy JSON")
+                                .on_hover_text("Copy this node as formatted JSON")
+                                .clicked()
+                            {
+                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
+                                    self.pending_copy =
+                                        Some(CopyRequest::Subtree(node.json_path.clone()));
+                                }
+                                close_context_menu = true;
+                            }
+
+                            if ui
+                                .button("📋 Copy Type Signature")
+                                .on_hover_text("Copy a summary of this node's structure and types")
+                                .clicked()
+                            {
+                                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
+                                    self.pending_copy =
+                                        Some(CopyRequest::TypeSignature(node.json_path.clone()));
+                                }
+                                close_context_menu = true;
+                            }
+
+                            for (label, hint, relative) in [
+                                (
+                                    "📋 Copy Leaf Paths",
+                                    "Copy every descendant leaf path from the root",
+                                    false,
+                                ),
+                                (
+                                    "📋 Copy Relative Leaf Paths",
+                                    "Copy every descendant leaf path relative to this node",
+                                    true,
+                                ),
+                            ] {
+                                if ui.button(label).on_hover_text(hint).clicked() {
+                                    if let Some(node) = self.nodes.iter().find(|n| n.id == node_id)
+                                    {
+                                        self.pending_copy = Some(CopyRequest::LeafPaths {
+                                            path: node.json_path.clone(),
+                                            relative,
+                                        });
+                                    }
+                                    close_context_menu = true;
+                                }
+                            }
                         }
 
                         ui.separator();
@@ -1634,12 +3095,24 @@ impl JsonGraph {
             self.context_menu = None;
         }
 
-        // Render minimap in bottom-right corner
+        // Render minimap in bottom-right corner (without nodes in collapsed subtrees)
+        let visible_nodes: Vec<GraphNode>;
+        let minimap_nodes = if self.collapsed_paths.is_empty() {
+            &self.nodes
+        } else {
+            visible_nodes = self
+                .nodes
+                .iter()
+                .filter(|n| !self.is_hidden(n))
+                .cloned()
+                .collect();
+            &visible_nodes
+        };
         if let Some(new_offset) = self.minimap.render(
             ui,
             &painter,
             canvas_rect,
-            &self.nodes,
+            minimap_nodes,
             self.zoom,
             self.offset,
         ) {
@@ -1650,200 +3123,109 @@ impl JsonGraph {
         selection_changed
     }
 
-    /// Transform position with zoom and offset
-    fn transform_pos(&self, pos: Pos2, canvas_rect: Rect) -> Pos2 {
-        let transformed = pos.to_vec2() * self.zoom + self.offset;
-        canvas_rect.min + transformed
+    /// Change the zoom, keeping the canvas point `anchor` (relative to the canvas's
+    /// top-left corner) fixed on screen
+    fn zoom_around(&mut self, zoom: f32, anchor: Vec2) {
+        let old_zoom = self.zoom;
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+
+        // Before zoom: world_pos = (anchor - offset) / old_zoom
+        // After zoom:  world_pos = (anchor - new_offset) / new_zoom
+        // We want world_pos to remain the same, so:
+        // new_offset = anchor - (anchor - offset) / old_zoom * new_zoom
+        let world_pos = (anchor - self.offset) / old_zoom;
+        self.offset = anchor - world_pos * self.zoom;
     }
 
-    /// Check if a click position is on an action area (edit, delete, add button)
-    /// Returns None if clicking on header or empty space
-    fn get_click_action(
-        &self,
-        node: &GraphNode,
-        rect: Rect,
-        click_pos: Pos2,
-    ) -> Option<ClickAction> {
-        let header_height = 25.0 * self.zoom;
-        let row_height = 22.0 * self.zoom;
-        let delete_button_size = 16.0 * self.zoom;
-
-        // Check if click is below header
-        if click_pos.y < rect.min.y + header_height {
-            return None; // Clicking on header
-        }
-
-        // Calculate which row was clicked
-        let relative_y = click_pos.y - (rect.min.y + header_height);
-        let row_index = (relative_y / row_height).floor() as usize;
-
-        match &node.content {
-            NodeContent::Object(pairs) => {
-                let max_visible_rows = pairs.len().min(10);
-                let key_column_width = rect.width() * 0.4;
-                let delete_button_x = rect.max.x - delete_button_size - 5.0;
-
-                // Check if clicking within a valid row
-                if row_index < max_visible_rows {
-                    let pair = &pairs[row_index];
-                    let y = rect.min.y + header_height + (row_index as f32 * row_height);
-
-                    // Check if clicking on delete button
-                    let delete_center_x = delete_button_x + delete_button_size / 2.0;
-                    let delete_center_y = y + row_height / 2.0;
-                    let distance = ((click_pos.x - delete_center_x).powi(2)
-                        + (click_pos.y - delete_center_y).powi(2))
-                    .sqrt();
-                    if distance <= delete_button_size / 2.0 {
-                        return Some(ClickAction::DeleteRow(pair.key.clone()));
-                    }
-
-                    // Check if clicking on key column for renaming
-                    if click_pos.x >= rect.min.x + 5.0
-                        && click_pos.x <= rect.min.x + key_column_width - 5.0
-                    {
-                        return Some(ClickAction::RenameKey(pair.key.clone()));
-                    }
+    /// Whether an edit, add, rename, paste or Edit JSON dialog is capturing the keyboard
+    pub fn dialog_open(&self) -> bool {
+        self.editing_cell.is_some()
+            || self.adding_state.is_some()
+            || self.renaming_key.is_some()
+            || self.pasting_state.is_some()
+            || self.subtree_edit.is_some()
+    }
 
-                    // Check if clicking on value column for editing (only primitives)
-                    if !pair.is_reference
-                        && click_pos.x > rect.min.x + key_column_width
-                        && click_pos.x < delete_button_x - 5.0
-                    {
-                        return Some(ClickAction::EditCell(
-                            pair.key.clone(),
-                            pair.value_type.clone(),
-                        ));
-                    }
-                }
+    /// Pan with the arrow keys or WASD (faster with Shift), zoom around the canvas
+    /// center with +/- and reset the zoom with 0
+    /// Returns true if the view changed
+    fn keyboard_navigation(&mut self, ui: &egui::Ui, canvas_size: Vec2) -> bool {
+        use egui::Key;
+        let (pan, zoom) = ui.input(|i| {
+            if i.modifiers.command || i.modifiers.alt {
+                return (Vec2::ZERO, None);
             }
-            NodeContent::Array(items) => {
-                let max_visible_rows = items.len().min(10);
-                let index_column_width = 40.0 * self.zoom;
-                let delete_button_x = rect.max.x - delete_button_size - 5.0;
-
-                // Check if clicking within a valid row
-                if row_index < max_visible_rows {
-                    let item = &items[row_index];
-                    let y = rect.min.y + header_height + (row_index as f32 * row_height);
-
-                    // Check if clicking on delete button
-                    let delete_center_x = delete_button_x + delete_button_size / 2.0;
-                    let delete_center_y = y + row_height / 2.0;
-                    let distance = ((click_pos.x - delete_center_x).powi(2)
-                        + (click_pos.y - delete_center_y).powi(2))
-                    .sqrt();
-                    if distance <= delete_button_size / 2.0 {
-                        return Some(ClickAction::DeleteRow(item.index.to_string()));
-                    }
-
-                    // Check if clicking on value column for editing (only primitives)
-                    if !item.is_reference
-                        && click_pos.x > rect.min.x + index_column_width
-                        && click_pos.x < delete_button_x - 5.0
-                    {
-                        return Some(ClickAction::EditCell(
-                            item.index.to_string(),
-                            item.value_type.clone(),
-                        ));
-                    }
-                }
+            let held = |keys: [Key; 2]| keys.into_iter().any(|key| i.key_down(key));
+            // Moving the view left shifts the content right
+            let mut direction = Vec2::ZERO;
+            if held([Key::ArrowLeft, Key::A]) {
+                direction.x += 1.0;
             }
-            NodeContent::Primitive(_) => {
-                // Primitive nodes don't have interactive elements
-                return None;
+            if held([Key::ArrowRight, Key::D]) {
+                direction.x -= 1.0;
             }
-        }
-
-        None
-    }
+            if held([Key::ArrowUp, Key::W]) {
+                direction.y += 1.0;
+            }
+            if held([Key::ArrowDown, Key::S]) {
+                direction.y -= 1.0;
+            }
+            let step = if i.modifiers.shift {
+                KEYBOARD_PAN_STEP * 4.0
+            } else {
+                KEYBOARD_PAN_STEP
+            };
 
-    /// Get context menu information for a right-click position
-    /// Returns None if not clicking on a row
-    fn get_context_menu_info(
-        &self,
-        node: &GraphNode,
-        rect: Rect,
-        click_pos: Pos2,
-    ) -> Option<ContextMenuState> {
-        let header_height = 25.0 * self.zoom;
-        let row_height = 22.0 * self.zoom;
+            let zoom = if i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals) {
+                Some(self.zoom * KEYBOARD_ZOOM_STEP)
+            } else if i.key_pressed(Key::Minus) {
+                Some(self.zoom / KEYBOARD_ZOOM_STEP)
+            } else if i.key_pressed(Key::Num0) {
+                Some(1.0)
+            } else {
+                None
+            };
+            (direction * step, zoom)
+        });
 
-        // Check if click is below header
-        if click_pos.y < rect.min.y + header_height {
-            return None; // Clicking on header
+        self.offset += pan;
+        if let Some(zoom) = zoom {
+            self.zoom_around(zoom, canvas_size / 2.0);
+            self.log_to_console(&format!("Zoom: {:.2}x", self.zoom));
         }
+        pan != Vec2::ZERO || zoom.is_some()
+    }
 
-        // Calculate which row was clicked
-        let relative_y = click_pos.y - (rect.min.y + header_height);
-        let row_index = (relative_y / row_height).floor() as usize;
+    /// Pan so a node's center is in the middle of the canvas, keeping the zoom
+    /// Returns false if no node has this id
+    pub fn center_on_node(&mut self, id: usize) -> bool {
+        let Some(node) = self.nodes.iter().find(|n| n.id == id) else {
+            return false;
+        };
+        self.offset = self.offset_to_center(node, self.canvas_size);
+        self.log_to_console(&format!("Centered on node {}", id));
+        true
+    }
 
-        match &node.content {
-            NodeContent::Object(pairs) => {
-                let max_visible_rows = pairs.len().min(10);
-
-                // Check if clicking within a valid row
-                if row_index < max_visible_rows {
-                    let pair = &pairs[row_index];
-                    Some(ContextMenuState {
-                        node_id: node.id,
-                        row_key: Some(pair.key.clone()),
-                        is_object: true,
-                        is_primitive: !pair.is_reference,
-                        value_type: if !pair.is_reference {
-                            Some(pair.value_type.clone())
-                        } else {
-                            None
-                        },
-                        position: Pos2::ZERO, // Will be set by caller
-                    })
-                } else {
-                    // Clicking in empty space below rows - show Add menu
-                    Some(ContextMenuState {
-                        node_id: node.id,
-                        row_key: None,
-                        is_object: true,
-                        is_primitive: false,
-                        value_type: None,
-                        position: Pos2::ZERO, // Will be set by caller
-                    })
-                }
-            }
-            NodeContent::Array(items) => {
-                let max_visible_rows = items.len().min(10);
-
-                // Check if clicking within a valid row
-                if row_index < max_visible_rows {
-                    let item = &items[row_index];
-                    Some(ContextMenuState {
-                        node_id: node.id,
-                        row_key: Some(item.index.to_string()),
-                        is_object: false,
-                        is_primitive: !item.is_reference,
-                        value_type: if !item.is_reference {
-                            Some(item.value_type.clone())
-                        } else {
-                            None
-                        },
-                        position: Pos2::ZERO, // Will be set by caller
-                    })
-                } else {
-                    // Clicking in empty space below rows - show Add menu
-                    Some(ContextMenuState {
-                        node_id: node.id,
-                        row_key: None,
-                        is_object: false,
-                        is_primitive: false,
-                        value_type: None,
-                        position: Pos2::ZERO, // Will be set by caller
-                    })
+    /// Row of selectable labels choosing a value type in the Add dialog
+    /// Returns the labels' IDs in order (for keyboard focus)
+    fn value_type_selector(ui: &mut egui::Ui, value_type: &mut NodeType) -> [egui::Id; 4] {
+        ui.horizontal(|ui| {
+            [
+                NodeType::String,
+                NodeType::Number,
+                NodeType::Boolean,
+                NodeType::Null,
+            ]
+            .map(|option| {
+                let response = ui.selectable_label(*value_type == option, format!("{:?}", option));
+                if response.clicked() {
+                    *value_type = option;
                 }
-            }
-            NodeContent::Primitive(_) => {
-                // Primitive nodes don't have rows
-                None
-            }
-        }
+                response.id
+            })
+        })
+        .inner
     }
 
     /// Validate a value based on its type
@@ -1851,16 +3233,17 @@ impl JsonGraph {
     fn validate_value(new_value: &str, value_type: &NodeType) -> Option<String> {
         match value_type {
             NodeType::String => {
-                // Strings are always valid
-                Some(format!("\"{}\"", new_value))
+                // Strings are always valid; serialize so quotes, backslashes and
+                // newlines are escaped
+                serde_json::to_string(new_value).ok()
             }
             NodeType::Number => {
-                // Try to parse as number
-                if new_value.parse::<f64>().is_ok() {
-                    Some(new_value.to_string())
-                } else {
-                    None
-                }
+                // Must be a JSON number (so no `NaN`/`inf`, and no leading zeros like
+                // `007`) that is finite as an f64; `1e309` parses but would overflow
+                serde_json::from_str::<serde_json::Number>(new_value.trim())
+                    .ok()
+                    .filter(|number| number.as_f64().is_some())
+                    .map(|number| number.to_string())
             }
             NodeType::Boolean => {
                 // Must be "true" or "false"
@@ -1913,48 +3296,6 @@ impl JsonGraph {
         false
     }
 
-    /// Get the current value of a cell as a string
-    fn get_cell_value(&self, node: &GraphNode, key: &str) -> Option<String> {
-        match &node.content {
-            NodeContent::Object(pairs) => {
-                pairs.iter().find(|p| p.key == key).map(|p| {
-                    // Return value without quotes for strings, raw for others
-                    if p.value_type == NodeType::String {
-                        // Remove quotes from display
-                        let display = &p.value_display;
-                        if display.starts_with('"') && display.ends_with('"') {
-                            display[1..display.len() - 1].to_string()
-                        } else {
-                            display.clone()
-                        }
-                    } else {
-                        p.value_display.clone()
-                    }
-                })
-            }
-            NodeContent::Array(items) => {
-                if let Ok(index) = key.parse::<usize>() {
-                    items.get(index).map(|item| {
-                        // Return value without quotes for strings, raw for others
-                        if item.value_type == NodeType::String {
-                            let display = &item.value_display;
-                            if display.starts_with('"') && display.ends_with('"') {
-                                display[1..display.len() - 1].to_string()
-                            } else {
-                                display.clone()
-                            }
-                        } else {
-                            item.value_display.clone()
-                        }
-                    })
-                } else {
-                    None
-                }
-            }
-            NodeContent::Primitive(_) => None,
-        }
-    }
-
     /// Log message to browser console (WASM) or stdout (desktop)
     fn log_to_console(&self, message: &str) {
         utils::log("JSON Graph", message);
@@ -1974,160 +3315,584 @@ mod tests {
     }
 
     #[test]
-    fn test_build_simple_object() {
+    fn test_select_by_path_closest_match() {
         let mut graph = JsonGraph::new();
-        let json = json!({"key": "value"});
-        graph.build_from_json(&json);
-
-        // Only 1 node: the root object (primitive values shown in table)
-        assert_eq!(graph.nodes.len(), 1);
-        assert_eq!(graph.edges.len(), 0); // No edges since no child nodes
-
-        // Check that the object has the key in its content
-        if let NodeContent::Object(pairs) = &graph.nodes[0].content {
-            assert_eq!(pairs.len(), 1);
-            assert_eq!(pairs[0].key, "key");
-            assert_eq!(pairs[0].value_display, "\"value\"");
-        } else {
-            panic!("Expected Object content");
-        }
+        graph.build_from_json(&json!({"a": {"b": 1}}));
+        let missing = vec!["a".to_string(), "missing".to_string()];
+
+        // Default: falls back to the closest ancestor and flags it
+        assert!(graph.select_by_path(&missing));
+        assert_eq!(graph.get_selected_path(), Some(vec!["a".to_string()]));
+        assert!(graph.is_approximate_selection());
+
+        // Exact match clears the flag
+        assert!(graph.select_by_path(&["a".to_string()]));
+        assert!(!graph.is_approximate_selection());
+
+        // Exact-only: a missing path selects nothing
+        graph.clear_selection();
+        graph.set_exact_path_only(true);
+        assert!(!graph.select_by_path(&missing));
+        assert_eq!(graph.get_selected_path(), None);
+        assert!(!graph.is_approximate_selection());
     }
 
     #[test]
-    fn test_build_array() {
+    fn test_reference_rows_edit_as_json() {
+        let value = json!({"child": {"a": 1}, "list": [[2]]});
         let mut graph = JsonGraph::new();
-        let json = json!([1, 2, 3]);
-        graph.build_from_json(&json);
-
-        // Only 1 node: the array (primitive values shown in table)
-        assert_eq!(graph.nodes.len(), 1);
-        assert_eq!(graph.edges.len(), 0); // No edges since no child nodes
-
-        // Check that the array has 3 items in its content
-        if let NodeContent::Array(items) = &graph.nodes[0].content {
-            assert_eq!(items.len(), 3);
-            assert_eq!(items[0].value_display, "1");
-            assert_eq!(items[1].value_display, "2");
-            assert_eq!(items[2].value_display, "3");
-        } else {
-            panic!("Expected Array content");
+        graph.build_from_json(&value);
+
+        // Clicking the value of an Object row asks to edit it as JSON
+        let root = graph.nodes[0].clone();
+        let rect = Rect::from_min_size(root.position, root.size);
+        let click = Pos2::new(rect.min.x + rect.width() * 0.6, rect.min.y + 25.0 + 11.0);
+        match graph.get_click_action(&root, rect, click) {
+            Some(ClickAction::EditCell(key, NodeType::Object)) => assert_eq!(key, "child"),
+            other => panic!("Expected EditCell on an Object, got {:?}", other),
         }
+
+        // Same for an Array item
+        let list = graph
+            .find_node_by_path(&["list".to_string()])
+            .unwrap()
+            .clone();
+        let rect = Rect::from_min_size(list.position, list.size);
+        let click = Pos2::new(rect.min.x + rect.width() * 0.6, rect.min.y + 25.0 + 11.0);
+        match graph.get_click_action(&list, rect, click) {
+            Some(ClickAction::EditCell(key, NodeType::Array)) => assert_eq!(key, "0"),
+            other => panic!("Expected EditCell on an Array, got {:?}", other),
+        }
+
+        // App answers the request by opening the dialog; a rebuild cancels it
+        graph.pending_value_edit = Some(vec!["child".to_string()]);
+        let path = graph.take_pending_value_edit().unwrap();
+        assert!(graph.take_pending_value_edit().is_none());
+        assert!(!graph.open_cell_editor(&path, &json!({"a": 1})));
+        graph.open_subtree_editor(path, "{\n  \"a\": 1\n}".to_string());
+        assert!(graph.dialog_open());
+        graph.build_from_json(&value);
+        assert!(!graph.dialog_open());
     }
 
     #[test]
-    fn test_build_nested() {
+    fn test_subtree_edit_keeps_parse_error() {
         let mut graph = JsonGraph::new();
-        let json = json!({
-            "user": {
-                "name": "Alice",
-                "age": 30
-            }
-        });
-        graph.build_from_json(&json);
+        graph.build_from_json(&json!({"child": {"a": 1}}));
+        graph.open_subtree_editor(vec!["child".to_string()], "{\"a\": }".to_string());
+
+        // Invalid JSON stays in the dialog with the error
+        assert!(!graph.save_subtree_edit());
+        assert!(graph.pending_edit.is_none());
+        let editing = graph.subtree_edit.as_mut().unwrap();
+        assert!(editing.error.as_deref().unwrap().starts_with("JSON Error:"));
+
+        editing.text = "{\"a\": 2}".to_string();
+        assert!(graph.save_subtree_edit());
+        assert!(graph.pending_edit.is_some());
+    }
 
-        // 2 nodes: root object + user object (name and age are shown in user's table)
-        assert_eq!(graph.nodes.len(), 2);
-        assert_eq!(graph.edges.len(), 1); // One edge from root to user
+    #[test]
+    fn test_one_based_indices_are_display_only() {
+        let value = json!([{"a": 1}, "x"]);
+        let mut graph = JsonGraph::new();
+        graph.set_one_based_indices(true);
+        graph.build_from_json(&value);
 
-        // Check that user object has name and age in its content
-        // Find the second object node (id > 0)
-        let user_node = graph
-            .nodes
-            .iter()
-            .find(|n| n.id > 0 && n.label.contains("Object"));
-        assert!(user_node.is_some());
-        if let NodeContent::Object(pairs) = &user_node.unwrap().content {
-            assert_eq!(pairs.len(), 2);
-            assert!(pairs.iter().any(|p| p.key == "name"));
-            assert!(pairs.iter().any(|p| p.key == "age"));
+        let child = graph.find_node_by_path(&["0".to_string()]).unwrap().clone();
+        let edge = graph.edges.iter().find(|e| e.to == child.id).unwrap();
+        assert_eq!(edge.label.as_deref(), Some("[1]"));
+        assert_eq!(
+            graph.breadcrumb_labels(&child.json_path),
+            vec!["root".to_string(), "[1]".to_string()]
+        );
+
+        // Internal paths and cell edits stay 0-based
+        assert!(graph.open_cell_editor(&["1".to_string()], &json!("x")));
+        assert_eq!(graph.editing_cell.as_ref().unwrap().key, "1");
+        assert_eq!(index_label(0, false), "[0]");
+    }
+
+    #[test]
+    fn test_number_validation_follows_json_syntax() {
+        let validate = |text: &str| JsonGraph::validate_value(text, &NodeType::Number);
+        assert_eq!(validate("1.5e-3").as_deref(), Some("1.5e-3"));
+        assert_eq!(validate(" -42 ").as_deref(), Some("-42"));
+        assert!(validate("1E10").is_some());
+
+        // Not JSON numbers, or not finite
+        for text in [
+            "NaN",
+            "nan",
+            "inf",
+            "Infinity",
+            "-infinity",
+            "1e309",
+            "",
+            "1.",
+            "+1",
+        ] {
+            assert!(validate(text).is_none(), "{:?} should be rejected", text);
         }
     }
 
     #[test]
-    fn test_node_type_colors() {
-        assert_ne!(NodeType::Object.color(), NodeType::Array.color());
-        assert_ne!(NodeType::String.color(), NodeType::Number.color());
+    fn test_number_edits_round_trip_through_editor() {
+        use crate::json_editor::JsonEditor;
+
+        // Applied the way App applies graph edits and adds
+        let mut editor = JsonEditor::with_text(r#"{"n": 1, "list": []}"#.to_string());
+        for text in ["1.5e-3", "-42", "0", "-0.0", "1e10"] {
+            let expected: Value = serde_json::from_str(text).unwrap();
+            let literal = JsonGraph::validate_value(text, &NodeType::Number).unwrap();
+            assert!(
+                editor
+                    .update_value_at_path(&["n".to_string()], &literal)
+                    .is_ok()
+            );
+            assert_eq!(editor.parsed_value().unwrap()["n"], expected, "{}", text);
+            assert!(
+                editor
+                    .add_value_at_path(&["list".to_string()], "", &literal)
+                    .is_ok()
+            );
+            let list = editor.parsed_value().unwrap()["list"].as_array().unwrap();
+            assert_eq!(list.last(), Some(&expected), "{}", text);
+        }
+        assert!(editor.text().contains("-42") && !editor.text().contains("-42.0"));
+
+        // Leading zeros aren't JSON, so the graph rejects them as numbers
+        assert!(JsonGraph::validate_value("007", &NodeType::Number).is_none());
     }
 
     #[test]
-    fn test_build_default_json() {
+    fn test_string_edits_round_trip_special_characters() {
+        use crate::json_editor::JsonEditor;
+
+        let typed = "He said \"hi\"\nbye \\ done";
+        let literal = JsonGraph::validate_value(typed, &NodeType::String).unwrap();
+        assert_eq!(serde_json::from_str::<String>(&literal).unwrap(), typed);
+
+        // Applied the way App applies a graph edit
+        let mut editor = JsonEditor::with_text(r#"{"quote": "", "long": ""}"#.to_string());
+        assert!(
+            editor
+                .update_value_at_path(&["quote".to_string()], &literal)
+                .is_ok()
+        );
+        let long = format!("{}{}", typed, "x".repeat(DEFAULT_PREVIEW_LEN));
+        let long_literal = JsonGraph::validate_value(&long, &NodeType::String).unwrap();
+        assert!(
+            editor
+                .update_value_at_path(&["long".to_string()], &long_literal)
+                .is_ok()
+        );
+        let document = editor.parsed_value().unwrap().clone();
+        assert_eq!(document["quote"], json!(typed));
+
+        // Re-opening the cells starts from exactly the stored strings (provided by
+        // App from the document), even when the preview is truncated
         let mut graph = JsonGraph::new();
-        let json = json!({
-            "name": "example",
-            "version": "1.0.0",
-            "items": [
-                {"id": 1, "value": "first"},
-                {"id": 2, "value": "second"}
-            ]
+        graph.build_from_json(&document);
+        for key in ["quote", "long"] {
+            let path = vec![key.to_string()];
+            let value = editor.value_at_path(&path).unwrap();
+            assert!(graph.open_cell_editor(&path, value));
+            let editing = graph.editing_cell.take().unwrap();
+            assert_eq!(editing.value_type, NodeType::String);
+            assert_eq!(Some(editing.text.as_str()), value.as_str());
+        }
+        let NodeContent::Object(pairs) = &graph.nodes[0].content else {
+            panic!("Expected an Object root");
+        };
+        assert!(pairs[1].value_display.ends_with("…\""));
+    }
+
+    #[test]
+    fn test_type_filter_dims_deselected_nodes() {
+        let value = json!({"users": [{"age": 30}], "count": 2});
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+        assert!(NodeType::ALL.iter().all(|t| graph.is_type_visible(t)));
+        assert!(graph.type_filtered_ids().is_empty());
+
+        // Primitive types only dim rows, never whole nodes
+        graph.set_type_visible(NodeType::Number, false);
+        assert!(!graph.is_type_visible(&NodeType::Number));
+        assert!(graph.type_filtered_ids().is_empty());
+
+        // Deselecting Object dims the Object nodes but not the Array between them
+        graph.set_type_visible(NodeType::Object, false);
+        let dimmed = graph.type_filtered_ids();
+        let users = graph.find_node_by_path(&["users".to_string()]).unwrap().id;
+        assert_eq!(dimmed.len(), 2);
+        assert!(!dimmed.contains(&users));
+
+        graph.set_type_visible(NodeType::Object, true);
+        assert!(graph.type_filtered_ids().is_empty());
+    }
+
+    #[test]
+    fn test_filter_dims_non_matching_nodes() {
+        let value = json!({
+            "users": [{"name": "Alice"}, {"name": "Bob"}],
+            "config": {"theme": "dark"}
         });
-        graph.build_from_json(&json);
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+        let id = |graph: &JsonGraph, path: &[&str]| {
+            let path: Vec<String> = path.iter().map(|s| s.to_string()).collect();
+            graph.find_node_by_path(&path).unwrap().id
+        };
+        assert!(graph.filtered_out_ids().is_empty());
+
+        // Primitive value match (case-insensitive) keeps its ancestors
+        graph.set_filter("aLiCe");
+        let dimmed = graph.filtered_out_ids();
+        assert!(!dimmed.contains(&id(&graph, &["users", "0"])));
+        assert!(!dimmed.contains(&id(&graph, &["users"])));
+        assert!(!dimmed.contains(&id(&graph, &[])));
+        assert!(dimmed.contains(&id(&graph, &["users", "1"])));
+        assert!(dimmed.contains(&id(&graph, &["config"])));
+
+        // Key names match too
+        graph.set_filter("theme");
+        let dimmed = graph.filtered_out_ids();
+        assert!(!dimmed.contains(&id(&graph, &["config"])));
+        assert!(dimmed.contains(&id(&graph, &["users"])));
+
+        // Only direct matches are outlined; the first one is found in build order
+        let matches = graph.filter_match_ids();
+        assert!(matches.contains(&id(&graph, &["config"])));
+        assert!(!matches.contains(&id(&graph, &[])));
+        assert_eq!(graph.first_filter_match(), Some(id(&graph, &["config"])));
+        graph.set_filter("name");
+        assert_eq!(
+            graph.first_filter_match(),
+            Some(id(&graph, &["users", "0"]))
+        );
+        graph.set_collapsed(&["users".to_string()], true);
+        assert_eq!(graph.first_filter_match(), None);
 
-        println!("\nAll nodes created:");
-        for node in &graph.nodes {
-            println!(
-                "  Node {}: {} at pos ({}, {})",
-                node.id, node.label, node.position.x, node.position.y
-            );
+        graph.set_filter("");
+        assert!(graph.filtered_out_ids().is_empty());
+        assert!(graph.filter_match_ids().is_empty());
+        assert_eq!(graph.first_filter_match(), None);
+    }
+
+    #[test]
+    fn test_tab_cycles_within_add_dialog() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"a": 1}));
+        graph.adding_state = Some(AddingState {
+            node_id: graph.nodes[0].id,
+            is_object: true,
+            key: String::new(),
+            value: String::new(),
+            value_type: NodeType::String,
+            index: None,
+        });
+
+        let ctx = egui::Context::default();
+        let mut frame = |tab: Option<egui::Modifiers>| {
+            let input = egui::RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(1200.0, 800.0))),
+                events: tab
+                    .map(|modifiers| egui::Event::Key {
+                        key: egui::Key::Tab,
+                        physical_key: None,
+                        pressed: true,
+                        repeat: false,
+                        modifiers,
+                    })
+                    .into_iter()
+                    .collect(),
+                modifiers: tab.unwrap_or_default(),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    graph.ui(ui);
+                });
+            });
+            let focused = ctx.memory(|mem| mem.focused()).unwrap();
+            let layer = ctx.read_response(focused).unwrap().layer_id;
+            (focused, layer)
+        };
+
+        // Opening the dialog focuses the property name
+        frame(None);
+        let (first, dialog_layer) = frame(None);
+        assert_ne!(dialog_layer, egui::LayerId::background());
+
+        // Name, 4 type labels, value, Add and Cancel, then back to the name
+        let mut visited = vec![first];
+        loop {
+            let (focused, layer) = frame(Some(egui::Modifiers::NONE));
+            assert_eq!(layer, dialog_layer);
+            if focused == first {
+                break;
+            }
+            assert!(!visited.contains(&focused));
+            visited.push(focused);
         }
+        assert_eq!(visited.len(), 8);
 
-        println!("\nAll edges:");
-        for edge in &graph.edges {
-            let from_label = graph
-                .nodes
-                .iter()
-                .find(|n| n.id == edge.from)
-                .map(|n| n.label.as_str())
-                .unwrap_or("?");
-            let to_label = graph
-                .nodes
-                .iter()
-                .find(|n| n.id == edge.to)
-                .map(|n| n.label.as_str())
-                .unwrap_or("?");
-            println!(
-                "  Edge: {} -> {} (label: {:?})",
-                from_label, to_label, edge.label
-            );
+        // Shift+Tab walks the same cycle backwards
+        for expected in visited.iter().rev() {
+            assert_eq!(frame(Some(egui::Modifiers::SHIFT)).0, *expected);
+        }
+        assert!(graph.adding_state.is_some());
+    }
+
+    #[test]
+    fn test_paste_clipboard_text() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"pasted": 1, "list": []}));
+        let root = graph.nodes[0].id;
+        let list = graph.find_node_by_path(&["list".to_string()]).unwrap().id;
+
+        // Object children get a free key
+        graph.clipboard_paste = Some((root, true));
+        assert!(graph.wants_clipboard_text());
+        graph.paste_clipboard_text(Some(r#"{"a": [1]}"#.to_string()));
+        assert!(!graph.wants_clipboard_text());
+        match graph.take_pending_edit() {
+            Some(EditResult {
+                json_path,
+                operation: ModifyOperation::AddJson { key, value },
+            }) => {
+                assert!(json_path.is_empty());
+                assert_eq!(key, "pasted_2");
+                assert_eq!(value, json!({"a": [1]}));
+            }
+            other => panic!("Expected AddJson, got {:?}", other),
         }
 
-        // Expected: 4 nodes (only Objects and Arrays, not primitives)
-        // 0: Object (3) - root (name, version shown in table)
-        // 1: Array [2] - items
-        // 2: Object (2) - items[0] (id, value shown in table)
-        // 3: Object (2) - items[1] (id, value shown in table)
+        // Arrays append; invalid JSON is rejected
+        graph.clipboard_paste = Some((list, false));
+        graph.paste_clipboard_text(Some("[1,".to_string()));
+        assert!(graph.take_pending_edit().is_none());
+        graph.clipboard_paste = Some((list, false));
+        graph.paste_clipboard_text(Some("true".to_string()));
+        assert!(matches!(
+            graph.take_pending_edit(),
+            Some(EditResult { operation: ModifyOperation::AddJson { key, .. }, .. }) if key.is_empty()
+        ));
+
+        // Without clipboard text the paste dialog opens instead
+        graph.clipboard_paste = Some((list, false));
+        graph.paste_clipboard_text(None);
+        assert!(graph.take_pending_edit().is_none());
+        assert!(graph.pasting_state.is_some());
+    }
 
-        assert_eq!(
-            graph.nodes.len(),
-            4,
-            "Expected 4 nodes for default_json structure"
+    #[test]
+    fn test_collapse_hides_descendants() {
+        let value = json!({"a": {"b": {"c": [1]}}, "d": {"e": 1}});
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+
+        let a = vec!["a".to_string()];
+        let node = |graph: &JsonGraph, path: &[&str]| {
+            let path: Vec<String> = path.iter().map(|s| s.to_string()).collect();
+            graph.find_node_by_path(&path).unwrap().clone()
+        };
+
+        // Header toggle collapses the subtree
+        let a_node = node(&graph, &["a"]);
+        let rect = Rect::from_min_size(a_node.position, a_node.size);
+        let toggle = graph.collapse_toggle_rect(rect).center();
+        assert!(matches!(
+            graph.get_click_action(&a_node, rect, toggle),
+            Some(ClickAction::ToggleCollapse)
+        ));
+        // Leaf containers have no toggle
+        let leaf = node(&graph, &["d"]);
+        let leaf_rect = Rect::from_min_size(leaf.position, leaf.size);
+        let leaf_toggle = graph.collapse_toggle_rect(leaf_rect).center();
+        assert!(
+            graph
+                .get_click_action(&leaf, leaf_rect, leaf_toggle)
+                .is_none()
         );
 
-        // Check that root object has name and version in its content
-        let root_node = &graph.nodes[0];
-        if let NodeContent::Object(pairs) = &root_node.content {
-            assert_eq!(pairs.len(), 3); // name, version, items
-            assert!(pairs.iter().any(|p| p.key == "name"));
-            assert!(pairs.iter().any(|p| p.key == "version"));
-        }
+        graph.set_collapsed(&a, true);
+        assert!(!graph.is_hidden(&a_node));
+        assert!(graph.is_hidden(&node(&graph, &["a", "b"])));
+        assert!(graph.is_hidden(&node(&graph, &["a", "b", "c"])));
+        assert!(!graph.is_hidden(&leaf));
+        assert_eq!(graph.descendant_count(&a_node), 2);
+        assert_eq!(graph.descendant_count(&graph.nodes[0].clone()), 4);
+
+        // Counts follow incremental updates
+        graph.update_from_json(&json!({"a": {"b": {"c": [1], "x": {}}}, "d": {"e": 1}}));
+        let a_node = node(&graph, &["a"]);
+        assert_eq!(graph.descendant_count(&a_node), 3);
+        assert!(graph.has_child_nodes(&a_node));
+        assert!(!graph.has_child_nodes(&node(&graph, &["a", "b", "x"])));
+        graph.update_from_json(&value);
+
+        // Survives a rebuild with the same structure, not one that removes the subtree
+        graph.build_from_json(&value);
+        assert!(graph.is_collapsed(&a));
+        graph.build_from_json(&json!({"a": 1, "d": {"e": 1}}));
+        assert!(!graph.is_collapsed(&a));
+
+        // Selecting a hidden node by path expands its ancestors
+        graph.build_from_json(&value);
+        graph.set_collapsed(&a, true);
+        assert!(graph.select_by_path(&["a".to_string(), "b".to_string()]));
+        assert!(!graph.is_collapsed(&a));
+    }
 
-        // Check that items array has 2 objects as child nodes
-        let items_node = graph.nodes.iter().find(|n| n.label.contains("Array"));
-        assert!(items_node.is_some());
+    #[test]
+    fn test_breadcrumb_labels() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"items": [1, 2, {"value": {"x": 1}}]}));
 
-        // Check that item objects have id and value in their content
-        let item_objects: Vec<_> = graph
-            .nodes
+        let path: Vec<String> = ["items", "2", "value"]
             .iter()
-            .filter(|n| n.label.contains("Object") && n.id > 0)
+            .map(|s| s.to_string())
             .collect();
-        assert_eq!(item_objects.len(), 2);
+        assert_eq!(
+            graph.breadcrumb_labels(&path),
+            vec!["root", "items", "[2]", "value"]
+        );
+        assert_eq!(graph.breadcrumb_labels(&[]), vec!["root"]);
+    }
 
-        for item in item_objects {
-            if let NodeContent::Object(pairs) = &item.content {
-                assert_eq!(pairs.len(), 2); // id and value
-                assert!(pairs.iter().any(|p| p.key == "id"));
-                assert!(pairs.iter().any(|p| p.key == "value"));
-            }
-        }
+    #[test]
+    fn test_keyboard_pans_and_zooms_focused_canvas() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"a": 1}));
+
+        let ctx = egui::Context::default();
+        let frame = |graph: &mut JsonGraph, events: Vec<egui::Event>| {
+            let input = egui::RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(1200.0, 800.0))),
+                events,
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    graph.ui(ui);
+                });
+            });
+        };
+        let key = |key: egui::Key, pressed: bool| egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        };
+        let click = |pressed: bool| egui::Event::PointerButton {
+            pos: Pos2::new(700.0, 450.0),
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+
+        // Keys do nothing until the canvas is clicked
+        frame(&mut graph, vec![key(egui::Key::ArrowRight, true)]);
+        frame(&mut graph, vec![key(egui::Key::ArrowRight, false)]);
+        assert_eq!(graph.offset, Vec2::ZERO);
+
+        frame(
+            &mut graph,
+            vec![egui::Event::PointerMoved(Pos2::new(700.0, 450.0))],
+        );
+        frame(&mut graph, vec![click(true)]);
+        frame(&mut graph, vec![click(false)]);
+
+        // Held keys pan every frame
+        frame(&mut graph, vec![key(egui::Key::D, true)]);
+        frame(&mut graph, vec![]);
+        frame(&mut graph, vec![key(egui::Key::D, false)]);
+        assert_eq!(graph.offset, Vec2::new(-2.0 * KEYBOARD_PAN_STEP, 0.0));
+
+        // + zooms around the canvas center; 0 resets the zoom
+        let center = graph.canvas_size / 2.0;
+        let world_center = (center - graph.offset) / graph.zoom;
+        frame(&mut graph, vec![key(egui::Key::Plus, true)]);
+        assert!((graph.zoom - KEYBOARD_ZOOM_STEP).abs() < 1e-6);
+        assert!((world_center * graph.zoom + graph.offset - center).length() < 1e-3);
+        frame(
+            &mut graph,
+            vec![key(egui::Key::Plus, false), key(egui::Key::Num0, true)],
+        );
+        assert_eq!(graph.zoom, 1.0);
+        frame(&mut graph, vec![key(egui::Key::Num0, false)]);
+
+        // An open dialog keeps the keys
+        let offset = graph.offset;
+        graph.adding_state = Some(AddingState {
+            node_id: graph.nodes[0].id,
+            is_object: true,
+            key: String::new(),
+            value: String::new(),
+            value_type: NodeType::String,
+            index: None,
+        });
+        frame(&mut graph, vec![key(egui::Key::ArrowLeft, true)]);
+        frame(&mut graph, vec![]);
+        assert_eq!(graph.offset, offset);
+    }
+
+    #[test]
+    fn test_center_on_node_keeps_zoom() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"a": {"b": 1}, "c": [1, 2]}));
+        graph.zoom = 0.5;
+        let canvas_rect = Rect::from_min_size(Pos2::new(50.0, 20.0), Vec2::new(800.0, 600.0));
+        graph.canvas_size = canvas_rect.size();
+
+        let node = graph.find_node_by_path(&["c".to_string()]).unwrap().clone();
+        assert!(graph.center_on_node(node.id));
+        let center = graph.transform_pos(node.position + node.size / 2.0, canvas_rect);
+        assert!((center - canvas_rect.center()).length() < 0.001);
+        assert_eq!(graph.zoom, 0.5);
+
+        assert!(!graph.center_on_node(usize::MAX));
+    }
+
+    #[test]
+    fn test_diff_highlights() {
+        use crate::json_editor::diff;
+
+        let mut graph = JsonGraph::new();
+        let old = json!({"a": 1, "b": {"x": 1, "y": 2}, "c": [1]});
+        let new = json!({"a": 2, "b": {"x": 1}, "c": [1, 2]});
+        graph.build_from_json(&new);
+        graph.set_diff_highlights(&diff(&old, &new));
+
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+        assert_eq!(graph.diff_highlight(&path(&["a"])), Some(Color32::YELLOW));
+        assert_eq!(
+            graph.diff_highlight(&path(&["c", "1"])),
+            Some(Color32::from_rgb(100, 200, 100))
+        );
+        // Removed "b.y" highlights its parent node instead
+        assert_eq!(
+            graph.diff_highlight(&path(&["b"])),
+            Some(Color32::from_rgb(230, 100, 100))
+        );
+        assert_eq!(graph.diff_highlight(&path(&["b", "x"])), None);
+
+        graph.set_diff_highlights(&[]);
+        assert_eq!(graph.diff_highlight(&path(&["a"])), None);
+    }
+
+    #[test]
+    fn test_node_type_colors() {
+        assert_ne!(NodeType::Object.color(), NodeType::Array.color());
+        assert_ne!(NodeType::String.color(), NodeType::Number.color());
+        assert_eq!(NodeType::Object.fill_color(true), NodeType::Object.color());
+        assert_eq!(
+            NodeType::Object.fill_color(false),
+            Color32::from_rgb(75, 112, 150)
+        );
     }
 }
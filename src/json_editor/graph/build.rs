@@ -0,0 +1,1030 @@
+//! Building graph nodes from a JSON value and laying them out
+
+use egui::{Color32, Pos2, Vec2};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use super::super::MAX_DEPTH;
+use super::{
+    ArrayItem, GraphEdge, GraphNode, JsonGraph, KeyValuePair, LayoutDirection, NodeContent,
+    NodeType, ROW_HEIGHT, TABLE_NODE_WIDTH, VALUE_FONT_SIZE, index_label, value_column_width,
+};
+
+/// Gap between depth levels of the layout (room for edge labels)
+const LEVEL_GAP: f32 = 100.0;
+/// Gap between neighboring subtrees of the layout
+const SIBLING_GAP: f32 = 50.0;
+/// Position of the layout's top-left corner
+const LAYOUT_ORIGIN: Pos2 = Pos2::new(100.0, 50.0);
+
+/// Label of the placeholder node added at `MAX_DEPTH`
+const DEPTH_LIMIT_LABEL: &str = "… (depth limit reached)";
+
+/// Quote a string for display, truncating it to `max_chars` characters
+/// Cuts on a char boundary so multi-byte UTF-8 text never panics
+fn truncate_preview(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((byte_index, _)) => format!("\"{}…\"", &s[..byte_index]),
+        None => format!("\"{}\"", s),
+    }
+}
+
+/// Nodes and edges of the previous build, reused by `update_from_json`
+#[derive(Default)]
+struct BuildCache {
+    /// Nodes by JSON path (their `json_path` is moved into the key)
+    nodes: HashMap<Vec<String>, GraphNode>,
+    /// Edges by child node ID
+    edges: HashMap<usize, GraphEdge>,
+}
+
+/// Feed the parts of a value that show up in its parent's table row into a hasher
+/// Child containers only contribute their kind and length
+fn hash_shallow(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Object(map) => (0u8, map.len()).hash(hasher),
+        Value::Array(arr) => (1u8, arr.len()).hash(hasher),
+        Value::String(s) => (2u8, s).hash(hasher),
+        Value::Number(n) => (3u8, n.as_str()).hash(hasher),
+        Value::Bool(b) => (4u8, b).hash(hasher),
+        Value::Null => 5u8.hash(hasher),
+    }
+}
+
+impl JsonGraph {
+    /// Build graph from JSON value
+    /// Starts from scratch: clears the selection and cancels any in-progress interaction
+    pub fn build_from_json(&mut self, value: &Value) {
+        self.clear();
+
+        if value.is_null() {
+            return;
+        }
+
+        self.build_tree(value, &mut BuildCache::default());
+        self.log_to_console(&format!("Built graph with {} nodes", self.nodes.len()));
+    }
+
+    /// Update the graph after the document changed, rebuilding only what changed
+    /// Node IDs are keyed on JSON path, so the selection survives as long as its path
+    /// exists; nodes whose content didn't change are reused as they are
+    pub fn update_from_json(&mut self, value: &Value) {
+        if value.is_null() || self.nodes.is_empty() {
+            self.build_from_json(value);
+            return;
+        }
+
+        let mut cache = BuildCache {
+            nodes: self
+                .nodes
+                .drain(..)
+                .map(|mut node| (std::mem::take(&mut node.json_path), node))
+                .collect(),
+            edges: self.edges.drain(..).map(|edge| (edge.to, edge)).collect(),
+        };
+        self.build_tree(value, &mut cache);
+
+        let exists = |id: &usize| self.nodes.iter().any(|n| n.id == *id);
+        self.selected_node = self.selected_node.filter(exists);
+        self.approximate_match = self.approximate_match.filter(exists);
+        // Scrolled tables keep their position, within the rows they have now
+        let max_offsets: HashMap<usize, usize> = self
+            .nodes
+            .iter()
+            .map(|n| (n.id, n.max_scroll_offset()))
+            .collect();
+        self.scroll_offsets
+            .retain(|id, offset| match max_offsets.get(id) {
+                Some(&max) => {
+                    *offset = (*offset).min(max);
+                    *offset > 0
+                }
+                None => false,
+            });
+        // Rows of in-progress edits and menus may have moved
+        self.cancel_interactions();
+    }
+
+    /// Number of nodes `build_from_json` would create for a value
+    /// Every Object/Array is a node (a primitive root is a single node); counted without recursion
+    pub fn estimate_node_count(value: &Value) -> usize {
+        if value.is_null() {
+            return 0;
+        }
+        if !value.is_object() && !value.is_array() {
+            return 1;
+        }
+        let mut count = 0;
+        let mut stack = vec![value];
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::Object(map) => {
+                    count += 1;
+                    stack.extend(map.values());
+                }
+                Value::Array(arr) => {
+                    count += 1;
+                    stack.extend(arr.iter());
+                }
+                _ => {}
+            }
+        }
+        count
+    }
+
+    /// The complete text of a string whose preview is truncated at `preview_len` chars
+    fn full_value_if_truncated(value: &Value, preview_len: usize) -> Option<String> {
+        match value {
+            Value::String(s) if s.chars().nth(preview_len).is_some() => {
+                Some(Value::String(s.clone()).to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Lay out the nodes for a value, reusing unchanged nodes from `cache`
+    fn build_tree(&mut self, value: &Value, cache: &mut BuildCache) {
+        self.build_node(value, None, 0, &mut Vec::new(), cache);
+        self.index_children();
+        self.layout_tree();
+
+        // Collapsed subtrees survive rebuilds as long as their node still has children
+        let container_paths: HashSet<&Vec<String>> = self
+            .nodes
+            .iter()
+            .filter(|n| self.children.contains_key(&n.id))
+            .map(|n| &n.json_path)
+            .collect();
+        self.collapsed_paths
+            .retain(|path| container_paths.contains(path));
+    }
+
+    /// Hash of everything a node's own content is built from: the keys and primitive
+    /// values of its value, the shape of its child containers and the display settings
+    fn content_signature(&self, value: &Value, depth_limited: bool) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            depth_limited,
+            self.preview_len,
+            self.max_visible_rows,
+            self.wrap_values,
+            self.one_based_indices,
+        )
+            .hash(&mut hasher);
+        match value {
+            Value::Object(map) => {
+                (0u8, map.len()).hash(&mut hasher);
+                for (key, val) in map {
+                    key.hash(&mut hasher);
+                    hash_shallow(val, &mut hasher);
+                }
+            }
+            Value::Array(arr) => {
+                (1u8, arr.len()).hash(&mut hasher);
+                for val in arr {
+                    hash_shallow(val, &mut hasher);
+                }
+            }
+            _ => hash_shallow(value, &mut hasher),
+        }
+        hasher.finish()
+    }
+
+    /// Recursively build nodes from JSON value
+    /// `parent` is the parent node's ID and whether it is an Array; `json_path` is the
+    /// path of this value and is restored before returning
+    /// Nodes in `cache` keep their ID, and are reused if their content is unchanged
+    /// Nodes are added in pre-order (parents before children) and positioned later
+    /// by `layout_tree`
+    /// Below `MAX_DEPTH` a single placeholder node is added instead, so
+    /// pathologically deep documents can't overflow the stack
+    fn build_node(
+        &mut self,
+        value: &Value,
+        parent: Option<(usize, bool)>,
+        depth: usize,
+        json_path: &mut Vec<String>,
+        cache: &mut BuildCache,
+    ) {
+        let depth_limited = depth >= MAX_DEPTH;
+        let signature = self.content_signature(value, depth_limited);
+        let (node_id, reused) = match cache.nodes.remove_entry(json_path.as_slice()) {
+            Some((path, node)) if node.signature == signature => (
+                node.id,
+                Some(GraphNode {
+                    json_path: path,
+                    ..node
+                }),
+            ),
+            Some((_, node)) => (node.id, None),
+            None => {
+                let id = self.next_id;
+                self.next_id += 1;
+                (id, None)
+            }
+        };
+        let is_reused = reused.is_some();
+        let node = reused.unwrap_or_else(|| {
+            self.new_node(node_id, value, depth_limited, json_path.clone(), signature)
+        });
+        self.nodes.push(node);
+
+        // Create edge from parent (or keep the old one if its label still fits)
+        if let Some((parent_id, in_array)) = parent {
+            let segment = json_path.last().map_or("", String::as_str);
+            let edge = match cache.edges.remove(&node_id) {
+                Some(edge)
+                    if is_reused
+                        && edge.from == parent_id
+                        && edge.label.as_deref().is_some_and(|label| {
+                            if in_array {
+                                label.starts_with('[')
+                            } else {
+                                label == segment
+                            }
+                        }) =>
+                {
+                    edge
+                }
+                _ => GraphEdge {
+                    from: parent_id,
+                    to: node_id,
+                    label: Some(if in_array {
+                        index_label(segment.parse().unwrap_or(0), self.one_based_indices)
+                    } else {
+                        segment.to_string()
+                    }),
+                },
+            };
+            self.edges.push(edge);
+        }
+
+        // Only create child nodes for Object and Array values (not primitives)
+        match value {
+            _ if depth_limited => {}
+            Value::Object(map) => {
+                for (key, child_value) in map {
+                    // Only create child nodes for Object and Array types
+                    if child_value.is_object() || child_value.is_array() {
+                        json_path.push(key.clone());
+                        self.build_node(
+                            child_value,
+                            Some((node_id, false)),
+                            depth + 1,
+                            json_path,
+                            cache,
+                        );
+                        json_path.pop();
+                    }
+                    // Primitive values are already displayed in the table
+                }
+            }
+            Value::Array(arr) => {
+                for (idx, child_value) in arr.iter().enumerate() {
+                    // Only create child nodes for Object and Array types
+                    if child_value.is_object() || child_value.is_array() {
+                        json_path.push(idx.to_string());
+                        self.build_node(
+                            child_value,
+                            Some((node_id, true)),
+                            depth + 1,
+                            json_path,
+                            cache,
+                        );
+                        json_path.pop();
+                    }
+                    // Primitive values are already displayed in the table
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Position the nodes as a tidy tree (two passes over the pre-order node list)
+    /// Bottom-up, each subtree gets a span along the sibling axis that fits both its
+    /// node and its children's spans; top-down, children are placed side by side in
+    /// their parent's span with the parent centered over them. Each depth level is
+    /// as deep as its largest node, so neither siblings nor levels overlap
+    fn layout_tree(&mut self) {
+        let count = self.nodes.len();
+        let index: HashMap<usize, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect();
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for edge in &self.edges {
+            if let (Some(&parent), Some(&child)) = (index.get(&edge.from), index.get(&edge.to)) {
+                children[parent].push(child);
+            }
+        }
+
+        // Node size as (along the sibling axis, along the depth axis)
+        let left_right = self.layout_direction == LayoutDirection::LeftRight;
+        let axes = |size: Vec2| {
+            if left_right {
+                (size.y, size.x)
+            } else {
+                (size.x, size.y)
+            }
+        };
+
+        // Parents come before their children, so one forward pass finds every depth
+        let mut depth = vec![0; count];
+        for parent in 0..count {
+            for &child in &children[parent] {
+                depth[child] = depth[parent] + 1;
+            }
+        }
+
+        // Bottom-up: span of each subtree and of its children side by side
+        let mut span = vec![0.0; count];
+        let mut children_span = vec![0.0; count];
+        for i in (0..count).rev() {
+            let gaps = children[i].len().saturating_sub(1) as f32 * SIBLING_GAP;
+            children_span[i] = children[i].iter().map(|&c| span[c]).sum::<f32>() + gaps;
+            span[i] = axes(self.nodes[i].size).0.max(children_span[i]);
+        }
+
+        // Start of each depth level along the depth axis
+        let levels = depth.iter().max().map_or(0, |max| max + 1);
+        let mut level_depth = vec![0.0f32; levels];
+        for (node, &d) in self.nodes.iter().zip(&depth) {
+            level_depth[d] = level_depth[d].max(axes(node.size).1);
+        }
+        let level_start: Vec<f32> = level_depth
+            .iter()
+            .scan(0.0, |start, size| {
+                let current = *start;
+                *start += size + LEVEL_GAP;
+                Some(current)
+            })
+            .collect();
+
+        // Top-down: place each node centered in its span and its children below it
+        let mut span_start = vec![0.0; count];
+        for i in 0..count {
+            let mut child_start = span_start[i] + (span[i] - children_span[i]) / 2.0;
+            for &child in &children[i] {
+                span_start[child] = child_start;
+                child_start += span[child] + SIBLING_GAP;
+            }
+
+            let along = span_start[i] + (span[i] - axes(self.nodes[i].size).0) / 2.0;
+            let level = level_start[depth[i]];
+            self.nodes[i].position = if left_right {
+                LAYOUT_ORIGIN + Vec2::new(level, along)
+            } else {
+                LAYOUT_ORIGIN + Vec2::new(along, level)
+            };
+        }
+    }
+
+    /// Build a node's label, table content and size (positioned by `build_node`)
+    fn new_node(
+        &self,
+        id: usize,
+        value: &Value,
+        depth_limited: bool,
+        json_path: Vec<String>,
+        signature: u64,
+    ) -> GraphNode {
+        let (label, node_type, content) = match value {
+            _ if depth_limited => (
+                DEPTH_LIMIT_LABEL.to_string(),
+                NodeType::Null,
+                NodeContent::Primitive(DEPTH_LIMIT_LABEL.to_string()),
+            ),
+            Value::Object(map) => {
+                let label = format!("Object ({})", map.len());
+                let mut pairs = Vec::new();
+
+                for (key, val) in map {
+                    let (value_display, value_type, is_reference) = match val {
+                        Value::Object(m) => (format!("{{ {} }}", m.len()), NodeType::Object, true),
+                        Value::Array(a) => (format!("[ {} ]", a.len()), NodeType::Array, true),
+                        Value::String(s) => (
+                            truncate_preview(s, self.preview_len),
+                            NodeType::String,
+                            false,
+                        ),
+                        Value::Number(n) => (n.to_string(), NodeType::Number, false),
+                        Value::Bool(b) => (b.to_string(), NodeType::Boolean, false),
+                        Value::Null => ("null".to_string(), NodeType::Null, false),
+                    };
+
+                    pairs.push(KeyValuePair {
+                        key: key.clone(),
+                        value_display,
+                        full_value: Self::full_value_if_truncated(val, self.preview_len),
+                        value_type,
+                        is_reference,
+                    });
+                }
+
+                (label, NodeType::Object, NodeContent::Object(pairs))
+            }
+            Value::Array(arr) => {
+                let label = format!("Array [{}]", arr.len());
+                let mut items = Vec::new();
+
+                for (index, val) in arr.iter().enumerate() {
+                    let (value_display, value_type, is_reference) = match val {
+                        Value::Object(m) => (format!("{{ {} }}", m.len()), NodeType::Object, true),
+                        Value::Array(a) => (format!("[ {} ]", a.len()), NodeType::Array, true),
+                        Value::String(s) => (
+                            truncate_preview(s, self.preview_len),
+                            NodeType::String,
+                            false,
+                        ),
+                        Value::Number(n) => (n.to_string(), NodeType::Number, false),
+                        Value::Bool(b) => (b.to_string(), NodeType::Boolean, false),
+                        Value::Null => ("null".to_string(), NodeType::Null, false),
+                    };
+
+                    items.push(ArrayItem {
+                        index,
+                        value_display,
+                        full_value: Self::full_value_if_truncated(val, self.preview_len),
+                        value_type,
+                        is_reference,
+                    });
+                }
+
+                (label, NodeType::Array, NodeContent::Array(items))
+            }
+            Value::String(s) => {
+                let display = truncate_preview(s, self.preview_len);
+                (
+                    display.clone(),
+                    NodeType::String,
+                    NodeContent::Primitive(display),
+                )
+            }
+            Value::Number(n) => {
+                let display = n.to_string();
+                (
+                    display.clone(),
+                    NodeType::Number,
+                    NodeContent::Primitive(display),
+                )
+            }
+            Value::Bool(b) => {
+                let display = b.to_string();
+                (
+                    display.clone(),
+                    NodeType::Boolean,
+                    NodeContent::Primitive(display),
+                )
+            }
+            Value::Null => (
+                "null".to_string(),
+                NodeType::Null,
+                NodeContent::Primitive("null".to_string()),
+            ),
+        };
+
+        // Calculate node size based on content
+        let row_heights = self.calculate_row_heights(&content);
+        let size = self.calculate_node_size(&content, &row_heights);
+        let search_text = GraphNode::search_text(&json_path, &label, &content);
+
+        GraphNode {
+            id,
+            label,
+            node_type,
+            position: Pos2::ZERO,
+            size,
+            json_path,
+            content,
+            row_heights,
+            rows_measured: !self.wrap_values,
+            search_text,
+            signature,
+        }
+    }
+
+    /// Single-line heights of the visible table rows
+    /// `measure_wrapped_rows` grows them to fit wrapped values once fonts are available
+    fn calculate_row_heights(&self, content: &NodeContent) -> Vec<f32> {
+        // Limit height for very large objects
+        let row_count = match content {
+            NodeContent::Object(pairs) => pairs.len(),
+            NodeContent::Array(items) => items.len(),
+            NodeContent::Primitive(_) => 0,
+        };
+        vec![ROW_HEIGHT; row_count.min(self.max_visible_rows)]
+    }
+
+    /// Whether a node's values are wrapped over several lines
+    /// Scrollable tables keep single-line rows, so any scrolled-in row fits its slot
+    pub(super) fn wraps_rows(&self, node: &GraphNode) -> bool {
+        self.wrap_values && node.row_count() <= node.row_heights.len()
+    }
+
+    /// Fit the rows of nodes built since the last frame to their wrapped values
+    /// Values are laid out in full with the UI's fonts at the value column width;
+    /// the tree is laid out again if any node changed size
+    pub(super) fn measure_wrapped_rows(&mut self, ctx: &egui::Context) {
+        if self.nodes.iter().all(|node| node.rows_measured) {
+            return;
+        }
+        let font_id = egui::FontId::monospace(VALUE_FONT_SIZE);
+        let measured: Vec<(usize, Vec<f32>)> = ctx.fonts_mut(|fonts| {
+            let line_height = fonts.row_height(&font_id);
+            self.nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| !node.rows_measured && self.wraps_rows(node))
+                .map(|(index, node)| {
+                    let wrap_width =
+                        value_column_width(matches!(node.content, NodeContent::Object(_)));
+                    let heights = node
+                        .visible_values()
+                        .into_iter()
+                        .map(|(text, is_reference)| {
+                            if is_reference {
+                                return ROW_HEIGHT;
+                            }
+                            let galley = fonts.layout(
+                                text.to_string(),
+                                font_id.clone(),
+                                Color32::WHITE,
+                                wrap_width,
+                            );
+                            let extra_lines = galley.rows.len().saturating_sub(1);
+                            ROW_HEIGHT + extra_lines as f32 * line_height
+                        })
+                        .collect();
+                    (index, heights)
+                })
+                .collect()
+        });
+
+        let mut resized = false;
+        for (index, heights) in measured {
+            if self.nodes[index].row_heights != heights {
+                let size = self.calculate_node_size(&self.nodes[index].content, &heights);
+                let node = &mut self.nodes[index];
+                node.row_heights = heights;
+                node.size = size;
+                resized = true;
+            }
+        }
+        for node in &mut self.nodes {
+            node.rows_measured = true;
+        }
+        if resized {
+            self.layout_tree();
+        }
+    }
+
+    /// Calculate node size based on content and row heights
+    fn calculate_node_size(&self, content: &NodeContent, row_heights: &[f32]) -> Vec2 {
+        match content {
+            NodeContent::Object(_) | NodeContent::Array(_) => {
+                // Height: header + rows + padding
+                let header_height = 25.0;
+                let padding = 10.0;
+                let height = header_height + row_heights.iter().sum::<f32>() + padding;
+                Vec2::new(TABLE_NODE_WIDTH, height.max(60.0))
+            }
+            NodeContent::Primitive(_) => {
+                // Small fixed size for primitive values
+                Vec2::new(120.0, 40.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_simple_object() {
+        let mut graph = JsonGraph::new();
+        let json = json!({"key": "value"});
+        graph.build_from_json(&json);
+
+        // Only 1 node: the root object (primitive values shown in table)
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.edges.len(), 0); // No edges since no child nodes
+
+        // Check that the object has the key in its content
+        if let NodeContent::Object(pairs) = &graph.nodes[0].content {
+            assert_eq!(pairs.len(), 1);
+            assert_eq!(pairs[0].key, "key");
+            assert_eq!(pairs[0].value_display, "\"value\"");
+        } else {
+            panic!("Expected Object content");
+        }
+    }
+
+    #[test]
+    fn test_build_array() {
+        let mut graph = JsonGraph::new();
+        let json = json!([1, 2, 3]);
+        graph.build_from_json(&json);
+
+        // Only 1 node: the array (primitive values shown in table)
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.edges.len(), 0); // No edges since no child nodes
+
+        // Check that the array has 3 items in its content
+        if let NodeContent::Array(items) = &graph.nodes[0].content {
+            assert_eq!(items.len(), 3);
+            assert_eq!(items[0].value_display, "1");
+            assert_eq!(items[1].value_display, "2");
+            assert_eq!(items[2].value_display, "3");
+        } else {
+            panic!("Expected Array content");
+        }
+    }
+
+    #[test]
+    fn test_build_nested() {
+        let mut graph = JsonGraph::new();
+        let json = json!({
+            "user": {
+                "name": "Alice",
+                "age": 30
+            }
+        });
+        graph.build_from_json(&json);
+
+        // 2 nodes: root object + user object (name and age are shown in user's table)
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1); // One edge from root to user
+
+        // Check that user object has name and age in its content
+        // Find the second object node (id > 0)
+        let user_node = graph
+            .nodes
+            .iter()
+            .find(|n| n.id > 0 && n.label.contains("Object"));
+        assert!(user_node.is_some());
+        if let NodeContent::Object(pairs) = &user_node.unwrap().content {
+            assert_eq!(pairs.len(), 2);
+            assert!(pairs.iter().any(|p| p.key == "name"));
+            assert!(pairs.iter().any(|p| p.key == "age"));
+        }
+    }
+
+    #[test]
+    fn test_preview_len_truncates_on_char_boundary() {
+        let mut graph = JsonGraph::new();
+        graph.set_preview_len(10);
+        graph.build_from_json(&json!({"greeting": "안녕하세요 세계, 你好世界 hello"}));
+
+        if let NodeContent::Object(pairs) = &graph.nodes[0].content {
+            assert_eq!(pairs[0].value_display, "\"안녕하세요 세계, …\"");
+        } else {
+            panic!("Expected Object content");
+        }
+
+        // Short strings are not truncated
+        assert_eq!(truncate_preview("abc", 10), "\"abc\"");
+    }
+
+    #[test]
+    fn test_cjk_string_preview_does_not_panic() {
+        // 40 three-byte characters: byte offsets 30 and 20 fall mid-codepoint
+        let long: String = "日本語の文字列".chars().cycle().take(40).collect();
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"text": long, "items": [long]}));
+
+        let expected = format!("\"{}…\"", long.chars().take(30).collect::<String>());
+        if let NodeContent::Object(pairs) = &graph.nodes[0].content {
+            let text = pairs.iter().find(|p| p.key == "text").unwrap();
+            assert_eq!(text.value_display, expected);
+        } else {
+            panic!("Expected Object content");
+        }
+
+        // Primitive root node
+        graph.build_from_json(&json!(long));
+        assert_eq!(graph.nodes[0].label, expected);
+    }
+
+    #[test]
+    fn test_child_nodes_follow_source_order() {
+        let mut graph = JsonGraph::new();
+        let value: Value = serde_json::from_str(
+            r#"{"zeta": {"a": 1}, "name": "x", "alpha": [1, 2], "count": 3, "mid": {"b": 2}}"#,
+        )
+        .unwrap();
+        graph.build_from_json(&value);
+
+        // Table rows keep the key order of the source
+        if let NodeContent::Object(pairs) = &graph.nodes[0].content {
+            let keys: Vec<&str> = pairs.iter().map(|p| p.key.as_str()).collect();
+            assert_eq!(keys, vec!["zeta", "name", "alpha", "count", "mid"]);
+        } else {
+            panic!("Expected Object content");
+        }
+
+        // Child nodes are laid out left-to-right in the same order
+        let x_of = |key: &str| {
+            graph
+                .nodes
+                .iter()
+                .find(|n| n.json_path == vec![key.to_string()])
+                .map(|n| n.position.x)
+                .unwrap()
+        };
+        assert!(x_of("zeta") < x_of("alpha"));
+        assert!(x_of("alpha") < x_of("mid"));
+    }
+
+    #[test]
+    fn test_layout_has_no_overlapping_nodes() {
+        use crate::json_editor::JsonEditor;
+
+        // The sample document, plus a wide subtree next to narrow ones and a node
+        // taller than the old fixed 200px level spacing
+        let sample = JsonEditor::default().parsed_value_owned().unwrap();
+        let uneven = json!({
+            "wide": [{"a": {"x": 1}, "b": {"y": 2}, "c": {"z": 3}}],
+            "narrow": {"tall": {
+                "k1": {"deep": 1}, "k2": 2, "k3": 3, "k4": 4, "k5": 5,
+                "k6": 6, "k7": 7, "k8": 8, "k9": 9
+            }},
+            "items": [{"id": 1}, {"id": 2}, [3, [4]]]
+        });
+        for direction in [LayoutDirection::TopDown, LayoutDirection::LeftRight] {
+            for value in [&sample, &uneven] {
+                let mut graph = JsonGraph::new();
+                graph.set_layout_direction(direction);
+                graph.build_from_json(value);
+
+                let rects: Vec<Rect> = graph
+                    .nodes
+                    .iter()
+                    .map(|n| Rect::from_min_size(n.position, n.size))
+                    .collect();
+                for (i, a) in rects.iter().enumerate() {
+                    for b in &rects[i + 1..] {
+                        assert!(
+                            !a.intersects(*b),
+                            "{:?}: {:?} overlaps {:?}",
+                            direction,
+                            a,
+                            b
+                        );
+                    }
+                }
+
+                // Parents are centered over their children
+                let items = graph.find_node_by_path(&["items".to_string()]).unwrap();
+                let siblings: Vec<&GraphNode> = graph
+                    .edges
+                    .iter()
+                    .filter(|e| e.from == items.id)
+                    .map(|e| graph.nodes.iter().find(|n| n.id == e.to).unwrap())
+                    .collect();
+                let along = |p: Pos2| {
+                    if direction == LayoutDirection::TopDown {
+                        p.x
+                    } else {
+                        p.y
+                    }
+                };
+                let first = siblings.first().unwrap().position;
+                let last = siblings.last().unwrap();
+                let children_center = (along(first) + along(last.position + last.size)) / 2.0;
+                assert!((along(items.position + items.size / 2.0) - children_center).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn test_left_right_layout() {
+        let value = json!({"a": {"x": 1}, "b": [{"y": 2}, {"z": 3}], "c": {"w": 4}});
+        let mut graph = JsonGraph::new();
+        graph.set_layout_direction(LayoutDirection::LeftRight);
+        graph.build_from_json(&value);
+        let node = |graph: &JsonGraph, path: &[&str]| {
+            let path: Vec<String> = path.iter().map(|s| s.to_string()).collect();
+            graph.find_node_by_path(&path).unwrap().clone()
+        };
+
+        // Children extend to the right, one column per depth
+        let (root, a, b, c) = (
+            node(&graph, &[]),
+            node(&graph, &["a"]),
+            node(&graph, &["b"]),
+            node(&graph, &["c"]),
+        );
+        let b0 = node(&graph, &["b", "0"]);
+        assert!(a.position.x > root.position.x + root.size.x);
+        assert!(b0.position.x > b.position.x + b.size.x);
+        assert_eq!([a.position.x, b.position.x], [c.position.x; 2]);
+
+        // Siblings stack vertically without overlapping
+        let stacked = [&a, &b, &c];
+        for pair in stacked.windows(2) {
+            assert!(pair[1].position.y >= pair[0].position.y + pair[0].size.y);
+        }
+
+        // Edges run from the parent's right side to the child's left side
+        let (from, to) = graph.edge_anchors(&root, &a);
+        assert_eq!(
+            from,
+            root.position + Vec2::new(root.size.x, root.size.y / 2.0)
+        );
+        assert_eq!(to, a.position + Vec2::new(0.0, a.size.y / 2.0));
+
+        // Top-down is unchanged
+        graph.set_layout_direction(LayoutDirection::TopDown);
+        graph.build_from_json(&value);
+        let (root, a, c) = (
+            node(&graph, &[]),
+            node(&graph, &["a"]),
+            node(&graph, &["c"]),
+        );
+        assert_eq!(a.position.y, root.position.y + root.size.y + LEVEL_GAP);
+        assert_eq!(a.position.y, c.position.y);
+        assert!(c.position.x > a.position.x);
+    }
+
+    #[test]
+    fn test_update_from_json_keeps_unchanged_nodes() {
+        let path = |segments: &[&str]| segments.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let id_of = |graph: &JsonGraph, segments: &[&str]| {
+            graph.find_node_by_path(&path(segments)).map(|n| n.id)
+        };
+
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"a": {"x": 1}, "b": [{"y": 2}], "c": 3}));
+        assert!(graph.select_by_path(&path(&["b", "0"])));
+        let selected = graph.selected_node;
+        let b0_label = graph
+            .find_node_by_path(&path(&["b", "0"]))
+            .unwrap()
+            .label
+            .as_ptr();
+        let ids: Vec<_> = graph
+            .nodes
+            .iter()
+            .map(|n| (n.json_path.clone(), n.id))
+            .collect();
+
+        // Changing a value only rebuilds the nodes showing it
+        graph.update_from_json(&json!({"a": {"x": 5}, "b": [{"y": 2}], "c": 4}));
+        for (node_path, id) in &ids {
+            assert_eq!(graph.find_node_by_path(node_path).map(|n| n.id), Some(*id));
+        }
+        assert_eq!(graph.selected_node, selected);
+        let b0 = graph.find_node_by_path(&path(&["b", "0"])).unwrap();
+        assert_eq!(b0.label.as_ptr(), b0_label);
+        let a = graph.find_node_by_path(&path(&["a"])).unwrap();
+        assert!(matches!(&a.content, NodeContent::Object(pairs) if pairs[0].value_display == "5"));
+        assert_eq!(graph.edges.len(), 3);
+
+        // Removed paths drop their node (and the selection); new paths get fresh IDs
+        graph.update_from_json(&json!({"a": {"x": 5}, "d": {}}));
+        assert_eq!(id_of(&graph, &["b"]), None);
+        assert_eq!(graph.selected_node, None);
+        let max_old_id = ids.iter().map(|(_, id)| *id).max().unwrap();
+        assert!(id_of(&graph, &["d"]).unwrap() > max_old_id);
+        assert_eq!(
+            id_of(&graph, &["a"]),
+            ids.iter()
+                .find(|(p, _)| p == &path(&["a"]))
+                .map(|(_, id)| *id)
+        );
+
+        // Display settings invalidate reused nodes and edge labels
+        graph.update_from_json(&json!([[1], [2]]));
+        graph.set_one_based_indices(true);
+        graph.update_from_json(&json!([[1], [2]]));
+        let labels: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| e.label.clone().unwrap())
+            .collect();
+        assert_eq!(labels, vec!["[1]", "[2]"]);
+    }
+
+    #[test]
+    fn test_estimate_node_count_matches_build() {
+        let value = json!({"a": {"b": [1, {"c": null}]}, "d": [], "e": "x"});
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+        assert_eq!(JsonGraph::estimate_node_count(&value), graph.node_count());
+        assert_eq!(JsonGraph::estimate_node_count(&json!(null)), 0);
+
+        graph.clear();
+        assert_eq!(graph.node_count(), 0);
+    }
+
+    #[test]
+    fn test_deep_nesting_is_capped() {
+        let mut value = json!(1);
+        for _ in 0..10_000 {
+            value = Value::Array(vec![value]);
+        }
+
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+
+        // One node per level up to the limit, plus the placeholder
+        assert_eq!(graph.node_count(), MAX_DEPTH + 1);
+        let placeholder = graph.nodes.last().unwrap();
+        assert_eq!(placeholder.label, DEPTH_LIMIT_LABEL);
+        assert_eq!(placeholder.json_path.len(), MAX_DEPTH);
+
+        // Unwrap iteratively so dropping the value doesn't recurse 10,000 levels
+        while let Value::Array(mut items) = value {
+            value = items.pop().unwrap_or(Value::Null);
+        }
+    }
+
+    #[test]
+    fn test_build_default_json() {
+        let mut graph = JsonGraph::new();
+        let json = json!({
+            "name": "example",
+            "version": "1.0.0",
+            "items": [
+                {"id": 1, "value": "first"},
+                {"id": 2, "value": "second"}
+            ]
+        });
+        graph.build_from_json(&json);
+
+        println!("\nAll nodes created:");
+        for node in &graph.nodes {
+            println!(
+                "  Node {}: {} at pos ({}, {})",
+                node.id, node.label, node.position.x, node.position.y
+            );
+        }
+
+        println!("\nAll edges:");
+        for edge in &graph.edges {
+            let from_label = graph
+                .nodes
+                .iter()
+                .find(|n| n.id == edge.from)
+                .map(|n| n.label.as_str())
+                .unwrap_or("?");
+            let to_label = graph
+                .nodes
+                .iter()
+                .find(|n| n.id == edge.to)
+                .map(|n| n.label.as_str())
+                .unwrap_or("?");
+            println!(
+                "  Edge: {} -> {} (label: {:?})",
+                from_label, to_label, edge.label
+            );
+        }
+
+        // Expected: 4 nodes (only Objects and Arrays, not primitives)
+        // 0: Object (3) - root (name, version shown in table)
+        // 1: Array [2] - items
+        // 2: Object (2) - items[0] (id, value shown in table)
+        // 3: Object (2) - items[1] (id, value shown in table)
+
+        assert_eq!(
+            graph.nodes.len(),
+            4,
+            "Expected 4 nodes for default_json structure"
+        );
+
+        // Check that root object has name and version in its content
+        let root_node = &graph.nodes[0];
+        if let NodeContent::Object(pairs) = &root_node.content {
+            assert_eq!(pairs.len(), 3); // name, version, items
+            assert!(pairs.iter().any(|p| p.key == "name"));
+            assert!(pairs.iter().any(|p| p.key == "version"));
+        }
+
+        // Check that items array has 2 objects as child nodes
+        let items_node = graph.nodes.iter().find(|n| n.label.contains("Array"));
+        assert!(items_node.is_some());
+
+        // Check that item objects have id and value in their content
+        let item_objects: Vec<_> = graph
+            .nodes
+            .iter()
+            .filter(|n| n.label.contains("Object") && n.id > 0)
+            .collect();
+        assert_eq!(item_objects.len(), 2);
+
+        for item in item_objects {
+            if let NodeContent::Object(pairs) = &item.content {
+                assert_eq!(pairs.len(), 2); // id and value
+                assert!(pairs.iter().any(|p| p.key == "id"));
+                assert!(pairs.iter().any(|p| p.key == "value"));
+            }
+        }
+    }
+}
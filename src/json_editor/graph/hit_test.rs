@@ -0,0 +1,662 @@
+//! Mapping canvas positions to nodes, rows and click actions
+
+use egui::{Pos2, Rect, Vec2};
+
+use super::super::editor::json_pointer;
+use super::{
+    ClickAction, ContextMenuState, GraphNode, JsonGraph, LayoutDirection, NodeContent, ROW_HEIGHT,
+};
+
+impl JsonGraph {
+    /// Graph-space end points of the edge between a parent and a child node:
+    /// bottom-center to top-center in `TopDown`, right-center to left-center in `LeftRight`
+    pub(super) fn edge_anchors(&self, from: &GraphNode, to: &GraphNode) -> (Pos2, Pos2) {
+        match self.layout_direction {
+            LayoutDirection::TopDown => (
+                from.position + Vec2::new(from.size.x / 2.0, from.size.y),
+                to.position + Vec2::new(to.size.x / 2.0, 0.0),
+            ),
+            LayoutDirection::LeftRight => (
+                from.position + Vec2::new(from.size.x, from.size.y / 2.0),
+                to.position + Vec2::new(0.0, to.size.y / 2.0),
+            ),
+        }
+    }
+
+    /// Pan offset that puts a node's center in the middle of the canvas
+    pub(super) fn offset_to_center(&self, node: &GraphNode, canvas_size: Vec2) -> Vec2 {
+        let node_center = node.position.to_vec2() + node.size / 2.0;
+        canvas_size / 2.0 - node_center * self.zoom
+    }
+
+    /// Transform position with zoom and offset
+    pub(super) fn transform_pos(&self, pos: Pos2, canvas_rect: Rect) -> Pos2 {
+        let transformed = pos.to_vec2() * self.zoom + self.offset;
+        canvas_rect.min + transformed
+    }
+
+    /// Visible table row of a node under a screen position
+    /// `rect` is the node's screen rect (zoom and pan applied). Returns None over the
+    /// header, below the last row, or for primitive nodes
+    /// Rows are found even below the detail zoom, where they aren't drawn, so their
+    /// tooltips still show when zoomed out (see `clickable_row_at_pos` for clicks)
+    pub(super) fn row_at_pos(&self, node: &GraphNode, rect: Rect, pos: Pos2) -> Option<usize> {
+        let header_height = 25.0 * self.zoom;
+        if !rect.contains(pos) || pos.y < rect.min.y + header_height {
+            return None;
+        }
+        let relative_y = pos.y - (rect.min.y + header_height);
+        let slot = node.row_at(relative_y / self.zoom);
+        let row_index = slot + self.scroll_offset(node);
+        (slot < node.row_heights.len() && row_index < node.row_count()).then_some(row_index)
+    }
+
+    /// Table row under a screen position that can be clicked or right-clicked
+    /// Rows aren't drawn below the detail zoom, so they can't be clicked either
+    fn clickable_row_at_pos(&self, node: &GraphNode, rect: Rect, pos: Pos2) -> Option<usize> {
+        if !self.shows_tables() {
+            return None;
+        }
+        self.row_at_pos(node, rect, pos)
+    }
+
+    /// First shown table row of a node (0 unless it has been scrolled)
+    pub(super) fn scroll_offset(&self, node: &GraphNode) -> usize {
+        self.scroll_offsets.get(&node.id).copied().unwrap_or(0)
+    }
+
+    /// Scroll a node's table by a number of rows (positive = towards the last row),
+    /// clamped so the last row can't scroll past the bottom slot
+    /// Returns whether the shown rows changed
+    pub(super) fn scroll_rows(&mut self, node_id: usize, rows: isize) -> bool {
+        let Some(node) = self.nodes.iter().find(|n| n.id == node_id) else {
+            return false;
+        };
+        let current = self.scroll_offset(node);
+        let offset = current
+            .saturating_add_signed(rows)
+            .min(node.max_scroll_offset());
+        if offset == 0 {
+            self.scroll_offsets.remove(&node_id);
+        } else {
+            self.scroll_offsets.insert(node_id, offset);
+        }
+        offset != current
+    }
+
+    /// Topmost shown node whose table rows are under a screen position and don't all fit
+    pub(super) fn scrollable_node_at(&self, pos: Pos2, canvas_rect: Rect) -> Option<usize> {
+        self.nodes
+            .iter()
+            .rev() // Later nodes are drawn on top
+            .filter(|n| !self.is_hidden(n))
+            .find_map(|node| {
+                let pos_min = self.transform_pos(node.position, canvas_rect);
+                let rect = Rect::from_min_size(pos_min, node.size * self.zoom);
+                rect.contains(pos).then_some((node, rect))
+            })
+            .filter(|(node, rect)| {
+                self.shows_tables()
+                    && node.max_scroll_offset() > 0
+                    && pos.y >= rect.min.y + 25.0 * self.zoom
+            })
+            .map(|(node, _)| node.id)
+    }
+
+    /// Hover text for a node header: its JSON Pointer
+    /// None when pointer tooltips are off, and for the root (the empty pointer)
+    pub(super) fn pointer_tooltip(&self, node: &GraphNode) -> Option<String> {
+        (self.show_pointer_tooltips && !node.json_path.is_empty())
+            .then(|| json_pointer(&node.json_path))
+    }
+
+    /// Hover text for a table row: the complete value and its type, or the child
+    /// count and path for Object/Array rows
+    pub(super) fn row_tooltip(&self, node: &GraphNode, row: usize) -> Option<String> {
+        let (segment, display, full_value, value_type, is_reference) = match &node.content {
+            NodeContent::Object(pairs) => {
+                let pair = pairs.get(row)?;
+                let key = pair.key.clone();
+                (
+                    key,
+                    &pair.value_display,
+                    &pair.full_value,
+                    &pair.value_type,
+                    pair.is_reference,
+                )
+            }
+            NodeContent::Array(items) => {
+                let item = items.get(row)?;
+                let index = item.index.to_string();
+                (
+                    index,
+                    &item.value_display,
+                    &item.full_value,
+                    &item.value_type,
+                    item.is_reference,
+                )
+            }
+            NodeContent::Primitive(_) => return None,
+        };
+
+        if !is_reference {
+            let value = full_value.as_ref().unwrap_or(display);
+            return Some(format!("{}\nType: {:?}", value, value_type));
+        }
+
+        let mut path = node.json_path.clone();
+        path.push(segment);
+        let summary = match self.find_node_by_path(&path).map(|child| &child.content) {
+            Some(NodeContent::Object(pairs)) => format!("Object with {} keys", pairs.len()),
+            Some(NodeContent::Array(items)) => format!("Array with {} items", items.len()),
+            _ => display.clone(),
+        };
+        Some(format!(
+            "{}\nPath: {}",
+            summary,
+            self.breadcrumb_labels(&path).join(" › ")
+        ))
+    }
+
+    /// Check if a click position is on an action area (edit, delete, add button)
+    /// Returns None if clicking on header or empty space
+    pub(super) fn get_click_action(
+        &self,
+        node: &GraphNode,
+        rect: Rect,
+        click_pos: Pos2,
+    ) -> Option<ClickAction> {
+        let header_height = 25.0 * self.zoom;
+        let line_height = ROW_HEIGHT * self.zoom;
+        let delete_button_size = 16.0 * self.zoom;
+
+        // Check if click is below header
+        if click_pos.y < rect.min.y + header_height {
+            if self.has_child_nodes(node) && self.collapse_toggle_rect(rect).contains(click_pos) {
+                return Some(ClickAction::ToggleCollapse);
+            }
+            return None; // Clicking on header
+        }
+
+        // Calculate which row was clicked
+        let row_index = self.clickable_row_at_pos(node, rect, click_pos)?;
+
+        match &node.content {
+            NodeContent::Object(pairs) => {
+                let key_column_width = rect.width() * 0.4;
+                let delete_button_x = rect.max.x - delete_button_size - 5.0;
+
+                let pair = &pairs[row_index];
+                let slot = row_index - self.scroll_offset(node);
+                let y = rect.min.y + header_height + node.row_top(slot) * self.zoom;
+
+                // Check if clicking on delete button
+                let delete_center_x = delete_button_x + delete_button_size / 2.0;
+                let delete_center_y = y + line_height / 2.0;
+                let distance = ((click_pos.x - delete_center_x).powi(2)
+                    + (click_pos.y - delete_center_y).powi(2))
+                .sqrt();
+                if distance <= delete_button_size / 2.0 {
+                    return Some(ClickAction::DeleteRow(pair.key.clone()));
+                }
+
+                // Check if clicking on key column for renaming
+                if click_pos.x >= rect.min.x + 5.0
+                    && click_pos.x <= rect.min.x + key_column_width - 5.0
+                {
+                    return Some(ClickAction::RenameKey(pair.key.clone()));
+                }
+
+                // Check if clicking on value column for editing
+                // (Object/Array values open the Edit JSON dialog)
+                if click_pos.x > rect.min.x + key_column_width
+                    && click_pos.x < delete_button_x - 5.0
+                {
+                    return Some(ClickAction::EditCell(
+                        pair.key.clone(),
+                        pair.value_type.clone(),
+                    ));
+                }
+            }
+            NodeContent::Array(items) => {
+                let index_column_width = 40.0 * self.zoom;
+                let delete_button_x = rect.max.x - delete_button_size - 5.0;
+
+                let item = &items[row_index];
+                let slot = row_index - self.scroll_offset(node);
+                let y = rect.min.y + header_height + node.row_top(slot) * self.zoom;
+
+                // Check if clicking on delete button
+                let delete_center_x = delete_button_x + delete_button_size / 2.0;
+                let delete_center_y = y + line_height / 2.0;
+                let distance = ((click_pos.x - delete_center_x).powi(2)
+                    + (click_pos.y - delete_center_y).powi(2))
+                .sqrt();
+                if distance <= delete_button_size / 2.0 {
+                    return Some(ClickAction::DeleteRow(item.index.to_string()));
+                }
+
+                // Check if clicking on value column for editing
+                // (Object/Array values open the Edit JSON dialog)
+                if click_pos.x > rect.min.x + index_column_width
+                    && click_pos.x < delete_button_x - 5.0
+                {
+                    return Some(ClickAction::EditCell(
+                        item.index.to_string(),
+                        item.value_type.clone(),
+                    ));
+                }
+            }
+            NodeContent::Primitive(_) => {
+                // Primitive nodes don't have interactive elements
+                return None;
+            }
+        }
+
+        None
+    }
+
+    /// Get context menu information for a right-click position
+    /// Returns None if not clicking on a row
+    pub(super) fn get_context_menu_info(
+        &self,
+        node: &GraphNode,
+        rect: Rect,
+        click_pos: Pos2,
+    ) -> Option<ContextMenuState> {
+        let header_height = 25.0 * self.zoom;
+
+        // Clicking on the header of an Object/Array shows the container menu
+        if click_pos.y < rect.min.y + header_height {
+            return match &node.content {
+                NodeContent::Object(_) | NodeContent::Array(_) => Some(ContextMenuState {
+                    node_id: node.id,
+                    row_key: None,
+                    is_object: matches!(node.content, NodeContent::Object(_)),
+                    is_primitive: false,
+                    value_type: None,
+                    position: Pos2::ZERO, // Will be set by caller
+                }),
+                NodeContent::Primitive(_) => None,
+            };
+        }
+
+        // Calculate which row was clicked
+        let row_index = self.clickable_row_at_pos(node, rect, click_pos);
+
+        match &node.content {
+            NodeContent::Object(pairs) => {
+                // Check if clicking within a valid row
+                if let Some(row_index) = row_index {
+                    let pair = &pairs[row_index];
+                    Some(ContextMenuState {
+                        node_id: node.id,
+                        row_key: Some(pair.key.clone()),
+                        is_object: true,
+                        is_primitive: !pair.is_reference,
+                        value_type: if !pair.is_reference {
+                            Some(pair.value_type.clone())
+                        } else {
+                            None
+                        },
+                        position: Pos2::ZERO, // Will be set by caller
+                    })
+                } else {
+                    // Clicking in empty space below rows - show Add menu
+                    Some(ContextMenuState {
+                        node_id: node.id,
+                        row_key: None,
+                        is_object: true,
+                        is_primitive: false,
+                        value_type: None,
+                        position: Pos2::ZERO, // Will be set by caller
+                    })
+                }
+            }
+            NodeContent::Array(items) => {
+                // Check if clicking within a valid row
+                if let Some(row_index) = row_index {
+                    let item = &items[row_index];
+                    Some(ContextMenuState {
+                        node_id: node.id,
+                        row_key: Some(item.index.to_string()),
+                        is_object: false,
+                        is_primitive: !item.is_reference,
+                        value_type: if !item.is_reference {
+                            Some(item.value_type.clone())
+                        } else {
+                            None
+                        },
+                        position: Pos2::ZERO, // Will be set by caller
+                    })
+                } else {
+                    // Clicking in empty space below rows - show Add menu
+                    Some(ContextMenuState {
+                        node_id: node.id,
+                        row_key: None,
+                        is_object: false,
+                        is_primitive: false,
+                        value_type: None,
+                        position: Pos2::ZERO, // Will be set by caller
+                    })
+                }
+            }
+            NodeContent::Primitive(_) => {
+                // Primitive nodes don't have rows
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_max_visible_rows_click_handling() {
+        let map: serde_json::Map<String, Value> =
+            (0..15).map(|i| (format!("k{:02}", i), json!(i))).collect();
+        let value = Value::Object(map);
+
+        // Click on the value column of row 12 (zoom 1.0, no offset)
+        let click_row = |graph: &JsonGraph| {
+            let node = &graph.nodes[0];
+            let rect = Rect::from_min_size(node.position, node.size);
+            let click = Pos2::new(
+                rect.min.x + rect.width() * 0.6,
+                rect.min.y + 25.0 + 12.5 * 22.0,
+            );
+            graph.get_click_action(node, rect, click)
+        };
+
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+        assert!(click_row(&graph).is_none());
+
+        graph.set_max_visible_rows(20);
+        graph.build_from_json(&value);
+        assert_eq!(graph.nodes[0].size.y, 25.0 + 15.0 * 22.0 + 10.0);
+        match click_row(&graph) {
+            Some(ClickAction::EditCell(key, _)) => assert_eq!(key, "k12"),
+            other => panic!("Expected EditCell, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_max_visible_rows_boundary_hit_testing() {
+        let value = json!({"items": (0..15).collect::<Vec<i32>>()});
+
+        let mut graph = JsonGraph::new();
+        graph.set_max_visible_rows(5);
+        graph.build_from_json(&value);
+        let node = graph
+            .nodes
+            .iter()
+            .find(|n| n.json_path == ["items"])
+            .unwrap()
+            .clone();
+        assert_eq!(node.row_heights.len(), 5);
+        assert_eq!(node.size.y, 25.0 + 5.0 * ROW_HEIGHT + 10.0);
+
+        let rect = Rect::from_min_size(node.position, node.size);
+        let at_row = |row: f32| {
+            Pos2::new(
+                rect.min.x + rect.width() * 0.6,
+                rect.min.y + 25.0 + row * ROW_HEIGHT,
+            )
+        };
+
+        // The last drawn row is still hit by both left and right clicks
+        match graph.get_click_action(&node, rect, at_row(4.5)) {
+            Some(ClickAction::EditCell(key, _)) => assert_eq!(key, "4"),
+            other => panic!("Expected EditCell, got {:?}", other),
+        }
+        let menu = graph.get_context_menu_info(&node, rect, at_row(4.5));
+        assert_eq!(menu.unwrap().row_key.as_deref(), Some("4"));
+
+        // Just past it (the "... more" line) maps to no row, not to the hidden item 5
+        assert!(graph.get_click_action(&node, rect, at_row(5.1)).is_none());
+        let menu = graph.get_context_menu_info(&node, rect, at_row(5.1));
+        assert_eq!(menu.unwrap().row_key, None);
+    }
+
+    #[test]
+    fn test_compact_nodes_below_detail_zoom_keep_row_tooltips() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"a": 1, "b": 2}));
+        let node = graph.nodes[0].clone();
+        let click = |graph: &JsonGraph| {
+            let rect = Rect::from_min_size(node.position, node.size * graph.zoom);
+            let pos = Pos2::new(
+                rect.min.x + rect.width() * 0.6,
+                rect.min.y + (25.0 + ROW_HEIGHT / 2.0) * graph.zoom,
+            );
+            (
+                graph.row_at_pos(&node, rect, pos),
+                graph.get_click_action(&node, rect, pos),
+            )
+        };
+
+        graph.zoom = 0.5;
+        assert!(matches!(
+            click(&graph),
+            (Some(0), Some(ClickAction::EditCell(..)))
+        ));
+
+        // Rows aren't drawn below the threshold, so they can't be clicked either,
+        // but hovering them still shows their tooltip
+        graph.zoom = 0.3;
+        assert!(matches!(click(&graph), (Some(0), None)));
+        assert_eq!(
+            graph.row_tooltip(&node, 0).as_deref(),
+            Some("1\nType: Number")
+        );
+        graph.set_detail_zoom(0.2);
+        assert!(matches!(click(&graph), (Some(0), Some(_))));
+
+        graph.set_detail_zoom(10.0);
+        assert_eq!(graph.detail_zoom(), MAX_DETAIL_ZOOM);
+    }
+
+    #[test]
+    fn test_scrolled_rows_hit_testing_and_clamping() {
+        let value = json!({"items": (0..15).collect::<Vec<i32>>()});
+
+        let mut graph = JsonGraph::new();
+        graph.set_max_visible_rows(5);
+        graph.build_from_json(&value);
+        let items_path = ["items".to_string()];
+        let node = graph.find_node_by_path(&items_path).unwrap().clone();
+        let rect = Rect::from_min_size(node.position, node.size);
+        let at_slot = |slot: f32| {
+            Pos2::new(
+                rect.min.x + rect.width() * 0.6,
+                rect.min.y + 25.0 + slot * ROW_HEIGHT,
+            )
+        };
+        let edited_key =
+            |graph: &JsonGraph, slot: f32| match graph.get_click_action(&node, rect, at_slot(slot))
+            {
+                Some(ClickAction::EditCell(key, _)) => key,
+                other => panic!("Expected EditCell, got {:?}", other),
+            };
+
+        // Clicks map through the scroll offset, for both buttons
+        assert!(graph.scroll_rows(node.id, 3));
+        assert_eq!(edited_key(&graph, 0.5), "3");
+        let menu = graph.get_context_menu_info(&node, rect, at_slot(4.5));
+        assert_eq!(menu.unwrap().row_key.as_deref(), Some("7"));
+        assert_eq!(rows_footer(15, 5, 3), "4–8 of 15");
+
+        // The last row can't scroll past the bottom slot, nor the first past the top
+        graph.scroll_rows(node.id, 100);
+        assert_eq!(graph.scroll_offset(&node), 10);
+        assert_eq!(edited_key(&graph, 4.5), "14");
+        assert!(!graph.scroll_rows(node.id, 1));
+        graph.scroll_rows(node.id, -100);
+        assert_eq!(graph.scroll_offset(&node), 0);
+        assert!(graph.scroll_offsets.is_empty());
+
+        // An update keeps the position, clamped to the rows left
+        graph.scroll_rows(node.id, 8);
+        graph.update_from_json(&json!({"items": (0..8).collect::<Vec<i32>>()}));
+        let node = graph.find_node_by_path(&items_path).unwrap();
+        assert_eq!(graph.scroll_offset(node), 3);
+
+        // Tables whose rows all fit don't scroll
+        graph.update_from_json(&json!({"items": [1, 2]}));
+        let node = graph.find_node_by_path(&items_path).unwrap().clone();
+        assert_eq!(graph.scroll_offset(&node), 0);
+        assert!(!graph.scroll_rows(node.id, 1));
+    }
+
+    #[test]
+    fn test_wrap_values_row_heights_and_hit_testing() {
+        let long = "x".repeat(40);
+        let value = json!({"url": long, "next": 1});
+
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&value);
+        assert_eq!(graph.nodes[0].row_heights, vec![ROW_HEIGHT, ROW_HEIGHT]);
+
+        graph.set_wrap_values(true);
+        graph.build_from_json(&value);
+        // Built with single-line rows until measured with the UI's fonts
+        assert_eq!(graph.nodes[0].row_heights, vec![ROW_HEIGHT, ROW_HEIGHT]);
+
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            graph.measure_wrapped_rows(ctx);
+        });
+        let node = graph.nodes[0].clone();
+        // The full value is wrapped, not the (default) truncated preview
+        let (galley, line_height) = ctx.fonts_mut(|fonts| {
+            let font_id = egui::FontId::monospace(VALUE_FONT_SIZE);
+            let galley = fonts.layout(
+                format!("\"{}\"", long),
+                font_id.clone(),
+                Color32::WHITE,
+                value_column_width(true),
+            );
+            (galley, fonts.row_height(&font_id))
+        });
+        assert!(galley.rows.len() >= 3);
+        let wrapped_height = ROW_HEIGHT + (galley.rows.len() - 1) as f32 * line_height;
+        assert_eq!(node.row_heights, vec![wrapped_height, ROW_HEIGHT]);
+        assert_eq!(node.size.y, 25.0 + wrapped_height + ROW_HEIGHT + 10.0);
+        assert!(graph.nodes.iter().all(|node| node.rows_measured));
+
+        // A click on the third line of the wrapped row still hits that row,
+        // and the row below is found past the wrapped height
+        let rect = Rect::from_min_size(node.position, node.size);
+        let click_at = |offset: f32| {
+            let click = Pos2::new(rect.min.x + rect.width() * 0.6, rect.min.y + 25.0 + offset);
+            graph.get_click_action(&node, rect, click)
+        };
+        match click_at(wrapped_height - ROW_HEIGHT / 2.0) {
+            Some(ClickAction::EditCell(key, _)) => assert_eq!(key, "url"),
+            other => panic!("Expected EditCell, got {:?}", other),
+        }
+        match click_at(wrapped_height + ROW_HEIGHT / 2.0) {
+            Some(ClickAction::EditCell(key, _)) => assert_eq!(key, "next"),
+            other => panic!("Expected EditCell, got {:?}", other),
+        }
+
+        // Delete button stays on the first line of the wrapped row
+        let delete = Pos2::new(rect.max.x - 13.0, rect.min.y + 25.0 + ROW_HEIGHT / 2.0);
+        match graph.get_click_action(&node, rect, delete) {
+            Some(ClickAction::DeleteRow(key)) => assert_eq!(key, "url"),
+            other => panic!("Expected DeleteRow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pointer_tooltip() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"items": [{"a/b": {"c~d": 1}}]}));
+        let path =
+            |segments: &[&str]| -> Vec<String> { segments.iter().map(|s| s.to_string()).collect() };
+        let item = graph
+            .find_node_by_path(&path(&["items", "0"]))
+            .unwrap()
+            .clone();
+        assert_eq!(graph.pointer_tooltip(&item), None);
+
+        graph.set_show_pointer_tooltips(true);
+        assert_eq!(graph.pointer_tooltip(&item).as_deref(), Some("/items/0"));
+        let escaped = graph
+            .find_node_by_path(&path(&["items", "0", "a/b"]))
+            .unwrap()
+            .clone();
+        assert_eq!(
+            graph.pointer_tooltip(&escaped).as_deref(),
+            Some("/items/0/a~1b")
+        );
+        assert_eq!(graph.pointer_tooltip(&graph.nodes[0].clone()), None);
+        assert_eq!(json_pointer(&path(&["c~d"])), "/c~0d");
+    }
+
+    #[test]
+    fn test_row_tooltip_with_zoom_and_pan() {
+        let long = "a very long description that does not fit";
+        let value = json!({"short": 1, "text": long, "list": [1, 2, 3]});
+        let mut graph = JsonGraph::new();
+        graph.set_preview_len(10);
+        graph.build_from_json(&value);
+        graph.zoom = 0.5;
+        graph.offset = Vec2::new(-40.0, 25.0);
+
+        let canvas = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::splat(2000.0));
+        let root = graph.nodes[0].clone();
+        let rect = Rect::from_min_size(
+            graph.transform_pos(root.position, canvas),
+            root.size * graph.zoom,
+        );
+        // Middle of a row, in screen space
+        let row_pos = |row: usize| {
+            let y = rect.min.y
+                + 25.0 * graph.zoom
+                + (root.row_top(row) + root.row_heights[row] / 2.0) * graph.zoom;
+            Pos2::new(rect.center().x, y)
+        };
+
+        assert_eq!(graph.row_at_pos(&root, rect, row_pos(1)), Some(1));
+        assert_eq!(
+            graph.row_at_pos(&root, rect, rect.min + Vec2::splat(1.0)),
+            None
+        );
+        assert_eq!(
+            graph.row_at_pos(&root, rect, rect.max + Vec2::splat(1.0)),
+            None
+        );
+
+        // Truncated string: the whole value and its type
+        let tooltip = graph.row_tooltip(&root, 1).unwrap();
+        assert!(tooltip.contains(long));
+        assert!(tooltip.contains("Type: String"));
+        assert_eq!(
+            graph.row_tooltip(&root, 0),
+            Some("1\nType: Number".to_string())
+        );
+        // Reference row: child count and path
+        assert_eq!(
+            graph.row_tooltip(&root, 2),
+            Some("Array with 3 items\nPath: root › list".to_string())
+        );
+    }
+
+    #[test]
+    fn test_offset_to_center() {
+        let mut graph = JsonGraph::new();
+        graph.build_from_json(&json!({"a": {"b": 1}}));
+        graph.zoom = 2.0;
+        let canvas_rect = Rect::from_min_size(Pos2::new(50.0, 20.0), Vec2::new(800.0, 600.0));
+
+        let node = graph.find_node_by_path(&["a".to_string()]).unwrap().clone();
+        graph.offset = graph.offset_to_center(&node, canvas_rect.size());
+        let center = graph.transform_pos(node.position + node.size / 2.0, canvas_rect);
+        assert!((center - canvas_rect.center()).length() < 0.001);
+    }
+}
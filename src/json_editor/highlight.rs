@@ -0,0 +1,443 @@
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId};
+use std::cell::Cell;
+
+/// Kind of a JSON token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// String used as an object key (followed by ':')
+    Key,
+    /// String value
+    String,
+    /// Number value
+    Number,
+    /// true / false
+    Boolean,
+    /// null
+    Null,
+    /// Structural characters: { } [ ] : ,
+    Punctuation,
+    /// Spaces, tabs and newlines
+    Whitespace,
+    /// `// line` or `/* block */` comment (JSONC)
+    Comment,
+    /// Anything else (invalid or partially typed input)
+    Other,
+}
+
+impl TokenKind {
+    /// Display color for this token kind (matches the tree/graph type colors)
+    fn color(&self, default_color: Color32) -> Color32 {
+        match self {
+            TokenKind::Key => Color32::from_rgb(150, 200, 255),
+            TokenKind::String => Color32::from_rgb(100, 200, 100),
+            TokenKind::Number => Color32::from_rgb(200, 150, 100),
+            TokenKind::Boolean => Color32::from_rgb(200, 100, 150),
+            TokenKind::Null => Color32::from_gray(150),
+            TokenKind::Punctuation => Color32::from_gray(180),
+            TokenKind::Comment => Color32::from_gray(110),
+            TokenKind::Whitespace | TokenKind::Other => default_color,
+        }
+    }
+}
+
+/// A token with its byte range in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Start byte offset (inclusive)
+    pub start: usize,
+    /// End byte offset (exclusive)
+    pub end: usize,
+}
+
+/// Split JSON text into tokens
+/// The tokenizer is lenient: invalid input produces `Other` tokens instead of failing,
+/// and every byte of the input is covered by exactly one token
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                while i < bytes.len() && matches!(bytes[i], b' ' | b'\t' | b'\n' | b'\r') {
+                    i += 1;
+                }
+                TokenKind::Whitespace
+            }
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => {
+                i += 1;
+                TokenKind::Punctuation
+            }
+            b'"' => {
+                i += 1;
+                // Scan to the closing quote, skipping escapes
+                // Stop at a newline so an unterminated string doesn't color the rest
+                while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                if i < bytes.len() && bytes[i] == b'"' {
+                    i += 1;
+                }
+                i = i.min(bytes.len());
+                TokenKind::String
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                // Line comment runs to the end of the line
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                TokenKind::Comment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                // Block comment runs to the closing "*/" (or the end of the text)
+                i = text[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                TokenKind::Comment
+            }
+            b'-' | b'0'..=b'9' => {
+                while i < bytes.len()
+                    && matches!(bytes[i], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            b if b.is_ascii_alphabetic() => {
+                while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                match &text[start..i] {
+                    "true" | "false" => TokenKind::Boolean,
+                    "null" => TokenKind::Null,
+                    _ => TokenKind::Other,
+                }
+            }
+            _ => {
+                // Advance by a whole character to stay on a UTF-8 boundary
+                i += text[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Other
+            }
+        };
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+        });
+    }
+
+    mark_keys(text, &mut tokens);
+    tokens
+}
+
+/// Re-classify strings followed by ':' as keys
+fn mark_keys(text: &str, tokens: &mut [Token]) {
+    for i in 0..tokens.len() {
+        if tokens[i].kind != TokenKind::String {
+            continue;
+        }
+
+        let next = tokens[i + 1..]
+            .iter()
+            .find(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment));
+        if let Some(next) = next
+            && next.kind == TokenKind::Punctuation
+            && &text[next.start..next.end] == ":"
+        {
+            tokens[i].kind = TokenKind::Key;
+        }
+    }
+}
+
+/// Byte offsets and bytes of the structural brackets among the tokens of `text`
+/// Brackets inside strings (and comments) belong to those tokens, so they're skipped
+pub fn bracket_tokens(text: &str, tokens: &[Token]) -> Vec<(usize, u8)> {
+    tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::Punctuation)
+        .map(|t| (t.start, text.as_bytes()[t.start]))
+        .filter(|(_, b)| matches!(b, b'{' | b'}' | b'[' | b']'))
+        .collect()
+}
+
+/// Build a colored layout job for JSON text
+pub fn layout_job(text: &str, font_id: FontId, default_color: Color32) -> LayoutJob {
+    build_layout_job(text, &tokenize(text), font_id, default_color)
+}
+
+/// Build a colored layout job from already computed tokens
+fn build_layout_job(
+    text: &str,
+    tokens: &[Token],
+    font_id: FontId,
+    default_color: Color32,
+) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    for token in tokens {
+        job.append(
+            &text[token.start..token.end],
+            0.0,
+            TextFormat::simple(font_id.clone(), token.kind.color(default_color)),
+        );
+    }
+    job
+}
+
+/// Version of the editor text: the editor's revision plus the edits a text field
+/// made to it since (see `TrackedText`)
+/// Text typed in a frame is laid out before the editor moves on to a new revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextVersion {
+    pub revision: u64,
+    pub edits: u64,
+}
+
+/// Text buffer for a `TextEdit` that counts the edits made through it
+/// Gives the layouter a version for text that changed within the frame
+pub struct TrackedText<'a> {
+    pub text: &'a mut String,
+    pub edits: &'a Cell<u64>,
+}
+
+impl egui::TextBuffer for TrackedText<'_> {
+    fn is_mutable(&self) -> bool {
+        true
+    }
+
+    fn as_str(&self) -> &str {
+        self.text.as_str()
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        self.edits.set(self.edits.get() + 1);
+        self.text.insert_text(text, char_index)
+    }
+
+    fn delete_char_range(&mut self, char_range: std::ops::Range<usize>) {
+        self.edits.set(self.edits.get() + 1);
+        self.text.delete_char_range(char_range);
+    }
+
+    fn type_id(&self) -> std::any::TypeId {
+        std::any::TypeId::of::<TrackedText<'static>>()
+    }
+}
+
+/// Cache of the highlighted layout job
+/// The text is only re-tokenized when its version (or the font/color) changes, so
+/// the per-frame layouter stays cheap on large documents
+#[derive(Default)]
+pub struct HighlightCache {
+    /// Text version, font and color the cached job was built from
+    key: Option<(TextVersion, FontId, Color32)>,
+    /// Cached layout job (without wrap settings)
+    job: LayoutJob,
+    /// Editor revision the cached brackets were found at
+    brackets_revision: Option<u64>,
+    /// Structural brackets of that text (see `bracket_tokens`)
+    brackets: Vec<(usize, u8)>,
+}
+
+impl HighlightCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the layout job for `text` at `version`, re-tokenizing only if the input changed
+    pub fn layout_job(
+        &mut self,
+        version: TextVersion,
+        text: &str,
+        font_id: FontId,
+        default_color: Color32,
+    ) -> LayoutJob {
+        self.layout_job_with(version, text, font_id, default_color, tokenize)
+    }
+
+    /// Same as `layout_job`, with an injectable tokenizer
+    fn layout_job_with(
+        &mut self,
+        version: TextVersion,
+        text: &str,
+        font_id: FontId,
+        default_color: Color32,
+        tokenizer: impl FnOnce(&str) -> Vec<Token>,
+    ) -> LayoutJob {
+        let key = (version, font_id, default_color);
+        if self.key.as_ref() != Some(&key) {
+            let tokens = tokenizer(text);
+            self.job = build_layout_job(text, &tokens, key.1.clone(), default_color);
+            // Text edited this frame isn't at any revision yet
+            if version.edits == 0 {
+                self.brackets = bracket_tokens(text, &tokens);
+                self.brackets_revision = Some(version.revision);
+            }
+            self.key = Some(key);
+        }
+
+        self.job.clone()
+    }
+
+    /// Structural brackets of `text` at editor revision `revision` (see `bracket_tokens`)
+    /// Reuses the tokens of the last highlighted text, so with highlighting on the
+    /// text isn't tokenized again
+    pub fn brackets(&mut self, revision: u64, text: &str) -> &[(usize, u8)] {
+        self.brackets_with(revision, text, tokenize)
+    }
+
+    /// Same as `brackets`, with an injectable tokenizer
+    fn brackets_with(
+        &mut self,
+        revision: u64,
+        text: &str,
+        tokenizer: impl FnOnce(&str) -> Vec<Token>,
+    ) -> &[(usize, u8)] {
+        if self.brackets_revision != Some(revision) {
+            self.brackets = bracket_tokens(text, &tokenizer(text));
+            self.brackets_revision = Some(revision);
+        }
+        &self.brackets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tokenize and drop whitespace, returning (kind, text) pairs
+    fn classify(text: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(text)
+            .into_iter()
+            .filter(|t| t.kind != TokenKind::Whitespace)
+            .map(|t| (t.kind, &text[t.start..t.end]))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_object() {
+        assert_eq!(
+            classify(r#"{"a":1,"b":"x"}"#),
+            vec![
+                (TokenKind::Punctuation, "{"),
+                (TokenKind::Key, "\"a\""),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::Number, "1"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Key, "\"b\""),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::String, "\"x\""),
+                (TokenKind::Punctuation, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_escapes_and_literals() {
+        assert_eq!(
+            classify(r#"["a\"b", true, null, -1.5e3]"#),
+            vec![
+                (TokenKind::Punctuation, "["),
+                (TokenKind::String, r#""a\"b""#),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Boolean, "true"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Null, "null"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Number, "-1.5e3"),
+                (TokenKind::Punctuation, "]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        assert_eq!(
+            classify("{ // note\n\"a\": /* x */ 1 }"),
+            vec![
+                (TokenKind::Punctuation, "{"),
+                (TokenKind::Comment, "// note"),
+                (TokenKind::Key, "\"a\""),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::Comment, "/* x */"),
+                (TokenKind::Number, "1"),
+                (TokenKind::Punctuation, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cache_tokenizes_once_per_change() {
+        let mut cache = HighlightCache::new();
+        let mut calls = 0;
+        let font_id = FontId::monospace(12.0);
+        let version = |revision, edits| TextVersion { revision, edits };
+
+        // Several frames at the same revision
+        for _ in 0..5 {
+            cache.layout_job_with(
+                version(1, 0),
+                r#"{"a":1}"#,
+                font_id.clone(),
+                Color32::WHITE,
+                |t| {
+                    calls += 1;
+                    tokenize(t)
+                },
+            );
+        }
+        assert_eq!(calls, 1);
+
+        // Typed within a frame, then the editor moves on to the next revision
+        for edited in [version(1, 1), version(2, 0), version(2, 0)] {
+            cache.layout_job_with(edited, r#"{"a":2}"#, font_id.clone(), Color32::WHITE, |t| {
+                calls += 1;
+                tokenize(t)
+            });
+        }
+        assert_eq!(calls, 3);
+
+        // Brackets reuse the highlighted text's tokens
+        let brackets = cache
+            .brackets_with(2, r#"{"a":2}"#, |t| {
+                calls += 1;
+                tokenize(t)
+            })
+            .to_vec();
+        assert_eq!(calls, 3);
+        assert_eq!(brackets, vec![(0, b'{'), (6, b'}')]);
+
+        cache.brackets_with(3, "[]", |t| {
+            calls += 1;
+            tokenize(t)
+        });
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn test_tracked_text_counts_edits() {
+        use egui::TextBuffer;
+
+        let mut text = "ab".to_string();
+        let edits = Cell::new(0);
+        let mut buffer = TrackedText {
+            text: &mut text,
+            edits: &edits,
+        };
+        buffer.insert_text("c", 2);
+        buffer.delete_char_range(0..1);
+        assert_eq!(buffer.as_str(), "bc");
+        assert_eq!(edits.get(), 2);
+    }
+
+    #[test]
+    fn test_tokenize_covers_multibyte_input() {
+        let text = "{\"한\": \"안녕\", 你}";
+        let tokens = tokenize(text);
+        let rebuilt: String = tokens.iter().map(|t| &text[t.start..t.end]).collect();
+        assert_eq!(rebuilt, text);
+    }
+}
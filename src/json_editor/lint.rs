@@ -0,0 +1,396 @@
+use super::MAX_DEPTH;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Nesting depth beyond which a structure is reported as deeply nested
+const DEEP_NESTING_THRESHOLD: usize = 10;
+
+/// Kind of lint produced by the lint pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// Object key with leading or trailing whitespace
+    KeyWhitespace,
+    /// Object key that is an empty string
+    EmptyKey,
+    /// Number stored as a string (e.g. "42")
+    NumericString,
+    /// Keys whose casing differs across sibling objects (e.g. userId vs user_id)
+    InconsistentKeyCasing,
+    /// Structure nested deeper than the threshold
+    DeepNesting,
+}
+
+impl LintKind {
+    /// All lint kinds, in display order
+    pub const ALL: [LintKind; 5] = [
+        LintKind::KeyWhitespace,
+        LintKind::EmptyKey,
+        LintKind::NumericString,
+        LintKind::InconsistentKeyCasing,
+        LintKind::DeepNesting,
+    ];
+
+    /// Human-readable name for UI toggles
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintKind::KeyWhitespace => "Key whitespace",
+            LintKind::EmptyKey => "Empty keys",
+            LintKind::NumericString => "Numeric strings",
+            LintKind::InconsistentKeyCasing => "Key casing",
+            LintKind::DeepNesting => "Deep nesting",
+        }
+    }
+}
+
+/// A single lint finding
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    /// Kind of lint
+    pub kind: LintKind,
+    /// JSON path to the offending value (e.g., ["items", "0", "id"])
+    pub path: Vec<String>,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl Lint {
+    /// Format the path for display (e.g., "items.0.id", or "(root)")
+    pub fn path_display(&self) -> String {
+        if self.path.is_empty() {
+            "(root)".to_string()
+        } else {
+            self.path.join(".")
+        }
+    }
+}
+
+/// Which lints are enabled
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintSettings {
+    pub key_whitespace: bool,
+    pub empty_key: bool,
+    pub numeric_string: bool,
+    pub inconsistent_key_casing: bool,
+    pub deep_nesting: bool,
+}
+
+impl Default for LintSettings {
+    fn default() -> Self {
+        Self {
+            key_whitespace: true,
+            empty_key: true,
+            numeric_string: true,
+            inconsistent_key_casing: true,
+            deep_nesting: true,
+        }
+    }
+}
+
+impl LintSettings {
+    /// Check if a lint kind is enabled
+    pub fn is_enabled(&self, kind: LintKind) -> bool {
+        *self.flag(kind)
+    }
+
+    /// Mutable access to the flag for a lint kind (for UI checkboxes)
+    pub fn flag_mut(&mut self, kind: LintKind) -> &mut bool {
+        match kind {
+            LintKind::KeyWhitespace => &mut self.key_whitespace,
+            LintKind::EmptyKey => &mut self.empty_key,
+            LintKind::NumericString => &mut self.numeric_string,
+            LintKind::InconsistentKeyCasing => &mut self.inconsistent_key_casing,
+            LintKind::DeepNesting => &mut self.deep_nesting,
+        }
+    }
+
+    fn flag(&self, kind: LintKind) -> &bool {
+        match kind {
+            LintKind::KeyWhitespace => &self.key_whitespace,
+            LintKind::EmptyKey => &self.empty_key,
+            LintKind::NumericString => &self.numeric_string,
+            LintKind::InconsistentKeyCasing => &self.inconsistent_key_casing,
+            LintKind::DeepNesting => &self.deep_nesting,
+        }
+    }
+}
+
+/// Run all lints on a JSON value
+pub fn run_lints(value: &Value) -> Vec<Lint> {
+    run_lints_with(value, &LintSettings::default())
+}
+
+/// Run the enabled lints on a JSON value
+pub fn run_lints_with(value: &Value, settings: &LintSettings) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    lint_value(value, &mut Vec::new(), 0, settings, &mut lints);
+    lints
+}
+
+/// Recursively lint a value and its children, down to `MAX_DEPTH`
+fn lint_value(
+    value: &Value,
+    path: &mut Vec<String>,
+    depth: usize,
+    settings: &LintSettings,
+    lints: &mut Vec<Lint>,
+) {
+    // Report only the first level that crosses the threshold
+    if depth == DEEP_NESTING_THRESHOLD + 1
+        && (value.is_object() || value.is_array())
+        && settings.is_enabled(LintKind::DeepNesting)
+    {
+        lints.push(Lint {
+            kind: LintKind::DeepNesting,
+            path: path.clone(),
+            message: format!(
+                "Structure is nested more than {} levels deep",
+                DEEP_NESTING_THRESHOLD
+            ),
+        });
+    }
+    if depth >= MAX_DEPTH {
+        return;
+    }
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                path.push(key.clone());
+
+                if key.is_empty() {
+                    if settings.is_enabled(LintKind::EmptyKey) {
+                        lints.push(Lint {
+                            kind: LintKind::EmptyKey,
+                            path: path.clone(),
+                            message: "Empty string key".to_string(),
+                        });
+                    }
+                } else if key.trim() != key && settings.is_enabled(LintKind::KeyWhitespace) {
+                    lints.push(Lint {
+                        kind: LintKind::KeyWhitespace,
+                        path: path.clone(),
+                        message: format!("Key \"{}\" has leading or trailing whitespace", key),
+                    });
+                }
+
+                lint_value(child, path, depth + 1, settings, lints);
+                path.pop();
+            }
+
+            if settings.is_enabled(LintKind::InconsistentKeyCasing) {
+                lint_sibling_casing(map.iter().map(|(k, v)| (k.clone(), v)), path, lints);
+            }
+        }
+        Value::Array(arr) => {
+            for (index, child) in arr.iter().enumerate() {
+                path.push(index.to_string());
+                lint_value(child, path, depth + 1, settings, lints);
+                path.pop();
+            }
+
+            if settings.is_enabled(LintKind::InconsistentKeyCasing) {
+                lint_sibling_casing(
+                    arr.iter().enumerate().map(|(i, v)| (i.to_string(), v)),
+                    path,
+                    lints,
+                );
+            }
+        }
+        Value::String(s)
+            if settings.is_enabled(LintKind::NumericString) && is_numeric_string(s) =>
+        {
+            lints.push(Lint {
+                kind: LintKind::NumericString,
+                path: path.clone(),
+                message: format!("Number stored as string: \"{}\"", s),
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Compare keys across sibling objects and report keys that only differ in casing/separators
+fn lint_sibling_casing<'a>(
+    children: impl Iterator<Item = (String, &'a Value)>,
+    parent_path: &[String],
+    lints: &mut Vec<Lint>,
+) {
+    // Normalized key -> first spelling seen
+    let mut seen: HashMap<String, String> = HashMap::new();
+
+    for (segment, child) in children {
+        let Value::Object(map) = child else {
+            continue;
+        };
+
+        for key in map.keys() {
+            let normalized = normalize_key(key);
+            match seen.get(&normalized) {
+                Some(first) if first != key => {
+                    let mut path = parent_path.to_vec();
+                    path.push(segment.clone());
+                    path.push(key.clone());
+                    lints.push(Lint {
+                        kind: LintKind::InconsistentKeyCasing,
+                        path,
+                        message: format!(
+                            "Key \"{}\" is inconsistent with sibling key \"{}\"",
+                            key, first
+                        ),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(normalized, key.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Normalize a key for casing comparison (lowercase, without '_' and '-')
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find the deepest leaf path in a value
+/// Returns the path and its depth (number of segments); the first deepest leaf wins ties
+/// Empty objects and arrays count as leaves, and so do containers at `MAX_DEPTH`
+pub fn deepest_path(value: &Value) -> (Vec<String>, usize) {
+    let (mut path, depth) = deepest_path_reversed(value, 0);
+    path.reverse();
+    (path, depth)
+}
+
+/// `deepest_path` of a value nested `depth` levels deep, with the segments leaf first
+/// (each level pushes its segment rather than inserting it at the front)
+fn deepest_path_reversed(value: &Value, depth: usize) -> (Vec<String>, usize) {
+    if depth >= MAX_DEPTH {
+        return (Vec::new(), 0);
+    }
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut deepest: (Vec<String>, usize) = (Vec::new(), 0);
+    for (segment, child) in children {
+        let (mut path, child_depth) = deepest_path_reversed(child, depth + 1);
+        if deepest.0.is_empty() || child_depth + 1 > deepest.1 {
+            path.push(segment);
+            deepest = (path, child_depth + 1);
+        }
+    }
+    deepest
+}
+
+/// Check if a string contains a valid JSON number
+fn is_numeric_string(s: &str) -> bool {
+    matches!(serde_json::from_str::<Value>(s), Ok(Value::Number(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_with_leading_space() {
+        let lints = run_lints(&json!({" key": true}));
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::KeyWhitespace);
+        assert_eq!(lints[0].path, vec![" key".to_string()]);
+    }
+
+    #[test]
+    fn test_numeric_string() {
+        let lints = run_lints(&json!({"count": "42"}));
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::NumericString);
+        assert_eq!(lints[0].path, vec!["count".to_string()]);
+    }
+
+    #[test]
+    fn test_empty_key() {
+        let lints = run_lints(&json!({"": 1}));
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::EmptyKey);
+    }
+
+    #[test]
+    fn test_inconsistent_key_casing() {
+        let lints = run_lints(&json!([{"userId": 1}, {"user_id": 2}]));
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::InconsistentKeyCasing);
+        assert_eq!(lints[0].path, vec!["1".to_string(), "user_id".to_string()]);
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let mut value = json!(1);
+        for _ in 0..12 {
+            value = json!([value]);
+        }
+        let lints = run_lints(&value);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::DeepNesting);
+
+        // Past `MAX_DEPTH` the walk stops, so a string at the bottom isn't reached
+        let mut value = json!("42");
+        for _ in 0..MAX_DEPTH + 10 {
+            value = json!([value]);
+        }
+        let lints = run_lints(&value);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::DeepNesting);
+    }
+
+    #[test]
+    fn test_deepest_path() {
+        let value = json!({
+            "shallow": 1,
+            "deep": {"a": [0, {"b": {"c": true}}]},
+            "medium": {"x": {"y": 2}}
+        });
+        let (path, depth) = deepest_path(&value);
+        assert_eq!(path, vec!["deep", "a", "1", "b", "c"]);
+        assert_eq!(depth, 5);
+
+        assert_eq!(deepest_path(&json!(42)), (Vec::new(), 0));
+
+        // Stops at the shared depth limit, where the stats panel stops too
+        let mut value = json!(1);
+        for _ in 0..10_000 {
+            value = Value::Array(vec![value]);
+        }
+        let (path, depth) = deepest_path(&value);
+        assert_eq!(depth, MAX_DEPTH);
+        assert_eq!(path, vec!["0".to_string(); MAX_DEPTH]);
+        assert_eq!(
+            crate::json_editor::stats::compute_stats(&value).max_depth,
+            MAX_DEPTH
+        );
+
+        // Unwrap iteratively so dropping the value doesn't recurse 10,000 levels
+        while let Value::Array(mut items) = value {
+            value = items.pop().unwrap_or(Value::Null);
+        }
+    }
+
+    #[test]
+    fn test_disabled_lint() {
+        let settings = LintSettings {
+            numeric_string: false,
+            ..LintSettings::default()
+        };
+        assert!(run_lints_with(&json!({"count": "42"}), &settings).is_empty());
+    }
+}
@@ -1,14 +1,77 @@
 use egui::{Color32, Pos2, Rect, Stroke, StrokeKind, Vec2};
+use serde::{Deserialize, Serialize};
 
 use super::graph::GraphNode;
 
+/// Height of the title strip above the minimap body
+const TITLE_HEIGHT: f32 = 18.0;
+/// Size of the resize handle square
+const RESIZE_HANDLE_SIZE: f32 = 10.0;
+/// Allowed range for the minimap body size
+const MIN_SIZE: Vec2 = Vec2::new(120.0, 90.0);
+const MAX_SIZE: Vec2 = Vec2::new(600.0, 450.0);
+
+/// Corner of the canvas the minimap is anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MinimapCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl MinimapCorner {
+    /// Next corner, clockwise
+    pub fn next(self) -> Self {
+        match self {
+            MinimapCorner::TopLeft => MinimapCorner::TopRight,
+            MinimapCorner::TopRight => MinimapCorner::BottomRight,
+            MinimapCorner::BottomRight => MinimapCorner::BottomLeft,
+            MinimapCorner::BottomLeft => MinimapCorner::TopLeft,
+        }
+    }
+
+    fn is_right(self) -> bool {
+        matches!(self, MinimapCorner::TopRight | MinimapCorner::BottomRight)
+    }
+
+    fn is_bottom(self) -> bool {
+        matches!(self, MinimapCorner::BottomLeft | MinimapCorner::BottomRight)
+    }
+}
+
+/// Persisted minimap preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MinimapSettings {
+    pub visible: bool,
+    pub collapsed: bool,
+    pub corner: MinimapCorner,
+    /// Body size in points (width, height)
+    pub size: [f32; 2],
+    /// Background opacity (0.0..=1.0)
+    pub opacity: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        let minimap = Minimap::default();
+        minimap.settings()
+    }
+}
+
 /// Minimap for graph visualization
-/// Displays a small overview of the entire graph in the bottom-right corner
+/// Displays a small overview of the entire graph in a corner of the canvas
 pub struct Minimap {
-    /// Size of the minimap
+    /// Size of the minimap body (without the title strip)
     size: Vec2,
     /// Whether the minimap is visible
     visible: bool,
+    /// Whether the minimap is collapsed to its title strip
+    collapsed: bool,
+    /// Canvas corner the minimap is anchored to
+    corner: MinimapCorner,
     /// Background opacity (0.0 = transparent, 1.0 = opaque)
     background_opacity: f32,
 }
@@ -18,6 +81,8 @@ impl Default for Minimap {
         Self {
             size: Vec2::new(200.0, 150.0),
             visible: true,
+            collapsed: false,
+            corner: MinimapCorner::default(),
             background_opacity: 0.8,
         }
     }
@@ -43,10 +108,206 @@ impl Minimap {
         self.visible
     }
 
+    /// Set the background opacity (clamped to 0.0..=1.0)
+    pub fn set_background_opacity(&mut self, opacity: f32) {
+        self.background_opacity = if opacity.is_nan() {
+            1.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+    }
+
+    /// Background opacity (0.0 = transparent, 1.0 = opaque)
+    pub fn background_opacity(&self) -> f32 {
+        self.background_opacity
+    }
+
+    /// Alpha channel for the background fill
+    fn background_alpha(&self) -> u8 {
+        (255.0 * self.background_opacity).round() as u8
+    }
+
+    /// Collapse the minimap to its title strip (or expand it)
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
+    /// Check if minimap is collapsed
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Anchor the minimap to a corner of the canvas
+    pub fn set_corner(&mut self, corner: MinimapCorner) {
+        self.corner = corner;
+    }
+
+    /// Canvas corner the minimap is anchored to
+    pub fn corner(&self) -> MinimapCorner {
+        self.corner
+    }
+
+    /// Set the minimap body size (clamped to a sensible range)
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size.clamp(MIN_SIZE, MAX_SIZE);
+    }
+
+    /// Minimap body size
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Current preferences (for persistence)
+    pub fn settings(&self) -> MinimapSettings {
+        MinimapSettings {
+            visible: self.visible,
+            collapsed: self.collapsed,
+            corner: self.corner,
+            size: [self.size.x, self.size.y],
+            opacity: self.background_opacity,
+        }
+    }
+
+    /// Apply persisted preferences
+    pub fn apply_settings(&mut self, settings: &MinimapSettings) {
+        self.visible = settings.visible;
+        self.collapsed = settings.collapsed;
+        self.corner = settings.corner;
+        self.set_size(Vec2::new(settings.size[0], settings.size[1]));
+        self.set_background_opacity(settings.opacity);
+    }
+
+    /// Outer rect (title strip + body, or just the strip when collapsed) in its corner
+    fn outer_rect(&self, canvas_rect: Rect) -> Rect {
+        let padding = 10.0;
+        let body_height = if self.collapsed { 0.0 } else { self.size.y };
+        let outer_size = Vec2::new(self.size.x, TITLE_HEIGHT + body_height);
+
+        let x = if self.corner.is_right() {
+            canvas_rect.max.x - outer_size.x - padding
+        } else {
+            canvas_rect.min.x + padding
+        };
+        let y = if self.corner.is_bottom() {
+            canvas_rect.max.y - outer_size.y - padding
+        } else {
+            canvas_rect.min.y + padding
+        };
+        Rect::from_min_size(Pos2::new(x, y), outer_size)
+    }
+
+    /// Render the title strip with collapse and corner buttons
+    fn render_title_strip(&mut self, ui: &mut egui::Ui, painter: &egui::Painter, strip: Rect) {
+        painter.rect_filled(
+            strip,
+            3.0,
+            Color32::from_rgba_unmultiplied(45, 45, 45, self.background_alpha()),
+        );
+        painter.text(
+            Pos2::new(strip.min.x + 22.0, strip.center().y),
+            egui::Align2::LEFT_CENTER,
+            "Minimap",
+            egui::FontId::proportional(11.0),
+            Color32::from_gray(200),
+        );
+
+        // Collapse caret
+        let caret_rect = Rect::from_min_size(strip.min, Vec2::splat(TITLE_HEIGHT));
+        let caret = ui
+            .interact(
+                caret_rect,
+                ui.id().with("minimap_caret"),
+                egui::Sense::click(),
+            )
+            .on_hover_text(if self.collapsed { "Expand" } else { "Collapse" });
+        painter.text(
+            caret_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            if self.collapsed { "▸" } else { "▾" },
+            egui::FontId::proportional(12.0),
+            if caret.hovered() {
+                Color32::WHITE
+            } else {
+                Color32::from_gray(180)
+            },
+        );
+        if caret.clicked() {
+            self.collapsed = !self.collapsed;
+        }
+
+        // Corner button (cycles clockwise)
+        let corner_rect = Rect::from_min_size(
+            Pos2::new(strip.max.x - TITLE_HEIGHT, strip.min.y),
+            Vec2::splat(TITLE_HEIGHT),
+        );
+        let corner = ui
+            .interact(
+                corner_rect,
+                ui.id().with("minimap_corner"),
+                egui::Sense::click(),
+            )
+            .on_hover_text("Move to next corner");
+        painter.text(
+            corner_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "⤡",
+            egui::FontId::proportional(12.0),
+            if corner.hovered() {
+                Color32::WHITE
+            } else {
+                Color32::from_gray(180)
+            },
+        );
+        if corner.clicked() {
+            self.corner = self.corner.next();
+        }
+    }
+
+    /// Render the resize handle on the corner facing the canvas center
+    fn render_resize_handle(&mut self, ui: &mut egui::Ui, painter: &egui::Painter, outer: Rect) {
+        let x = if self.corner.is_right() {
+            outer.min.x
+        } else {
+            outer.max.x - RESIZE_HANDLE_SIZE
+        };
+        let y = if self.corner.is_bottom() {
+            outer.min.y
+        } else {
+            outer.max.y - RESIZE_HANDLE_SIZE
+        };
+        let handle_rect = Rect::from_min_size(Pos2::new(x, y), Vec2::splat(RESIZE_HANDLE_SIZE));
+
+        let response = ui
+            .interact(
+                handle_rect,
+                ui.id().with("minimap_resize"),
+                egui::Sense::drag(),
+            )
+            .on_hover_cursor(egui::CursorIcon::ResizeNwSe);
+        painter.rect_filled(
+            handle_rect.shrink(2.0),
+            1.0,
+            if response.hovered() || response.dragged() {
+                Color32::from_rgb(255, 200, 0)
+            } else {
+                Color32::from_gray(120)
+            },
+        );
+
+        if response.dragged() {
+            // Growing away from the anchored corner
+            let sign = Vec2::new(
+                if self.corner.is_right() { -1.0 } else { 1.0 },
+                if self.corner.is_bottom() { -1.0 } else { 1.0 },
+            );
+            self.set_size(self.size + response.drag_delta() * sign);
+        }
+    }
+
     /// Render the minimap
     /// Returns Some(new_offset) if the user clicked on the minimap to navigate
     pub fn render(
-        &self,
+        &mut self,
         ui: &mut egui::Ui,
         painter: &egui::Painter,
         canvas_rect: Rect,
@@ -58,13 +319,16 @@ impl Minimap {
             return None;
         }
 
-        // Calculate minimap position (bottom-right corner with padding)
-        let padding = 10.0;
-        let minimap_pos = Pos2::new(
-            canvas_rect.max.x - self.size.x - padding,
-            canvas_rect.max.y - self.size.y - padding,
-        );
-        let minimap_rect = Rect::from_min_size(minimap_pos, self.size);
+        let outer = self.outer_rect(canvas_rect);
+        let strip = Rect::from_min_size(outer.min, Vec2::new(outer.width(), TITLE_HEIGHT));
+
+        if self.collapsed {
+            self.render_title_strip(ui, painter, strip);
+            return None;
+        }
+
+        // Minimap body below the title strip
+        let minimap_rect = Rect::from_min_size(Pos2::new(outer.min.x, strip.max.y), self.size);
 
         // Calculate bounds of all nodes to determine the scale
         let (min_bounds, max_bounds) = self.calculate_graph_bounds(nodes);
@@ -81,8 +345,7 @@ impl Minimap {
         let scale = scale_x.min(scale_y).min(0.5); // Cap at 0.5 for readability
 
         // Draw minimap background
-        let bg_color =
-            Color32::from_rgba_unmultiplied(30, 30, 30, (255.0 * self.background_opacity) as u8);
+        let bg_color = Color32::from_rgba_unmultiplied(30, 30, 30, self.background_alpha());
         painter.rect_filled(minimap_rect, 3.0, bg_color);
         painter.rect_stroke(
             minimap_rect,
@@ -145,24 +408,44 @@ impl Minimap {
         );
 
         // Handle minimap interaction
-        let response = ui.interact(minimap_rect, ui.id().with("minimap"), egui::Sense::click());
+        // Single clicks recenter; dragging the viewport rectangle pans 1:1,
+        // dragging elsewhere keeps recentering on the pointer every frame
+        let response = ui.interact(
+            minimap_rect,
+            ui.id().with("minimap"),
+            egui::Sense::click_and_drag(),
+        );
 
-        if response.clicked()
+        let grabbed_viewport = ui
+            .input(|i| i.pointer.press_origin())
+            .is_some_and(|origin| clamped_viewport.contains(origin));
+        let mut new_offset = None;
+        if response.dragged() && grabbed_viewport {
+            new_offset = Some(self.drag_to_offset(
+                response.drag_delta(),
+                scale,
+                current_zoom,
+                current_offset,
+            ));
+        } else if (response.clicked() || response.dragged())
             && let Some(click_pos) = response.interact_pointer_pos()
         {
             // Convert click position to world coordinates
-            let new_offset = self.minimap_to_world_offset(
+            new_offset = Some(self.minimap_to_world_offset(
                 click_pos,
                 minimap_rect,
                 canvas_rect,
                 min_bounds,
                 scale,
                 current_zoom,
-            );
-            return Some(new_offset);
+            ));
         }
 
-        None
+        // Title strip and resize handle go on top of the body interaction
+        self.render_title_strip(ui, painter, strip);
+        self.render_resize_handle(ui, painter, outer);
+
+        new_offset
     }
 
     /// Calculate the bounding box of all nodes
@@ -262,6 +545,20 @@ impl Minimap {
         -target_world_pos * current_zoom
     }
 
+    /// Convert a drag of the viewport rectangle (in minimap space) to a new offset
+    /// Inverse of `world_to_minimap`: a minimap delta is `world_delta * scale`, and the
+    /// viewport's world position moves opposite to the offset (`world = -offset / zoom`)
+    fn drag_to_offset(
+        &self,
+        minimap_delta: Vec2,
+        scale: f32,
+        current_zoom: f32,
+        current_offset: Vec2,
+    ) -> Vec2 {
+        let world_delta = minimap_delta / scale;
+        current_offset - world_delta * current_zoom
+    }
+
     /// Clamp a rectangle to stay within bounds
     fn clamp_rect_to_bounds(&self, rect: Rect, bounds: Rect) -> Rect {
         let min_x = rect.min.x.max(bounds.min.x);
@@ -278,3 +575,82 @@ impl Minimap {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corner_positions() {
+        let canvas_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+        let mut minimap = Minimap::new();
+
+        let outer = minimap.outer_rect(canvas_rect);
+        assert_eq!(outer.max, Pos2::new(790.0, 590.0));
+
+        minimap.set_corner(MinimapCorner::TopLeft);
+        assert_eq!(minimap.outer_rect(canvas_rect).min, Pos2::new(10.0, 10.0));
+
+        // Collapsed to the title strip, still anchored to the bottom edge
+        minimap.set_corner(MinimapCorner::BottomLeft);
+        minimap.set_collapsed(true);
+        let outer = minimap.outer_rect(canvas_rect);
+        assert_eq!(outer.height(), TITLE_HEIGHT);
+        assert_eq!(outer.max.y, 590.0);
+    }
+
+    #[test]
+    fn test_background_opacity_is_clamped() {
+        let mut minimap = Minimap::new();
+        assert_eq!(minimap.background_alpha(), 204);
+
+        minimap.set_background_opacity(1.5);
+        assert_eq!(minimap.background_opacity(), 1.0);
+        assert_eq!(minimap.background_alpha(), 255);
+
+        minimap.set_background_opacity(-0.2);
+        assert_eq!(minimap.background_opacity(), 0.0);
+        assert_eq!(minimap.background_alpha(), 0);
+
+        minimap.set_background_opacity(0.5);
+        assert_eq!(minimap.background_alpha(), 128);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut minimap = Minimap::new();
+        minimap.set_corner(MinimapCorner::TopRight);
+        minimap.set_collapsed(true);
+        minimap.set_size(Vec2::new(1000.0, 300.0));
+        assert_eq!(minimap.size(), Vec2::new(600.0, 300.0));
+
+        let mut restored = Minimap::new();
+        restored.apply_settings(&minimap.settings());
+        assert_eq!(restored.settings(), minimap.settings());
+    }
+
+    #[test]
+    fn test_drag_to_offset_is_one_to_one() {
+        let minimap = Minimap::new();
+        let minimap_rect = Rect::from_min_size(Pos2::ZERO, minimap.size);
+        let canvas_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+        let (scale, zoom, offset) = (0.25, 2.0, Vec2::new(-100.0, 40.0));
+
+        let viewport = |offset: Vec2| {
+            minimap.calculate_viewport_in_minimap(
+                canvas_rect,
+                minimap_rect,
+                Vec2::ZERO,
+                scale,
+                zoom,
+                offset,
+            )
+        };
+
+        // Dragging the viewport rectangle by N minimap pixels moves it by exactly N
+        let delta = Vec2::new(12.0, -8.0);
+        let new_offset = minimap.drag_to_offset(delta, scale, zoom, offset);
+        let moved = viewport(new_offset).min - viewport(offset).min;
+        assert!((moved - delta).length() < 1e-3);
+    }
+}
@@ -1,10 +1,32 @@
 /// JSON Editor module
 ///
 /// Provides a JSON editor with syntax checking, folding, and pretty printing
+pub mod diff;
 pub mod editor;
+pub mod error;
+pub mod event;
 pub mod graph;
+pub mod highlight;
+pub mod lint;
 pub mod minimap;
+pub mod number_format;
+pub mod source_map;
+pub mod stats;
+pub mod tree_view;
 
-pub use editor::JsonEditor;
-pub use graph::{JsonGraph, ModifyOperation};
-pub use minimap::Minimap;
+/// Nesting depth at which recursive walks over a document stop descending
+/// Matches serde_json's parse limit, so only edits can nest values deeper
+pub const MAX_DEPTH: usize = 128;
+
+pub use diff::{DiffEntry, DiffKind, diff};
+pub use editor::{
+    JsonEditor, ViewMode, all_leaf_paths, format_json_path, json_pointer, parse_ndjson,
+    type_signature,
+};
+pub use error::EditorError;
+pub use event::EditEvent;
+pub use graph::{CopyRequest, JsonGraph, LayoutDirection, ModifyOperation};
+pub use lint::{Lint, LintKind, LintSettings, deepest_path, run_lints};
+pub use minimap::{Minimap, MinimapCorner, MinimapSettings};
+pub use number_format::{NumberFormat, format_number_display};
+pub use stats::{DocumentStats, JsonStats};
@@ -0,0 +1,110 @@
+use serde_json::Number;
+
+/// Largest precision offered for decimal numbers
+pub const MAX_NUMBER_PRECISION: usize = 10;
+
+/// Display-only formatting for numbers in the tree view
+/// The default leaves numbers exactly as written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Round decimal numbers to this many fraction digits (None = as written)
+    /// Integers are never rounded, so they stay exact
+    pub precision: Option<usize>,
+    /// Group the integer part with thousands separators (1,234,567)
+    pub thousands_separator: bool,
+}
+
+/// Format a number for display
+/// Never used for the document text: it only changes how a number is shown
+pub fn format_number_display(n: &Number, opts: &NumberFormat) -> String {
+    let token = n.to_string();
+    let is_decimal = token.contains(['.', 'e', 'E']);
+
+    let text = match opts.precision {
+        Some(precision) if is_decimal => match n.as_f64() {
+            Some(f) if f.is_finite() => format!("{:.*}", precision.min(MAX_NUMBER_PRECISION), f),
+            _ => token,
+        },
+        _ => token,
+    };
+
+    if opts.thousands_separator {
+        group_thousands(&text)
+    } else {
+        text
+    }
+}
+
+/// Insert `,` every three digits in the integer part of a numeric string
+/// The sign, fraction and exponent are left untouched
+fn group_thousands(text: &str) -> String {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let digits_end = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let (digits, rest) = unsigned.split_at(digits_end);
+
+    let mut grouped = String::with_capacity(text.len() + digits.len() / 3);
+    grouped.push_str(sign);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(rest);
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(text: &str) -> Number {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_default_format_keeps_numbers_as_written() {
+        let opts = NumberFormat::default();
+        assert_eq!(format_number_display(&number("42"), &opts), "42");
+        assert_eq!(
+            format_number_display(&number("12345678901234567890"), &opts),
+            "12345678901234567890"
+        );
+        assert_eq!(format_number_display(&number("0.125"), &opts), "0.125");
+    }
+
+    #[test]
+    fn test_precision_rounds_decimals_only() {
+        let opts = NumberFormat {
+            precision: Some(2),
+            thousands_separator: false,
+        };
+        assert_eq!(format_number_display(&number("3.14159"), &opts), "3.14");
+        assert_eq!(format_number_display(&number("2.5"), &opts), "2.50");
+        assert_eq!(format_number_display(&number("1e3"), &opts), "1000.00");
+        // Integers stay exact
+        assert_eq!(
+            format_number_display(&number("12345678901234567890"), &opts),
+            "12345678901234567890"
+        );
+    }
+
+    #[test]
+    fn test_thousands_separator() {
+        let opts = NumberFormat {
+            precision: Some(2),
+            thousands_separator: true,
+        };
+        assert_eq!(
+            format_number_display(&number("-1234567.891"), &opts),
+            "-1,234,567.89"
+        );
+        assert_eq!(format_number_display(&number("999"), &opts), "999");
+        assert_eq!(format_number_display(&number("1000"), &opts), "1,000");
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::MAX_DEPTH;
+use super::highlight::{self, Token, TokenKind};
+
+/// Location of a value in the source text
+/// Spans refer to their parent by index, so a path is only built when asked for
+/// (see `span_path`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueSpan {
+    /// Index of the enclosing Object/Array's span (None for the root value)
+    pub parent: Option<usize>,
+    /// Last JSON path segment: the decoded key or the array index (empty for the root)
+    pub segment: String,
+    /// Nesting depth (0 for the root value)
+    pub depth: usize,
+    /// Byte range of the object key (including quotes), if the value is an object member
+    pub key: Option<Range<usize>>,
+    /// Byte range of the value (the whole container for objects and arrays)
+    pub value: Range<usize>,
+    /// Index just past the span's last descendant
+    end: usize,
+}
+
+/// Object or Array still open while scanning
+struct OpenContainer {
+    /// Index of the container's span
+    span: usize,
+    /// Closing bracket
+    close: &'static str,
+    /// Key waiting for its value (Objects only)
+    key: Option<(String, Range<usize>)>,
+    /// Number of items so far (Arrays only)
+    items: usize,
+}
+
+/// Map every value in the text to its byte range
+/// Spans are returned in source order (parents before children)
+/// The scan is lenient: comments and trailing commas are skipped, and
+/// malformed input yields a best-effort partial map instead of failing
+/// It keeps an explicit stack, so arbitrarily deep (or unbalanced) input is fine
+pub fn value_spans(text: &str) -> Vec<ValueSpan> {
+    let tokens: Vec<Token> = highlight::tokenize(text)
+        .into_iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+
+    let mut spans: Vec<ValueSpan> = Vec::new();
+    let mut stack: Vec<OpenContainer> = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index];
+        let token_text = &text[token.start..token.end];
+        let is_close =
+            token.kind == TokenKind::Punctuation && (token_text == "}" || token_text == "]");
+
+        let (parent, segment, key) = match stack.last_mut() {
+            Some(open) if is_close => {
+                // A mismatched close ends the container before it and is left for the parent
+                let value_end = if token_text == open.close {
+                    index += 1;
+                    token.end
+                } else {
+                    tokens[index - 1].end
+                };
+                let span = open.span;
+                spans[span].value.end = value_end;
+                spans[span].end = spans.len();
+                stack.pop();
+                if stack.is_empty() {
+                    // Only the first top-level value is mapped
+                    break;
+                }
+                continue;
+            }
+            Some(_) if token_text == "," || token_text == ":" => {
+                index += 1;
+                continue;
+            }
+            Some(open) if open.close == "}" => match open.key.take() {
+                Some((key, key_range)) => (Some(open.span), key, Some(key_range)),
+                None => {
+                    if matches!(token.kind, TokenKind::Key | TokenKind::String) {
+                        open.key = Some((decode_key(token_text), token.start..token.end));
+                    }
+                    index += 1;
+                    continue;
+                }
+            },
+            Some(open) => {
+                open.items += 1;
+                (Some(open.span), (open.items - 1).to_string(), None)
+            }
+            // A stray close before the root value
+            None if is_close => break,
+            None => (None, String::new(), None),
+        };
+
+        let span = spans.len();
+        spans.push(ValueSpan {
+            parent,
+            segment,
+            depth: stack.len(),
+            key,
+            value: token.start..token.end,
+            end: span + 1,
+        });
+        index += 1;
+
+        if token_text == "{" || token_text == "[" {
+            stack.push(OpenContainer {
+                span,
+                close: if token_text == "{" { "}" } else { "]" },
+                key: None,
+                items: 0,
+            });
+        } else if stack.is_empty() {
+            break;
+        }
+    }
+
+    // Containers left open run to the end of the text
+    let text_end = tokens.last().map_or(0, |token| token.end);
+    for open in stack {
+        spans[open.span].value.end = text_end;
+        spans[open.span].end = spans.len();
+    }
+    spans
+}
+
+/// Indices of the direct members of the span at `index`, in source order
+fn children(spans: &[ValueSpan], index: usize) -> impl Iterator<Item = usize> + '_ {
+    let end = spans[index].end;
+    let first = Some(index + 1).filter(|&child| child < end);
+    std::iter::successors(first, move |&child| {
+        Some(spans[child].end).filter(|&next| next < end)
+    })
+}
+
+/// Index of the span of the value at a JSON path
+/// With duplicate keys the last occurrence wins, matching serde_json
+pub fn find_span_index(spans: &[ValueSpan], path: &[String]) -> Option<usize> {
+    if spans.is_empty() {
+        return None;
+    }
+    path.iter().try_fold(0, |current, segment| {
+        children(spans, current)
+            .filter(|&child| spans[child].segment == *segment)
+            .last()
+    })
+}
+
+/// Find the span of the value at a JSON path
+/// With duplicate keys the last occurrence wins, matching serde_json
+pub fn find_span<'a>(spans: &'a [ValueSpan], path: &[String]) -> Option<&'a ValueSpan> {
+    find_span_index(spans, path).map(|index| &spans[index])
+}
+
+/// Spans of the direct members of the Object/Array at a JSON path, in source order
+pub fn member_spans<'a>(spans: &'a [ValueSpan], path: &[String]) -> Vec<&'a ValueSpan> {
+    find_span_index(spans, path).map_or_else(Vec::new, |index| {
+        children(spans, index).map(|child| &spans[child]).collect()
+    })
+}
+
+/// JSON path of the span at `index`
+pub fn span_path(spans: &[ValueSpan], index: usize) -> Vec<String> {
+    let mut path: Vec<String> =
+        std::iter::successors(Some(index), |&current| spans[current].parent)
+            .take_while(|&current| spans[current].parent.is_some())
+            .map(|current| spans[current].segment.clone())
+            .collect();
+    path.reverse();
+    path
+}
+
+/// Byte ranges of the tokens other than whitespace and comments, in source order
+pub fn significant_tokens(text: &str) -> Vec<Range<usize>> {
+    highlight::tokenize(text)
+        .into_iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .map(|t| t.start..t.end)
+        .collect()
+}
+
+/// Pretty-print a value with object keys in the order they appear in `text`
+/// Doesn't rely on the map's own ordering, so it works without `preserve_order`
+/// Keys missing from the text (e.g. just added) follow in map order
+pub fn to_pretty_string_in_source_order(value: &Value, text: &str, indent: &str) -> Option<String> {
+    let spans = value_spans(text);
+    let root = (!spans.is_empty()).then_some(0);
+    let mut out = String::new();
+    write_in_source_order(value, &spans, root, indent, 0, &mut out)?;
+    Some(out)
+}
+
+/// Object key (None for Array items), value and source span of a member being written
+type SourceMember<'a> = (Option<&'a String>, &'a Value, Option<usize>);
+
+/// Recursive writer for `to_pretty_string_in_source_order`
+/// `span` is the value's span in the source, if it has one
+/// Matches serde_json's PrettyFormatter layout; values at `MAX_DEPTH` are written
+/// by serde_json in map order
+fn write_in_source_order(
+    value: &Value,
+    spans: &[ValueSpan],
+    span: Option<usize>,
+    indent: &str,
+    depth: usize,
+    out: &mut String,
+) -> Option<()> {
+    if depth >= MAX_DEPTH {
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut buffer = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        value.serialize(&mut serializer).ok()?;
+        let pretty = String::from_utf8(buffer).ok()?;
+        out.push_str(&pretty.replace('\n', &format!("\n{}", indent.repeat(depth))));
+        return Some(());
+    }
+
+    let source_members: Vec<usize> =
+        span.map_or_else(Vec::new, |span| children(spans, span).collect());
+
+    let (open, close, members): (&str, &str, Vec<SourceMember>) = match value {
+        Value::Object(map) => {
+            // With duplicate keys the last occurrence holds the value, matching serde_json
+            let mut member_spans: HashMap<&str, usize> = HashMap::new();
+            let mut keys: Vec<&String> = Vec::new();
+            for &child in &source_members {
+                let segment = spans[child].segment.as_str();
+                if member_spans.insert(segment, child).is_none()
+                    && let Some((key, _)) = map.get_key_value(segment)
+                {
+                    keys.push(key);
+                }
+            }
+            keys.extend(
+                map.keys()
+                    .filter(|key| !member_spans.contains_key(key.as_str())),
+            );
+            let members = keys
+                .into_iter()
+                .map(|key| {
+                    (
+                        Some(key),
+                        &map[key],
+                        member_spans.get(key.as_str()).copied(),
+                    )
+                })
+                .collect();
+            ("{", "}", members)
+        }
+        Value::Array(items) => {
+            let members = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (None, item, source_members.get(i).copied()))
+                .collect();
+            ("[", "]", members)
+        }
+        _ => {
+            out.push_str(&serde_json::to_string(value).ok()?);
+            return Some(());
+        }
+    };
+
+    out.push_str(open);
+    if members.is_empty() {
+        out.push_str(close);
+        return Some(());
+    }
+    for (i, (key, member, member_span)) in members.into_iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        out.push_str(&indent.repeat(depth + 1));
+        if let Some(key) = key {
+            out.push_str(&serde_json::to_string(key).ok()?);
+            out.push_str(": ");
+        }
+        write_in_source_order(member, spans, member_span, indent, depth + 1, out)?;
+    }
+    out.push('\n');
+    out.push_str(&indent.repeat(depth));
+    out.push_str(close);
+    Some(())
+}
+
+/// Decode a quoted key token, falling back to the raw text between the quotes
+fn decode_key(token_text: &str) -> String {
+    serde_json::from_str::<String>(token_text).unwrap_or_else(|_| {
+        token_text
+            .trim_start_matches('"')
+            .trim_end_matches('"')
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pretty_in_source_order() {
+        // Build the map in a different order than the text
+        let mut map = serde_json::Map::new();
+        map.insert("a".to_string(), Value::from(2));
+        map.insert("b".to_string(), Value::from(1));
+        let value = Value::Object(map);
+
+        let pretty = to_pretty_string_in_source_order(&value, r#"{"b":1,"a":2}"#, "  ").unwrap();
+        assert_eq!(pretty, "{\n  \"b\": 1,\n  \"a\": 2\n}");
+    }
+
+    #[test]
+    fn test_pretty_in_source_order_matches_serde_layout() {
+        let text = r#"{"z": [1, {"y": null, "x": []}], "e": {}, "n": {"k": "v"}}"#;
+        let value: Value = serde_json::from_str(text).unwrap();
+        let pretty = to_pretty_string_in_source_order(&value, text, "    ").unwrap();
+        assert_eq!(
+            pretty,
+            serde_json::to_string_pretty(&value)
+                .unwrap()
+                .replace("  ", "    ")
+        );
+
+        // Keys missing from the text are appended
+        let mut value = value;
+        value["n"]["new"] = Value::Bool(true);
+        let pretty = to_pretty_string_in_source_order(&value, text, "  ").unwrap();
+        assert!(pretty.contains("\"k\": \"v\",\n    \"new\": true"));
+    }
+
+    #[test]
+    fn test_value_spans_nested() {
+        let text = r#"{"a": {"name": "x"}, "list": [1, {"name": 2}]}"#;
+        let spans = value_spans(text);
+
+        let span = find_span(&spans, &path(&["list", "1", "name"])).unwrap();
+        assert_eq!(&text[span.value.clone()], "2");
+        assert_eq!(&text[span.key.clone().unwrap()], "\"name\"");
+
+        let span = find_span(&spans, &path(&["a"])).unwrap();
+        assert_eq!(&text[span.value.clone()], r#"{"name": "x"}"#);
+
+        let root = find_span(&spans, &[]).unwrap();
+        assert_eq!(root.value, 0..text.len());
+    }
+
+    #[test]
+    fn test_value_spans_skip_comments_and_trailing_commas() {
+        let text = "{\n  // comment with \"quotes\": 1\n  \"a\": /* inline */ [1, 2,],\n}";
+        let spans = value_spans(text);
+
+        let span = find_span(&spans, &path(&["a", "1"])).unwrap();
+        assert_eq!(&text[span.value.clone()], "2");
+        assert!(find_span(&spans, &path(&["quotes"])).is_none());
+    }
+
+    #[test]
+    fn test_value_spans_mismatched_close() {
+        // The `}` after the array closes the array and the object around it
+        let text = r#"{"a": {"x": [1, 2}, "b": 3}"#;
+        let spans = value_spans(text);
+
+        let span = find_span(&spans, &path(&["a", "x"])).unwrap();
+        assert_eq!(&text[span.value.clone()], "[1, 2");
+        let span = find_span(&spans, &path(&["a"])).unwrap();
+        assert_eq!(&text[span.value.clone()], r#"{"x": [1, 2}"#);
+        assert!(find_span(&spans, &path(&["b"])).is_some());
+        assert_eq!(span_path(&spans, spans.len() - 1), path(&["b"]));
+    }
+
+    #[test]
+    fn test_value_spans_deep_unbalanced_input() {
+        let depth = 100_000;
+        let text = format!("{}1", "[".repeat(depth));
+        let spans = value_spans(&text);
+        assert_eq!(spans.len(), depth + 1);
+
+        let deepest = spans.len() - 1;
+        assert_eq!(spans[deepest].depth, depth);
+        assert_eq!(&text[spans[deepest].value.clone()], "1");
+        assert_eq!(spans[0].value, 0..text.len());
+
+        let deepest_path = span_path(&spans, deepest);
+        assert_eq!(deepest_path.len(), depth);
+        assert_eq!(find_span_index(&spans, &deepest_path), Some(deepest));
+    }
+}
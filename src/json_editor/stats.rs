@@ -0,0 +1,226 @@
+use super::MAX_DEPTH;
+use serde_json::Value;
+
+/// Counts of value types and the shape of a JSON document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonStats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// Deepest container nesting (0 for a scalar document)
+    pub max_depth: usize,
+    /// Whether values below `MAX_DEPTH` were skipped
+    /// When set, `max_depth` is a lower bound
+    pub depth_truncated: bool,
+}
+
+impl JsonStats {
+    /// Total number of values (containers and scalars)
+    pub fn total(&self) -> usize {
+        self.objects + self.arrays + self.strings + self.numbers + self.booleans + self.nulls
+    }
+
+    /// Maximum depth for display (e.g. "3", or "≥ 128" if truncated)
+    pub fn depth_display(&self) -> String {
+        if self.depth_truncated {
+            format!("≥ {}", self.max_depth)
+        } else {
+            self.max_depth.to_string()
+        }
+    }
+}
+
+/// Size of the document text plus the value statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+    /// Number of lines in the text
+    pub lines: usize,
+    /// Number of characters (not bytes) in the text
+    pub chars: usize,
+    /// Number of keys in the root object (None if the root isn't an object)
+    pub top_level_keys: Option<usize>,
+    /// Value statistics (None if the document is invalid)
+    pub values: Option<JsonStats>,
+}
+
+/// Cache of the document statistics
+/// Recomputed only when the editor's revision moves on, not every frame
+#[derive(Default)]
+pub struct StatsCache {
+    /// Editor revision the cached stats were computed for
+    revision: Option<u64>,
+    /// Cached statistics
+    stats: DocumentStats,
+}
+
+impl StatsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the statistics for the text at `revision`, recomputing only if it changed
+    /// The revision covers the parsed value too (e.g. switching NDJSON mode)
+    pub fn stats(&mut self, revision: u64, text: &str, value: Option<&Value>) -> DocumentStats {
+        self.stats_with(revision, text, value, compute_stats)
+    }
+
+    /// Same as `stats`, with an injectable value walk
+    fn stats_with(
+        &mut self,
+        revision: u64,
+        text: &str,
+        value: Option<&Value>,
+        walk: impl FnOnce(&Value) -> JsonStats,
+    ) -> DocumentStats {
+        if self.revision != Some(revision) {
+            self.stats = DocumentStats {
+                lines: text.lines().count().max(1),
+                chars: text.chars().count(),
+                top_level_keys: match value {
+                    Some(Value::Object(map)) => Some(map.len()),
+                    _ => None,
+                },
+                values: value.map(walk),
+            };
+            self.revision = Some(revision);
+        }
+
+        self.stats.clone()
+    }
+}
+
+/// Walk a value once (iteratively, without recursion) and count its contents
+pub fn compute_stats(value: &Value) -> JsonStats {
+    compute_stats_with_limit(value, MAX_DEPTH)
+}
+
+/// Like `compute_stats`, but with a custom depth limit
+fn compute_stats_with_limit(value: &Value, max_depth: usize) -> JsonStats {
+    let mut stats = JsonStats::default();
+    // Depth of a value = number of containers enclosing it
+    let mut stack = vec![(value, 0usize)];
+
+    while let Some((value, depth)) = stack.pop() {
+        match value {
+            Value::Object(map) => {
+                stats.objects += 1;
+                descend(&mut stack, &mut stats, map.values(), depth, max_depth);
+            }
+            Value::Array(arr) => {
+                stats.arrays += 1;
+                descend(&mut stack, &mut stats, arr.iter(), depth, max_depth);
+            }
+            Value::String(_) => stats.strings += 1,
+            Value::Number(_) => stats.numbers += 1,
+            Value::Bool(_) => stats.booleans += 1,
+            Value::Null => stats.nulls += 1,
+        }
+    }
+
+    stats
+}
+
+/// Record a container at `depth` and queue its children, unless they are past the limit
+fn descend<'a>(
+    stack: &mut Vec<(&'a Value, usize)>,
+    stats: &mut JsonStats,
+    children: impl ExactSizeIterator<Item = &'a Value>,
+    depth: usize,
+    max_depth: usize,
+) {
+    stats.max_depth = stats.max_depth.max(depth + 1);
+    if depth + 1 >= max_depth {
+        stats.depth_truncated |= children.len() > 0;
+    } else {
+        stack.extend(children.map(|child| (child, depth + 1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_counts() {
+        let value = json!({"a": [1, 2.5, "x"], "b": {"c": null, "d": true}, "e": []});
+        let stats = compute_stats(&value);
+        assert_eq!(
+            stats,
+            JsonStats {
+                objects: 2,
+                arrays: 2,
+                strings: 1,
+                numbers: 2,
+                booleans: 1,
+                nulls: 1,
+                max_depth: 2,
+                depth_truncated: false,
+            }
+        );
+        assert_eq!(stats.total(), 9);
+        assert_eq!(stats.depth_display(), "2");
+        assert_eq!(compute_stats(&json!(1)).max_depth, 0);
+    }
+
+    #[test]
+    fn test_cache_recomputes_only_on_change() {
+        let mut cache = StatsCache::new();
+        let mut walks = 0;
+        let text = "{\n  \"a\": 1,\n  \"b\": [true]\n}";
+        let value: Value = serde_json::from_str(text).unwrap();
+
+        // Several frames with the same text
+        for _ in 0..5 {
+            let stats = cache.stats_with(1, text, Some(&value), |v| {
+                walks += 1;
+                compute_stats(v)
+            });
+            assert_eq!(stats.lines, 4);
+            assert_eq!(stats.chars, text.chars().count());
+            assert_eq!(stats.top_level_keys, Some(2));
+            assert_eq!(stats.values.map(|v| v.total()), Some(4));
+        }
+        assert_eq!(walks, 1);
+
+        // An edit that breaks the document
+        let stats = cache.stats_with(2, "{\"a\": 한", None, |v| {
+            walks += 1;
+            compute_stats(v)
+        });
+        assert_eq!(walks, 1);
+        assert_eq!(stats.chars, 7);
+        assert_eq!(stats.top_level_keys, None);
+        assert_eq!(stats.values, None);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let value = json!([[[[1]]]]);
+        assert_eq!(compute_stats(&value).max_depth, 4);
+
+        let stats = compute_stats_with_limit(&value, 2);
+        assert!(stats.depth_truncated);
+        assert_eq!(stats.arrays, 2);
+        assert_eq!(stats.depth_display(), "≥ 2");
+
+        // Empty containers at the limit don't count as truncated
+        assert!(!compute_stats_with_limit(&json!([[]]), 2).depth_truncated);
+    }
+
+    #[test]
+    fn test_depth_agrees_with_deepest_path() {
+        let mut value = json!(1);
+        for _ in 0..100 {
+            value = json!([value]);
+        }
+
+        let stats = compute_stats(&value);
+        assert!(!stats.depth_truncated);
+        assert_eq!(stats.max_depth, 100);
+        assert_eq!(crate::json_editor::deepest_path(&value).1, 100);
+    }
+}
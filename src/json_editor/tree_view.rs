@@ -0,0 +1,885 @@
+use super::MAX_DEPTH;
+use super::graph;
+use super::number_format::{MAX_NUMBER_PRECISION, NumberFormat, format_number_display};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Arrays with more items than this start collapsed in the tree view
+const TREE_COLLAPSE_ARRAY_LEN: usize = 100;
+
+/// Maximum children rendered at once for one tree view container (windowed rendering)
+const TREE_PAGE_SIZE: usize = 200;
+
+/// Objects with more keys than this only build their visible rows (while the rows are
+/// one line each, i.e. no child container is expanded)
+const TREE_VIRTUAL_MIN_KEYS: usize = 50;
+
+/// Height of the scroll area holding a virtualized object's rows
+const TREE_VIRTUAL_HEIGHT: f32 = 400.0;
+
+/// Whether a tree entry is an Object property (its key can be renamed)
+/// Array items are labeled `[index]`, which never equals their path segment
+fn is_tree_property(key: Option<&str>, path: &[String]) -> bool {
+    key.is_some_and(|k| path.last().is_some_and(|last| last == k))
+}
+
+/// Inline edit of a leaf value in the tree view
+struct TreeEdit {
+    /// JSON path of the value being edited
+    path: Vec<String>,
+    /// Value text (a JSON literal; unquoted text is taken as a string)
+    text: String,
+}
+
+/// Inline rename of a property key in the tree view
+struct TreeRename {
+    /// JSON path of the property being renamed
+    path: Vec<String>,
+    /// New key text
+    text: String,
+}
+
+/// Inline "+ Add" row for an Object/Array in the tree view
+struct TreeAdd {
+    /// JSON path of the container
+    path: Vec<String>,
+    /// New property name (unused for arrays)
+    key: String,
+    /// New value text
+    value: String,
+}
+
+/// Tree view edit, applied by the editor after the tree has been rendered
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeOperation {
+    Update {
+        path: Vec<String>,
+        value: String,
+    },
+    Delete {
+        path: Vec<String>,
+    },
+    Rename {
+        path: Vec<String>,
+        new_key: String,
+    },
+    Add {
+        path: Vec<String>,
+        key: String,
+        value: String,
+    },
+}
+
+/// Tree view of a JSON document: display options, inline edits and expansion state
+/// Edits are only queued here (see `take_operation`); the editor applies them to the text
+#[derive(Default)]
+pub struct TreeView {
+    /// Selected JSON path (synced from the graph), highlighted in the tree
+    selected_path: Option<Vec<String>>,
+    /// Scroll to the selected entry on the next frame
+    scroll_to_selection: bool,
+    /// Show array indices as 1-based (display only)
+    one_based_indices: bool,
+    /// Display-only number formatting
+    number_format: NumberFormat,
+    /// Leaf value being edited (if any)
+    edit: Option<TreeEdit>,
+    /// Property key being renamed (if any)
+    rename: Option<TreeRename>,
+    /// Container with an open "+ Add" row (if any)
+    add: Option<TreeAdd>,
+    /// Edit waiting to be applied
+    pending_operation: Option<TreeOperation>,
+    /// Expanded containers, by JSON path
+    /// Kept across document edits so changing a deep value doesn't collapse its parents
+    expanded_paths: HashSet<Vec<String>>,
+    /// Containers that have been shown (their default expansion was applied)
+    known_paths: HashSet<Vec<String>>,
+    /// First visible child of large containers, by JSON path
+    page_starts: HashMap<Vec<String>, usize>,
+}
+
+impl TreeView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget everything tied to the current document
+    /// (selection, open edits and expansion state; display options are kept)
+    pub fn reset(&mut self) {
+        self.selected_path = None;
+        self.scroll_to_selection = false;
+        self.edit = None;
+        self.rename = None;
+        self.add = None;
+        self.pending_operation = None;
+        self.expanded_paths.clear();
+        self.known_paths.clear();
+        self.page_starts.clear();
+    }
+
+    /// Set the selected JSON path (e.g. from the graph selection)
+    /// The tree highlights it and scrolls to it when it changes
+    pub fn set_selected_path(&mut self, path: Option<Vec<String>>) {
+        if self.selected_path != path {
+            self.scroll_to_selection = path.is_some();
+            self.selected_path = path;
+        }
+    }
+
+    /// Selected JSON path, if any
+    pub fn selected_path(&self) -> Option<&[String]> {
+        self.selected_path.as_deref()
+    }
+
+    /// Show array indices starting at 1 instead of 0 (display only)
+    pub fn set_one_based_indices(&mut self, one_based: bool) {
+        self.one_based_indices = one_based;
+    }
+
+    /// Check if array indices start at 1
+    pub fn one_based_indices(&self) -> bool {
+        self.one_based_indices
+    }
+
+    /// Set how numbers are displayed (the text is never changed)
+    pub fn set_number_format(&mut self, number_format: NumberFormat) {
+        self.number_format = number_format;
+    }
+
+    /// Get the number format
+    pub fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    /// Take the edit queued while rendering, if any
+    pub fn take_operation(&mut self) -> Option<TreeOperation> {
+        self.pending_operation.take()
+    }
+
+    /// Whether a container is expanded
+    /// Containers start expanded the first time they are shown, except large arrays
+    pub fn is_expanded(&mut self, path: &[String], value: &Value) -> bool {
+        if !self.known_paths.contains(path) {
+            self.known_paths.insert(path.to_vec());
+            let large_array =
+                matches!(value, Value::Array(arr) if arr.len() > TREE_COLLAPSE_ARRAY_LEN);
+            if !large_array {
+                self.expanded_paths.insert(path.to_vec());
+            }
+        }
+        self.expanded_paths.contains(path)
+    }
+
+    /// Expand or collapse a container
+    pub fn set_expanded(&mut self, path: &[String], expanded: bool) {
+        self.known_paths.insert(path.to_vec());
+        if expanded {
+            self.expanded_paths.insert(path.to_vec());
+        } else {
+            self.expanded_paths.remove(path);
+        }
+    }
+
+    /// Paths of every Object/Array in `value` (walked without recursion)
+    fn container_paths(value: &Value) -> Vec<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut stack = vec![(Vec::<String>::new(), value)];
+        while let Some((path, value)) = stack.pop() {
+            match value {
+                Value::Object(map) => {
+                    for (k, v) in map {
+                        let mut child = path.clone();
+                        child.push(k.clone());
+                        stack.push((child, v));
+                    }
+                }
+                Value::Array(arr) => {
+                    for (idx, v) in arr.iter().enumerate() {
+                        let mut child = path.clone();
+                        child.push(idx.to_string());
+                        stack.push((child, v));
+                    }
+                }
+                _ => continue,
+            }
+            keys.push(path);
+        }
+        keys
+    }
+
+    /// Expand every container of `value`
+    pub fn expand_all(&mut self, value: &Value) {
+        let keys = Self::container_paths(value);
+        self.known_paths.extend(keys.iter().cloned());
+        self.expanded_paths.extend(keys);
+    }
+
+    /// Collapse every container of `value`
+    pub fn collapse_all(&mut self, value: &Value) {
+        // Mark everything as known so nothing re-expands by default
+        let keys = Self::container_paths(value);
+        self.known_paths.extend(keys);
+        self.expanded_paths.clear();
+    }
+
+    /// Whether a tree entry is the selected path
+    fn is_selected(&self, path: &[String]) -> bool {
+        self.selected_path.as_deref() == Some(path)
+    }
+
+    /// Label text for a tree entry, bold on a highlighted background if selected
+    fn entry_text(
+        &self,
+        ui: &egui::Ui,
+        text: String,
+        color: egui::Color32,
+        path: &[String],
+    ) -> egui::RichText {
+        let text = egui::RichText::new(text).color(color);
+        if self.is_selected(path) {
+            text.strong()
+                .background_color(ui.visuals().selection.bg_fill)
+        } else {
+            text
+        }
+    }
+
+    /// Scroll to a tree entry if it is selected and a scroll is pending
+    fn scroll_entry(&mut self, response: &egui::Response, path: &[String]) {
+        if self.scroll_to_selection && self.is_selected(path) {
+            response.scroll_to_me(Some(egui::Align::Center));
+            self.scroll_to_selection = false;
+        }
+    }
+
+    /// Render the whole document as a tree
+    pub fn show(&mut self, ui: &mut egui::Ui, value: &Value) {
+        self.render(ui, value, None, &[]);
+    }
+
+    /// Render a value and its children recursively
+    /// `path` is the JSON path of `value` (e.g., ["items", "0"])
+    /// Edits are queued in `pending_operation`
+    fn render(&mut self, ui: &mut egui::Ui, value: &Value, key: Option<&str>, path: &[String]) {
+        let child_path = |segment: String| -> Vec<String> {
+            let mut child = path.to_vec();
+            child.push(segment);
+            child
+        };
+
+        // Stop descending into pathologically deep documents
+        if path.len() >= MAX_DEPTH && (value.is_object() || value.is_array()) {
+            let text = match key {
+                Some(k) => format!("{}: … (depth limit reached)", k),
+                None => "… (depth limit reached)".to_string(),
+            };
+            ui.label(egui::RichText::new(text).italics().weak());
+            return;
+        }
+
+        match value {
+            Value::Object(map) => {
+                let summary = format!("{{ {} items }}", map.len());
+                self.render_container(ui, key, summary, path, value, |tree, ui| {
+                    if map.len() > TREE_VIRTUAL_MIN_KEYS && tree.rows_uniform(path, map) {
+                        tree.render_object_rows(ui, map, path, |tree, ui, k, v| {
+                            tree.render(ui, v, Some(k), &child_path(k.to_string()));
+                        });
+                        return;
+                    }
+                    let window = tree.child_window(ui, path, map.len());
+                    for (k, v) in map.iter().skip(window.start).take(window.len()) {
+                        tree.render(ui, v, Some(k), &child_path(k.clone()));
+                    }
+                });
+            }
+            Value::Array(arr) => {
+                let summary = format!("[ {} items ]", arr.len());
+                self.render_container(ui, key, summary, path, value, |tree, ui| {
+                    let window = tree.child_window(ui, path, arr.len());
+                    for (idx, v) in arr.iter().enumerate().skip(window.start).take(window.len()) {
+                        tree.render(
+                            ui,
+                            v,
+                            Some(&graph::index_label(idx, tree.one_based_indices)),
+                            &child_path(idx.to_string()),
+                        );
+                    }
+                });
+            }
+            _ => self.render_leaf(ui, value, key, path),
+        }
+    }
+
+    /// Whether every entry of an object renders as a single row
+    /// (no child Object/Array is expanded)
+    fn rows_uniform(&mut self, path: &[String], map: &serde_json::Map<String, Value>) -> bool {
+        let mut child = path.to_vec();
+        child.push(String::new());
+        map.iter().all(|(k, v)| {
+            if !(v.is_object() || v.is_array()) {
+                return true;
+            }
+            k.clone_into(child.last_mut().unwrap());
+            !self.is_expanded(&child, v)
+        })
+    }
+
+    /// Render an object's entries in a scroll area that only builds the visible rows
+    /// `render_row` draws one entry, which must be a single row high
+    fn render_object_rows(
+        &mut self,
+        ui: &mut egui::Ui,
+        map: &serde_json::Map<String, Value>,
+        path: &[String],
+        mut render_row: impl FnMut(&mut Self, &mut egui::Ui, &str, &Value),
+    ) {
+        let row_height = ui.spacing().interact_size.y;
+        let mut scroll = egui::ScrollArea::vertical()
+            .id_salt(("tree_rows", path))
+            .max_height(TREE_VIRTUAL_HEIGHT)
+            .auto_shrink([false, true]);
+
+        // The selected entry may be outside the built rows, so scroll it into view first
+        if self.scroll_to_selection
+            && let Some(selected) = &self.selected_path
+            && selected.len() > path.len()
+            && selected.starts_with(path)
+            && let Some(index) = map.keys().position(|k| *k == selected[path.len()])
+        {
+            let row_span = row_height + ui.spacing().item_spacing.y;
+            let offset = index as f32 * row_span - (TREE_VIRTUAL_HEIGHT - row_span) / 2.0;
+            scroll = scroll.vertical_scroll_offset(offset.max(0.0));
+        }
+
+        scroll.show_rows(ui, row_height, map.len(), |ui, rows| {
+            for (k, v) in map.iter().skip(rows.start).take(rows.len()) {
+                render_row(self, ui, k, v);
+            }
+        });
+    }
+
+    /// Range of children to render for a container with `len` children
+    /// Large containers are windowed to `TREE_PAGE_SIZE` children with paging controls
+    fn child_window(
+        &mut self,
+        ui: &mut egui::Ui,
+        path: &[String],
+        len: usize,
+    ) -> std::ops::Range<usize> {
+        if len <= TREE_PAGE_SIZE {
+            return 0..len;
+        }
+        let last_page = (len - 1) / TREE_PAGE_SIZE * TREE_PAGE_SIZE;
+        let mut start = self
+            .page_starts
+            .get(path)
+            .copied()
+            .unwrap_or(0)
+            .min(last_page);
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(start > 0, egui::Button::new("◀").small())
+                .clicked()
+            {
+                start -= TREE_PAGE_SIZE;
+            }
+            if ui
+                .add_enabled(start < last_page, egui::Button::new("▶").small())
+                .clicked()
+            {
+                start += TREE_PAGE_SIZE;
+            }
+            let first = start + usize::from(self.one_based_indices);
+            ui.label(format!(
+                "{}–{} of {}",
+                first,
+                first + (start + TREE_PAGE_SIZE).min(len) - start - 1,
+                len
+            ));
+        });
+
+        self.page_starts.insert(path.to_vec(), start);
+        start..(start + TREE_PAGE_SIZE).min(len)
+    }
+
+    /// Number display options (precision and thousands separators)
+    pub fn render_number_format_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("# Numbers", |ui| {
+            let format = &mut self.number_format;
+            let mut fixed = format.precision.is_some();
+            if ui.checkbox(&mut fixed, "Fixed decimals").changed() {
+                format.precision = fixed.then_some(2);
+            }
+            if let Some(precision) = &mut format.precision {
+                ui.add(egui::Slider::new(precision, 0..=MAX_NUMBER_PRECISION).text("Decimals"));
+            }
+            ui.checkbox(&mut format.thousands_separator, "Thousands separators");
+        })
+        .response
+        .on_hover_text("How numbers are shown in the tree (the text is not changed)");
+    }
+
+    /// Render an Object/Array as a collapsing header with "+ Add", Rename and Delete actions
+    /// `summary` is the header text after the key, e.g. `{ 3 items }`
+    fn render_container(
+        &mut self,
+        ui: &mut egui::Ui,
+        key: Option<&str>,
+        summary: String,
+        path: &[String],
+        value: &Value,
+        add_children: impl FnOnce(&mut Self, &mut egui::Ui),
+    ) {
+        let is_object = value.is_object();
+        let renamable = is_tree_property(key, path);
+        let id = ui.make_persistent_id(path);
+
+        // The expanded set is authoritative; egui's stored state just follows it
+        let expanded = self.is_expanded(path, value);
+        let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+            ui.ctx(),
+            id,
+            expanded,
+        );
+        state.set_open(expanded);
+
+        let (toggle, header, _) = state
+            .show_header(ui, |ui| {
+                // The key turns into a text field while it is being renamed
+                let header_text = if self.render_rename(ui, path) {
+                    summary
+                } else {
+                    match key {
+                        Some(k) => format!("{}: {}", k, summary),
+                        None => summary,
+                    }
+                };
+                let header_text = self.entry_text(ui, header_text, ui.visuals().text_color(), path);
+                let response = ui.label(header_text);
+                if ui.small_button("+ Add").clicked() {
+                    self.add = Some(TreeAdd {
+                        path: path.to_vec(),
+                        key: String::new(),
+                        value: String::new(),
+                    });
+                }
+                if !path.is_empty() {
+                    self.render_delete_button(ui, path);
+                    response.context_menu(|ui| {
+                        if renamable && ui.button("✏ Rename").clicked() {
+                            self.start_rename(path);
+                            ui.close();
+                        }
+                        if ui.button("🗑 Delete").clicked() {
+                            self.pending_operation = Some(TreeOperation::Delete {
+                                path: path.to_vec(),
+                            });
+                            ui.close();
+                        }
+                    });
+                }
+                response
+            })
+            .body(|ui| {
+                add_children(self, ui);
+                self.render_add_row(ui, path, is_object);
+            });
+        if toggle.clicked() {
+            self.set_expanded(path, !expanded);
+        }
+        self.scroll_entry(&header.inner, path);
+    }
+
+    /// Render the inline "+ Add" row if it is open for this container
+    /// Objects need a key and a value; arrays append, so they only need a value
+    fn render_add_row(&mut self, ui: &mut egui::Ui, path: &[String], is_object: bool) {
+        let Some(add) = &mut self.add else {
+            return;
+        };
+        if add.path != path {
+            return;
+        }
+
+        let mut submit = false;
+        let mut cancel = false;
+
+        ui.horizontal(|ui| {
+            if is_object {
+                ui.add(
+                    egui::TextEdit::singleline(&mut add.key)
+                        .hint_text("key")
+                        .desired_width(80.0),
+                );
+            }
+            let value_response = ui.add(
+                egui::TextEdit::singleline(&mut add.value)
+                    .hint_text("value")
+                    .desired_width(120.0),
+            );
+            submit = ui.small_button("✓").clicked()
+                || (value_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+            cancel =
+                ui.small_button("✗").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+        });
+
+        if submit {
+            self.pending_operation = Some(TreeOperation::Add {
+                path: add.path.clone(),
+                key: add.key.clone(),
+                value: add.value.clone(),
+            });
+        }
+        if submit || cancel {
+            self.add = None;
+        }
+    }
+
+    /// Render a String/Number/Bool/Null leaf, with inline editing and a context menu
+    fn render_leaf(
+        &mut self,
+        ui: &mut egui::Ui,
+        value: &Value,
+        key: Option<&str>,
+        path: &[String],
+    ) {
+        // Inline editor replaces the label while editing
+        if let Some(edit) = &mut self.edit
+            && edit.path == path
+        {
+            let mut submit = false;
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                if let Some(k) = key {
+                    ui.label(format!("{}:", k));
+                }
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut edit.text)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(160.0),
+                );
+                if !response.has_focus() && !response.lost_focus() {
+                    response.request_focus();
+                }
+                submit = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                cancel = ui.input(|i| i.key_pressed(egui::Key::Escape));
+            });
+
+            if submit {
+                self.pending_operation = Some(TreeOperation::Update {
+                    path: edit.path.clone(),
+                    value: edit.text.clone(),
+                });
+            }
+            if submit || cancel {
+                self.edit = None;
+            }
+            return;
+        }
+
+        let (display, color) = match value {
+            Value::String(s) => (format!("\"{}\"", s), egui::Color32::from_rgb(100, 200, 100)),
+            Value::Number(n) => (
+                format_number_display(n, &self.number_format),
+                egui::Color32::from_rgb(200, 150, 100),
+            ),
+            Value::Bool(b) => (b.to_string(), egui::Color32::from_rgb(200, 100, 150)),
+            _ => ("null".to_string(), egui::Color32::from_gray(150)),
+        };
+        let renamable = is_tree_property(key, path);
+
+        ui.horizontal(|ui| {
+            // Object keys are renamed with a click, array indices are just labels
+            if !self.render_rename(ui, path)
+                && let Some(k) = key
+            {
+                let key_text =
+                    self.entry_text(ui, format!("{}:", k), ui.visuals().text_color(), path);
+                let key_label = egui::Label::new(key_text);
+                if renamable {
+                    let response = ui
+                        .add(key_label.sense(egui::Sense::click()))
+                        .on_hover_text("Click to rename");
+                    if response.clicked() {
+                        self.start_rename(path);
+                    }
+                } else {
+                    ui.add(key_label);
+                }
+            }
+
+            let text = self.entry_text(ui, display, color, path);
+            let response = ui
+                .add(egui::Label::new(text).sense(egui::Sense::click()))
+                .on_hover_text("Click to edit, right-click for more");
+            if response.clicked() {
+                self.start_edit(path, value);
+            }
+            response.context_menu(|ui| {
+                if ui.button("✏ Edit").clicked() {
+                    self.start_edit(path, value);
+                    ui.close();
+                }
+                if renamable && ui.button("✏ Rename").clicked() {
+                    self.start_rename(path);
+                    ui.close();
+                }
+                if !path.is_empty() && ui.button("🗑 Delete").clicked() {
+                    self.pending_operation = Some(TreeOperation::Delete {
+                        path: path.to_vec(),
+                    });
+                    ui.close();
+                }
+            });
+            if !path.is_empty() {
+                self.render_delete_button(ui, path);
+            }
+            self.scroll_entry(&response, path);
+        });
+    }
+
+    /// Small trailing button that deletes a tree entry
+    fn render_delete_button(&mut self, ui: &mut egui::Ui, path: &[String]) {
+        if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+            self.pending_operation = Some(TreeOperation::Delete {
+                path: path.to_vec(),
+            });
+        }
+    }
+
+    /// Render the key text field if this entry's key is being renamed
+    /// Returns true if it was rendered (in place of the key label)
+    fn render_rename(&mut self, ui: &mut egui::Ui, path: &[String]) -> bool {
+        let Some(rename) = &mut self.rename else {
+            return false;
+        };
+        if rename.path != path {
+            return false;
+        }
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut rename.text)
+                .hint_text("key")
+                .desired_width(80.0),
+        );
+        if !response.has_focus() && !response.lost_focus() {
+            response.request_focus();
+        }
+        let submit = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let cancel = ui.input(|i| i.key_pressed(egui::Key::Escape));
+
+        if submit {
+            self.pending_operation = Some(TreeOperation::Rename {
+                path: rename.path.clone(),
+                new_key: rename.text.clone(),
+            });
+        }
+        if submit || cancel {
+            self.rename = None;
+        }
+        true
+    }
+
+    /// Start renaming an Object property
+    fn start_rename(&mut self, path: &[String]) {
+        if let Some(key) = path.last() {
+            self.rename = Some(TreeRename {
+                path: path.to_vec(),
+                text: key.clone(),
+            });
+        }
+    }
+
+    /// Start editing a leaf value
+    /// The text is a JSON literal, so strings keep their quotes
+    fn start_edit(&mut self, path: &[String], value: &Value) {
+        self.edit = Some(TreeEdit {
+            path: path.to_vec(),
+            text: value.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Render `value` once in a headless frame
+    fn show(tree: &mut TreeView, value: &Value) {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                tree.show(ui, value);
+            });
+        });
+    }
+
+    #[test]
+    fn test_flags_selected_path() {
+        let mut tree = TreeView::new();
+        let value = json!({"a": {"b": [1, "x"]}, "c": null});
+        let selected = path(&["a", "b", "1"]);
+        tree.set_selected_path(Some(selected.clone()));
+
+        assert!(tree.is_selected(&selected));
+        assert!(!tree.is_selected(&selected[..2]));
+        assert!(!tree.is_selected(&path(&["c"])));
+
+        // Rendering the tree reaches the selected entry and consumes the pending scroll
+        assert!(tree.scroll_to_selection);
+        show(&mut tree, &value);
+        assert!(!tree.scroll_to_selection);
+
+        // Re-selecting the same path doesn't scroll again
+        tree.set_selected_path(Some(selected));
+        assert!(!tree.scroll_to_selection);
+    }
+
+    #[test]
+    fn test_caps_deep_nesting() {
+        let mut value = json!(1);
+        for _ in 0..10_000 {
+            value = Value::Array(vec![value]);
+        }
+
+        let mut tree = TreeView::new();
+        show(&mut tree, &value);
+
+        // Containers are shown down to the limit, then a single placeholder
+        assert_eq!(tree.known_paths.len(), MAX_DEPTH);
+        let deepest = tree.known_paths.iter().map(Vec::len).max();
+        assert_eq!(deepest, Some(MAX_DEPTH - 1));
+
+        // Unwrap iteratively so dropping the value doesn't recurse 10,000 levels
+        while let Value::Array(mut items) = value {
+            value = items.pop().unwrap_or(Value::Null);
+        }
+    }
+
+    #[test]
+    fn test_edits_are_queued() {
+        let mut tree = TreeView::new();
+
+        // Edit text is a JSON literal, so strings round-trip with their quotes
+        tree.start_edit(&path(&["name"]), &json!("a"));
+        assert_eq!(tree.edit.as_ref().unwrap().text, "\"a\"");
+        tree.start_rename(&path(&["name"]));
+        assert_eq!(tree.rename.as_ref().unwrap().text, "name");
+        // The root has no key to rename
+        tree.rename = None;
+        tree.start_rename(&[]);
+        assert!(tree.rename.is_none());
+
+        assert_eq!(tree.take_operation(), None);
+        tree.pending_operation = Some(TreeOperation::Delete {
+            path: path(&["old"]),
+        });
+        assert!(tree.take_operation().is_some());
+        assert_eq!(tree.take_operation(), None);
+
+        // Only Object properties are renamable, not array items or the root
+        assert!(is_tree_property(Some("tags"), &path(&["tags"])));
+        assert!(!is_tree_property(Some("[0]"), &path(&["tags", "0"])));
+        assert!(!is_tree_property(None, &[]));
+    }
+
+    #[test]
+    fn test_expansion_state() {
+        let big: Vec<u32> = (0..=TREE_COLLAPSE_ARRAY_LEN as u32).collect();
+        let value = json!({"a": {"b": [1, 2]}, "big": big});
+        let mut tree = TreeView::new();
+
+        // Defaults: containers open, large arrays collapsed
+        assert!(tree.is_expanded(&path(&["a"]), &value["a"]));
+        assert!(!tree.is_expanded(&path(&["big"]), &value["big"]));
+
+        tree.set_expanded(&path(&["a"]), false);
+        assert!(!tree.is_expanded(&path(&["a"]), &value["a"]));
+
+        tree.expand_all(&value);
+        assert!(tree.is_expanded(&path(&["big"]), &value["big"]));
+        assert!(tree.is_expanded(&path(&["a", "b"]), &value["a"]["b"]));
+
+        tree.collapse_all(&value);
+        assert!(!tree.is_expanded(&[], &value));
+        assert!(!tree.is_expanded(&path(&["a", "b"]), &value["a"]["b"]));
+
+        // Reset forgets the state, so containers open by default again
+        tree.reset();
+        assert!(tree.is_expanded(&path(&["a"]), &value["a"]));
+    }
+
+    #[test]
+    fn test_child_window() {
+        let mut tree = TreeView::new();
+        let ctx = egui::Context::default();
+        let window = |tree: &mut TreeView, len: usize| {
+            let mut range = 0..0;
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    range = tree.child_window(ui, &path(&["items"]), len);
+                });
+            });
+            range
+        };
+
+        assert_eq!(window(&mut tree, 10), 0..10);
+        assert_eq!(window(&mut tree, 450), 0..TREE_PAGE_SIZE);
+
+        // A stored page start past the end clamps to the last page
+        tree.page_starts.insert(path(&["items"]), 1000);
+        assert_eq!(window(&mut tree, 450), 400..450);
+    }
+
+    #[test]
+    fn test_wide_object_builds_visible_rows_only() {
+        let wide: serde_json::Map<String, Value> = (0..500)
+            .map(|i| (format!("k{}", i), Value::from(i)))
+            .collect();
+        let mut tree = TreeView::new();
+        let wide_path = path(&["wide"]);
+        let ctx = egui::Context::default();
+        let render = |tree: &mut TreeView| {
+            let mut keys = Vec::new();
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    tree.render_object_rows(ui, &wide, &wide_path, |_, ui, k, _| {
+                        ui.label(k);
+                        keys.push(k.to_string());
+                    });
+                });
+            });
+            keys
+        };
+
+        let bound = 60;
+        let keys = render(&mut tree);
+        assert!(!keys.is_empty() && keys.len() < bound);
+        assert_eq!(keys[0], "k0");
+
+        // Scrolled to a selected entry far down: still a bounded, ordered run of rows
+        tree.set_selected_path(Some(path(&["wide", "k400"])));
+        let keys = render(&mut tree);
+        assert!(keys.len() < bound);
+        assert!(keys.contains(&"k400".to_string()));
+        let first: usize = keys[0][1..].parse().unwrap();
+        let expected: Vec<String> = (first..first + keys.len())
+            .map(|i| format!("k{}", i))
+            .collect();
+        assert_eq!(keys, expected);
+
+        // Rows stop being uniform once a child container is expanded
+        let value = json!({"a": {"b": 1}, "c": 2});
+        let map = value.as_object().unwrap();
+        assert!(!tree.rows_uniform(&[], map));
+        tree.set_expanded(&path(&["a"]), false);
+        assert!(tree.rows_uniform(&[], map));
+    }
+}
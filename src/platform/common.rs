@@ -1,7 +1,8 @@
 /// Common event handling logic shared between desktop and WASM platforms
 use crate::state::State;
+use web_time::Instant;
 use winit::event::WindowEvent;
-use winit::event_loop::ActiveEventLoop;
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
 
 /// Handle window events common to both platforms
 ///
@@ -19,8 +20,7 @@ pub fn handle_window_event(
         match event {
             WindowEvent::CloseRequested => {
                 log::info!("Close requested");
-                event_loop.exit();
-                return false;
+                return request_exit(state, event_loop);
             }
             WindowEvent::Resized(physical_size) => {
                 log::info!("Resized to: {:?}", physical_size);
@@ -49,7 +49,41 @@ pub fn handle_window_event(
                 log::error!("Render error: {:?}", e);
             }
         }
+
+        // The unsaved changes prompt was answered with Save or Discard
+        if state.app.take_close_confirmed() {
+            log::info!("Closing after unsaved changes prompt");
+            event_loop.exit();
+            return false;
+        }
+        state.sync_window_title();
     }
 
     true
 }
+
+/// Exit, or show the unsaved changes prompt first if the document is dirty
+///
+/// Returns true if the application should continue running
+pub fn request_exit(state: &mut State, event_loop: &ActiveEventLoop) -> bool {
+    if state.app.request_close() {
+        event_loop.exit();
+        return false;
+    }
+    log::info!("Unsaved changes - asking before closing");
+    state.window().request_redraw();
+    true
+}
+
+/// Redraw only when needed: right away if a frame is due, otherwise sleep until the
+/// next one egui asked for (or until the next event if it's idle)
+pub fn schedule_redraw(state: &State, event_loop: &ActiveEventLoop) {
+    match state.repaint_at() {
+        Some(at) if at <= Instant::now() => {
+            state.window().request_redraw();
+            event_loop.set_control_flow(ControlFlow::Wait);
+        }
+        Some(at) => event_loop.set_control_flow(ControlFlow::WaitUntil(at)),
+        None => event_loop.set_control_flow(ControlFlow::Wait),
+    }
+}
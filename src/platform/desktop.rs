@@ -66,7 +66,7 @@ impl ApplicationHandler for DesktopApp {
 
         let state = self.state.as_mut().unwrap();
 
-        // Handle desktop-specific events (Escape key)
+        // Handle desktop-specific events (Escape key closes unless the UI uses it)
         if let WindowEvent::KeyboardInput {
             event:
                 KeyEvent {
@@ -76,9 +76,10 @@ impl ApplicationHandler for DesktopApp {
                 },
             ..
         } = event
+            && !state.ui_wants_escape()
         {
             log::info!("Escape pressed, closing");
-            event_loop.exit();
+            common::request_exit(state, event_loop);
             return;
         }
 
@@ -86,9 +87,9 @@ impl ApplicationHandler for DesktopApp {
         common::handle_window_event(state, event_loop, event);
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(window) = &self.window {
-            window.request_redraw();
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(state) = &self.state {
+            common::schedule_redraw(state, event_loop);
         }
     }
 }
@@ -10,6 +10,8 @@ use std::rc::Rc;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsCast;
 #[cfg(target_arch = "wasm32")]
+use wasm_bindgen::closure::Closure;
+#[cfg(target_arch = "wasm32")]
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalSize,
@@ -25,6 +27,8 @@ pub struct WasmAppState {
     pub window: Option<Window>,
     pub state: Option<State<'static>>,
     pub initializing: bool,
+    /// `beforeunload` listener, registered only while there are unsaved changes
+    pub unload_guard: Option<Closure<dyn FnMut(web_sys::BeforeUnloadEvent)>>,
 }
 
 /// WASM Application structure with shared state
@@ -41,11 +45,50 @@ impl WasmApp {
                 window: None,
                 state: None,
                 initializing: false,
+                unload_guard: None,
             })),
         }
     }
 }
 
+/// Register or remove the `beforeunload` listener so the browser asks before
+/// leaving the page only while the document has unsaved changes
+#[cfg(target_arch = "wasm32")]
+fn sync_unload_guard(
+    guard: &mut Option<Closure<dyn FnMut(web_sys::BeforeUnloadEvent)>>,
+    dirty: bool,
+) {
+    if dirty == guard.is_some() {
+        return;
+    }
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    if let Some(listener) = guard.take() {
+        let _ = window
+            .remove_event_listener_with_callback("beforeunload", listener.as_ref().unchecked_ref());
+        log::debug!("Unsaved changes guard removed");
+    } else {
+        let listener = Closure::<dyn FnMut(web_sys::BeforeUnloadEvent)>::new(
+            |event: web_sys::BeforeUnloadEvent| {
+                // Browsers show their own generic message; older ones need a return value
+                event.prevent_default();
+                event.set_return_value("");
+            },
+        );
+        match window
+            .add_event_listener_with_callback("beforeunload", listener.as_ref().unchecked_ref())
+        {
+            Ok(()) => {
+                *guard = Some(listener);
+                log::debug!("Unsaved changes guard registered");
+            }
+            Err(e) => log::warn!("Failed to register beforeunload listener: {:?}", e),
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 impl Default for WasmApp {
     fn default() -> Self {
@@ -196,10 +239,17 @@ impl ApplicationHandler for WasmApp {
         common::handle_window_event(state, event_loop, event);
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        let app_state = self.state.borrow();
-        if let Some(window) = &app_state.window {
-            window.request_redraw();
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // The initial frames are requested by the async initialization in `resumed`
+        let mut app_state = self.state.borrow_mut();
+        let WasmAppState {
+            state,
+            unload_guard,
+            ..
+        } = &mut *app_state;
+        if let Some(state) = state {
+            sync_unload_guard(unload_guard, state.app.has_unsaved_changes());
+            common::schedule_redraw(state, event_loop);
         }
     }
 }
@@ -3,6 +3,7 @@
 /// This module contains the core state management for the canvas editor.
 /// It maintains the application state and handles updates.
 use crate::ui::App;
+use web_time::Instant;
 use wgpu;
 use winit::dpi::PhysicalSize;
 use winit::event::WindowEvent;
@@ -18,6 +19,10 @@ pub struct State<'a> {
     pub queue: wgpu::Queue,
     /// Surface configuration
     pub config: wgpu::SurfaceConfiguration,
+    /// Present modes the surface supports
+    present_modes: Vec<wgpu::PresentMode>,
+    /// Whether the surface is configured for vsync
+    vsync: bool,
     /// Window size
     pub size: PhysicalSize<u32>,
     /// Reference to the window
@@ -30,6 +35,10 @@ pub struct State<'a> {
     pub egui_renderer: egui_wgpu::Renderer,
     /// Application UI
     pub app: App,
+    /// When the next frame should be drawn (None = idle until input arrives)
+    repaint_at: Option<Instant>,
+    /// Window title last set from the app (None = not set yet)
+    title: Option<String>,
 }
 
 impl<'a> State<'a> {
@@ -158,13 +167,17 @@ impl<'a> State<'a> {
             .copied()
             .unwrap_or(surface_caps.formats[0]);
 
+        // Initialize application (its settings pick the initial present mode)
+        let app = App::new();
+        let vsync = app.vsync();
+
         // Configure surface
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0],
+            present_mode: choose_present_mode(&surface_caps.present_modes, vsync),
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
@@ -197,20 +210,24 @@ impl<'a> State<'a> {
             egui_wgpu::RendererOptions::default(),
         );
 
-        // Initialize application
-        let app = App::new();
+        let present_modes = surface_caps.present_modes;
 
         Self {
             surface,
             device,
             queue,
             config,
+            present_modes,
+            vsync,
             size,
             window,
             egui_ctx,
             egui_state,
             egui_renderer,
             app,
+            // Draw the first frame right away
+            repaint_at: Some(Instant::now()),
+            title: None,
         }
     }
 
@@ -230,12 +247,53 @@ impl<'a> State<'a> {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.repaint_at = Some(Instant::now());
+        }
+    }
+
+    /// Switch vsync on or off, reconfiguring the surface if the present mode changes
+    /// Must not be called while a frame from `get_current_texture` is still held
+    ///
+    /// # Arguments
+    ///
+    /// * `vsync` - Prefer `Fifo` (vsync) when true, an uncapped mode otherwise
+    pub fn set_present_mode(&mut self, vsync: bool) {
+        self.vsync = vsync;
+        let present_mode = choose_present_mode(&self.present_modes, vsync);
+        if present_mode != self.config.present_mode {
+            log::info!("Present mode: {:?}", present_mode);
+            self.config.present_mode = present_mode;
+            self.surface.configure(&self.device, &self.config);
         }
     }
 
     /// Handle window event
+    /// Events that change the UI (as judged by egui) schedule a redraw
     pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
-        self.egui_state.on_window_event(self.window, event).consumed
+        let response = self.egui_state.on_window_event(self.window, event);
+        if response.repaint {
+            self.repaint_at = Some(Instant::now());
+        }
+        response.consumed
+    }
+
+    /// Whether the UI uses Escape: a dialog is open or a text field has focus
+    pub fn ui_wants_escape(&self) -> bool {
+        self.app.dialog_open() || self.egui_ctx.wants_keyboard_input()
+    }
+
+    /// Update the window title if the app's title changed (e.g. unsaved changes marker)
+    pub fn sync_window_title(&mut self) {
+        let title = self.app.window_title();
+        if self.title.as_ref() != Some(&title) {
+            self.window.set_title(&title);
+            self.title = Some(title);
+        }
+    }
+
+    /// When the next frame should be drawn (None = nothing to draw until input arrives)
+    pub fn repaint_at(&self) -> Option<Instant> {
+        self.repaint_at
     }
 
     /// Update state
@@ -280,6 +338,24 @@ impl<'a> State<'a> {
         self.egui_state
             .handle_platform_output(self.window, full_output.platform_output);
 
+        // egui reports when it needs another frame (animations, timers, follow-up
+        // frames after input); Duration::MAX means it's idle
+        self.repaint_at = full_output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .and_then(|viewport| Instant::now().checked_add(viewport.repaint_delay));
+
+        // Actions that read the clipboard get its text right after the frame
+        if self.app.wants_clipboard_text() {
+            #[cfg(not(target_arch = "wasm32"))]
+            let text = self.egui_state.clipboard_text();
+            // The browser only hands out clipboard text in paste events
+            #[cfg(target_arch = "wasm32")]
+            let text = None;
+            self.app.deliver_clipboard_text(text);
+            self.repaint_at = Some(Instant::now());
+        }
+
         let tris = self
             .egui_ctx
             .tessellate(full_output.shapes, full_output.pixels_per_point);
@@ -305,6 +381,7 @@ impl<'a> State<'a> {
         );
 
         // Render
+        let clear_color = self.app.clear_color();
         {
             let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
@@ -312,12 +389,7 @@ impl<'a> State<'a> {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(clear_color),
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
@@ -343,6 +415,63 @@ impl<'a> State<'a> {
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
 
+        // Takes effect from the next frame; the surface can only be reconfigured
+        // once this frame's texture has been presented
+        if self.app.vsync() != self.vsync {
+            self.set_present_mode(self.app.vsync());
+        }
+
         Ok(())
     }
 }
+
+/// Pick a present mode from the supported ones
+/// Vsync prefers `Fifo`; otherwise `Immediate`, then `Mailbox`. Falls back to
+/// `Fifo` (always supported) or the first supported mode
+fn choose_present_mode(available: &[wgpu::PresentMode], vsync: bool) -> wgpu::PresentMode {
+    use wgpu::PresentMode;
+
+    let preferred: &[PresentMode] = if vsync {
+        &[PresentMode::Fifo, PresentMode::FifoRelaxed]
+    } else {
+        &[PresentMode::Immediate, PresentMode::Mailbox]
+    };
+    preferred
+        .iter()
+        .chain(&[PresentMode::Fifo])
+        .find(|mode| available.contains(mode))
+        .or(available.first())
+        .copied()
+        .unwrap_or(PresentMode::Fifo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::PresentMode;
+
+    #[test]
+    fn test_choose_present_mode() {
+        let all = [
+            PresentMode::Immediate,
+            PresentMode::Mailbox,
+            PresentMode::Fifo,
+        ];
+        assert_eq!(choose_present_mode(&all, true), PresentMode::Fifo);
+        assert_eq!(choose_present_mode(&all, false), PresentMode::Immediate);
+        assert_eq!(
+            choose_present_mode(&[PresentMode::Mailbox, PresentMode::Fifo], false),
+            PresentMode::Mailbox
+        );
+        // Only vsync available (e.g. WebGPU)
+        assert_eq!(
+            choose_present_mode(&[PresentMode::Fifo], false),
+            PresentMode::Fifo
+        );
+        // Fall back to whatever the surface reports
+        assert_eq!(
+            choose_present_mode(&[PresentMode::Immediate], true),
+            PresentMode::Immediate
+        );
+    }
+}
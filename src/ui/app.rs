@@ -1,10 +1,22 @@
 /// Application UI and state
 ///
 /// This module contains the main application UI logic using egui
-use crate::json_editor::{JsonEditor, JsonGraph};
+use super::compare::CompareView;
+use super::loader::{self, LoadDecision, PendingLoad};
+use super::settings::{AppSettings, Theme};
+use crate::json_editor::{CopyRequest, JsonEditor, JsonGraph};
 use crate::utils;
 use egui;
 
+/// Documents larger than this (in bytes) open in text-only mode
+const AUTO_TEXT_ONLY_BYTES: usize = 16 * 1024 * 1024;
+/// Documents with more graph nodes than this open in text-only mode
+const AUTO_TEXT_ONLY_NODES: usize = 20_000;
+/// With auto-freeze, the graph rebuilds once typing pauses for this many seconds
+const TYPING_PAUSE_SECS: f64 = 0.8;
+/// Window title (prefixed with the file name and an asterisk when there are unsaved changes)
+const WINDOW_TITLE: &str = "WGPU Canvas Editor - JSON Visualizer";
+
 /// Main application structure
 pub struct App {
     /// JSON editor instance
@@ -15,37 +27,689 @@ pub struct App {
     left_panel_width: f32,
     /// Whether the graph has been initialized
     graph_initialized: bool,
+    /// Current color theme
+    theme: Theme,
+    /// Custom background clear color (None = the theme's color)
+    clear_color: Option<wgpu::Color>,
+    /// Present frames in sync with the display (applied by `State`)
+    vsync: bool,
+    /// Compare mode (diff against a baseline)
+    compare: CompareView,
+    /// Files larger than this ask for confirmation before loading
+    max_load_bytes: u64,
+    /// Large dropped file waiting for confirmation, with its size in MB
+    pending_load: Option<(PendingLoad, f64)>,
+    /// Whether the graph is built and shown
+    /// Disabled (text-only mode) for very large documents to stay responsive
+    graph_enabled: bool,
+    /// Settings as last loaded or saved (used to detect changes)
+    saved_settings: AppSettings,
+    /// Graph rebuilds are suspended until resumed
+    graph_frozen: bool,
+    /// Suspend graph rebuilds while typing, rebuilding once typing pauses
+    auto_freeze_while_typing: bool,
+    /// The document changed while rebuilds were suspended
+    graph_stale: bool,
+    /// Input time of the last editor change (for auto-freeze)
+    last_edit_time: f64,
+    /// Graph selection to restore once the document parses again
+    /// (the graph is cleared while the text is invalid)
+    graph_selection: Option<Vec<String>>,
+    /// Editor text version as last loaded or saved; any other counts as unsaved changes
+    clean_version: u64,
+    /// "New" waiting for confirmation because of unsaved changes
+    /// (true = empty Array, false = empty Object)
+    pending_new: Option<bool>,
+    /// File the document was loaded from, and is saved back to (desktop only)
+    file_path: Option<std::path::PathBuf>,
+    /// Closing the window is waiting for Save / Discard / Cancel
+    close_prompt: bool,
+    /// The window may close now (processed by the platform event loop)
+    close_confirmed: bool,
 }
 
 impl Default for App {
     fn default() -> Self {
+        let json_editor = JsonEditor::new();
+        let clean_version = json_editor.text_version();
         Self {
-            json_editor: JsonEditor::new(),
+            json_editor,
             json_graph: JsonGraph::new(),
             left_panel_width: 400.0,
             graph_initialized: false,
+            theme: Theme::default(),
+            clear_color: None,
+            vsync: true,
+            compare: CompareView::default(),
+            max_load_bytes: loader::DEFAULT_MAX_LOAD_BYTES,
+            pending_load: None,
+            graph_enabled: true,
+            saved_settings: AppSettings::default(),
+            graph_frozen: false,
+            auto_freeze_while_typing: false,
+            graph_stale: false,
+            last_edit_time: 0.0,
+            graph_selection: None,
+            clean_version,
+            pending_new: None,
+            file_path: None,
+            close_prompt: false,
+            close_confirmed: false,
         }
     }
 }
 
 impl App {
     pub fn new() -> Self {
-        Self::default()
+        let mut app = Self::default();
+        app.apply_settings(AppSettings::load());
+        app
+    }
+
+    /// Apply settings and remember them as the saved state
+    fn apply_settings(&mut self, settings: AppSettings) {
+        self.json_graph.apply_minimap_settings(&settings.minimap);
+        self.theme = settings.theme;
+        self.clear_color = settings.background.map(|[r, g, b]| wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: 1.0,
+        });
+        self.max_load_bytes = settings.max_load_bytes;
+        self.vsync = settings.vsync;
+        self.set_one_based_indices(settings.one_based_indices);
+        self.saved_settings = settings;
+    }
+
+    /// Collect the current settings from the UI components
+    fn current_settings(&self) -> AppSettings {
+        AppSettings {
+            minimap: self.json_graph.minimap_settings(),
+            theme: self.theme,
+            max_load_bytes: self.max_load_bytes,
+            one_based_indices: self.json_graph.one_based_indices(),
+            background: self
+                .clear_color
+                .map(|c| [c.r as f32, c.g as f32, c.b as f32]),
+            vsync: self.vsync,
+        }
+    }
+
+    /// Show array indices starting at 1 in the graph and tree view
+    fn set_one_based_indices(&mut self, one_based: bool) {
+        self.json_graph.set_one_based_indices(one_based);
+        self.json_editor.set_one_based_indices(one_based);
+    }
+
+    /// Save settings if anything changed since the last save
+    fn persist_settings(&mut self) {
+        let settings = self.current_settings();
+        if settings != self.saved_settings {
+            settings.save();
+            self.saved_settings = settings;
+            utils::log("App", "Settings saved");
+        }
+    }
+
+    /// Reparse the editor text and rebuild the graph from scratch
+    /// Clears the graph's selection and any in-progress edits
+    pub fn force_rebuild_graph(&mut self) {
+        self.graph_selection = None;
+        if self.json_editor.validate()
+            && let Some(value) = self.json_editor.parsed_value()
+        {
+            self.json_graph.build_from_json(value);
+            utils::log("App", "Graph force rebuilt from JSON");
+        } else {
+            self.json_graph.build_from_json(&serde_json::Value::Null);
+            utils::log("App", "Graph cleared on rebuild - invalid JSON");
+        }
+        self.graph_initialized = true;
+        self.graph_stale = false;
+    }
+
+    /// Apply changed graph display settings, rebuilding just the affected nodes
+    /// While rebuilds are suspended the settings apply when the graph resumes
+    fn refresh_graph(&mut self) {
+        if !self.graph_stale {
+            self.sync_graph_to_document();
+        }
+    }
+
+    /// Update the graph after the editor text changed
+    /// While frozen (or typing with auto-freeze on) the rebuild is deferred to
+    /// `flush_stale_graph`. Returns whether the graph was rebuilt
+    fn on_document_changed(&mut self, now: f64) -> bool {
+        if !self.graph_enabled {
+            return false;
+        }
+        self.last_edit_time = now;
+        if self.graph_frozen || self.auto_freeze_while_typing {
+            self.graph_stale = true;
+            return false;
+        }
+        self.sync_graph_to_document();
+        true
+    }
+
+    /// Run the deferred rebuild once the graph is unfrozen and typing has paused
+    /// Returns whether the graph was rebuilt
+    fn flush_stale_graph(&mut self, now: f64) -> bool {
+        if !self.graph_stale
+            || self.graph_frozen
+            || (self.auto_freeze_while_typing && now - self.last_edit_time < TYPING_PAUSE_SECS)
+        {
+            return false;
+        }
+        self.sync_graph_to_document();
+        utils::log("App", "Graph resumed - rebuilt once");
+        true
+    }
+
+    /// Update the graph to the document, or clear it if the document is invalid
+    /// The selection stays on its path, including across edits that pass through
+    /// invalid text, unless the edit removed that path
+    fn sync_graph_to_document(&mut self) {
+        self.graph_stale = false;
+        if let Some(value) = self.json_editor.parsed_value() {
+            self.json_graph.update_from_json(value);
+            self.graph_initialized = true;
+            if let Some(path) = self.graph_selection.take()
+                && self.json_graph.get_selected_path().is_none()
+                && self.json_graph.has_node_at_path(&path)
+            {
+                self.json_graph.select_by_path(&path);
+            }
+            utils::log("App", "Graph updated from JSON");
+        } else {
+            if let Some(path) = self.json_graph.get_selected_path() {
+                self.graph_selection = Some(path);
+            }
+            self.json_graph.build_from_json(&serde_json::Value::Null);
+            utils::log("App", "Graph cleared - invalid JSON");
+        }
+    }
+
+    /// Freeze or resume graph rebuilds
+    /// Resuming rebuilds once if the document changed while frozen
+    pub fn set_graph_frozen(&mut self, frozen: bool) {
+        self.graph_frozen = frozen;
+        utils::log(
+            "App",
+            if frozen {
+                "Graph frozen"
+            } else {
+                "Graph unfrozen"
+            },
+        );
+    }
+
+    /// Whether a document is too large to build the graph automatically
+    fn is_oversized(text: &str, value: Option<&serde_json::Value>) -> bool {
+        text.len() > AUTO_TEXT_ONLY_BYTES
+            || value
+                .is_some_and(|value| JsonGraph::estimate_node_count(value) > AUTO_TEXT_ONLY_NODES)
+    }
+
+    /// Replace the document with loaded text and rebuild the graph
+    /// The graph stays disabled (text-only mode) if `text_only` is set or the
+    /// document is oversized; it can be enabled from the graph panel
+    pub fn load_text(&mut self, text: String, text_only: bool) {
+        self.json_editor.set_text(text);
+        self.clean_version = self.json_editor.text_version();
+        let oversized =
+            Self::is_oversized(self.json_editor.text(), self.json_editor.parsed_value());
+        self.graph_enabled = !text_only && !oversized;
+
+        if self.graph_enabled {
+            self.force_rebuild_graph();
+        } else {
+            self.json_graph.clear();
+            self.graph_initialized = true;
+            utils::log(
+                "App",
+                if oversized {
+                    "Large document - opened in text-only mode"
+                } else {
+                    "Opened in text-only mode"
+                },
+            );
+        }
+    }
+
+    /// Load a file dropped onto the window, asking first if it is very large
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let Some(file) = ctx.input(|i| i.raw.dropped_files.first().cloned()) else {
+            return;
+        };
+        let load = PendingLoad::new(file);
+        match loader::check_load_size(load.size().unwrap_or(0), self.max_load_bytes) {
+            LoadDecision::Load => self.finish_load(&load, false),
+            LoadDecision::Prompt { size_mb } => {
+                utils::log(
+                    "App",
+                    &format!("{} is {:.1} MB - asking before loading", load.name, size_mb),
+                );
+                self.pending_load = Some((load, size_mb));
+            }
+        }
+    }
+
+    /// Read a dropped file into the editor
+    fn finish_load(&mut self, load: &PendingLoad, text_only: bool) {
+        match load.read() {
+            Ok(text) => {
+                self.load_text(text, text_only);
+                self.file_path = load.path().map(|path| path.to_path_buf());
+                utils::log(
+                    "App",
+                    &format!(
+                        "Loaded {}{}",
+                        load.name,
+                        if text_only { " (text only)" } else { "" }
+                    ),
+                );
+            }
+            Err(e) => utils::log("App", &e),
+        }
+    }
+
+    /// Render the confirmation prompt for a large file
+    fn render_load_prompt(&mut self, ctx: &egui::Context) {
+        let Some((load, size_mb)) = &self.pending_load else {
+            return;
+        };
+
+        let mut choice: Option<Option<bool>> = None;
+        egui::Window::new("Large File")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} is {:.1} MB and may be slow — open anyway?",
+                    load.name, size_mb
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Open").clicked() {
+                        choice = Some(Some(false));
+                    }
+                    if ui
+                        .button("Open as Text Only")
+                        .on_hover_text("Skip building the graph")
+                        .clicked()
+                    {
+                        choice = Some(Some(true));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(None);
+                    }
+                });
+            });
+
+        if let Some(choice) = choice
+            && let Some((load, _)) = self.pending_load.take()
+        {
+            match choice {
+                Some(text_only) => self.finish_load(&load, text_only),
+                None => utils::log("App", &format!("Cancelled loading {}", load.name)),
+            }
+        }
+    }
+
+    /// Start a new empty document (`[]` if `as_array`, otherwise `{}`)
+    /// Forgets undo history and rebuilds the graph right away
+    pub fn new_document(&mut self, as_array: bool) {
+        if as_array {
+            self.json_editor.clear_to_array();
+        } else {
+            self.json_editor.clear();
+        }
+        self.clean_version = self.json_editor.text_version();
+        self.file_path = None;
+        self.graph_enabled = true;
+        self.force_rebuild_graph();
+        utils::log("App", &format!("New document: {}", self.json_editor.text()));
+    }
+
+    /// Start a new document, asking first if there are unsaved changes
+    fn request_new_document(&mut self, as_array: bool) {
+        if self.has_unsaved_changes() {
+            self.pending_new = Some(as_array);
+        } else {
+            self.new_document(as_array);
+        }
+    }
+
+    /// Render the confirmation prompt for discarding unsaved changes
+    fn render_new_prompt(&mut self, ctx: &egui::Context) {
+        let Some(as_array) = self.pending_new else {
+            return;
+        };
+
+        let mut choice: Option<bool> = None;
+        egui::Window::new("Unsaved Changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Discard your changes and start a new document?");
+                ui.horizontal(|ui| {
+                    if ui.button("Discard").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        if let Some(discard) = choice {
+            self.pending_new = None;
+            if discard {
+                self.new_document(as_array);
+            } else {
+                utils::log("App", "Cancelled new document");
+            }
+        }
+    }
+
+    /// Current color theme
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// Color the surface is cleared to before the UI is drawn
+    /// Defaults to the theme's background unless a custom color is set
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color.unwrap_or_else(|| {
+            let [r, g, b] = self.theme.clear_color();
+            wgpu::Color { r, g, b, a: 1.0 }
+        })
+    }
+
+    /// Set a custom clear color (None = follow the theme again)
+    pub fn set_clear_color(&mut self, color: Option<wgpu::Color>) {
+        self.clear_color = color;
+    }
+
+    /// Whether the document changed since it was loaded or saved
+    /// Undoing back to that text counts as clean again
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.json_editor.text_version() != self.clean_version
+    }
+
+    /// Write the document back to the file it was loaded from
+    pub fn save(&mut self) -> Result<(), String> {
+        let path = self.file_path.as_ref().ok_or("No file to save to")?;
+        std::fs::write(path, self.json_editor.text())
+            .map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+        self.clean_version = self.json_editor.text_version();
+        utils::log("App", &format!("Saved {}", path.display()));
+        Ok(())
+    }
+
+    /// Save, logging any failure; returns whether the document was saved
+    fn save_and_log(&mut self) -> bool {
+        match self.save() {
+            Ok(()) => true,
+            Err(e) => {
+                utils::log("App", &e);
+                false
+            }
+        }
+    }
+
+    /// Window title: the file name (if any), marked with `*` while there are unsaved changes
+    pub fn window_title(&self) -> String {
+        let dirty = if self.has_unsaved_changes() { "*" } else { "" };
+        match self.file_path.as_ref().and_then(|path| path.file_name()) {
+            Some(name) => format!("{}{} - {}", dirty, name.to_string_lossy(), WINDOW_TITLE),
+            None => format!("{}{}", dirty, WINDOW_TITLE),
+        }
+    }
+
+    /// Ask to close the window; returns true if it can close right away
+    /// With unsaved changes a Save / Discard / Cancel prompt is shown instead, and
+    /// `take_close_confirmed` reports when the window may close
+    pub fn request_close(&mut self) -> bool {
+        if !self.has_unsaved_changes() {
+            return true;
+        }
+        self.close_prompt = true;
+        false
+    }
+
+    /// Whether any dialog is open (Escape then belongs to the dialog, not the window)
+    pub fn dialog_open(&self) -> bool {
+        self.pending_load.is_some()
+            || self.pending_new.is_some()
+            || self.close_prompt
+            || self.compare.dialog_open()
+            || self.json_editor.dialog_open()
+            || self.json_graph.dialog_open()
+    }
+
+    /// Take and return whether the close prompt was answered with Save or Discard
+    pub fn take_close_confirmed(&mut self) -> bool {
+        std::mem::take(&mut self.close_confirmed)
+    }
+
+    /// Whether an action is waiting for the clipboard text
+    /// The platform layer reads it after the frame and calls `deliver_clipboard_text`
+    pub fn wants_clipboard_text(&self) -> bool {
+        self.json_graph.wants_clipboard_text() || self.json_editor.wants_clipboard_text()
+    }
+
+    /// Hand the clipboard text to the actions waiting for it
+    /// None means the clipboard can't be read directly (on the web), so the actions
+    /// fall back to a dialog to paste into
+    pub fn deliver_clipboard_text(&mut self, text: Option<String>) {
+        self.json_graph.paste_clipboard_text(text.clone());
+        self.json_editor.diff_clipboard_text(text);
+    }
+
+    /// Render the Save / Discard / Cancel prompt shown before closing with unsaved changes
+    fn render_close_prompt(&mut self, ctx: &egui::Context) {
+        if !self.close_prompt {
+            return;
+        }
+
+        let mut choice: Option<Option<bool>> = None;
+        egui::Window::new("Close Editor")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The document has unsaved changes. Save before closing?");
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.file_path.is_some(), egui::Button::new("Save"))
+                        .on_disabled_hover_text("The document wasn't loaded from a file")
+                        .clicked()
+                    {
+                        choice = Some(Some(true));
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(Some(false));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(None);
+                    }
+                });
+            });
+
+        match choice {
+            // A failed save keeps the prompt open
+            Some(Some(save)) if !save || self.save_and_log() => {
+                self.close_prompt = false;
+                self.close_confirmed = true;
+            }
+            Some(None) => {
+                self.close_prompt = false;
+                utils::log("App", "Cancelled closing");
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether frames should be presented in sync with the display
+    pub fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    /// Background color picker, with a reset back to the theme's color
+    fn render_background_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("🎨 Background", |ui| {
+            let color = self.clear_color();
+            let mut rgb = [color.r as f32, color.g as f32, color.b as f32];
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                if ui.color_edit_button_rgb(&mut rgb).changed() {
+                    let [r, g, b] = rgb.map(f64::from);
+                    self.set_clear_color(Some(wgpu::Color { r, g, b, a: 1.0 }));
+                }
+            });
+            if ui
+                .add_enabled(
+                    self.clear_color.is_some(),
+                    egui::Button::new("Use theme color"),
+                )
+                .clicked()
+            {
+                self.set_clear_color(None);
+                utils::log("App", "Background reset to the theme color");
+            }
+        });
     }
 
     /// Update the UI
     pub fn update(&mut self, ctx: &egui::Context) {
+        // Keep egui visuals in sync with the selected theme
+        if ctx.style().visuals.dark_mode != self.theme.is_dark() {
+            ctx.set_visuals(self.theme.visuals());
+        }
+
         // Top panel for title and controls
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("WGPU Canvas Editor - JSON Visualizer");
                 ui.separator();
 
+                if ui
+                    .add_enabled(self.file_path.is_some(), egui::Button::new("💾 Save"))
+                    .on_disabled_hover_text("Drop a file onto the window to save back to it")
+                    .clicked()
+                {
+                    self.save_and_log();
+                }
+                if self.has_unsaved_changes() {
+                    ui.label("●").on_hover_text("Unsaved changes");
+                }
+
+                ui.menu_button("📄 New", |ui| {
+                    if ui.button("Empty Object {}").clicked() {
+                        self.request_new_document(false);
+                        ui.close();
+                    }
+                    if ui.button("Empty Array []").clicked() {
+                        self.request_new_document(true);
+                        ui.close();
+                    }
+                });
+
                 if ui.button("Reset Layout").clicked() {
                     self.left_panel_width = 400.0;
                     utils::log("App", "Layout reset");
                 }
 
+                let theme_label = if self.theme.is_dark() {
+                    "☀ Light Mode"
+                } else {
+                    "🌙 Dark Mode"
+                };
+                if ui.button(theme_label).clicked() {
+                    self.theme = self.theme.toggled();
+                    ui.ctx().set_visuals(self.theme.visuals());
+                    utils::log("App", &format!("Theme: {:?}", self.theme));
+                }
+                self.render_background_menu(ui);
+                ui.checkbox(&mut self.vsync, "VSync").on_hover_text(
+                    "Sync frames to the display (saves power; off may be smoother on some GPUs)",
+                );
+
+                let mut one_based = self.json_graph.one_based_indices();
+                if ui
+                    .checkbox(&mut one_based, "1-based indices")
+                    .on_hover_text("Show array indices starting at 1 (paths stay 0-based)")
+                    .changed()
+                {
+                    self.set_one_based_indices(one_based);
+                    // Edge labels are set at build time
+                    self.json_graph.request_refresh();
+                }
+
+                ui.menu_button("📊 Stats", |ui| {
+                    let stats = self.json_editor.stats();
+                    egui::Grid::new("stats_grid").striped(true).show(ui, |ui| {
+                        let mut rows = vec![
+                            ("Lines", stats.lines.to_string()),
+                            ("Characters", stats.chars.to_string()),
+                        ];
+                        if let Some(keys) = stats.top_level_keys {
+                            rows.push(("Top-level keys", keys.to_string()));
+                        }
+                        if let Some(values) = &stats.values {
+                            rows.extend([
+                                ("Graph nodes", self.json_graph.node_count().to_string()),
+                                ("Objects", values.objects.to_string()),
+                                ("Arrays", values.arrays.to_string()),
+                                ("Strings", values.strings.to_string()),
+                                ("Numbers", values.numbers.to_string()),
+                                ("Booleans", values.booleans.to_string()),
+                                ("Nulls", values.nulls.to_string()),
+                                ("Max depth", values.depth_display()),
+                                ("Total values", values.total().to_string()),
+                            ]);
+                        }
+                        for (label, value) in rows {
+                            ui.label(label);
+                            ui.monospace(value);
+                            ui.end_row();
+                        }
+                    });
+                    if stats.values.is_none() {
+                        ui.label("Document is not valid JSON");
+                    }
+                });
+
+                let mut frozen = self.graph_frozen;
+                if ui
+                    .toggle_value(&mut frozen, "❄ Freeze Graph")
+                    .on_hover_text("Stop rebuilding the graph on edits; unfreeze to catch up")
+                    .changed()
+                {
+                    self.set_graph_frozen(frozen);
+                }
+                ui.checkbox(&mut self.auto_freeze_while_typing, "Auto-freeze")
+                    .on_hover_text("Rebuild the graph only after typing pauses");
+                if self.graph_stale {
+                    ui.weak("(graph out of date)");
+                }
+
+                let mut compare_active = self.compare.is_active();
+                if ui.toggle_value(&mut compare_active, "🔍 Compare").changed() {
+                    self.compare.set_active(compare_active);
+                    utils::log(
+                        "App",
+                        &format!(
+                            "Compare mode: {}",
+                            if compare_active { "on" } else { "off" }
+                        ),
+                    );
+                }
+
                 // Right-aligned GitHub link button
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("🔗 GitHub Source").clicked() {
@@ -58,6 +722,15 @@ impl App {
             });
         });
 
+        self.handle_dropped_files(ctx);
+        self.render_load_prompt(ctx);
+        self.render_new_prompt(ctx);
+        self.render_close_prompt(ctx);
+
+        // Keep the editor's tree view highlighting the graph selection
+        self.json_editor
+            .set_selected_path(self.json_graph.get_selected_path());
+
         // Left panel for JSON editor
         egui::SidePanel::left("json_editor_panel")
             .resizable(true)
@@ -83,23 +756,62 @@ impl App {
                     );
                 }
 
-                // Update graph if JSON changed and is valid
-                // OR if graph hasn't been initialized yet but JSON is valid
-                if changed && self.json_editor.is_valid() {
-                    if let Some(value) = self.json_editor.parsed_value() {
-                        self.json_graph.build_from_json(value);
-                        self.graph_initialized = true;
-                        utils::log("App", "Graph updated from JSON");
-                    }
-                } else if changed && !self.json_editor.is_valid() {
-                    // Clear graph if JSON becomes invalid
-                    self.json_graph.build_from_json(&serde_json::Value::Null);
-                    utils::log("App", "Graph cleared - invalid JSON");
+                // Update graph if JSON changed (cleared if it became invalid)
+                if changed {
+                    self.on_document_changed(ui.input(|i| i.time));
                 }
             });
 
+        // Catch up on a rebuild deferred by freezing or typing
+        let now = ctx.input(|i| i.time);
+        if !self.flush_stale_graph(now)
+            && self.graph_stale
+            && !self.graph_frozen
+            && self.auto_freeze_while_typing
+        {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                (self.last_edit_time + TYPING_PAUSE_SECS - now).max(0.0),
+            ));
+        }
+
+        // Compare mode: keep the diff current and show the baseline on the right
+        self.compare.render_dialog(ctx);
+        if self.compare.is_active() {
+            if self.compare.refresh(&self.json_editor) {
+                self.json_graph.set_diff_highlights(self.compare.entries());
+            }
+
+            egui::SidePanel::right("compare_panel")
+                .resizable(true)
+                .default_width(350.0)
+                .width_range(200.0..=700.0)
+                .show(ctx, |ui| {
+                    if let Some(path) = self.compare.render_panel(ui) {
+                        self.json_graph.select_by_path(&path);
+                        if let Some(line) = self.json_editor.find_line_for_path(&path) {
+                            self.json_editor.scroll_to_line(line);
+                        }
+                        utils::log("App", &format!("Jumped to difference: {:?}", path));
+                    }
+                });
+        } else {
+            self.json_graph.set_diff_highlights(&[]);
+        }
+
         // Central panel for graph visualization
         egui::CentralPanel::default().show(ctx, |ui| {
+            if !self.graph_enabled {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(40.0);
+                    ui.label("Graph disabled for this large document (text-only mode)");
+                    if ui.button("Enable graph").clicked() {
+                        self.graph_enabled = true;
+                        self.force_rebuild_graph();
+                    }
+                });
+                return;
+            }
+
             // Initialize graph on first frame if JSON is valid
             if !self.graph_initialized
                 && self.json_editor.is_valid()
@@ -111,11 +823,21 @@ impl App {
 
             let selection_changed = self.json_graph.ui(ui);
 
+            // Rebuild the graph from scratch if requested (Rebuild Graph button)
+            if self.json_graph.take_pending_rebuild() {
+                self.force_rebuild_graph();
+            }
+            if self.json_graph.take_pending_refresh() {
+                self.refresh_graph();
+            }
+
             // Check if there's a pending edit from the graph
             if let Some(edit_result) = self.json_graph.take_pending_edit() {
                 use crate::json_editor::graph::ModifyOperation;
 
-                let success = match edit_result.operation {
+                // One graph edit is one undo step
+                self.json_editor.begin_undo_group();
+                let result = match edit_result.operation {
                     ModifyOperation::Update { ref new_value } => {
                         utils::log(
                             "App",
@@ -146,6 +868,17 @@ impl App {
                         self.json_editor
                             .add_value_at_path(&edit_result.json_path, key, value)
                     }
+                    ModifyOperation::InsertAt { index, ref value } => {
+                        utils::log(
+                            "App",
+                            &format!(
+                                "Processing graph insert: {:?} [{}] = {}",
+                                edit_result.json_path, index, value
+                            ),
+                        );
+                        self.json_editor
+                            .insert_at_path(&edit_result.json_path, index, value)
+                    }
                     ModifyOperation::Rename {
                         ref old_key,
                         ref new_key,
@@ -163,16 +896,134 @@ impl App {
                             new_key,
                         )
                     }
+                    ModifyOperation::AddJson { ref key, ref value } => {
+                        utils::log(
+                            "App",
+                            &format!(
+                                "Processing graph paste: {:?} + {} = {}",
+                                edit_result.json_path, key, value
+                            ),
+                        );
+                        self.json_editor.add_json_value_at_path(
+                            &edit_result.json_path,
+                            key,
+                            value.clone(),
+                        )
+                    }
+                    ModifyOperation::UpdateJson { ref value } => {
+                        utils::log(
+                            "App",
+                            &format!(
+                                "Processing graph JSON update: {:?} = {}",
+                                edit_result.json_path, value
+                            ),
+                        );
+                        self.json_editor
+                            .update_subtree_at_path(&edit_result.json_path, value.clone())
+                    }
+                    ModifyOperation::ChangeType { ref target } => {
+                        utils::log(
+                            "App",
+                            &format!(
+                                "Processing graph type change: {:?} -> {:?}",
+                                edit_result.json_path, target
+                            ),
+                        );
+                        self.json_editor
+                            .change_type_at_path(&edit_result.json_path, target)
+                    }
+                    ModifyOperation::Duplicate => {
+                        utils::log(
+                            "App",
+                            &format!("Processing graph duplicate: {:?}", edit_result.json_path),
+                        );
+                        self.json_editor.duplicate_at_path(&edit_result.json_path)
+                    }
+                    ModifyOperation::SortArray {
+                        descending,
+                        ref key,
+                    } => {
+                        utils::log(
+                            "App",
+                            &format!(
+                                "Processing graph sort: {:?} (descending: {}, key: {:?})",
+                                edit_result.json_path, descending, key
+                            ),
+                        );
+                        self.json_editor.sort_array_at_path(
+                            &edit_result.json_path,
+                            descending,
+                            key.as_deref(),
+                        )
+                    }
                 };
+                self.json_editor.end_undo_group();
+
+                match result {
+                    Ok(()) => {
+                        // Rebuild graph from updated JSON
+                        if let Some(value) = self.json_editor.parsed_value() {
+                            self.json_graph.update_from_json(value);
+                            utils::log("App", "Graph updated after modification");
+                        }
+                    }
+                    Err(error) => {
+                        utils::log(
+                            "App",
+                            &format!("Failed to apply modification from graph: {}", error),
+                        );
+                    }
+                }
+            }
+
+            // Copy to the clipboard if requested from the graph
+            match self.json_graph.take_pending_copy() {
+                Some(CopyRequest::Subtree(path)) => {
+                    if let Some(json) = self.json_editor.get_subtree_json(&path) {
+                        ui.ctx().copy_text(json);
+                        utils::log("App", &format!("Copied JSON subtree: {:?}", path));
+                    } else {
+                        utils::log("App", &format!("Failed to copy JSON subtree: {:?}", path));
+                    }
+                }
+                Some(CopyRequest::LeafPaths { path, relative }) => {
+                    if let Some(paths) = self.json_editor.leaf_paths_at(&path, relative) {
+                        ui.ctx().copy_text(paths);
+                        utils::log("App", &format!("Copied leaf paths under: {:?}", path));
+                    } else {
+                        utils::log("App", &format!("Failed to copy leaf paths: {:?}", path));
+                    }
+                }
+                Some(CopyRequest::TypeSignature(path)) => {
+                    if let Some(signature) = self.json_editor.type_signature_at(&path) {
+                        ui.ctx().copy_text(signature);
+                        utils::log("App", &format!("Copied type signature of: {:?}", path));
+                    } else {
+                        utils::log("App", &format!("Failed to copy type signature: {:?}", path));
+                    }
+                }
+                None => {}
+            }
 
-                if success {
-                    // Rebuild graph from updated JSON
-                    if let Some(value) = self.json_editor.parsed_value() {
-                        self.json_graph.build_from_json(value);
-                        utils::log("App", "Graph rebuilt after modification");
+            // Open the graph's value editor with the current value (cells only show previews)
+            if let Some(path) = self.json_graph.take_pending_value_edit() {
+                let opened = match self.json_editor.value_at_path(&path) {
+                    Some(value) if value.is_object() || value.is_array() => {
+                        match self.json_editor.get_subtree_json(&path) {
+                            Some(json) => {
+                                self.json_graph.open_subtree_editor(path.clone(), json);
+                                true
+                            }
+                            None => false,
+                        }
                     }
+                    Some(value) => self.json_graph.open_cell_editor(&path, value),
+                    None => false,
+                };
+                if opened {
+                    ui.ctx().request_repaint();
                 } else {
-                    utils::log("App", "Failed to apply modification from graph");
+                    utils::log("App", &format!("Failed to load value to edit: {:?}", path));
                 }
             }
 
@@ -188,5 +1039,274 @@ impl App {
                 );
             }
         });
+
+        // Structured edits applied this frame (from the graph or the tree view)
+        for event in self.json_editor.drain_events() {
+            utils::log("App", &format!("Edit applied at path: {:?}", event.path()));
+        }
+
+        self.persist_settings();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_force_rebuild_graph_restores_state() {
+        let mut app = App::new();
+        app.json_editor
+            .set_text(r#"{"a": {"b": 1}, "c": [{"d": 2}, {"e": 3}]}"#.to_string());
+        app.force_rebuild_graph();
+        let expected = app.json_graph.node_count();
+        assert_eq!(expected, 5);
+
+        // Tamper with graph state so it no longer matches the document
+        app.json_graph.build_from_json(&json!([1, [2], [3], [4]]));
+        assert!(app.json_graph.select_by_path(&["1".to_string()]));
+        assert_ne!(app.json_graph.node_count(), expected);
+
+        app.force_rebuild_graph();
+        assert_eq!(app.json_graph.node_count(), expected);
+        assert!(app.json_graph.get_selected_path().is_none());
+    }
+
+    #[test]
+    fn test_frozen_graph_rebuilds_once_on_resume() {
+        let mut app = App::default();
+        app.load_text(r#"{"a": 1}"#.to_string(), false);
+        assert_eq!(app.json_graph.node_count(), 1);
+
+        app.set_graph_frozen(true);
+        for (i, text) in [r#"{"a": [1]}"#, r#"{"a": [1, {"b": 2}]}"#, r#"{"a": [{}]}"#]
+            .into_iter()
+            .enumerate()
+        {
+            app.json_editor.set_text(text.to_string());
+            assert!(!app.on_document_changed(i as f64));
+            assert!(!app.flush_stale_graph(i as f64 + 10.0));
+        }
+        // Still the graph of the first document
+        assert_eq!(app.json_graph.node_count(), 1);
+
+        app.set_graph_frozen(false);
+        assert!(app.flush_stale_graph(20.0));
+        assert!(!app.flush_stale_graph(21.0));
+        assert_eq!(app.json_graph.node_count(), 3);
+
+        // Auto-freeze waits for a pause in typing
+        app.auto_freeze_while_typing = true;
+        app.json_editor.set_text(r#"{"a": 1}"#.to_string());
+        assert!(!app.on_document_changed(30.0));
+        assert!(!app.flush_stale_graph(30.0 + TYPING_PAUSE_SECS / 2.0));
+        assert!(app.flush_stale_graph(30.0 + TYPING_PAUSE_SECS));
+        assert_eq!(app.json_graph.node_count(), 1);
+
+        // Without freezing, each change rebuilds right away
+        app.auto_freeze_while_typing = false;
+        app.json_editor.set_text(r#"{"a": [1]}"#.to_string());
+        assert!(app.on_document_changed(40.0));
+        assert!(!app.flush_stale_graph(40.0));
+    }
+
+    #[test]
+    fn test_text_edits_keep_graph_selection_by_path() {
+        let mut app = App::default();
+        app.load_text(r#"{"a": {"b": [1]}, "c": {}}"#.to_string(), false);
+        let path = vec!["a".to_string(), "b".to_string()];
+        assert!(app.json_graph.select_by_path(&path));
+
+        // An edit elsewhere keeps the selection
+        app.json_editor
+            .set_text(r#"{"a": {"b": [1, 2]}, "c": {"d": 3}}"#.to_string());
+        assert!(app.on_document_changed(1.0));
+        assert_eq!(app.json_graph.get_selected_path(), Some(path.clone()));
+
+        // So does typing through invalid text
+        app.json_editor
+            .set_text(r#"{"a": {"b": [1, 2]}, "c": {"d": }}"#.to_string());
+        app.on_document_changed(2.0);
+        assert_eq!(app.json_graph.node_count(), 0);
+        app.json_editor
+            .set_text(r#"{"a": {"b": [1, 2]}, "c": {"d": 4}}"#.to_string());
+        app.on_document_changed(3.0);
+        assert_eq!(app.json_graph.get_selected_path(), Some(path.clone()));
+
+        // Removing the path doesn't fall back to an ancestor
+        app.json_editor.set_text(r#"{"a": {"x": "}"#.to_string());
+        app.on_document_changed(4.0);
+        app.json_editor.set_text(r#"{"a": {}}"#.to_string());
+        app.on_document_changed(5.0);
+        assert!(app.json_graph.get_selected_path().is_none());
+    }
+
+    #[test]
+    fn test_display_settings_keep_graph_selection() {
+        let mut app = App::default();
+        app.load_text(r#"{"a": {"b": "long string value"}}"#.to_string(), false);
+        let path = vec!["a".to_string()];
+        assert!(app.json_graph.select_by_path(&path));
+
+        app.json_graph.set_preview_len(5);
+        app.json_graph.request_refresh();
+        assert!(app.json_graph.take_pending_refresh());
+        app.refresh_graph();
+        assert_eq!(app.json_graph.get_selected_path(), Some(path));
+        assert!(!app.json_graph.take_pending_rebuild());
+    }
+
+    #[test]
+    fn test_unsaved_changes_follow_loaded_text() {
+        let mut app = App::default();
+        assert!(!app.has_unsaved_changes());
+
+        app.load_text(r#"{"a": 1}"#.to_string(), false);
+        assert!(!app.has_unsaved_changes());
+
+        app.json_editor.set_text(r#"{"a": 2}"#.to_string());
+        assert!(app.has_unsaved_changes());
+        assert!(app.json_editor.undo());
+        assert!(!app.has_unsaved_changes());
+
+        // Rebuilding re-validates without changing the text
+        app.force_rebuild_graph();
+        assert!(!app.has_unsaved_changes());
+
+        app.json_editor.set_text(r#"{"b": 3}"#.to_string());
+        app.load_text(r#"{"c": 4}"#.to_string(), true);
+        assert!(!app.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_new_document_asks_before_discarding_changes() {
+        let mut app = App::default();
+        app.force_rebuild_graph();
+        assert!(app.json_graph.node_count() > 1);
+
+        // The sample is clean, so New replaces it right away
+        app.request_new_document(false);
+        assert!(app.pending_new.is_none());
+        assert_eq!(app.json_editor.text(), "{}");
+        assert!(!app.json_editor.can_undo());
+        assert!(!app.has_unsaved_changes());
+        assert_eq!(app.json_graph.node_count(), 1);
+
+        // With unsaved changes, New waits for confirmation
+        app.json_editor.set_text(r#"{"a": {"b": 1}}"#.to_string());
+        app.request_new_document(true);
+        assert_eq!(app.pending_new, Some(true));
+        assert_eq!(app.json_editor.text(), r#"{"a": {"b": 1}}"#);
+
+        app.new_document(true);
+        assert_eq!(app.json_editor.text(), "[]");
+        assert!(!app.has_unsaved_changes());
+        assert_eq!(app.json_graph.node_count(), 1);
+    }
+
+    #[test]
+    fn test_close_asks_while_dirty_and_save_clears_it() {
+        let path =
+            std::env::temp_dir().join(format!("json_editor_save_{}.json", std::process::id()));
+        let mut app = App::default();
+        app.load_text(r#"{"a": 1}"#.to_string(), false);
+        assert_eq!(app.window_title(), WINDOW_TITLE);
+        assert!(app.request_close());
+
+        app.json_editor.set_text(r#"{"a": 2}"#.to_string());
+        assert_eq!(app.window_title(), format!("*{}", WINDOW_TITLE));
+        assert!(!app.dialog_open());
+        assert!(!app.request_close());
+        assert!(app.close_prompt);
+        // Escape goes to the prompt instead of closing the window again
+        assert!(app.dialog_open());
+        assert!(!app.take_close_confirmed());
+
+        // Not loaded from a file: nothing to save to
+        assert!(app.save().is_err());
+        assert!(app.has_unsaved_changes());
+
+        app.file_path = Some(path.clone());
+        assert!(
+            app.window_title()
+                .starts_with(&format!("*{}", path.file_name().unwrap().to_string_lossy()))
+        );
+        assert!(app.save().is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{"a": 2}"#);
+        assert!(!app.has_unsaved_changes());
+        assert!(app.request_close());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_load_text_text_only() {
+        let mut app = App::default();
+        app.load_text(r#"{"a": [1, 2]}"#.to_string(), true);
+        assert!(!app.graph_enabled);
+        assert_eq!(app.json_graph.node_count(), 0);
+        assert!(app.json_editor.is_valid());
+
+        app.load_text(r#"{"a": [1, 2]}"#.to_string(), false);
+        assert!(app.graph_enabled);
+        assert_eq!(app.json_graph.node_count(), 2);
+    }
+
+    #[test]
+    fn test_oversized_document_disables_graph() {
+        let mut app = App::default();
+        app.force_rebuild_graph();
+        assert!(app.json_graph.node_count() > 0);
+
+        // One node per array element pushes the estimate over the limit
+        let items = vec![serde_json::json!([]); AUTO_TEXT_ONLY_NODES];
+        let text = serde_json::Value::Array(items).to_string();
+        app.load_text(text, false);
+
+        assert!(!app.graph_enabled);
+        assert!(app.json_editor.is_valid());
+        // The graph was cleared, not built from the document
+        assert_eq!(app.json_graph.node_count(), 0);
+    }
+
+    #[test]
+    fn test_apply_settings_updates_minimap() {
+        use crate::json_editor::MinimapCorner;
+
+        let mut app = App::default();
+        let mut settings = AppSettings::default();
+        settings.minimap.corner = MinimapCorner::TopLeft;
+        settings.minimap.collapsed = true;
+        settings.one_based_indices = true;
+        app.apply_settings(settings.clone());
+        assert_eq!(app.current_settings(), settings);
+        assert!(app.json_editor.one_based_indices());
+    }
+
+    #[test]
+    fn test_clear_color_defaults_to_theme() {
+        let mut app = App::default();
+        let [r, g, b] = Theme::Dark.clear_color();
+        assert_eq!(app.clear_color(), wgpu::Color { r, g, b, a: 1.0 });
+
+        let brand = wgpu::Color {
+            r: 0.5,
+            g: 0.25,
+            b: 0.0,
+            a: 1.0,
+        };
+        app.set_clear_color(Some(brand));
+        assert_eq!(app.clear_color(), brand);
+        // Persisted and restored with the settings
+        let settings = app.current_settings();
+        assert_eq!(settings.background, Some([0.5, 0.25, 0.0]));
+        let mut restored = App::default();
+        restored.apply_settings(settings);
+        assert_eq!(restored.clear_color(), brand);
+
+        app.set_clear_color(None);
+        app.theme = Theme::Light;
+        let [r, g, b] = Theme::Light.clear_color();
+        assert_eq!(app.clear_color(), wgpu::Color { r, g, b, a: 1.0 });
     }
 }
@@ -0,0 +1,281 @@
+/// Compare mode: diff the editor document against a baseline JSON
+///
+/// The baseline is pasted into a dialog (or loaded from a file on desktop) and shown
+/// read-only in a right-hand panel alongside the color-coded differences.
+use crate::json_editor::graph::diff_color;
+use crate::json_editor::{DiffEntry, JsonEditor};
+use crate::utils;
+use egui;
+use serde_json::Value;
+
+/// Dialog for entering the baseline JSON
+#[derive(Default)]
+struct BaselineDialog {
+    /// Pasted baseline text
+    text: String,
+    /// File path to load the baseline from (desktop only)
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    file_path: String,
+    /// Parse or load error to show
+    error: Option<String>,
+}
+
+/// Compare mode state
+#[derive(Default)]
+pub struct CompareView {
+    /// Whether compare mode is on
+    active: bool,
+    /// Baseline text as entered (shown read-only)
+    baseline_text: String,
+    /// Parsed baseline value
+    baseline: Option<Value>,
+    /// Counter bumped whenever the baseline changes
+    baseline_revision: u64,
+    /// Differences from the baseline to the document
+    entries: Vec<DiffEntry>,
+    /// Baseline and editor revisions the entries were computed for
+    compared_revisions: Option<(u64, u64)>,
+    /// Whether the document was valid JSON at the last refresh
+    document_valid: bool,
+    /// Baseline dialog (if open)
+    dialog: Option<BaselineDialog>,
+}
+
+impl CompareView {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Turn compare mode on or off
+    /// Turning it on without a baseline opens the baseline dialog
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if active && self.baseline.is_none() {
+            self.open_dialog();
+        }
+        if !active {
+            self.dialog = None;
+        }
+    }
+
+    /// Whether the baseline dialog is open
+    pub fn dialog_open(&self) -> bool {
+        self.dialog.is_some()
+    }
+
+    /// Open the baseline dialog, prefilled with the current baseline
+    pub fn open_dialog(&mut self) {
+        self.dialog = Some(BaselineDialog {
+            text: self.baseline_text.clone(),
+            ..Default::default()
+        });
+    }
+
+    /// Set the baseline from JSON text
+    pub fn set_baseline(&mut self, text: &str) -> Result<(), String> {
+        let value = serde_json::from_str::<Value>(text)
+            .map_err(|e| format!("Baseline is not valid JSON: {}", e))?;
+        self.baseline_text = text.to_string();
+        self.baseline = Some(value);
+        self.baseline_revision += 1;
+        Ok(())
+    }
+
+    /// Differences from the baseline to the document (as of the last refresh)
+    pub fn entries(&self) -> &[DiffEntry] {
+        &self.entries
+    }
+
+    /// Recompute the diff if the document or baseline changed
+    /// Returns true if the entries were recomputed
+    pub fn refresh(&mut self, editor: &JsonEditor) -> bool {
+        let revisions = (self.baseline_revision, editor.revision());
+        if self.compared_revisions == Some(revisions) {
+            return false;
+        }
+        let entries = self
+            .baseline
+            .as_ref()
+            .and_then(|baseline| editor.diff_against(baseline));
+        self.document_valid = entries.is_some() || self.baseline.is_none();
+        self.entries = entries.unwrap_or_default();
+        self.compared_revisions = Some(revisions);
+        true
+    }
+
+    /// Render the baseline dialog if open
+    pub fn render_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.dialog else {
+            return;
+        };
+
+        let mut close_dialog = false;
+        let mut submitted: Option<String> = None;
+
+        egui::Window::new("Compare Baseline")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Paste the baseline JSON:");
+                egui::ScrollArea::vertical()
+                    .id_salt("baseline_dialog_scroll")
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut dialog.text)
+                                .desired_width(400.0)
+                                .desired_rows(10)
+                                .font(egui::TextStyle::Monospace),
+                        );
+                    });
+
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    ui.label("Or load file:");
+                    ui.text_edit_singleline(&mut dialog.file_path);
+                    if ui.button("📂 Load").clicked() {
+                        match std::fs::read_to_string(dialog.file_path.trim()) {
+                            Ok(text) => {
+                                dialog.text = text;
+                                dialog.error = None;
+                            }
+                            Err(e) => {
+                                dialog.error = Some(format!("Failed to read file: {}", e));
+                            }
+                        }
+                    }
+                });
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("✓ Compare").clicked() {
+                        submitted = Some(dialog.text.clone());
+                    }
+                    if ui.button("✗ Cancel").clicked() {
+                        close_dialog = true;
+                    }
+                });
+
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close_dialog = true;
+                }
+            });
+
+        if let Some(text) = submitted {
+            match self.set_baseline(&text) {
+                Ok(()) => {
+                    close_dialog = true;
+                    utils::log("Compare", "Baseline loaded");
+                }
+                Err(error) => {
+                    if let Some(dialog) = &mut self.dialog {
+                        dialog.error = Some(error);
+                    }
+                }
+            }
+        }
+
+        if close_dialog {
+            self.dialog = None;
+            // Nothing to compare against: leave compare mode
+            if self.baseline.is_none() {
+                self.active = false;
+            }
+        }
+    }
+
+    /// Render the baseline and diff list
+    /// Returns the path of a diff entry the user clicked
+    pub fn render_panel(&mut self, ui: &mut egui::Ui) -> Option<Vec<String>> {
+        let mut clicked_path = None;
+
+        ui.horizontal(|ui| {
+            ui.heading("Baseline");
+            if ui.button("📋 Change Baseline…").clicked() {
+                self.open_dialog();
+            }
+        });
+        ui.separator();
+
+        let available = ui.available_height();
+        egui::ScrollArea::vertical()
+            .id_salt("baseline_text_scroll")
+            .max_height(available * 0.5)
+            .show(ui, |ui| {
+                // &str is a read-only text buffer
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.baseline_text.as_str())
+                        .desired_width(f32::INFINITY)
+                        .font(egui::TextStyle::Monospace),
+                );
+            });
+
+        ui.separator();
+
+        if self.baseline.is_none() {
+            ui.label(egui::RichText::new("No baseline loaded").italics());
+        } else if !self.document_valid {
+            ui.colored_label(egui::Color32::RED, "Document is not valid JSON");
+        } else if self.compared_revisions.is_some() && self.entries.is_empty() {
+            ui.colored_label(egui::Color32::GREEN, "✓ No differences");
+        } else {
+            ui.label(format!("{} difference(s):", self.entries.len()));
+            egui::ScrollArea::vertical()
+                .id_salt("compare_diff_scroll")
+                .show(ui, |ui| {
+                    for entry in &self.entries {
+                        let text = egui::RichText::new(entry.summary())
+                            .monospace()
+                            .color(diff_color(&entry.kind));
+                        if ui.link(text).clicked() {
+                            clicked_path = Some(entry.path.clone());
+                        }
+                    }
+                });
+        }
+
+        clicked_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_tracks_document_changes() {
+        let mut compare = CompareView::default();
+        compare.set_active(true);
+        assert!(compare.dialog.is_some());
+        assert!(compare.set_baseline("not json").is_err());
+        compare
+            .set_baseline(r#"{"port": 80, "debug": false}"#)
+            .unwrap();
+
+        let mut editor = JsonEditor::with_text(r#"{"port": 8080, "debug": false}"#.to_string());
+        assert!(compare.refresh(&editor));
+        assert_eq!(compare.entries().len(), 1);
+        assert_eq!(compare.entries()[0].summary(), "~ port: 80 → 8080");
+
+        // Unchanged document: nothing to recompute
+        assert!(!compare.refresh(&editor));
+
+        editor.set_text(r#"{"port": 80, "debug": false}"#.to_string());
+        assert!(compare.refresh(&editor));
+        assert!(compare.entries().is_empty());
+        assert!(compare.document_valid);
+
+        editor.set_text("{".to_string());
+        assert!(compare.refresh(&editor));
+        assert!(!compare.document_valid);
+
+        // A new baseline is compared against the same document
+        compare.set_baseline(r#"{"port": 80}"#).unwrap();
+        assert!(compare.refresh(&editor));
+        assert!(!compare.refresh(&editor));
+    }
+}
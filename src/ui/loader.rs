@@ -0,0 +1,110 @@
+/// File loading helpers
+///
+/// Handles files dropped onto the window, with a size guard for very large files.
+use egui::DroppedFile;
+
+/// Default maximum file size loaded without confirmation (64 MB)
+pub const DEFAULT_MAX_LOAD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// What to do with a file of a given size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadDecision {
+    /// Small enough to load right away
+    Load,
+    /// Larger than the threshold: ask the user first
+    Prompt { size_mb: f64 },
+}
+
+/// Decide whether a file of `size` bytes can be loaded without asking
+pub fn check_load_size(size: u64, max_load_bytes: u64) -> LoadDecision {
+    if size > max_load_bytes {
+        LoadDecision::Prompt {
+            size_mb: size as f64 / (1024.0 * 1024.0),
+        }
+    } else {
+        LoadDecision::Load
+    }
+}
+
+/// A dropped file waiting to be read
+pub struct PendingLoad {
+    /// File name for messages
+    pub name: String,
+    /// The dropped file (path on desktop, bytes on WASM)
+    file: DroppedFile,
+}
+
+impl PendingLoad {
+    pub fn new(file: DroppedFile) -> Self {
+        let name = file
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file.name.clone());
+        Self { name, file }
+    }
+
+    /// Path of the file on disk (desktop only; WASM files only have bytes)
+    pub fn path(&self) -> Option<&std::path::Path> {
+        self.file.path.as_deref()
+    }
+
+    /// File size in bytes, if known
+    pub fn size(&self) -> Option<u64> {
+        if let Some(bytes) = &self.file.bytes {
+            return Some(bytes.len() as u64);
+        }
+        let path = self.file.path.as_ref()?;
+        std::fs::metadata(path).ok().map(|metadata| metadata.len())
+    }
+
+    /// Read the file contents as UTF-8 text
+    pub fn read(&self) -> Result<String, String> {
+        if let Some(bytes) = &self.file.bytes {
+            return String::from_utf8(bytes.to_vec())
+                .map_err(|_| format!("{} is not valid UTF-8", self.name));
+        }
+        let path = self
+            .file
+            .path
+            .as_ref()
+            .ok_or_else(|| format!("{} has no contents", self.name))?;
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", self.name, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_load_size() {
+        let max = 64 * 1024 * 1024;
+        assert_eq!(check_load_size(0, max), LoadDecision::Load);
+        assert_eq!(check_load_size(max, max), LoadDecision::Load);
+        assert_eq!(
+            check_load_size(max * 2, max),
+            LoadDecision::Prompt { size_mb: 128.0 }
+        );
+        assert_eq!(
+            check_load_size(2048, 1024),
+            LoadDecision::Prompt {
+                size_mb: 2048.0 / (1024.0 * 1024.0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_pending_load_reads_bytes() {
+        let load = PendingLoad::new(DroppedFile {
+            name: "data.json".to_string(),
+            bytes: Some(br#"{"a": 1}"#.to_vec().into()),
+            ..Default::default()
+        });
+        assert_eq!(load.name, "data.json");
+        assert!(load.path().is_none());
+        assert_eq!(load.size(), Some(8));
+        assert_eq!(load.read().unwrap(), r#"{"a": 1}"#);
+    }
+}
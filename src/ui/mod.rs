@@ -2,5 +2,8 @@
 ///
 /// This module contains the user interface components.
 pub mod app;
+pub mod compare;
+pub mod loader;
+pub mod settings;
 
 pub use app::App;
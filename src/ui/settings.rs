@@ -0,0 +1,135 @@
+//! Persisted application settings
+//!
+//! Settings are stored as JSON through `utils::storage` and saved whenever they change.
+
+use super::loader::DEFAULT_MAX_LOAD_BYTES;
+use crate::json_editor::MinimapSettings;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+
+/// Storage key for the settings document
+const STORAGE_KEY: &str = "settings";
+
+/// Color theme for the whole app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    /// The other theme
+    pub fn toggled(self) -> Self {
+        match self {
+            Theme::Dark => Theme::Light,
+            Theme::Light => Theme::Dark,
+        }
+    }
+
+    pub fn is_dark(self) -> bool {
+        self == Theme::Dark
+    }
+
+    /// egui visuals for this theme
+    pub fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        }
+    }
+
+    /// Background clear color as linear RGB
+    pub fn clear_color(self) -> [f64; 3] {
+        match self {
+            Theme::Dark => [0.1, 0.2, 0.3],
+            Theme::Light => [0.85, 0.86, 0.88],
+        }
+    }
+}
+
+/// User preferences that survive restarts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Minimap visibility, corner, collapsed state and size
+    pub minimap: MinimapSettings,
+    /// Dark or light theme
+    pub theme: Theme,
+    /// Files larger than this ask for confirmation before loading
+    pub max_load_bytes: u64,
+    /// Show array indices starting at 1 in the graph and tree view
+    pub one_based_indices: bool,
+    /// Custom background clear color as linear RGB (None = follow the theme)
+    pub background: Option<[f32; 3]>,
+    /// Present frames in sync with the display (caps the frame rate)
+    pub vsync: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            minimap: MinimapSettings::default(),
+            theme: Theme::default(),
+            max_load_bytes: DEFAULT_MAX_LOAD_BYTES,
+            one_based_indices: false,
+            background: None,
+            vsync: true,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Load settings from storage, falling back to defaults
+    pub fn load() -> Self {
+        let Some(text) = utils::storage::load(STORAGE_KEY) else {
+            return Self::default();
+        };
+        match serde_json::from_str(&text) {
+            Ok(settings) => settings,
+            Err(e) => {
+                utils::log("Settings", &format!("Ignoring invalid settings: {}", e));
+                Self::default()
+            }
+        }
+    }
+
+    /// Save settings to storage
+    pub fn save(&self) {
+        match serde_json::to_string(self) {
+            Ok(text) => utils::storage::save(STORAGE_KEY, &text),
+            Err(e) => utils::log("Settings", &format!("Failed to serialize settings: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_editor::MinimapCorner;
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings: AppSettings =
+            serde_json::from_str(r#"{"minimap": {"corner": "TopLeft"}}"#).unwrap();
+        assert_eq!(settings.minimap.corner, MinimapCorner::TopLeft);
+        assert!(settings.minimap.visible);
+        assert_eq!(settings.minimap.size, AppSettings::default().minimap.size);
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.max_load_bytes, DEFAULT_MAX_LOAD_BYTES);
+        assert_eq!(settings.background, None);
+        assert!(settings.vsync);
+    }
+
+    #[test]
+    fn test_theme_round_trip() {
+        let settings = AppSettings {
+            theme: Theme::Light,
+            ..Default::default()
+        };
+        let text = serde_json::to_string(&settings).unwrap();
+        let restored: AppSettings = serde_json::from_str(&text).unwrap();
+        assert_eq!(restored.theme, Theme::Light);
+        assert_eq!(Theme::Light.toggled(), Theme::Dark);
+    }
+}
@@ -2,5 +2,6 @@
 ///
 /// This module contains common utilities used throughout the application.
 pub mod logging;
+pub mod storage;
 
 pub use logging::log;
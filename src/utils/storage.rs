@@ -0,0 +1,86 @@
+//! Persistent key-value storage for app preferences
+//!
+//! Uses `localStorage` on WASM and small files under the user's config directory on desktop.
+//! Tests use a per-process temp directory so they never touch the real config.
+
+/// Load a stored value, if any
+pub fn load(key: &str) -> Option<String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        storage.get_item(key).ok()?
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::read_to_string(desktop::path_for(key)?).ok()
+    }
+}
+
+/// Store a value, logging (but otherwise ignoring) failures
+pub fn save(key: &str, value: &str) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let result = web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .map(|storage| storage.set_item(key, value));
+        if !matches!(result, Some(Ok(()))) {
+            super::log("Storage", &format!("Failed to save '{}'", key));
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let Some(path) = desktop::path_for(key) else {
+            super::log("Storage", "No config directory available");
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, value));
+        if let Err(e) = result {
+            super::log("Storage", &format!("Failed to save '{}': {}", key, e));
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod desktop {
+    use std::path::PathBuf;
+
+    const APP_DIR: &str = "wgpu-canvas-editor";
+
+    /// Config file path for a storage key
+    pub fn path_for(key: &str) -> Option<PathBuf> {
+        Some(config_dir()?.join(APP_DIR).join(format!("{}.json", key)))
+    }
+
+    #[cfg(not(test))]
+    fn config_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+
+    #[cfg(test)]
+    fn config_dir() -> Option<PathBuf> {
+        Some(std::env::temp_dir().join(format!("{}-test-{}", APP_DIR, std::process::id())))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tests_store_outside_the_config_dir() {
+        let path = desktop::path_for("storage-test").unwrap();
+        assert!(path.starts_with(std::env::temp_dir()));
+
+        save("storage-test", "value");
+        assert_eq!(load("storage-test").as_deref(), Some("value"));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -2,4 +2,89 @@
 //!
 //! These tests verify the core functionality of the application.
 
-// Note: Integration tests will be added as features are implemented
+use serde_json::json;
+use wgpu_canvas_editor::json_editor::{EditorError, JsonEditor};
+
+fn path(segments: &[&str]) -> Vec<String> {
+    segments.iter().map(|s| s.to_string()).collect()
+}
+
+/// Drive a whole edit session through the public API, without any egui context
+#[test]
+fn headless_edit_session() {
+    let mut editor =
+        JsonEditor::with_text(r#"{"name":"app","tags":["a"],"version":1}"#.to_string());
+    editor.set_quiet(true);
+    assert!(editor.is_valid());
+
+    editor
+        .update_value_at_path(&path(&["version"]), "1.5")
+        .unwrap();
+    editor
+        .add_value_at_path(&path(&["tags"]), "", "\"b\"")
+        .unwrap();
+    editor
+        .add_value_at_path(&path(&[]), "debug", "true")
+        .unwrap();
+    editor
+        .rename_key_at_path(&path(&[]), "name", "title")
+        .unwrap();
+    editor.delete_value_at_path(&path(&["tags", "0"])).unwrap();
+
+    let expected = json!({"title": "app", "tags": ["b"], "version": 1.5, "debug": true});
+    assert_eq!(editor.parsed_value_owned(), Some(expected.clone()));
+
+    editor.apply_compact();
+    assert_eq!(
+        editor.text(),
+        r#"{"title":"app","tags":["b"],"version":1.5,"debug":true}"#
+    );
+    editor.apply_pretty_print();
+    assert!(editor.text().contains("\n  \"tags\": [\n    \"b\"\n  ]"));
+    assert_eq!(editor.parsed_value(), Some(&expected));
+
+    // Each edit is one undo step
+    assert!(editor.undo());
+    assert_eq!(
+        editor.parsed_value_owned(),
+        Some(json!({"title": "app", "tags": ["a", "b"], "version": 1.5, "debug": true}))
+    );
+    assert!(editor.redo());
+    assert_eq!(editor.parsed_value_owned(), Some(expected));
+}
+
+#[test]
+fn headless_edit_errors() {
+    let mut editor = JsonEditor::with_text(r#"{"a": [1, 2], "b": 3}"#.to_string());
+    editor.set_quiet(true);
+
+    assert_eq!(
+        editor.update_value_at_path(&path(&["missing"]), "1"),
+        Err(EditorError::PathNotFound(path(&["missing"])))
+    );
+    assert_eq!(
+        editor.rename_key_at_path(&path(&[]), "a", "b"),
+        Err(EditorError::KeyExists("b".to_string()))
+    );
+    assert!(matches!(
+        editor.add_value_at_path(&path(&["b"]), "x", "1"),
+        Err(EditorError::TypeMismatch(_))
+    ));
+    // Failed edits leave the document untouched
+    assert_eq!(
+        editor.parsed_value_owned(),
+        Some(json!({"a": [1, 2], "b": 3}))
+    );
+
+    editor.set_text("{\"a\": ".to_string());
+    assert!(!editor.is_valid());
+    assert_eq!(editor.parsed_value_owned(), None);
+    assert!(matches!(
+        editor.error(),
+        Some(EditorError::ParseError { .. })
+    ));
+    assert_eq!(
+        editor.delete_value_at_path(&path(&["a"])),
+        Err(EditorError::InvalidDocument)
+    );
+}